@@ -0,0 +1,20 @@
+#![no_main]
+//! Save an arbitrary Glyphs 2 or Glyphs 3 font, load it back and save again
+//!
+//! Arbitrary fonts need not be valid (a code point may be out of range), and
+//! saving is lossy, as with plists. So the check is that a font which has
+//! been saved and loaded once saves, loads and saves again unchanged.
+use glyphslib::Font;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|font: Font| {
+    let Ok(saved) = font.to_string() else {
+        return;
+    };
+    let Ok(font) = Font::load_str(&saved) else {
+        return;
+    };
+    let saved = font.to_string().expect("loaded font should serialize");
+    let reloaded = Font::load_str(&saved).expect("saved font should load");
+    assert_eq!(saved, reloaded.to_string().unwrap());
+});
@@ -0,0 +1,13 @@
+#![no_main]
+//! Load untrusted text as a font; anything which loads must save and load again
+use glyphslib::Font;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let Ok(font) = Font::load_str(text) else {
+        return;
+    };
+    let saved = font.to_string().expect("loaded font should serialize");
+    let reloaded = Font::load_str(&saved).expect("saved font should load");
+    assert_eq!(saved, reloaded.to_string().unwrap());
+});
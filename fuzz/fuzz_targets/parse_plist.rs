@@ -0,0 +1,13 @@
+#![no_main]
+//! Parse untrusted text; anything which parses must survive parse, serialize, parse
+use libfuzzer_sys::fuzz_target;
+use openstep_plist::Plist;
+
+fuzz_target!(|text: &str| {
+    let Ok(plist) = Plist::parse(text) else {
+        return;
+    };
+    let serialized = plist.to_string();
+    let reparsed = Plist::parse(&serialized).expect("serialized plist should parse");
+    assert_eq!(serialized, reparsed.to_string());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+//! Serialize an arbitrary property list, parse it back and serialize again
+//!
+//! Serializing is lossy (a float of `1.0` is written as `1`, which reads back
+//! as an integer), so the check is that a second round trip changes nothing.
+use libfuzzer_sys::fuzz_target;
+use openstep_plist::Plist;
+
+fuzz_target!(|plist: Plist| {
+    let serialized = plist.to_string();
+    let parsed = Plist::parse(&serialized).expect("serialized plist should parse");
+    let reserialized = parsed.to_string();
+    assert_eq!(serialized, reserialized);
+    assert_eq!(Plist::parse(&reserialized).unwrap(), parsed);
+});
@@ -0,0 +1,98 @@
+//! Conversion between the plist form of a font and JSON
+//!
+//! The JSON mirrors the structure of the `.glyphs` file, so keys are the
+//! same as in the file (`fontMaster`, `.formatVersion` and so on). Data
+//! values, which have no JSON equivalent, become `{"$data": "<hex>"}`.
+use openstep_plist::Plist;
+use serde_json::{json, Map, Value};
+
+const DATA_KEY: &str = "$data";
+
+pub(crate) fn plist_to_json(plist: &Plist) -> Value {
+    match plist {
+        Plist::Dictionary(dict) => Value::Object(
+            dict.iter()
+                .map(|(k, v)| (k.to_string(), plist_to_json(v)))
+                .collect(),
+        ),
+        Plist::Array(array) => Value::Array(array.iter().map(plist_to_json).collect()),
+        Plist::String(s) => Value::String(s.clone()),
+        Plist::Integer(i) => json!(i),
+        Plist::Float(f) => json!(f),
+        Plist::Data(data) => {
+            let hex: String = data.iter().map(|b| format!("{b:02x}")).collect();
+            json!({ DATA_KEY: hex })
+        }
+    }
+}
+
+fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    // Checked first, as digits are taken two bytes at a time
+    if !hex.is_ascii() {
+        return Err(format!("invalid hex in data value {hex}"));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(format!("odd number of digits in data value {hex}"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|ix| {
+            u8::from_str_radix(&hex[ix..ix + 2], 16)
+                .map_err(|_| format!("invalid hex in data value {hex}"))
+        })
+        .collect()
+}
+
+fn object_to_plist(object: &Map<String, Value>) -> Result<Plist, String> {
+    if let (1, Some(Value::String(hex))) = (object.len(), object.get(DATA_KEY)) {
+        return hex_to_bytes(hex).map(Plist::Data);
+    }
+    object
+        .iter()
+        .map(|(k, v)| Ok((k.as_str().into(), json_to_plist(v)?)))
+        .collect::<Result<_, String>>()
+        .map(Plist::Dictionary)
+}
+
+pub(crate) fn json_to_plist(value: &Value) -> Result<Plist, String> {
+    match value {
+        Value::Object(object) => object_to_plist(object),
+        Value::Array(array) => array
+            .iter()
+            .map(json_to_plist)
+            .collect::<Result<_, _>>()
+            .map(Plist::Array),
+        Value::String(s) => Ok(Plist::String(s.clone())),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(Plist::Integer(i)),
+            None => n
+                .as_f64()
+                .map(Plist::Float)
+                .ok_or_else(|| format!("number {n} is out of range")),
+        },
+        Value::Bool(b) => Ok(Plist::Integer(i64::from(*b))),
+        Value::Null => Err("null has no plist equivalent".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let plist =
+            Plist::parse(r#"{a = (1, 2.5, "three"); b = {c = <0aff>;}; ".formatVersion" = 3;}"#)
+                .unwrap();
+        let json = plist_to_json(&plist);
+        assert_eq!(json["b"]["c"], json!({"$data": "0aff"}));
+        assert_eq!(json_to_plist(&json).unwrap(), plist);
+    }
+
+    #[test]
+    fn invalid_data() {
+        for hex in ["0a0", "0g", "é0", "0é"] {
+            assert!(json_to_plist(&json!({"$data": hex})).is_err(), "{hex}");
+        }
+    }
+}
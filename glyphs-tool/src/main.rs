@@ -0,0 +1,592 @@
+//! `glyphs-tool`: inspect, convert, validate and compare Glyphs files
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use glyphslib::{
+    common::Point,
+    diff::{Change, KerningDirection},
+    validate::Severity,
+    Font,
+};
+use openstep_plist::{diff::Difference, Plist};
+use serde_json::{json, Value};
+
+mod json;
+use json::{json_to_plist, plist_to_json};
+
+const USAGE: &str = "Usage: glyphs-tool <command> [options]
+
+Commands:
+  info [--json] <font>                  Summarize a font
+  convert [--to 2|3] <input> <output>   Convert between formats; the output
+                                        is a package if it ends in .glyphspackage
+  validate [--json] <font>              Check the font for problems
+  stats [--json] <font>                 Report glyph, encoding and kerning statistics
+  diff [--json] <a> <b>                 Compare two fonts
+  normalize <input> [<output>]          Rewrite a font in the canonical layout,
+                                        in place if no output is given
+  dump [--json|--ndjson] <font> [<out>] Write the font as JSON, or as NDJSON with
+                                        the font first and then one glyph per line
+  load [--json|--ndjson] <in> <output>  Read a font from a JSON or NDJSON dump;
+                                        use - to read from standard input";
+
+type CommandResult = Result<ExitCode, Box<dyn Error>>;
+
+/// The command line, split into flags and positional arguments
+struct Args {
+    flags: Vec<(String, Option<String>)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    // Flags which take a value
+    const VALUED: [&'static str; 1] = ["--to"];
+
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut flags = vec![];
+        let mut positional = vec![];
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            if let Some((flag, value)) = arg.split_once('=').filter(|_| arg.starts_with("--")) {
+                flags.push((flag.to_string(), Some(value.to_string())));
+            } else if Self::VALUED.contains(&arg.as_str()) {
+                let value = args.next().ok_or(format!("{arg} needs a value"))?;
+                flags.push((arg, Some(value)));
+            } else if arg.starts_with("--") {
+                flags.push((arg, None));
+            } else {
+                positional.push(arg);
+            }
+        }
+        Ok(Args { flags, positional })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| flag == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .find(|(flag, _)| flag == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn expect_positional(&self, min: usize, max: usize) -> Result<(), String> {
+        if self.positional.len() < min || self.positional.len() > max {
+            return Err(USAGE.to_string());
+        }
+        Ok(())
+    }
+}
+
+fn load(path: &str) -> Result<Font, Box<dyn Error>> {
+    Font::load(Path::new(path)).map_err(|e| format!("{path}: {e}").into())
+}
+
+fn print_json(value: &Value) -> Result<(), Box<dyn Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn format_version(font: &Font) -> u8 {
+    match font {
+        Font::Glyphs2(_) => 2,
+        Font::Glyphs3(_) => 3,
+    }
+}
+
+fn info(args: &Args) -> CommandResult {
+    args.expect_positional(1, 1)?;
+    let font = load(&args.positional[0])?;
+    let (units_per_em, axes, instances) = match &font {
+        Font::Glyphs2(glyphs2) => (glyphs2.units_per_em, vec![], glyphs2.instances.len()),
+        Font::Glyphs3(glyphs3) => (
+            glyphs3.units_per_em,
+            glyphs3.axes.iter().map(|a| a.tag.clone()).collect(),
+            glyphs3.instances.len(),
+        ),
+    };
+    let file = font.font();
+    let masters: Vec<String> = file
+        .masters()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+    let glyphs = file.glyphs().len();
+    if args.flag("--json") {
+        print_json(&json!({
+            "format_version": format_version(&font),
+            "app_version": file.app_version(),
+            "family_name": file.family_name(),
+            "units_per_em": units_per_em,
+            "axes": axes,
+            "masters": masters,
+            "instances": instances,
+            "glyphs": glyphs,
+        }))?;
+    } else {
+        println!("Family:         {}", file.family_name());
+        println!("Format version: {}", format_version(&font));
+        println!("App version:    {}", file.app_version());
+        println!("Units per em:   {units_per_em}");
+        if !axes.is_empty() {
+            println!("Axes:           {}", axes.join(", "));
+        }
+        println!("Masters:        {}", masters.join(", "));
+        println!("Instances:      {instances}");
+        println!("Glyphs:         {glyphs}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(args: &Args) -> CommandResult {
+    args.expect_positional(2, 2)?;
+    let mut font = load(&args.positional[0])?;
+    match args.value("--to") {
+        None => {}
+        Some("3") => font.upgrade_in_place(),
+        Some("2") => font.downgrade_in_place(),
+        Some(other) => return Err(format!("unknown format version {other}").into()),
+    }
+    font.save(Path::new(&args.positional[1]))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+// Report every syntax error in a file which could not be parsed
+fn syntax_errors(args: &Args, path: &str) -> CommandResult {
+    let (_, errors) = Plist::parse_recovering(&fs::read_to_string(path)?);
+    if args.flag("--json") {
+        let entries: Vec<Value> = errors
+            .iter()
+            .map(|error| json!({"severity": "error", "location": path, "message": error.to_string()}))
+            .collect();
+        print_json(&Value::Array(entries))?;
+    } else {
+        for error in &errors {
+            println!("error: {path}: {error}");
+        }
+    }
+    Ok(ExitCode::FAILURE)
+}
+
+fn validate(args: &Args) -> CommandResult {
+    args.expect_positional(1, 1)?;
+    let path = &args.positional[0];
+    let diagnostics = match Font::load(Path::new(path)) {
+        Ok(font) => font.validate(),
+        Err(glyphslib::Error::Parse(_)) if Path::new(path).is_file() => {
+            return syntax_errors(args, path)
+        }
+        Err(error) => return Err(format!("{path}: {error}").into()),
+    };
+    if args.flag("--json") {
+        let entries: Vec<Value> = diagnostics
+            .iter()
+            .map(|d| {
+                json!({
+                    "severity": match d.severity {
+                        Severity::Warning => "warning",
+                        Severity::Error => "error",
+                    },
+                    "location": d.location.to_string(),
+                    "message": d.message,
+                })
+            })
+            .collect();
+        print_json(&Value::Array(entries))?;
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{diagnostic}");
+        }
+    }
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn count_map(counts: &BTreeMap<Option<String>, usize>) -> BTreeMap<String, usize> {
+    counts
+        .iter()
+        .map(|(key, count)| (key.clone().unwrap_or_else(|| "(unset)".to_string()), *count))
+        .collect()
+}
+
+fn stats(args: &Args) -> CommandResult {
+    args.expect_positional(1, 1)?;
+    let font = load(&args.positional[0])?.upgrade();
+    let glyphs3 = font.as_glyphs3().unwrap();
+    let stats = glyphs3.stats();
+    let master_name = |id: &str| {
+        glyphs3
+            .masters
+            .iter()
+            .find(|m| m.id == id)
+            .map_or(id.to_string(), |m| m.name.clone())
+    };
+    let by_category = count_map(&stats.glyphs_by_category);
+    let by_script = count_map(&stats.glyphs_by_script);
+    if args.flag("--json") {
+        let blocks: Vec<Value> = stats
+            .unicode_blocks
+            .iter()
+            .map(|b| json!({"name": b.name, "covered": b.covered, "size": b.size()}))
+            .collect();
+        let kerning: serde_json::Map<String, Value> = stats
+            .kerning_pairs
+            .iter()
+            .map(|(id, count)| (master_name(id), json!(count)))
+            .collect();
+        print_json(&json!({
+            "glyphs": stats.glyph_count,
+            "by_category": by_category,
+            "by_script": by_script,
+            "encoded": stats.encoded_glyphs,
+            "unencoded": stats.unencoded_glyphs,
+            "unicode_blocks": blocks,
+            "kerning_pairs": kerning,
+            "component_glyphs": stats.component_glyphs,
+            "outline_glyphs": stats.outline_glyphs,
+            "mixed_glyphs": stats.mixed_glyphs,
+            "empty_glyphs": stats.empty_glyphs,
+            "average_nodes": stats.average_nodes,
+        }))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    println!("Glyphs: {}", stats.glyph_count);
+    println!(
+        "  encoded {}, unencoded {}",
+        stats.encoded_glyphs, stats.unencoded_glyphs
+    );
+    println!(
+        "  outlines {}, components {}, mixed {}, empty {}",
+        stats.outline_glyphs, stats.component_glyphs, stats.mixed_glyphs, stats.empty_glyphs
+    );
+    println!(
+        "  average nodes per master layer: {:.1}",
+        stats.average_nodes
+    );
+    println!("By category:");
+    for (category, count) in by_category {
+        println!("  {category}: {count}");
+    }
+    println!("By script:");
+    for (script, count) in by_script {
+        println!("  {script}: {count}");
+    }
+    println!("Unicode blocks:");
+    for block in &stats.unicode_blocks {
+        println!("  {}: {}/{}", block.name, block.covered, block.size());
+    }
+    println!("Kerning pairs:");
+    for (id, count) in &stats.kerning_pairs {
+        println!("  {}: {count}", master_name(id));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn as_plist(font: &Font) -> Result<Plist, Box<dyn Error>> {
+    Ok(Plist::parse(&font.to_string()?)?)
+}
+
+fn difference_to_json(difference: &Difference) -> Value {
+    match difference {
+        Difference::Removed { path, value } => {
+            json!({"path": path, "kind": "removed", "left": plist_to_json(value)})
+        }
+        Difference::Added { path, value } => {
+            json!({"path": path, "kind": "added", "right": plist_to_json(value)})
+        }
+        Difference::Changed { path, left, right } => json!({
+            "path": path,
+            "kind": "changed",
+            "left": plist_to_json(left),
+            "right": plist_to_json(right),
+        }),
+    }
+}
+
+fn point_to_json(point: &Point) -> Value {
+    json!([point.x, point.y])
+}
+
+fn change_to_json(change: &Change) -> Value {
+    match change {
+        Change::GlyphAdded { glyph } => json!({"kind": "glyph added", "glyph": glyph}),
+        Change::GlyphRemoved { glyph } => json!({"kind": "glyph removed", "glyph": glyph}),
+        Change::LayerAdded { glyph, layer } => {
+            json!({"kind": "layer added", "glyph": glyph, "layer": layer})
+        }
+        Change::LayerRemoved { glyph, layer } => {
+            json!({"kind": "layer removed", "glyph": glyph, "layer": layer})
+        }
+        Change::NodeMoved {
+            glyph,
+            layer,
+            path,
+            node,
+            from,
+            to,
+        } => json!({
+            "kind": "node moved",
+            "glyph": glyph,
+            "layer": layer,
+            "path": path,
+            "node": node,
+            "from": point_to_json(from),
+            "to": point_to_json(to),
+        }),
+        Change::PathsChanged { glyph, layer } => {
+            json!({"kind": "paths changed", "glyph": glyph, "layer": layer})
+        }
+        Change::ComponentsChanged { glyph, layer } => {
+            json!({"kind": "components changed", "glyph": glyph, "layer": layer})
+        }
+        Change::AnchorChanged {
+            glyph,
+            layer,
+            anchor,
+            from,
+            to,
+        } => json!({
+            "kind": "anchor changed",
+            "glyph": glyph,
+            "layer": layer,
+            "anchor": anchor,
+            "from": from.as_ref().map(point_to_json),
+            "to": to.as_ref().map(point_to_json),
+        }),
+        Change::WidthChanged {
+            glyph,
+            layer,
+            from,
+            to,
+        } => json!({
+            "kind": "width changed",
+            "glyph": glyph,
+            "layer": layer,
+            "from": from,
+            "to": to,
+        }),
+        Change::KerningChanged {
+            direction,
+            master,
+            left,
+            right,
+            from,
+            to,
+        } => json!({
+            "kind": "kerning changed",
+            "direction": match direction {
+                KerningDirection::LeftToRight => "LTR",
+                KerningDirection::RightToLeft => "RTL",
+                KerningDirection::Vertical => "vertical",
+            },
+            "master": master,
+            "left": left,
+            "right": right,
+            "from": from,
+            "to": to,
+        }),
+        Change::ParameterChanged {
+            location,
+            name,
+            from,
+            to,
+        } => json!({
+            "kind": "parameter changed",
+            "location": location.to_string(),
+            "name": name,
+            "from": from.as_ref().map(plist_to_json),
+            "to": to.as_ref().map(plist_to_json),
+        }),
+        Change::PropertyChanged {
+            location,
+            difference,
+        } => json!({
+            "kind": "property changed",
+            "location": location.to_string(),
+            "difference": difference_to_json(difference),
+        }),
+    }
+}
+
+// Glyphs 2 fonts are compared as they would be upgraded, so fonts of
+// either version can be compared
+fn diff_fonts(args: &Args) -> CommandResult {
+    args.expect_positional(2, 2)?;
+    let left = load(&args.positional[0])?;
+    let right = load(&args.positional[1])?;
+    let differences = left.diff(&right);
+    if args.flag("--json") {
+        print_json(&Value::Array(
+            differences.changes.iter().map(change_to_json).collect(),
+        ))?;
+    } else {
+        print!("{differences}");
+    }
+    if differences.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn normalize(args: &Args) -> CommandResult {
+    args.expect_positional(1, 2)?;
+    let input = &args.positional[0];
+    let output = args.positional.get(1).unwrap_or(input);
+    load(input)?.save(Path::new(output))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn dump(args: &Args) -> CommandResult {
+    args.expect_positional(1, 2)?;
+    let Plist::Dictionary(mut font) = as_plist(&load(&args.positional[0])?)? else {
+        return Err("font is not a dictionary".into());
+    };
+    let mut writer: Box<dyn Write> = match args.positional.get(1) {
+        Some(path) => Box::new(BufWriter::new(fs::File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    if args.flag("--ndjson") {
+        let glyphs = font.remove("glyphs");
+        serde_json::to_writer(&mut writer, &plist_to_json(&Plist::Dictionary(font)))?;
+        writeln!(writer)?;
+        for glyph in glyphs
+            .as_ref()
+            .and_then(Plist::as_array)
+            .unwrap_or_default()
+        {
+            serde_json::to_writer(&mut writer, &plist_to_json(glyph))?;
+            writeln!(writer)?;
+        }
+    } else {
+        serde_json::to_writer(&mut writer, &plist_to_json(&Plist::Dictionary(font)))?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn load_json(args: &Args) -> CommandResult {
+    args.expect_positional(2, 2)?;
+    let reader: Box<dyn BufRead> = match args.positional[0].as_str() {
+        "-" => Box::new(BufReader::new(io::stdin().lock())),
+        path => Box::new(BufReader::new(fs::File::open(path)?)),
+    };
+    let plist = if args.flag("--ndjson") {
+        let mut lines = reader
+            .lines()
+            .filter(|l| l.as_ref().map_or(true, |l| !l.trim().is_empty()));
+        let header = lines.next().ok_or("empty NDJSON input")??;
+        let Plist::Dictionary(mut font) = json_to_plist(&serde_json::from_str(&header)?)? else {
+            return Err("the first NDJSON line must be an object".into());
+        };
+        let glyphs = lines
+            .map(|line| Ok(json_to_plist(&serde_json::from_str(&line?)?)?))
+            .collect::<Result<Vec<Plist>, Box<dyn Error>>>()?;
+        font.insert("glyphs".into(), Plist::Array(glyphs));
+        Plist::Dictionary(font)
+    } else {
+        let mut contents = String::new();
+        let mut reader = reader;
+        reader.read_to_string(&mut contents)?;
+        json_to_plist(&serde_json::from_str(&contents)?)?
+    };
+    Font::load_str(&plist.to_string())?.save(Path::new(&args.positional[1]))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run() -> CommandResult {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        return Err(USAGE.into());
+    };
+    let args = Args::parse(args)?;
+    match command.as_str() {
+        "info" => info(&args),
+        "convert" => convert(&args),
+        "validate" => validate(&args),
+        "stats" => stats(&args),
+        "diff" => diff_fonts(&args),
+        "normalize" => normalize(&args),
+        "dump" => dump(&args),
+        "load" => load_json(&args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("unknown command {command}\n\n{USAGE}").into()),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_to_glyphs2() {
+        let input = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../glyphslib/resources/RadioCanadaDisplay.glyphs"
+        );
+        let output = std::env::temp_dir().join("glyphs-tool-convert.glyphs");
+        let args = ["--to", "2", input, output.to_str().unwrap()];
+        let args = Args::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(convert(&args).unwrap(), ExitCode::SUCCESS);
+        let converted = Font::load(&output).unwrap();
+        assert_eq!(format_version(&converted), 2);
+        assert_eq!(
+            converted.font().glyphs().len(),
+            load(input).unwrap().font().glyphs().len()
+        );
+        fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn diff_uses_font_diff() {
+        let input = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../glyphslib/resources/RadioCanadaDisplay.glyphs"
+        );
+        let output = std::env::temp_dir().join("glyphs-tool-diff.glyphs");
+        let diff_with = |other: &str| {
+            let args = Args::parse([input, other].into_iter().map(String::from)).unwrap();
+            diff_fonts(&args).unwrap()
+        };
+        assert_eq!(diff_with(input), ExitCode::SUCCESS);
+
+        let mut font = load(input).unwrap();
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        glyphs3.glyph_mut("A").unwrap().layers[0].width += 10.0;
+        font.save(&output).unwrap();
+        assert_eq!(diff_with(output.to_str().unwrap()), ExitCode::FAILURE);
+        let differences = load(input).unwrap().diff(&font);
+        assert_eq!(
+            change_to_json(&differences.changes[0])["kind"],
+            "width changed"
+        );
+        fs::remove_file(output).unwrap();
+    }
+}
@@ -0,0 +1,408 @@
+//! # glyphslib-py
+//!
+//! Python bindings for glyphslib, built with [pyo3](https://pyo3.rs).
+//!
+//! The Python objects are views into a single shared font: a `Glyph` or
+//! `Layer` refers to its position in the font it came from, so changes made
+//! through its properties are visible from every other view and are written
+//! out by `Font.save`.
+//!
+//! ```python
+//! import glyphslib_rs
+//!
+//! font = glyphslib_rs.Font.load("MyFont.glyphs")
+//! font.upgrade()
+//! for glyph in font.glyphs3.glyphs:
+//!     print(glyph.name, [layer.width for layer in glyph.layers])
+//! font.save("MyFont-out.glyphs")
+//! ```
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use glyphslib::{
+    common::NodeType,
+    glyphs3::{self, Shape},
+};
+use pyo3::{
+    exceptions::{PyIOError, PyIndexError, PyValueError},
+    prelude::*,
+};
+
+type SharedFont = Arc<RwLock<glyphslib::Font>>;
+// A node as `(x, y, type, smooth)`
+type PyNode = (f64, f64, &'static str, bool);
+
+fn glyphs3_of<R>(font: &SharedFont, f: impl FnOnce(&glyphs3::Glyphs3) -> R) -> PyResult<R> {
+    let font = font.read().unwrap();
+    font.as_glyphs3()
+        .map(f)
+        .ok_or_else(|| PyValueError::new_err("font is not in Glyphs 3 format; call upgrade()"))
+}
+
+fn glyphs3_of_mut<R>(font: &SharedFont, f: impl FnOnce(&mut glyphs3::Glyphs3) -> R) -> PyResult<R> {
+    let mut font = font.write().unwrap();
+    match &mut *font {
+        glyphslib::Font::Glyphs3(glyphs3) => Ok(f(glyphs3)),
+        glyphslib::Font::Glyphs2(_) => Err(PyValueError::new_err(
+            "font is not in Glyphs 3 format; call upgrade()",
+        )),
+    }
+}
+
+// The names Glyphs' Python API gives node types; smoothness is separate
+fn node_type_name(node_type: NodeType) -> &'static str {
+    match node_type {
+        NodeType::Line | NodeType::LineSmooth => "line",
+        NodeType::Curve | NodeType::CurveSmooth => "curve",
+        NodeType::QCurve | NodeType::QCurveSmooth => "qcurve",
+        NodeType::OffCurve => "offcurve",
+    }
+}
+
+fn stale() -> PyErr {
+    PyIndexError::new_err("glyph or layer no longer exists in the font")
+}
+
+/// A Glyphs source file, either version 2 or 3
+#[pyclass(module = "glyphslib_rs")]
+struct Font {
+    inner: SharedFont,
+}
+
+#[pymethods]
+impl Font {
+    /// Load a `.glyphs` file or `.glyphspackage` directory
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        let font = glyphslib::Font::load(&path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        Ok(Font {
+            inner: Arc::new(RwLock::new(font)),
+        })
+    }
+
+    /// Parse a font from the contents of a `.glyphs` file
+    #[staticmethod]
+    fn loads(contents: &str) -> PyResult<Self> {
+        let font = glyphslib::Font::load_str(contents)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Font {
+            inner: Arc::new(RwLock::new(font)),
+        })
+    }
+
+    /// Save the font, as a package if the path ends in `.glyphspackage`
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        self.inner
+            .read()
+            .unwrap()
+            .save(&path)
+            .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    /// Serialize the font to the contents of a `.glyphs` file
+    fn dumps(&self) -> PyResult<String> {
+        self.inner
+            .read()
+            .unwrap()
+            .to_string()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Convert a Glyphs 2 font to Glyphs 3 in place
+    fn upgrade(&self) {
+        self.inner.write().unwrap().upgrade_in_place();
+    }
+
+    /// The file format version, 2 or 3
+    #[getter]
+    fn format_version(&self) -> u8 {
+        match &*self.inner.read().unwrap() {
+            glyphslib::Font::Glyphs2(_) => 2,
+            glyphslib::Font::Glyphs3(_) => 3,
+        }
+    }
+
+    /// The Glyphs 3 document, or `None` for a Glyphs 2 font
+    #[getter]
+    fn glyphs3(&self) -> Option<Glyphs3> {
+        (self.format_version() == 3).then(|| Glyphs3 {
+            font: self.inner.clone(),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        let version = self.format_version();
+        let font = self.inner.read().unwrap();
+        format!("<Font '{}' (Glyphs {version})>", font.font().family_name())
+    }
+}
+
+/// The contents of a Glyphs 3 file
+#[pyclass(module = "glyphslib_rs")]
+struct Glyphs3 {
+    font: SharedFont,
+}
+
+#[pymethods]
+impl Glyphs3 {
+    /// The family name of the font
+    #[getter]
+    fn family_name(&self) -> PyResult<String> {
+        glyphs3_of(&self.font, |f| f.family_name.clone())
+    }
+
+    #[setter]
+    fn set_family_name(&self, value: String) -> PyResult<()> {
+        glyphs3_of_mut(&self.font, |f| f.family_name = value)
+    }
+
+    /// The units per em of the font
+    #[getter]
+    fn units_per_em(&self) -> PyResult<i32> {
+        glyphs3_of(&self.font, |f| f.units_per_em)
+    }
+
+    #[setter]
+    fn set_units_per_em(&self, value: i32) -> PyResult<()> {
+        glyphs3_of_mut(&self.font, |f| f.units_per_em = value)
+    }
+
+    /// The IDs of the masters, in file order
+    #[getter]
+    fn master_ids(&self) -> PyResult<Vec<String>> {
+        glyphs3_of(&self.font, |f| {
+            f.masters.iter().map(|m| m.id.clone()).collect()
+        })
+    }
+
+    /// The glyphs of the font
+    #[getter]
+    fn glyphs(&self) -> PyResult<Vec<Glyph>> {
+        let count = glyphs3_of(&self.font, |f| f.glyphs.len())?;
+        Ok((0..count)
+            .map(|index| Glyph {
+                font: self.font.clone(),
+                index,
+            })
+            .collect())
+    }
+
+    /// Look up a glyph by name
+    fn glyph(&self, name: &str) -> PyResult<Option<Glyph>> {
+        let index = glyphs3_of(&self.font, |f| f.glyphs.iter().position(|g| g.name == name))?;
+        Ok(index.map(|index| Glyph {
+            font: self.font.clone(),
+            index,
+        }))
+    }
+
+    fn __len__(&self) -> PyResult<usize> {
+        glyphs3_of(&self.font, |f| f.glyphs.len())
+    }
+}
+
+/// A glyph of a Glyphs 3 font
+#[pyclass(module = "glyphslib_rs")]
+struct Glyph {
+    font: SharedFont,
+    index: usize,
+}
+
+impl Glyph {
+    fn with<R>(&self, f: impl FnOnce(&glyphs3::Glyph) -> R) -> PyResult<R> {
+        glyphs3_of(&self.font, |font| font.glyphs.get(self.index).map(f))?.ok_or_else(stale)
+    }
+
+    fn with_mut<R>(&self, f: impl FnOnce(&mut glyphs3::Glyph) -> R) -> PyResult<R> {
+        glyphs3_of_mut(&self.font, |font| font.glyphs.get_mut(self.index).map(f))?.ok_or_else(stale)
+    }
+}
+
+#[pymethods]
+impl Glyph {
+    /// The name of the glyph
+    #[getter]
+    fn name(&self) -> PyResult<String> {
+        self.with(|g| g.name.clone())
+    }
+
+    #[setter]
+    fn set_name(&self, value: String) -> PyResult<()> {
+        self.with_mut(|g| g.name = value)
+    }
+
+    /// Whether the glyph is exported
+    #[getter]
+    fn export(&self) -> PyResult<bool> {
+        self.with(|g| g.export)
+    }
+
+    #[setter]
+    fn set_export(&self, value: bool) -> PyResult<()> {
+        self.with_mut(|g| g.export = value)
+    }
+
+    /// The Unicode code points of the glyph
+    #[getter]
+    fn unicodes(&self) -> PyResult<Vec<u32>> {
+        self.with(|g| g.unicode.clone())
+    }
+
+    #[setter]
+    fn set_unicodes(&self, value: Vec<u32>) -> PyResult<()> {
+        self.with_mut(|g| g.unicode = value)
+    }
+
+    /// The category of the glyph, if set explicitly
+    #[getter]
+    fn category(&self) -> PyResult<Option<String>> {
+        self.with(|g| g.category.clone())
+    }
+
+    #[setter]
+    fn set_category(&self, value: Option<String>) -> PyResult<()> {
+        self.with_mut(|g| g.category = value)
+    }
+
+    /// The layers of the glyph
+    #[getter]
+    fn layers(&self) -> PyResult<Vec<Layer>> {
+        let count = self.with(|g| g.layers.len())?;
+        Ok((0..count)
+            .map(|index| Layer {
+                font: self.font.clone(),
+                glyph: self.index,
+                index,
+            })
+            .collect())
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        self.with(|g| format!("<Glyph '{}'>", g.name))
+    }
+}
+
+/// A layer of a glyph
+#[pyclass(module = "glyphslib_rs")]
+struct Layer {
+    font: SharedFont,
+    glyph: usize,
+    index: usize,
+}
+
+impl Layer {
+    fn with<R>(&self, f: impl FnOnce(&glyphs3::Layer) -> R) -> PyResult<R> {
+        glyphs3_of(&self.font, |font| {
+            font.glyphs
+                .get(self.glyph)
+                .and_then(|g| g.layers.get(self.index))
+                .map(f)
+        })?
+        .ok_or_else(stale)
+    }
+
+    fn with_mut<R>(&self, f: impl FnOnce(&mut glyphs3::Layer) -> R) -> PyResult<R> {
+        glyphs3_of_mut(&self.font, |font| {
+            font.glyphs
+                .get_mut(self.glyph)
+                .and_then(|g| g.layers.get_mut(self.index))
+                .map(f)
+        })?
+        .ok_or_else(stale)
+    }
+}
+
+#[pymethods]
+impl Layer {
+    /// The unique ID of the layer
+    #[getter]
+    fn layer_id(&self) -> PyResult<String> {
+        self.with(|l| l.layer_id.clone())
+    }
+
+    /// The master this layer belongs to, if it is not itself a master layer
+    #[getter]
+    fn associated_master_id(&self) -> PyResult<Option<String>> {
+        self.with(|l| l.associated_master_id.clone())
+    }
+
+    /// The name of the layer
+    #[getter]
+    fn name(&self) -> PyResult<Option<String>> {
+        self.with(|l| l.name.clone())
+    }
+
+    #[setter]
+    fn set_name(&self, value: Option<String>) -> PyResult<()> {
+        self.with_mut(|l| l.name = value)
+    }
+
+    /// The advance width of the layer
+    #[getter]
+    fn width(&self) -> PyResult<f64> {
+        self.with(|l| l.width)
+    }
+
+    #[setter]
+    fn set_width(&self, value: f64) -> PyResult<()> {
+        self.with_mut(|l| l.width = value)
+    }
+
+    /// The paths of the layer as lists of `(x, y, type, smooth)` node tuples
+    ///
+    /// The type is one of `"line"`, `"curve"`, `"qcurve"` or `"offcurve"`,
+    /// as in Glyphs' own Python API.
+    #[getter]
+    fn paths(&self) -> PyResult<Vec<Vec<PyNode>>> {
+        self.with(|l| {
+            l.shapes
+                .iter()
+                .filter_map(|shape| match shape {
+                    Shape::Path(path) => Some(
+                        path.nodes
+                            .iter()
+                            .map(|n| {
+                                (
+                                    n.x,
+                                    n.y,
+                                    node_type_name(n.node_type),
+                                    n.node_type.is_smooth(),
+                                )
+                            })
+                            .collect(),
+                    ),
+                    Shape::Component(_) => None,
+                })
+                .collect()
+        })
+    }
+
+    /// The components of the layer as `(glyph name, (x, y))` tuples
+    #[getter]
+    fn components(&self) -> PyResult<Vec<(String, (f64, f64))>> {
+        self.with(|l| {
+            l.shapes
+                .iter()
+                .filter_map(|shape| match shape {
+                    Shape::Component(c) => Some((c.component_glyph.clone(), c.position.into())),
+                    Shape::Path(_) => None,
+                })
+                .collect()
+        })
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        self.with(|l| format!("<Layer '{}'>", l.layer_id))
+    }
+}
+
+#[pymodule]
+fn glyphslib_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Font>()?;
+    m.add_class::<Glyphs3>()?;
+    m.add_class::<Glyph>()?;
+    m.add_class::<Layer>()?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! # glyphslib-wasm
+//!
+//! WebAssembly bindings for glyphslib, built with
+//! [wasm-bindgen](https://rustwasm.github.io/wasm-bindgen/).
+//!
+//! Fonts are loaded from and saved to byte arrays, since there is no
+//! filesystem in the browser.
+use std::collections::HashMap;
+
+use glyphslib::Font;
+use wasm_bindgen::prelude::*;
+
+// Glyphs 2 and 3 glyphs have these fields in common, but are different types
+macro_rules! glyph_info {
+    ($glyph:expr) => {
+        GlyphInfo {
+            name: $glyph.name.clone(),
+            unicodes: $glyph.unicode.clone(),
+            category: $glyph.category.clone(),
+            export: $glyph.export,
+            layer_count: $glyph.layers.len(),
+        }
+    };
+}
+
+fn js_error(error: impl std::fmt::Display) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// A Glyphs source file, either version 2 or 3
+#[wasm_bindgen]
+pub struct GlyphsFont {
+    font: Font,
+}
+
+#[wasm_bindgen]
+impl GlyphsFont {
+    /// Parse a font from the bytes of a `.glyphs` file
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<GlyphsFont, JsError> {
+        // Detects the encoding as loading a file does
+        let font = Font::from_reader(bytes).map_err(js_error)?;
+        Ok(GlyphsFont { font })
+    }
+
+    /// Load a font from the files of a `.glyphspackage`
+    ///
+    /// `paths` holds paths relative to the package root, such as
+    /// `fontinfo.plist` or `glyphs/A_.glyph`, and `contents` the text of the
+    /// file at the same index.
+    #[wasm_bindgen(js_name = fromPackageEntries)]
+    pub fn from_package_entries(
+        paths: Vec<String>,
+        contents: Vec<String>,
+    ) -> Result<GlyphsFont, JsError> {
+        if paths.len() != contents.len() {
+            return Err(JsError::new("paths and contents must have the same length"));
+        }
+        let entries: HashMap<String, String> = paths.into_iter().zip(contents).collect();
+        let font = Font::load_package_entries(&entries).map_err(js_error)?;
+        Ok(GlyphsFont { font })
+    }
+
+    /// Serialize the font to the bytes of a `.glyphs` file
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.font.to_string().map_err(js_error)?.into_bytes())
+    }
+
+    /// Convert a Glyphs 2 font to Glyphs 3 in place
+    pub fn upgrade(&mut self) {
+        self.font.upgrade_in_place();
+    }
+
+    /// The file format version, 2 or 3
+    #[wasm_bindgen(getter, js_name = formatVersion)]
+    pub fn format_version(&self) -> u8 {
+        match self.font {
+            Font::Glyphs2(_) => 2,
+            Font::Glyphs3(_) => 3,
+        }
+    }
+
+    /// The family name of the font
+    #[wasm_bindgen(getter, js_name = familyName)]
+    pub fn family_name(&self) -> String {
+        self.font.font().family_name().to_string()
+    }
+
+    /// The names of the masters, in file order
+    #[wasm_bindgen(js_name = masterNames)]
+    pub fn master_names(&self) -> Vec<String> {
+        self.font
+            .font()
+            .masters()
+            .iter()
+            .map(|m| m.name().to_string())
+            .collect()
+    }
+
+    /// The names of the glyphs, in file order
+    #[wasm_bindgen(js_name = glyphNames)]
+    pub fn glyph_names(&self) -> Vec<String> {
+        self.font
+            .font()
+            .glyphs()
+            .iter()
+            .map(|g| g.name().to_string())
+            .collect()
+    }
+
+    /// Metadata about the glyph with the given name, if there is one
+    pub fn glyph(&self, name: &str) -> Option<GlyphInfo> {
+        match &self.font {
+            Font::Glyphs2(font) => font.glyph(name).map(|glyph| glyph_info!(glyph)),
+            Font::Glyphs3(font) => font.glyph(name).map(|glyph| glyph_info!(glyph)),
+        }
+    }
+}
+
+/// A summary of one glyph
+#[wasm_bindgen(getter_with_clone)]
+pub struct GlyphInfo {
+    /// The name of the glyph
+    pub name: String,
+    /// The Unicode code points of the glyph
+    pub unicodes: Vec<u32>,
+    /// The category of the glyph, if set explicitly
+    pub category: Option<String>,
+    /// Whether the glyph is exported
+    pub export: bool,
+    /// The number of layers in the glyph
+    #[wasm_bindgen(js_name = layerCount)]
+    pub layer_count: usize,
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    common::Point,
+    geometry::Affine,
+    glyphs3::{Component, Glyphs3, Layer},
+};
+
+// The layer of a component's glyph matching a layer, or its master's
+fn component_layer<'a>(
+    font: &'a Glyphs3,
+    component: &Component,
+    layer: &Layer,
+) -> Option<&'a Layer> {
+    let master_id = layer
+        .associated_master_id
+        .as_ref()
+        .unwrap_or(&layer.layer_id);
+    let glyph = font.glyph(&component.component_glyph)?;
+    glyph
+        .layers
+        .iter()
+        .find(|l| l.layer_id == layer.layer_id)
+        .or_else(|| glyph.layer_for_master(master_id))
+}
+
+// Whether Glyphs places a component itself. Glyphs writes the position it
+// worked out for components with no alignment of their own, so those are
+// taken as they are.
+fn is_aligned(font: &Glyphs3, component: &Component) -> bool {
+    if !component.alignment_explicit {
+        return false;
+    }
+    match component.alignment {
+        -1 => false,
+        0 => !font.settings.disables_automatic_alignment,
+        _ => true,
+    }
+}
+
+// The anchor of the base a mark attaches to and the mark's own anchor
+fn attachment(
+    component: &Component,
+    mark: &Layer,
+    base: &BTreeMap<String, Point>,
+) -> Option<(Point, Point)> {
+    let mark_anchor = |name: &str| {
+        mark.anchors
+            .iter()
+            .find(|a| a.name.strip_prefix('_') == Some(name))
+            .map(|a| a.pos)
+    };
+    if let Some(target) = &component.anchor {
+        // An anchor such as `top_2` of a ligature takes a `_top` mark
+        let name = match target.rsplit_once('_') {
+            Some((name, index)) if index.chars().all(|c| c.is_ascii_digit()) => name,
+            _ => target,
+        };
+        let own = mark_anchor(target).or_else(|| mark_anchor(name))?;
+        return Some((*base.get(target)?, own));
+    }
+    mark.anchors.iter().find_map(|anchor| {
+        let name = anchor.name.strip_prefix('_')?;
+        Some((*base.get(name)?, anchor.pos))
+    })
+}
+
+impl Layer {
+    /// Where the components of the layer go, in order, with automatic
+    /// alignment applied
+    ///
+    /// Components with no alignment of their own or whose alignment is
+    /// disabled keep their position, as do all components of a font which disables automatic alignment unless
+    /// their alignment is forced. Of the others, the first goes at the
+    /// origin. The next attach by anchors: a mark with an `_top` anchor
+    /// goes where the `top` anchor of the components before it is, or on
+    /// the anchor the component names. A mark passes its own anchors on to
+    /// the marks after it. A component with nothing to attach to goes after
+    /// the advance width of the one before it. Horizontally aligned
+    /// components keep their vertical position. Only the anchors a glyph
+    /// has itself are used, not those of its components.
+    pub fn resolved_component_positions(&self, font: &Glyphs3) -> Vec<Point> {
+        let mut positions = vec![];
+        let mut anchors: BTreeMap<String, Point> = BTreeMap::new();
+        let mut advance = 0.0;
+        for component in self.components() {
+            let Some(layer) = component_layer(font, component, self) else {
+                positions.push(component.position);
+                continue;
+            };
+            let linear = component.transform_at(Point::ZERO);
+            let mut position = if !is_aligned(font, component) {
+                component.position
+            } else if positions.is_empty() {
+                Point::ZERO
+            } else if let Some((base, own)) = attachment(component, layer, &anchors) {
+                base - linear.apply(own)
+            } else {
+                Point::new(advance, 0.0)
+            };
+            if component.alignment == 3 {
+                position.y = component.position.y;
+            }
+            positions.push(position);
+            let transform = component.transform_at(position);
+            for anchor in layer.anchors.iter().filter(|a| !a.name.starts_with('_')) {
+                anchors.insert(anchor.name.clone(), transform.apply(anchor.pos));
+            }
+            advance = position.x + layer.width;
+        }
+        positions
+    }
+
+    // The transforms placing the components of the layer, in order, with
+    // automatic alignment applied
+    pub(crate) fn component_transforms(&self, font: &Glyphs3) -> Vec<Affine> {
+        if !self.components().any(|c| is_aligned(font, c)) {
+            return self
+                .components()
+                .map(Component::effective_transform)
+                .collect();
+        }
+        self.components()
+            .zip(self.resolved_component_positions(font))
+            .map(|(component, position)| component.transform_at(position))
+            .collect()
+    }
+}
+
+impl Component {
+    /// Where the component goes in a layer, with automatic alignment
+    /// applied as [`Layer::resolved_component_positions`] does
+    ///
+    /// Returns the component's own position if it is not in the layer.
+    pub fn resolved_position(&self, font: &Glyphs3, layer: &Layer) -> Point {
+        let index = layer
+            .components()
+            .position(|component| std::ptr::eq(component, self))
+            .or_else(|| layer.components().position(|component| component == self));
+        match index {
+            Some(index) => layer.resolved_component_positions(font)[index],
+            None => self.position,
+        }
+    }
+
+    // The transform of the component as if it were at a position
+    pub(crate) fn transform_at(&self, position: Point) -> Affine {
+        Component {
+            position,
+            ..self.clone()
+        }
+        .effective_transform()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyphs3::{Anchor, Glyph, Shape};
+
+    fn anchor(name: &str, pos: (f64, f64)) -> Anchor {
+        Anchor {
+            locked: false,
+            name: name.to_string(),
+            orientation: Default::default(),
+            pos: pos.into(),
+            user_data: None,
+        }
+    }
+
+    fn component(name: &str, alignment: i8) -> Shape {
+        Shape::Component(Component {
+            component_glyph: name.to_string(),
+            alignment,
+            alignment_explicit: true,
+            position: Point::new(5.0, 7.0),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_resolved_component_positions() {
+        let file = std::path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let crate::Font::Glyphs3(mut font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        font.settings.disables_automatic_alignment = false;
+        let master_id = font.masters[0].id.clone();
+        let mut template = font.glyphs[0]
+            .layers
+            .iter()
+            .find(|l| l.layer_id == master_id)
+            .unwrap()
+            .clone();
+        template.shapes = vec![];
+        template.anchors = vec![];
+        let glyphs = [
+            ("base", vec![anchor("top", (250.0, 500.0))], 500.0),
+            (
+                "mark",
+                vec![anchor("_top", (0.0, 450.0)), anchor("top", (0.0, 600.0))],
+                0.0,
+            ),
+            ("other", vec![], 300.0),
+        ];
+        for (name, anchors, width) in glyphs {
+            let mut layer = template.clone();
+            layer.anchors = anchors;
+            layer.width = width;
+            font.glyphs.push(Glyph {
+                name: name.to_string(),
+                layers: vec![layer],
+                ..Default::default()
+            });
+        }
+        let mut layer = template.clone();
+        layer.shapes = vec![
+            component("base", 0),
+            component("mark", 0),
+            component("mark", 3),
+            component("mark", -1),
+            component("other", 1),
+        ];
+        assert_eq!(
+            layer.resolved_component_positions(&font),
+            [
+                (0.0, 0.0),
+                (250.0, 50.0),
+                (250.0, 7.0),
+                (5.0, 7.0),
+                (5.0, 0.0),
+            ]
+            .map(Point::from)
+        );
+        let Shape::Component(mark) = &layer.shapes[1] else {
+            unreachable!()
+        };
+        assert_eq!(
+            mark.resolved_position(&font, &layer),
+            Point::new(250.0, 50.0)
+        );
+        font.settings.disables_automatic_alignment = true;
+        assert_eq!(
+            layer.resolved_component_positions(&font)[1],
+            Point::new(5.0, 7.0)
+        );
+    }
+}
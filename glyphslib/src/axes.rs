@@ -0,0 +1,322 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    common::{AxisLocation, CustomParameter},
+    glyphs3::{Glyphs3, Instance, Master},
+};
+
+/// A location in user or design space, keyed by axis tag
+pub type Location = BTreeMap<String, f64>;
+
+/// The mapping of one axis between user coordinates, as shown to the font
+/// user, and design coordinates, as in `axesValues`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AxisMapping {
+    /// The user-facing name of the axis
+    pub name: String,
+    /// The OpenType tag of the axis
+    pub tag: String,
+    /// Pairs of user and design coordinates, sorted by user coordinate
+    ///
+    /// Between the pairs the mapping is linear. An empty list maps each
+    /// coordinate to itself.
+    pub points: Vec<(f64, f64)>,
+}
+
+// Map through sorted pairs, shifting by the nearest pair beyond them
+fn piecewise_linear(points: impl Iterator<Item = (f64, f64)> + Clone, value: f64) -> f64 {
+    let mut below = None;
+    for (from, to) in points.clone() {
+        if value == from {
+            return to;
+        }
+        if value < from {
+            return match below {
+                Some((below_from, below_to)) => {
+                    below_to + (to - below_to) * (value - below_from) / (from - below_from)
+                }
+                None => value + to - from,
+            };
+        }
+        below = Some((from, to));
+    }
+    match below {
+        Some((from, to)) => value + to - from,
+        None => value,
+    }
+}
+
+impl AxisMapping {
+    /// Whether each coordinate maps to itself
+    pub fn is_identity(&self) -> bool {
+        self.points.iter().all(|(user, design)| user == design)
+    }
+
+    /// The design coordinate of a user coordinate
+    pub fn to_design(&self, user: f64) -> f64 {
+        piecewise_linear(self.points.iter().copied(), user)
+    }
+
+    /// The user coordinate of a design coordinate
+    pub fn to_user(&self, design: f64) -> f64 {
+        piecewise_linear(
+            self.points.iter().map(|&(user, design)| (design, user)),
+            design,
+        )
+    }
+
+    /// The mapping as an `avar` segment map, given the default user coordinate
+    ///
+    /// Both sides are normalized to -1 for the smallest coordinate of the
+    /// mapping, 0 for the default and 1 for the largest, as in the `avar`
+    /// table. The result is empty if the mapping is the identity, since the
+    /// axis then needs no segment map.
+    pub fn avar(&self, default: f64) -> Vec<(f64, f64)> {
+        if self.is_identity() {
+            return vec![];
+        }
+        let normalize = |value: f64, min: f64, default: f64, max: f64| {
+            if value < default && min < default {
+                (value - default) / (default - min)
+            } else if value > default && max > default {
+                (value - default) / (max - default)
+            } else {
+                0.0
+            }
+        };
+        let (user_min, user_max) = (self.points[0].0, self.points[self.points.len() - 1].0);
+        let design_default = self.to_design(default);
+        let (design_min, design_max) = (self.to_design(user_min), self.to_design(user_max));
+        let mut segments = vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)];
+        for &(user, design) in &self.points {
+            let from = normalize(user, user_min, default, user_max);
+            let to = normalize(design, design_min, design_default, design_max);
+            if !segments.iter().any(|(f, _)| *f == from) {
+                segments.push((from, to));
+            }
+        }
+        segments.sort_by(|a, b| a.0.total_cmp(&b.0));
+        segments
+    }
+}
+
+fn axis_locations(parameters: &[CustomParameter]) -> Option<Vec<AxisLocation>> {
+    parameters
+        .iter()
+        .find(|p| p.name == "Axis Location" && !p.disabled)?
+        .as_axis_locations()
+}
+
+// The user coordinate an `Axis Location` parameter gives for an axis
+fn user_coordinate(parameters: &[CustomParameter], axis: &str) -> Option<f64> {
+    axis_locations(parameters)?
+        .into_iter()
+        .find(|l| l.axis == axis)
+        .map(|l| l.location)
+}
+
+impl Glyphs3 {
+    /// The master at the default location of the variable font
+    ///
+    /// This is the master named by the `Variable Font Origin` custom parameter,
+    /// or the first master if the parameter is absent.
+    pub fn default_master(&self) -> Option<&Master> {
+        let origin = self
+            .custom_parameters
+            .iter()
+            .find(|p| p.name == "Variable Font Origin" && !p.disabled)
+            .and_then(|p| p.value.as_str());
+        origin
+            .and_then(|id| self.masters.iter().find(|m| m.id == id))
+            .or_else(|| self.masters.first())
+    }
+
+    /// The mapping between user and design coordinates of each axis
+    ///
+    /// As when Glyphs exports a variable font, the `Axis Mappings` parameter
+    /// of the font gives the mapping of the axes it lists. Other axes are
+    /// mapped by the `Axis Location` parameters of the masters, or failing
+    /// that of the instances, which pair a user coordinate with the design
+    /// coordinate in `axesValues`. An axis with none of these has the
+    /// identity mapping.
+    pub fn axis_mappings(&self) -> Vec<AxisMapping> {
+        let explicit = self
+            .custom_parameters
+            .iter()
+            .find(|p| p.name == "Axis Mappings" && !p.disabled)
+            .and_then(|p| p.as_axis_mappings())
+            .unwrap_or_default();
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(ix, axis)| {
+                let points = explicit.get(&axis.tag).cloned().unwrap_or_else(|| {
+                    let from = |located: Vec<(&[CustomParameter], &[f64])>| {
+                        let mut points = BTreeMap::new();
+                        for (parameters, values) in located {
+                            if let (Some(user), Some(design)) =
+                                (user_coordinate(parameters, &axis.name), values.get(ix))
+                            {
+                                points.entry(user.to_bits()).or_insert((user, *design));
+                            }
+                        }
+                        let mut points: Vec<_> = points.into_values().collect();
+                        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+                        points
+                    };
+                    let masters = from(
+                        self.masters
+                            .iter()
+                            .map(|m| (&m.custom_parameters[..], &m.axes_values[..]))
+                            .collect(),
+                    );
+                    if !masters.is_empty() {
+                        return masters;
+                    }
+                    from(
+                        self.instances
+                            .iter()
+                            .map(|i| (&i.custom_parameters[..], &i.axes_values[..]))
+                            .collect(),
+                    )
+                });
+                AxisMapping {
+                    name: axis.name.clone(),
+                    tag: axis.tag.clone(),
+                    points,
+                }
+            })
+            .collect()
+    }
+
+    /// The `avar` segment maps of the axes which need one, keyed by axis tag
+    ///
+    /// Each axis is normalized around the user coordinate of the
+    /// [default master](Self::default_master).
+    pub fn avar(&self) -> BTreeMap<String, Vec<(f64, f64)>> {
+        let default = self.default_master();
+        self.axis_mappings()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(ix, mapping)| {
+                let design = default.and_then(|m| m.axes_values.get(ix)).copied()?;
+                let segments = mapping.avar(mapping.to_user(design));
+                (!segments.is_empty()).then_some((mapping.tag, segments))
+            })
+            .collect()
+    }
+
+    fn user_location_of(
+        &self,
+        parameters: &[CustomParameter],
+        coordinates: &[f64],
+        mappings: &[AxisMapping],
+    ) -> Location {
+        self.axes
+            .iter()
+            .zip(coordinates)
+            .zip(mappings)
+            .map(|((axis, design), mapping)| {
+                let user = user_coordinate(parameters, &axis.name)
+                    .unwrap_or_else(|| mapping.to_user(*design));
+                (axis.tag.clone(), user)
+            })
+            .collect()
+    }
+
+    /// The user space location of a master
+    ///
+    /// This is what its `Axis Location` parameter gives for each axis, or
+    /// else its design location mapped through [`axis_mappings`](Self::axis_mappings).
+    pub fn master_user_location(&self, master: &Master) -> Location {
+        self.user_location_of(
+            &master.custom_parameters,
+            &master.axes_values,
+            &self.axis_mappings(),
+        )
+    }
+
+    /// The design space location of an instance
+    pub fn instance_location(&self, instance: &Instance) -> Location {
+        self.axes
+            .iter()
+            .zip(&instance.axes_values)
+            .map(|(axis, value)| (axis.tag.clone(), *value))
+            .collect()
+    }
+
+    /// The user space location of an instance, found as for a master
+    pub fn instance_user_location(&self, instance: &Instance) -> Location {
+        self.user_location_of(
+            &instance.custom_parameters,
+            &instance.axes_values,
+            &self.axis_mappings(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn load(file: &str) -> Glyphs3 {
+        let file = path::Path::new("resources").join(file);
+        match Font::load(&file).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_mapping_from_master_axis_locations() {
+        let font = load("WghtVar_AxisLocation.glyphs");
+        let mappings = font.axis_mappings();
+        assert_eq!(
+            mappings[0].points,
+            vec![(400.0, 0.0), (500.0, 8.0), (700.0, 10.0)]
+        );
+        assert_eq!(mappings[0].to_design(600.0), 9.0);
+        assert_eq!(mappings[0].to_user(4.0), 450.0);
+        // Beyond the mapping, coordinates shift with the nearest point
+        assert_eq!(mappings[0].to_design(800.0), 110.0);
+        let medium = &font.masters[1];
+        assert_eq!(
+            font.master_user_location(medium),
+            Location::from([("wght".to_string(), 500.0)])
+        );
+        assert_eq!(
+            font.avar()["wght"],
+            vec![(-1.0, -1.0), (0.0, 0.0), (1.0 / 3.0, 0.8), (1.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_mapping_from_axis_mappings_parameter() {
+        let mut font = load("Oswald-AE-comb.glyphs");
+        let mapping = &font.axis_mappings()[0];
+        assert_eq!(mapping.tag, "wght");
+        assert_eq!(mapping.points[1], (300.0, 275.0));
+        assert_eq!(mapping.to_user(560.0), 500.0);
+        let master = font.masters[0].clone();
+        assert_eq!(
+            font.master_user_location(&master)["wght"],
+            mapping.to_user(master.axes_values[0])
+        );
+
+        font.instances[0].axes_values = vec![644.0];
+        font.instances[0].custom_parameters.clear();
+        let instance = &font.instances[0];
+        assert_eq!(font.instance_location(instance)["wght"], 644.0);
+        assert_eq!(font.instance_user_location(instance)["wght"], 600.0);
+    }
+
+    #[test]
+    fn test_identity_mapping() {
+        let mapping = AxisMapping::default();
+        assert!(mapping.is_identity());
+        assert_eq!(mapping.to_design(123.0), 123.0);
+        assert!(mapping.avar(100.0).is_empty());
+    }
+}
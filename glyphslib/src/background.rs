@@ -0,0 +1,78 @@
+use crate::glyphs3::Layer;
+
+impl Layer {
+    // The background of the layer, made empty if it has none; backgrounds
+    // have no ID or width of their own, so none are written for them
+    fn background_mut(&mut self) -> &mut Layer {
+        self.background
+            .get_or_insert_with(|| Box::new(Layer::empty(String::new(), 0.0)))
+    }
+
+    /// Replace the shapes and anchors of the background with copies of those
+    /// of the layer, as Glyphs' Copy to Background does
+    ///
+    /// Anything else in the background, such as its guides, is kept.
+    pub fn copy_to_background(&mut self) {
+        let (shapes, anchors) = (self.shapes.clone(), self.anchors.clone());
+        let background = self.background_mut();
+        background.shapes = shapes;
+        background.anchors = anchors;
+    }
+
+    /// Exchange the shapes and anchors of the layer with those of its
+    /// background, as Glyphs' Swap with Background does
+    ///
+    /// A layer without a background gets one holding its shapes and
+    /// anchors, and is left empty.
+    pub fn swap_with_background(&mut self) {
+        let (mut shapes, mut anchors) = (
+            std::mem::take(&mut self.shapes),
+            std::mem::take(&mut self.anchors),
+        );
+        let background = self.background_mut();
+        std::mem::swap(&mut background.shapes, &mut shapes);
+        std::mem::swap(&mut background.anchors, &mut anchors);
+        self.shapes = shapes;
+        self.anchors = anchors;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_background_sync() {
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let master_id = &font.masters[0].id;
+        let mut layer = font
+            .glyph("eacute")
+            .unwrap()
+            .layer_for_master(master_id)
+            .unwrap()
+            .clone();
+        layer.background = None;
+        let shapes = layer.shapes.clone();
+        layer.copy_to_background();
+        let background = layer.background.as_ref().unwrap();
+        assert_eq!(background.shapes, shapes);
+        assert_eq!(background.anchors, layer.anchors);
+
+        layer.background.as_mut().unwrap().shapes.pop();
+        layer.swap_with_background();
+        assert_eq!(layer.shapes.len(), shapes.len() - 1);
+        assert_eq!(layer.background.as_ref().unwrap().shapes, shapes);
+
+        // The background is written without an ID or width, and read back
+        let text = openstep_plist::ser::to_string(&layer).unwrap();
+        let background = openstep_plist::ser::to_string(&layer.background).unwrap();
+        assert!(!background.contains("layerId") && !background.contains("width"));
+        let reloaded: Layer = openstep_plist::from_str(&text).unwrap();
+        assert_eq!(reloaded.background, layer.background);
+    }
+}
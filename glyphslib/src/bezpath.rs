@@ -0,0 +1,324 @@
+use kurbo::{BezPath, PathEl, Point};
+
+use crate::{common::NodeType, error::Error, glyphs2, glyphs3};
+
+// Draw the segments of a path, given as its nodes. Glyphs keeps the start of
+// a closed path as its last node, so drawing starts after the last on-curve
+// node and comes back round to it; an open path starts at its first node.
+fn to_bez_path(nodes: &[(Point, NodeType)], closed: bool) -> BezPath {
+    let mut bez = BezPath::new();
+    let last_on_curve = nodes.iter().rposition(|(_, t)| !t.is_off_curve());
+    let (start, rest) = match (closed, last_on_curve) {
+        (true, Some(last)) => {
+            let rest = nodes[last + 1..].iter().chain(&nodes[..=last]);
+            (nodes[last].0, rest.copied().collect::<Vec<_>>())
+        }
+        // A closed quadratic contour can be all off-curves, and then starts
+        // at the implied on-curve between its last and first nodes
+        (true, None) if !nodes.is_empty() => {
+            let start = nodes[nodes.len() - 1].0.midpoint(nodes[0].0);
+            let rest = nodes.iter().copied().chain([(start, NodeType::QCurve)]);
+            (start, rest.collect())
+        }
+        (false, _) => match nodes.iter().position(|(_, t)| !t.is_off_curve()) {
+            Some(first) => (nodes[first].0, nodes[first + 1..].to_vec()),
+            None => return bez,
+        },
+        _ => return bez,
+    };
+    bez.move_to(start);
+    let mut offs = Vec::new();
+    for (ix, &(point, node_type)) in rest.iter().enumerate() {
+        if node_type.is_off_curve() {
+            offs.push(point);
+            continue;
+        }
+        match (node_type.base_type(), offs.as_slice()) {
+            // The close draws the line back to the start
+            (_, []) if closed && ix == rest.len() - 1 => {}
+            (_, []) => bez.line_to(point),
+            // TrueType-style quadratic splines have implied on-curve points
+            // between consecutive off-curves
+            (NodeType::QCurve, offs) => {
+                for (off_ix, off) in offs.iter().enumerate() {
+                    let next = offs.get(off_ix + 1).map_or(point, |n| off.midpoint(*n));
+                    bez.quad_to(*off, next);
+                }
+            }
+            (_, [off]) => bez.quad_to(*off, point),
+            // Glyphs draws a cubic segment from its first and last off-curves
+            (_, [first, .., last]) => bez.curve_to(*first, *last, point),
+        }
+        offs.clear();
+    }
+    if closed {
+        bez.close_path();
+    }
+    bez
+}
+
+// The nodes of a path drawn as a single contour, and whether it is closed
+fn from_bez_path(bez: &BezPath) -> Result<(Vec<(Point, NodeType)>, bool), Error> {
+    let mut nodes = Vec::new();
+    let mut closed = false;
+    for (ix, element) in bez.elements().iter().enumerate() {
+        match *element {
+            _ if closed => {
+                return Err(Error::BezPath("more than one contour".to_string()));
+            }
+            PathEl::MoveTo(point) if ix == 0 => nodes.push((point, NodeType::Line)),
+            PathEl::MoveTo(_) => {
+                return Err(Error::BezPath("more than one contour".to_string()));
+            }
+            _ if ix == 0 => {
+                return Err(Error::BezPath("does not start with a move".to_string()));
+            }
+            PathEl::LineTo(point) => nodes.push((point, NodeType::Line)),
+            PathEl::QuadTo(off, point) => {
+                nodes.extend([(off, NodeType::OffCurve), (point, NodeType::QCurve)]);
+            }
+            PathEl::CurveTo(first, second, point) => nodes.extend([
+                (first, NodeType::OffCurve),
+                (second, NodeType::OffCurve),
+                (point, NodeType::Curve),
+            ]),
+            PathEl::ClosePath => closed = true,
+        }
+    }
+    if closed && nodes.len() > 1 {
+        // The start becomes the last node: the end of the segment coming
+        // back to it, or of the line the close draws
+        if nodes[nodes.len() - 1].0 == nodes[0].0 {
+            nodes.remove(0);
+        } else {
+            nodes.rotate_left(1);
+        }
+    }
+    Ok((nodes, closed))
+}
+
+macro_rules! impl_bez_path {
+    ($path:ty, $build:expr) => {
+        /// Draws the path with kurbo
+        ///
+        /// Smoothness is not kept. A cubic segment with more than two
+        /// off-curves is drawn through its first and last, and off-curves
+        /// ending an open path are dropped.
+        impl From<&$path> for BezPath {
+            fn from(path: &$path) -> Self {
+                let nodes: Vec<(Point, NodeType)> = path
+                    .nodes
+                    .iter()
+                    .map(|n| (Point::new(n.x, n.y), n.node_type))
+                    .collect();
+                to_bez_path(&nodes, path.closed)
+            }
+        }
+
+        /// Makes a path from a kurbo path of a single contour
+        ///
+        /// Fails if the kurbo path has more than one contour or does not
+        /// start with a move.
+        impl TryFrom<&BezPath> for $path {
+            type Error = Error;
+
+            fn try_from(bez: &BezPath) -> Result<Self, Error> {
+                let (nodes, closed) = from_bez_path(bez)?;
+                Ok($build(closed, nodes))
+            }
+        }
+    };
+}
+
+impl_bez_path!(glyphs2::Path, |closed, nodes: Vec<(Point, NodeType)>| {
+    glyphs2::Path {
+        closed,
+        nodes: nodes
+            .into_iter()
+            .map(|(point, node_type)| glyphs2::Node {
+                x: point.x,
+                y: point.y,
+                node_type,
+            })
+            .collect(),
+    }
+});
+impl_bez_path!(glyphs3::Path, |closed, nodes: Vec<(Point, NodeType)>| {
+    glyphs3::Path {
+        attr: Default::default(),
+        closed,
+        nodes: nodes
+            .into_iter()
+            .map(|(point, node_type)| glyphs3::Node {
+                x: point.x,
+                y: point.y,
+                node_type,
+                user_data: None,
+            })
+            .collect(),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(closed: bool, nodes: &[(f64, f64, NodeType)]) -> glyphs3::Path {
+        glyphs3::Path {
+            closed,
+            nodes: nodes
+                .iter()
+                .map(|&(x, y, node_type)| glyphs3::Node {
+                    x,
+                    y,
+                    node_type,
+                    user_data: None,
+                })
+                .collect(),
+            attr: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_open_path() {
+        let open = path(
+            false,
+            &[
+                (0.0, 0.0, NodeType::Line),
+                (100.0, 0.0, NodeType::Line),
+                (100.0, 100.0, NodeType::Line),
+            ],
+        );
+        let bez = BezPath::from(&open);
+        assert_eq!(bez.to_svg(), "M0,0 L100,0 L100,100");
+        assert_eq!(glyphs3::Path::try_from(&bez).unwrap(), open);
+    }
+
+    #[test]
+    fn test_closed_path() {
+        // Starting at its last node, whose line the close draws
+        let square = path(
+            true,
+            &[
+                (100.0, 0.0, NodeType::Line),
+                (100.0, 100.0, NodeType::Line),
+                (0.0, 100.0, NodeType::Line),
+                (0.0, 0.0, NodeType::Line),
+            ],
+        );
+        let bez = BezPath::from(&square);
+        assert_eq!(bez.to_svg(), "M0,0 L100,0 L100,100 L0,100 Z");
+        assert_eq!(glyphs3::Path::try_from(&bez).unwrap(), square);
+
+        // A close drawing no line of its own
+        let bez = BezPath::from_svg("M0,0 L100,0 L0,100 L0,0 Z").unwrap();
+        let triangle = glyphs3::Path::try_from(&bez).unwrap();
+        assert_eq!(triangle.nodes.len(), 3);
+        assert_eq!(
+            (triangle.nodes[2].x, triangle.nodes[2].y),
+            (0.0, 0.0),
+            "The start is the last node"
+        );
+    }
+
+    #[test]
+    fn test_curves() {
+        let curved = path(
+            true,
+            &[
+                (0.0, 50.0, NodeType::OffCurve),
+                (50.0, 100.0, NodeType::OffCurve),
+                (100.0, 100.0, NodeType::CurveSmooth),
+                (150.0, 50.0, NodeType::OffCurve),
+                (100.0, 0.0, NodeType::QCurve),
+                (0.0, 0.0, NodeType::Line),
+            ],
+        );
+        let bez = BezPath::from(&curved);
+        assert_eq!(bez.to_svg(), "M0,0 C0,50 50,100 100,100 Q150,50 100,0 Z");
+        let back = glyphs3::Path::try_from(&bez).unwrap();
+        // Smoothness is lost on the way
+        assert_eq!(back.nodes[2].node_type, NodeType::Curve);
+        let types: Vec<NodeType> = back.nodes.iter().map(|n| n.node_type).collect();
+        let expected: Vec<NodeType> = curved
+            .nodes
+            .iter()
+            .map(|n| n.node_type.with_smooth(false))
+            .collect();
+        assert_eq!(types, expected);
+
+        let glyphs2_path: glyphs2::Path = glyphs2::Path::try_from(&bez).unwrap();
+        assert_eq!(BezPath::from(&glyphs2_path), bez);
+    }
+
+    #[test]
+    fn test_off_curves() {
+        // Consecutive quadratic off-curves imply on-curves between them
+        let spline = path(
+            false,
+            &[
+                (0.0, 0.0, NodeType::Line),
+                (0.0, 100.0, NodeType::OffCurve),
+                (100.0, 100.0, NodeType::OffCurve),
+                (100.0, 0.0, NodeType::QCurve),
+                (200.0, 0.0, NodeType::OffCurve),
+            ],
+        );
+        // And a trailing off-curve goes nowhere
+        assert_eq!(
+            BezPath::from(&spline).to_svg(),
+            "M0,0 Q0,100 50,100 Q100,100 100,0"
+        );
+
+        // A contour of only off-curves starts between its last and first
+        let circle = path(
+            true,
+            &[
+                (0.0, 100.0, NodeType::OffCurve),
+                (100.0, 100.0, NodeType::OffCurve),
+                (100.0, 0.0, NodeType::OffCurve),
+                (0.0, 0.0, NodeType::OffCurve),
+            ],
+        );
+        assert_eq!(
+            BezPath::from(&circle).to_svg(),
+            "M0,50 Q0,100 50,100 Q100,100 100,50 Q100,0 50,0 Q0,0 0,50 Z"
+        );
+
+        // Leading off-curves of an open path are dropped
+        let open = path(
+            false,
+            &[(0.0, 50.0, NodeType::OffCurve), (0.0, 0.0, NodeType::Line)],
+        );
+        assert_eq!(BezPath::from(&open).to_svg(), "M0,0");
+
+        // Cubic segments take their first and last off-curves
+        let crowded = path(
+            false,
+            &[
+                (0.0, 0.0, NodeType::Line),
+                (0.0, 50.0, NodeType::OffCurve),
+                (25.0, 75.0, NodeType::OffCurve),
+                (50.0, 100.0, NodeType::OffCurve),
+                (100.0, 100.0, NodeType::Curve),
+            ],
+        );
+        assert_eq!(
+            BezPath::from(&crowded).to_svg(),
+            "M0,0 C0,50 50,100 100,100"
+        );
+    }
+
+    #[test]
+    fn test_bez_path_errors() {
+        let two = BezPath::from_svg("M0,0 L100,0 Z M0,0 L0,100").unwrap();
+        assert!(matches!(
+            glyphs3::Path::try_from(&two),
+            Err(Error::BezPath(_))
+        ));
+        assert!(matches!(
+            glyphs2::Path::try_from(&two),
+            Err(Error::BezPath(_))
+        ));
+    }
+}
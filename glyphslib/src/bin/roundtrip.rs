@@ -1,10 +1,60 @@
-use std::path::Path;
+use std::{fs, path::Path, process::ExitCode};
 
-fn main() {
-    let input = std::env::args().nth(1).expect("Please provide a file path");
-    let output = std::env::args()
-        .nth(2)
-        .expect("Please provide an output file path");
+use openstep_plist::{diff::diff, Plist};
+
+const USAGE: &str = "Usage: roundtrip [--check] [--ignore <path>]... <input> <output>
+
+  --check          Re-read the output and compare it with the input
+  --ignore <path>  Skip differences at or below <path> when checking, where
+                   <path> is like `glyphs[*].lastChange`; `*` matches any
+                   key or array index";
+
+// The plist as written on disk. A package is read back through the library,
+// as it has no single file to parse.
+fn read_plist(path: &Path) -> Result<Plist, Box<dyn std::error::Error>> {
+    if path.is_dir() {
+        return Ok(Plist::parse(&glyphslib::Font::load(path)?.to_string()?)?);
+    }
+    Ok(Plist::parse(&fs::read_to_string(path)?)?)
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    match (pattern.chars().next(), path.chars().next()) {
+        (None, None) => true,
+        // A pattern matches everything below it
+        (None, Some('.' | '[')) => true,
+        (None, Some(_)) => false,
+        (Some('*'), _) => {
+            let segment_end = path.find(['.', '[', ']']).unwrap_or(path.len());
+            (0..=segment_end).any(|ix| matches(&pattern[1..], &path[ix..]))
+        }
+        (Some(p), Some(c)) if p == c => matches(&pattern[p.len_utf8()..], &path[c.len_utf8()..]),
+        _ => false,
+    }
+}
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut ignores = vec![];
+    let mut positional = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--ignore" => match args.next() {
+                Some(path) => ignores.push(path),
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            _ => positional.push(arg),
+        }
+    }
+    let [input, output] = positional.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
     let start = std::time::Instant::now();
     let font = glyphslib::Font::load(Path::new(&input)).expect("Failed to read font file");
     let elapsed = start.elapsed();
@@ -14,4 +64,39 @@ fn main() {
         .expect("Failed to save font file");
     let elapsed = start.elapsed();
     println!("Saved font in: {elapsed:?}");
+    if !check {
+        return ExitCode::SUCCESS;
+    }
+
+    let before = read_plist(Path::new(input)).expect("Failed to parse input");
+    let after = read_plist(Path::new(output)).expect("Failed to parse output");
+    let differences: Vec<_> = diff(&before, &after)
+        .into_iter()
+        .filter(|d| !ignores.iter().any(|pattern| matches(pattern, d.path())))
+        .collect();
+    if differences.is_empty() {
+        println!("Round trip OK");
+        return ExitCode::SUCCESS;
+    }
+    for difference in &differences {
+        println!("{difference}");
+    }
+    println!("{} differences found", differences.len());
+    ExitCode::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn ignore_patterns() {
+        assert!(matches(".appVersion", ".appVersion"));
+        assert!(matches("glyphs", "glyphs[3].layers[0].width"));
+        assert!(matches("glyphs[*].lastChange", "glyphs[12].lastChange"));
+        assert!(matches("glyphs[*]", "glyphs[12].lastChange"));
+        assert!(matches("*.userData", "fontMaster.userData"));
+        assert!(!matches("glyphs[*].lastChange", "glyphs[12].layers[0]"));
+        assert!(!matches("glyph", "glyphs[0]"));
+    }
 }
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    common::{Point, Version},
+    glyphs3::{
+        Anchor, Axis, Glyph, Glyphs3, Layer, Master, Metric, MetricType, MetricValue, Shape,
+    },
+    ids::new_id,
+};
+
+// The metrics of a new font in Glyphs, with their positions and overshoots
+const DEFAULT_METRICS: [(MetricType, f64, f64); 5] = [
+    (MetricType::Ascender, 800.0, 16.0),
+    (MetricType::CapHeight, 700.0, 16.0),
+    (MetricType::XHeight, 500.0, 16.0),
+    (MetricType::Baseline, 0.0, -16.0),
+    (MetricType::Descender, -200.0, -16.0),
+];
+
+/// Builds a Glyphs 3 font from scratch
+///
+/// The font starts as Glyphs makes a new one: 1000 units per em, version
+/// 1.000, and the usual ascender, cap height, x-height, baseline and
+/// descender metrics. If no master is added, the font gets a single
+/// "Regular" master. Masters are given fresh IDs unless they set their own,
+/// and every glyph gets a layer for every master.
+///
+/// ```
+/// use glyphslib::builder::{GlyphBuilder, Glyphs3Builder, MasterBuilder};
+///
+/// let font = Glyphs3Builder::new("My Font")
+///     .axis("Weight", "wght")
+///     .master(MasterBuilder::new("Light").axes_values([300.0]))
+///     .master(MasterBuilder::new("Bold").axes_values([700.0]))
+///     .glyph(GlyphBuilder::new("space").unicode(0x20).width(250.0))
+///     .build();
+/// assert_eq!(font.glyph("space").unwrap().layers.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Glyphs3Builder {
+    family_name: String,
+    units_per_em: i32,
+    version: Version,
+    axes: Vec<Axis>,
+    masters: Vec<MasterBuilder>,
+    glyphs: Vec<GlyphBuilder>,
+}
+
+impl Glyphs3Builder {
+    /// Start a font with the given family name
+    pub fn new(family_name: &str) -> Self {
+        Glyphs3Builder {
+            family_name: family_name.to_string(),
+            units_per_em: 1000,
+            version: Version { major: 1, minor: 0 },
+            axes: vec![],
+            masters: vec![],
+            glyphs: vec![],
+        }
+    }
+
+    /// Set the units per em
+    pub fn units_per_em(mut self, units_per_em: i32) -> Self {
+        self.units_per_em = units_per_em;
+        self
+    }
+
+    /// Set the version of the font
+    pub fn version(mut self, major: i32, minor: i32) -> Self {
+        self.version = Version { major, minor };
+        self
+    }
+
+    /// Add an axis; masters give their coordinates on the axes in this order
+    pub fn axis(mut self, name: &str, tag: &str) -> Self {
+        self.axes.push(Axis {
+            name: name.to_string(),
+            tag: tag.to_string(),
+            hidden: false,
+        });
+        self
+    }
+
+    /// Add a master
+    pub fn master(mut self, master: MasterBuilder) -> Self {
+        self.masters.push(master);
+        self
+    }
+
+    /// Add a glyph
+    pub fn glyph(mut self, glyph: GlyphBuilder) -> Self {
+        self.glyphs.push(glyph);
+        self
+    }
+
+    /// Build the font
+    pub fn build(self) -> Glyphs3 {
+        let metrics: Vec<Metric> = DEFAULT_METRICS
+            .iter()
+            .map(|(metric_type, _, _)| Metric {
+                metric_type: Some(*metric_type),
+                ..Default::default()
+            })
+            .collect();
+        let mut masters = self.masters;
+        if masters.is_empty() {
+            masters.push(MasterBuilder::new("Regular"));
+        }
+        let masters: Vec<Master> = masters.into_iter().map(MasterBuilder::build).collect();
+        let glyphs = self
+            .glyphs
+            .into_iter()
+            .map(|glyph| glyph.build(&masters))
+            .collect();
+        Glyphs3 {
+            format_version: 3,
+            family_name: self.family_name,
+            units_per_em: self.units_per_em,
+            version: self.version,
+            axes: self.axes,
+            metrics,
+            masters,
+            glyphs,
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds a master for a [`Glyphs3Builder`]
+#[derive(Debug, Clone)]
+pub struct MasterBuilder {
+    name: String,
+    id: Option<String>,
+    axes_values: Vec<f64>,
+    metrics: BTreeMap<usize, f64>,
+}
+
+impl MasterBuilder {
+    /// Start a master with the given name
+    pub fn new(name: &str) -> Self {
+        MasterBuilder {
+            name: name.to_string(),
+            id: None,
+            axes_values: vec![],
+            metrics: BTreeMap::new(),
+        }
+    }
+
+    /// Use the given ID rather than a fresh one
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Set the coordinates of the master, one for each axis of the font
+    pub fn axes_values(mut self, values: impl IntoIterator<Item = f64>) -> Self {
+        self.axes_values = values.into_iter().collect();
+        self
+    }
+
+    /// Move one of the default metrics, such as the x-height, keeping its overshoot
+    ///
+    /// Types other than ascender, cap height, x-height, baseline and
+    /// descender are ignored.
+    pub fn metric(mut self, metric_type: MetricType, position: f64) -> Self {
+        if let Some(ix) = DEFAULT_METRICS
+            .iter()
+            .position(|(t, _, _)| *t == metric_type)
+        {
+            self.metrics.insert(ix, position);
+        }
+        self
+    }
+
+    fn build(self) -> Master {
+        let metric_values = DEFAULT_METRICS
+            .iter()
+            .enumerate()
+            .map(|(ix, (_, pos, over))| MetricValue {
+                pos: self.metrics.get(&ix).copied().unwrap_or(*pos),
+                over: *over,
+            })
+            .collect();
+        Master {
+            id: self.id.unwrap_or_else(new_id),
+            name: self.name,
+            axes_values: self.axes_values,
+            metric_values,
+            visible: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds a glyph for a [`Glyphs3Builder`]
+///
+/// The width, shapes and anchors apply to the glyph's layer in every
+/// master, unless a master's layer is given its own.
+#[derive(Debug, Clone)]
+pub struct GlyphBuilder {
+    name: String,
+    unicode: Vec<u32>,
+    category: Option<String>,
+    export: bool,
+    width: f64,
+    shapes: Vec<Shape>,
+    anchors: Vec<(String, Point)>,
+    master_widths: BTreeMap<String, f64>,
+    master_shapes: BTreeMap<String, Vec<Shape>>,
+}
+
+impl GlyphBuilder {
+    /// Start a glyph with the given name, 600 units wide
+    pub fn new(name: &str) -> Self {
+        GlyphBuilder {
+            name: name.to_string(),
+            unicode: vec![],
+            category: None,
+            export: true,
+            width: 600.0,
+            shapes: vec![],
+            anchors: vec![],
+            master_widths: BTreeMap::new(),
+            master_shapes: BTreeMap::new(),
+        }
+    }
+
+    /// Add a code point the glyph encodes
+    pub fn unicode(mut self, codepoint: u32) -> Self {
+        self.unicode.push(codepoint);
+        self
+    }
+
+    /// Set the category of the glyph, rather than leaving it to the glyph data
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Set whether the glyph is exported
+    pub fn export(mut self, export: bool) -> Self {
+        self.export = export;
+        self
+    }
+
+    /// Set the advance width of the glyph
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Add a path or component
+    pub fn shape(mut self, shape: Shape) -> Self {
+        self.shapes.push(shape);
+        self
+    }
+
+    /// Add an anchor
+    pub fn anchor(mut self, name: &str, pos: impl Into<Point>) -> Self {
+        self.anchors.push((name.to_string(), pos.into()));
+        self
+    }
+
+    /// Set the advance width of the glyph in the master with the given name
+    pub fn master_width(mut self, master: &str, width: f64) -> Self {
+        self.master_widths.insert(master.to_string(), width);
+        self
+    }
+
+    /// Set the shapes of the glyph in the master with the given name
+    pub fn master_shapes(mut self, master: &str, shapes: Vec<Shape>) -> Self {
+        self.master_shapes.insert(master.to_string(), shapes);
+        self
+    }
+
+    fn build(self, masters: &[Master]) -> Glyph {
+        let layers = masters
+            .iter()
+            .map(|master| Layer {
+                anchors: self
+                    .anchors
+                    .iter()
+                    .map(|(name, pos)| Anchor {
+                        locked: false,
+                        name: name.clone(),
+                        orientation: Default::default(),
+                        pos: *pos,
+                        user_data: None,
+                    })
+                    .collect(),
+                annotations: vec![],
+                associated_master_id: None,
+                attr: Default::default(),
+                background: None,
+                background_image: None,
+                color: None,
+                guides: vec![],
+                hints: vec![],
+                layer_id: master.id.clone(),
+                metric_bottom: None,
+                metric_left: None,
+                metric_right: None,
+                metric_top: None,
+                metric_vert_origin: None,
+                metric_vert_width: None,
+                metric_width: None,
+                name: None,
+                part_selection: BTreeMap::new(),
+                shapes: self
+                    .master_shapes
+                    .get(&master.name)
+                    .unwrap_or(&self.shapes)
+                    .clone(),
+                user_data: Default::default(),
+                vert_origin: None,
+                vert_width: None,
+                visible: true,
+                width: self
+                    .master_widths
+                    .get(&master.name)
+                    .copied()
+                    .unwrap_or(self.width),
+                other: Default::default(),
+            })
+            .collect();
+        Glyph {
+            name: self.name,
+            unicode: self.unicode,
+            category: self.category,
+            export: self.export,
+            layers,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::NodeType,
+        compatibility::check_compatibility,
+        glyphs3::{Node, Path},
+        Font,
+    };
+
+    fn square(size: f64) -> Shape {
+        let node = |x, y| Node {
+            x,
+            y,
+            node_type: NodeType::Line,
+            user_data: None,
+        };
+        Shape::Path(Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: vec![
+                node(0.0, 0.0),
+                node(size, 0.0),
+                node(size, size),
+                node(0.0, size),
+            ],
+        })
+    }
+
+    #[test]
+    fn test_default_font() {
+        let font = Glyphs3Builder::new("Test")
+            .glyph(GlyphBuilder::new("a"))
+            .build();
+        assert_eq!(font.units_per_em, 1000);
+        assert_eq!(font.masters.len(), 1);
+        assert_eq!(font.masters[0].name, "Regular");
+        assert_eq!(font.masters[0].metric_values.len(), font.metrics.len());
+        let id = &font.masters[0].id;
+        assert_eq!(id.len(), 36);
+        assert_eq!(id, &id.to_uppercase());
+        assert_eq!(font.glyphs[0].layers[0].layer_id, *id);
+    }
+
+    #[test]
+    fn test_built_font_round_trips() {
+        let font = Glyphs3Builder::new("Test")
+            .units_per_em(2048)
+            .version(2, 5)
+            .axis("Weight", "wght")
+            .master(MasterBuilder::new("Light").axes_values([300.0]))
+            .master(
+                MasterBuilder::new("Bold")
+                    .id("bold")
+                    .axes_values([700.0])
+                    .metric(MetricType::XHeight, 520.0),
+            )
+            .glyph(
+                GlyphBuilder::new("A")
+                    .unicode(0x41)
+                    .shape(square(100.0))
+                    .anchor("top", (50.0, 700.0))
+                    .master_shapes("Bold", vec![square(150.0)])
+                    .master_width("Bold", 650.0),
+            )
+            .glyph(GlyphBuilder::new(".notdef").export(false))
+            .build();
+        assert_ne!(font.masters[0].id, font.masters[1].id);
+        assert_eq!(font.masters[1].id, "bold");
+        assert_eq!(font.masters[1].metric_values[2].pos, 520.0);
+        let a = font.glyph("A").unwrap();
+        assert_eq!((a.layers[0].width, a.layers[1].width), (600.0, 650.0));
+        assert!(check_compatibility(&font).is_compatible());
+
+        let saved = Font::Glyphs3(font.clone()).to_string().unwrap();
+        let Font::Glyphs3(reloaded) = Font::load_str(&saved).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(reloaded, font);
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{
+    glyphs3::{Glyph, Glyphs3, Node, Shape},
+    Error, Result,
+};
+
+/// A single recorded change to a font, which can be undone
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A glyph was inserted at `index` in the glyph list
+    AddGlyph {
+        /// Position of the new glyph
+        index: usize,
+        /// The glyph which was added
+        glyph: Glyph,
+    },
+    /// The glyph at `index` was removed
+    RemoveGlyph {
+        /// Position the glyph was removed from
+        index: usize,
+        /// The glyph which was removed
+        glyph: Glyph,
+    },
+    /// A glyph was renamed, along with the components which refer to it
+    RenameGlyph {
+        /// The old name
+        from: String,
+        /// The new name
+        to: String,
+    },
+    /// A node of a path was replaced
+    SetNode {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer_id: String,
+        /// Index of the path among the shapes of the layer
+        shape: usize,
+        /// Index of the node within the path
+        node: usize,
+        /// The node before the change
+        from: Node,
+        /// The node after the change
+        to: Node,
+    },
+}
+
+impl Change {
+    /// The change which reverses this one
+    pub fn inverse(&self) -> Change {
+        match self.clone() {
+            Change::AddGlyph { index, glyph } => Change::RemoveGlyph { index, glyph },
+            Change::RemoveGlyph { index, glyph } => Change::AddGlyph { index, glyph },
+            Change::RenameGlyph { from, to } => Change::RenameGlyph { from: to, to: from },
+            Change::SetNode {
+                glyph,
+                layer_id,
+                shape,
+                node,
+                from,
+                to,
+            } => Change::SetNode {
+                glyph,
+                layer_id,
+                shape,
+                node,
+                from: to,
+                to: from,
+            },
+        }
+    }
+
+    // Whether applying the change edits a glyph, once it has been applied
+    fn touches(&self, glyph: &Glyph) -> bool {
+        match self {
+            Change::AddGlyph { glyph: added, .. } => glyph.name == added.name,
+            Change::RemoveGlyph { .. } => false,
+            Change::RenameGlyph { to, .. } => {
+                glyph.name == *to
+                    || glyph
+                        .layers
+                        .iter()
+                        .flat_map(|l| l.components())
+                        .any(|c| c.component_glyph == *to)
+            }
+            Change::SetNode { glyph: name, .. } => glyph.name == *name,
+        }
+    }
+
+    fn apply(&self, font: &mut Glyphs3) {
+        match self {
+            Change::AddGlyph { index, glyph } => {
+                font.glyphs.insert(*index, glyph.clone());
+            }
+            Change::RemoveGlyph { index, .. } => {
+                font.glyphs.remove(*index);
+            }
+            Change::RenameGlyph { from, to } => {
+                for glyph in font.glyphs.iter_mut() {
+                    if glyph.name == *from {
+                        glyph.name = to.clone();
+                    }
+                    for shape in glyph.layers.iter_mut().flat_map(|l| l.shapes.iter_mut()) {
+                        if let Shape::Component(component) = shape {
+                            if component.component_glyph == *from {
+                                component.component_glyph = to.clone();
+                            }
+                        }
+                    }
+                }
+            }
+            Change::SetNode {
+                glyph,
+                layer_id,
+                shape,
+                node,
+                to,
+                ..
+            } => {
+                if let Some(target) = node_mut(font, glyph, layer_id, *shape, *node) {
+                    *target = to.clone();
+                }
+            }
+        }
+    }
+}
+
+fn node_mut<'a>(
+    font: &'a mut Glyphs3,
+    glyph: &str,
+    layer_id: &str,
+    shape: usize,
+    node: usize,
+) -> Option<&'a mut Node> {
+    let layer = font
+        .glyph_mut(glyph)?
+        .layers
+        .iter_mut()
+        .find(|l| l.layer_id == layer_id)?;
+    layer
+        .shapes
+        .get_mut(shape)?
+        .as_path_mut()?
+        .nodes
+        .get_mut(node)
+}
+
+/// A record of changes made to a font, for undo, redo and auditing
+///
+/// Changes made through the methods of a `ChangeLog` are applied to the font
+/// and recorded. Changes made to the font directly are not seen, so undoing
+/// after one may not restore the font exactly.
+///
+/// With [`set_track_last_change`](Self::set_track_last_change), each glyph a
+/// change (or its undoing) edits is [touched](Glyph::touch), so that its
+/// `lastChange` is that of the edit.
+///
+/// ```
+/// use glyphslib::{changes::ChangeLog, glyphs3::{Glyph, Glyphs3}};
+///
+/// let mut font = Glyphs3::default();
+/// let mut log = ChangeLog::default();
+/// log.add_glyph(&mut font, Glyph { name: "A".to_string(), ..Default::default() });
+/// log.rename_glyph(&mut font, "A", "A.alt").unwrap();
+/// log.undo(&mut font);
+/// assert_eq!(font.glyphs[0].name, "A");
+/// log.redo(&mut font);
+/// assert_eq!(font.glyphs[0].name, "A.alt");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeLog {
+    done: Vec<Change>,
+    undone: Vec<Change>,
+    track_last_change: bool,
+}
+
+impl ChangeLog {
+    fn apply(&self, font: &mut Glyphs3, change: &Change) {
+        change.apply(font);
+        if self.track_last_change {
+            for glyph in font.glyphs.iter_mut().filter(|g| change.touches(g)) {
+                glyph.touch();
+            }
+        }
+    }
+
+    fn record(&mut self, font: &mut Glyphs3, change: Change) {
+        self.apply(font, &change);
+        self.done.push(change);
+        self.undone.clear();
+    }
+
+    /// Set whether the glyphs changes edit have their `lastChange` updated,
+    /// which is off at first
+    pub fn set_track_last_change(&mut self, track: bool) {
+        self.track_last_change = track;
+    }
+
+    /// The changes made so far, oldest first, not counting undone ones
+    pub fn changes(&self) -> &[Change] {
+        &self.done
+    }
+
+    /// Whether there is a change to undo
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    /// Whether there is an undone change to redo
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Forget all recorded changes, leaving the font as it is
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Reverse the most recent change; returns `false` if there was none
+    pub fn undo(&mut self, font: &mut Glyphs3) -> bool {
+        let Some(change) = self.done.pop() else {
+            return false;
+        };
+        self.apply(font, &change.inverse());
+        self.undone.push(change);
+        true
+    }
+
+    /// Reapply the most recently undone change; returns `false` if there was none
+    pub fn redo(&mut self, font: &mut Glyphs3) -> bool {
+        let Some(change) = self.undone.pop() else {
+            return false;
+        };
+        self.apply(font, &change);
+        self.done.push(change);
+        true
+    }
+
+    /// Add a glyph to the end of the font
+    pub fn add_glyph(&mut self, font: &mut Glyphs3, glyph: Glyph) {
+        let index = font.glyphs.len();
+        self.record(font, Change::AddGlyph { index, glyph });
+    }
+
+    /// Remove a glyph by name, returning it if it was present
+    pub fn remove_glyph(&mut self, font: &mut Glyphs3, name: &str) -> Option<Glyph> {
+        let index = font.glyphs.iter().position(|g| g.name == name)?;
+        let glyph = font.glyphs[index].clone();
+        self.record(
+            font,
+            Change::RemoveGlyph {
+                index,
+                glyph: glyph.clone(),
+            },
+        );
+        Some(glyph)
+    }
+
+    /// Rename a glyph, updating components which refer to it
+    pub fn rename_glyph(&mut self, font: &mut Glyphs3, from: &str, to: &str) -> Result<()> {
+        if font.glyph(from).is_none() {
+            return Err(Error::UnknownGlyph(from.to_string()));
+        }
+        if font.glyph(to).is_some() {
+            return Err(Error::DuplicateGlyph(to.to_string()));
+        }
+        self.record(
+            font,
+            Change::RenameGlyph {
+                from: from.to_string(),
+                to: to.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Replace a node of a path
+    ///
+    /// `shape` indexes the shapes of the layer and must be a path.
+    pub fn set_node(
+        &mut self,
+        font: &mut Glyphs3,
+        glyph: &str,
+        layer_id: &str,
+        shape: usize,
+        node: usize,
+        to: Node,
+    ) -> Result<()> {
+        let from = node_mut(font, glyph, layer_id, shape, node)
+            .ok_or_else(|| Error::NodeOutOfRange {
+                glyph: glyph.to_string(),
+                layer_id: layer_id.to_string(),
+                shape,
+                node,
+            })?
+            .clone();
+        self.record(
+            font,
+            Change::SetNode {
+                glyph: glyph.to_string(),
+                layer_id: layer_id.to_string(),
+                shape,
+                node,
+                from,
+                to,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::NodeType, Font};
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_restores_font() {
+        let original = load();
+        let mut font = original.clone();
+        let mut log = ChangeLog::default();
+        let layer_id = font.masters[0].id.clone();
+
+        log.remove_glyph(&mut font, "B").unwrap();
+        log.rename_glyph(&mut font, "A", "A.ss01").unwrap();
+        let node = Node {
+            x: 1.0,
+            y: 2.0,
+            node_type: NodeType::Line,
+            user_data: None,
+        };
+        log.set_node(&mut font, "C", &layer_id, 0, 0, node.clone())
+            .unwrap();
+        assert!(matches!(
+            log.set_node(&mut font, "C", &layer_id, 0, 9999, node),
+            Err(Error::NodeOutOfRange { node: 9999, .. })
+        ));
+        let edited = font.clone();
+        assert_eq!(log.changes().len(), 3);
+
+        while log.undo(&mut font) {}
+        assert_eq!(font, original);
+        while log.redo(&mut font) {}
+        assert_eq!(font, edited);
+    }
+
+    #[test]
+    fn test_rename_updates_components() {
+        let mut font = load();
+        let mut log = ChangeLog::default();
+        let uses_a = |font: &Glyphs3, name: &str| {
+            font.glyphs.iter().any(|g| {
+                g.layers
+                    .iter()
+                    .flat_map(|l| &l.shapes)
+                    .any(|s| matches!(s, Shape::Component(c) if c.component_glyph == name))
+            })
+        };
+        assert!(uses_a(&font, "A"));
+        log.rename_glyph(&mut font, "A", "A.ss01").unwrap();
+        assert!(!uses_a(&font, "A"));
+        assert!(uses_a(&font, "A.ss01"));
+        assert!(matches!(
+            log.rename_glyph(&mut font, "B", "A.ss01"),
+            Err(Error::DuplicateGlyph(name)) if name == "A.ss01"
+        ));
+        assert!(matches!(
+            log.rename_glyph(&mut font, "A", "A.ss02"),
+            Err(Error::UnknownGlyph(name)) if name == "A"
+        ));
+    }
+
+    #[test]
+    fn test_new_change_clears_redo() {
+        let mut font = load();
+        let mut log = ChangeLog::default();
+        log.remove_glyph(&mut font, "A");
+        log.undo(&mut font);
+        assert!(log.can_redo());
+        log.remove_glyph(&mut font, "B");
+        assert!(!log.can_redo());
+    }
+
+    #[test]
+    fn test_track_last_change() {
+        let mut font = load();
+        for glyph in font.glyphs.iter_mut() {
+            glyph.last_change = None;
+        }
+        let mut log = ChangeLog::default();
+        log.rename_glyph(&mut font, "B", "B.ss01").unwrap();
+        assert!(font.glyphs.iter().all(|g| g.last_change.is_none()));
+
+        log.set_track_last_change(true);
+        log.rename_glyph(&mut font, "A", "A.ss01").unwrap();
+        let touched: Vec<&str> = font
+            .glyphs
+            .iter()
+            .filter(|g| g.last_change.is_some())
+            .map(|g| g.name.as_str())
+            .collect();
+        assert!(touched.contains(&"A.ss01"));
+        assert!(touched.contains(&"Aacute"));
+        assert!(!touched.contains(&"B.ss01"));
+    }
+}
@@ -0,0 +1,297 @@
+use openstep_plist::Plist;
+
+use crate::{
+    common::parameter_list,
+    glyphs3::{BackgroundImage, Glyph, Glyphs3, Layer},
+};
+
+// The palette index Glyphs writes for the text color
+const FOREGROUND: i64 = 0xFFFF;
+
+/// A color of a palette, in 8-bit RGBA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteColor {
+    /// The red component
+    pub red: u8,
+    /// The green component
+    pub green: u8,
+    /// The blue component
+    pub blue: u8,
+    /// The alpha component, 255 for opaque
+    pub alpha: u8,
+}
+
+impl PaletteColor {
+    /// The color of a `Color Palettes` entry: a list of gray, gray and
+    /// alpha, RGB or RGBA values, or the same as a comma-separated string
+    pub fn from_plist(plist: &Plist) -> Option<Self> {
+        let values: Vec<i64> = match plist {
+            Plist::Array(values) => values.iter().map(Plist::as_i64).collect::<Option<_>>()?,
+            Plist::String(text) => text
+                .trim_matches(|c| c == '(' || c == ')')
+                .split(',')
+                .map(|v| v.trim().parse().ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        let values: Vec<u8> = values
+            .into_iter()
+            .map(|v| u8::try_from(v).ok())
+            .collect::<Option<_>>()?;
+        let (red, green, blue, alpha) = match values[..] {
+            [gray] => (gray, gray, gray, 255),
+            [gray, alpha] => (gray, gray, gray, alpha),
+            [red, green, blue] => (red, green, blue, 255),
+            [red, green, blue, alpha] => (red, green, blue, alpha),
+            _ => return None,
+        };
+        Some(PaletteColor {
+            red,
+            green,
+            blue,
+            alpha,
+        })
+    }
+}
+
+/// A layer of a color glyph, drawn in a color of the palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLayer {
+    /// The ID of the glyph layer with the outlines
+    pub layer_id: String,
+    /// The index of the color in the palette, or `None` for the text color
+    pub palette_index: Option<u16>,
+}
+
+/// How a glyph is drawn in color in one master, as a COLR table would have
+/// it: its color layers from bottom to top
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorGlyphDefinition {
+    /// The ID of the master
+    pub master_id: String,
+    /// The layers, in the order they are drawn
+    pub layers: Vec<ColorLayer>,
+}
+
+/// The image of an sbix layer, for one strike of the bitmaps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bitmap<'a> {
+    /// The pixels per em of the strike (`sbixSize`)
+    pub ppem: u16,
+    /// The image, whose path is relative to the font file
+    pub image: &'a BackgroundImage,
+}
+
+impl Layer {
+    /// The SVG image of an SVG layer, placed as its background image, whose
+    /// path is relative to the font file
+    ///
+    /// Returns `None` if this is not an SVG layer or it has no image.
+    pub fn svg_source(&self) -> Option<&BackgroundImage> {
+        self.attr
+            .svg
+            .then_some(self.background_image.as_ref())
+            .flatten()
+    }
+
+    /// The bitmap of an sbix layer
+    ///
+    /// Returns `None` if this is not an sbix layer or it has no image.
+    pub fn bitmap(&self) -> Option<Bitmap<'_>> {
+        Some(Bitmap {
+            ppem: u16::try_from(self.attr.sbix_size?).ok()?,
+            image: self.background_image.as_ref()?,
+        })
+    }
+
+    /// The palette index of a color palette layer (`colorPalette`), where
+    /// `None` within is the text color
+    pub fn palette_index(&self) -> Option<Option<u16>> {
+        if let Some(index) = self.attr.color_palette {
+            return Some(u16::try_from(index).ok().filter(|_| index != FOREGROUND));
+        }
+        // The text color may also be written as `*`
+        match self.attr.other.get("colorPalette")?.as_str()? {
+            "*" => Some(None),
+            _ => None,
+        }
+    }
+}
+
+impl Glyph {
+    /// The color palette layers of the glyph in a master
+    ///
+    /// Returns `None` if the glyph has no color palette layers in the master.
+    pub fn color_glyph_definition(&self, master_id: &str) -> Option<ColorGlyphDefinition> {
+        let layers: Vec<ColorLayer> = self
+            .layers
+            .iter()
+            .filter(|l| l.associated_master_id.as_deref() == Some(master_id))
+            .filter_map(|layer| {
+                Some(ColorLayer {
+                    layer_id: layer.layer_id.clone(),
+                    palette_index: layer.palette_index()?,
+                })
+            })
+            .collect();
+        (!layers.is_empty()).then(|| ColorGlyphDefinition {
+            master_id: master_id.to_string(),
+            layers,
+        })
+    }
+}
+
+impl Glyphs3 {
+    /// The palettes of the `Color Palettes` parameter
+    ///
+    /// Colors which cannot be read, such as CMYK ones, are opaque black, so
+    /// that the palette indices of the others stay as they are.
+    pub fn color_palettes(&self) -> Vec<Vec<PaletteColor>> {
+        let black = PaletteColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        };
+        parameter_list(&self.custom_parameters, "Color Palettes")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Plist::as_array)
+            .map(|palette| {
+                palette
+                    .iter()
+                    .map(|color| PaletteColor::from_plist(color).unwrap_or(black))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The pixel sizes of the sbix strikes, from the smallest
+    pub fn bitmap_strikes(&self) -> Vec<u16> {
+        let mut strikes: Vec<u16> = self
+            .glyphs
+            .iter()
+            .flat_map(|g| g.layers.iter().filter_map(|l| Some(l.bitmap()?.ppem)))
+            .collect();
+        strikes.sort();
+        strikes.dedup();
+        strikes
+    }
+
+    /// The color glyph definitions of every glyph with color palette
+    /// layers, by glyph name, for each master in turn
+    pub fn color_glyph_definitions(&self) -> Vec<(&str, ColorGlyphDefinition)> {
+        self.masters
+            .iter()
+            .flat_map(|master| {
+                self.glyphs.iter().filter_map(|glyph| {
+                    Some((
+                        glyph.name.as_str(),
+                        glyph.color_glyph_definition(&master.id)?,
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{CustomParameter, Point, Scale};
+
+    #[test]
+    fn test_color_palettes_and_layers() {
+        let file = std::path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let crate::Font::Glyphs3(mut font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        font.custom_parameters.push(CustomParameter {
+            name: "Color Palettes".to_string(),
+            value: Plist::parse("((\"255,0,0,255\", (0,0,255), (128)), ((1,2,3,4,5)))").unwrap(),
+            disabled: false,
+        });
+        let red = PaletteColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        };
+        let palettes = font.color_palettes();
+        assert_eq!(palettes.len(), 2);
+        assert_eq!(palettes[0][0], red);
+        assert_eq!(palettes[0][1].blue, 255);
+        assert_eq!(palettes[0][2].green, 128);
+        assert_eq!(palettes[1][0].red, 0);
+
+        let master_id = font.masters[0].id.clone();
+        let glyph = &mut font.glyphs[0];
+        let master_layer = glyph
+            .layers
+            .iter()
+            .find(|l| l.layer_id == master_id)
+            .unwrap()
+            .clone();
+        for (id, palette) in [("back", 1), ("front", FOREGROUND)] {
+            let mut layer = master_layer.clone();
+            layer.layer_id = id.to_string();
+            layer.associated_master_id = Some(master_id.clone());
+            layer.attr.color_palette = Some(palette);
+            glyph.layers.push(layer);
+        }
+        let definition = glyph.color_glyph_definition(&master_id).unwrap();
+        assert_eq!(
+            definition.layers,
+            [
+                ColorLayer {
+                    layer_id: "back".to_string(),
+                    palette_index: Some(1),
+                },
+                ColorLayer {
+                    layer_id: "front".to_string(),
+                    palette_index: None,
+                },
+            ]
+        );
+        let name = glyph.name.clone();
+        let all = font.color_glyph_definitions();
+        assert_eq!(all, [(name.as_str(), definition)]);
+    }
+
+    #[test]
+    fn test_svg_and_bitmap_layers() {
+        let file = std::path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let crate::Font::Glyphs3(mut font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let mut layer = font.glyphs[0].layers[0].clone();
+        assert_eq!(layer.svg_source(), None);
+        assert_eq!(layer.bitmap(), None);
+        let image = BackgroundImage {
+            angle: 0.0,
+            crop: None,
+            image_path: "a.png".to_string(),
+            locked: false,
+            pos: Point::ZERO,
+            scale: Scale::IDENTITY,
+        };
+        layer.background_image = Some(image.clone());
+        assert_eq!(layer.svg_source(), None);
+        layer.attr.sbix_size = Some(64);
+        assert_eq!(
+            layer.bitmap(),
+            Some(Bitmap {
+                ppem: 64,
+                image: &image
+            })
+        );
+        let mut svg = layer.clone();
+        svg.attr.sbix_size = None;
+        svg.attr.svg = true;
+        assert_eq!(svg.svg_source(), Some(&image));
+        let mut large = layer.clone();
+        large.attr.sbix_size = Some(160);
+        font.glyphs[0].layers.extend([large, svg, layer]);
+        assert_eq!(font.bitmap_strikes(), [64, 160]);
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// The OpenType layout classes of the font (`GSClass`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FeatureClass {
     /// Whether the code of the class is generated automatically.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -26,6 +27,7 @@ pub struct FeatureClass {
 
 /// Custom parameter (`GSCustomParameter`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CustomParameter {
     /// Whether the custom parameter is disabled.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -36,8 +38,142 @@ pub struct CustomParameter {
     pub value: Plist,
 }
 
+/// The location of an instance or master on one axis, from the `Axis Location` parameter
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AxisLocation {
+    /// The name of the axis, as in the font's axes
+    pub axis: String,
+    /// The user-facing location on the axis
+    pub location: f64,
+}
+
+// Glyphs writes numbers as strings often enough that both are accepted.
+fn plist_number(value: &Plist) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+impl CustomParameter {
+    /// The value as a number, whether it was written as one or as a string
+    pub fn as_number(&self) -> Option<f64> {
+        plist_number(&self.value)
+    }
+
+    /// The value as a flag, such as that of `Use Typo Metrics`
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_number().map(|n| n != 0.0)
+    }
+
+    /// The value of an `Axes` parameter, which defines the axes of a Glyphs 2 font
+    pub fn as_axes(&self) -> Option<Vec<crate::glyphs3::Axis>> {
+        let axes = self.value.as_array()?.iter().filter_map(|axis| {
+            let axis = axis.as_dict()?;
+            let field = |key| axis.get(key).and_then(Plist::as_str).unwrap_or_default();
+            Some(crate::glyphs3::Axis {
+                name: field("Name").to_string(),
+                tag: field("Tag").to_string(),
+                hidden: axis.contains_key("Hidden"),
+            })
+        });
+        Some(axes.collect())
+    }
+
+    /// The value of an `Axis Location` parameter
+    ///
+    /// Entries without an axis name or a numeric location are skipped.
+    pub fn as_axis_locations(&self) -> Option<Vec<AxisLocation>> {
+        let locations = self.value.as_array()?.iter().filter_map(|entry| {
+            let entry = entry.as_dict()?;
+            Some(AxisLocation {
+                axis: entry.get("Axis")?.as_str()?.to_string(),
+                location: plist_number(entry.get("Location")?)?,
+            })
+        });
+        Some(locations.collect())
+    }
+
+    /// The value of an `Axis Mappings` parameter, keyed by axis tag
+    ///
+    /// Each axis maps user coordinates to design coordinates; the pairs are
+    /// sorted by user coordinate. Entries which are not numbers are skipped.
+    pub fn as_axis_mappings(&self) -> Option<BTreeMap<String, Vec<(f64, f64)>>> {
+        let mappings = self.value.as_dict()?.iter().filter_map(|(tag, points)| {
+            let mut points: Vec<(f64, f64)> = points
+                .as_dict()?
+                .iter()
+                .filter_map(|(user, design)| {
+                    Some((user.trim().parse().ok()?, plist_number(design)?))
+                })
+                .collect();
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            Some((tag.to_string(), points))
+        });
+        Some(mappings.collect())
+    }
+
+    /// The glyph names of a `glyphOrder` parameter, in order
+    pub fn as_glyph_order(&self) -> Option<Vec<&str>> {
+        let names = self.value.as_array()?.iter().filter_map(Plist::as_str);
+        Some(names.collect())
+    }
+
+    /// The `OS/2.fsType` flags of an `fsType` parameter
+    ///
+    /// Glyphs stores the numbers of the bits which are set, so `(2, 3)` is
+    /// `0b1100`.
+    pub fn as_fs_type(&self) -> Option<u16> {
+        self.value.as_array()?.iter().try_fold(0u16, |flags, bit| {
+            let bit = plist_number(bit)?;
+            (0.0..16.0).contains(&bit).then(|| flags | 1 << bit as u16)
+        })
+    }
+
+    /// The ten PANOSE digits of a `panose` parameter
+    pub fn as_panose(&self) -> Option<[u8; 10]> {
+        let digits: Vec<u8> = self
+            .value
+            .as_array()?
+            .iter()
+            .map(|digit| plist_number(digit).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        digits.try_into().ok()
+    }
+}
+
+// The numeric value of the first enabled parameter with the name
+pub(crate) fn parameter_value(parameters: &[CustomParameter], name: &str) -> Option<f64> {
+    parameters
+        .iter()
+        .find(|p| p.name == name && !p.disabled)?
+        .as_number()
+}
+
+// The string value of the first enabled parameter with the name
+pub(crate) fn parameter_str<'a>(parameters: &'a [CustomParameter], name: &str) -> Option<&'a str> {
+    parameters
+        .iter()
+        .find(|p| p.name == name && !p.disabled)?
+        .value
+        .as_str()
+}
+
+// The list value of the first enabled parameter with the name
+pub(crate) fn parameter_list<'a>(
+    parameters: &'a [CustomParameter],
+    name: &str,
+) -> Option<&'a [Plist]> {
+    parameters
+        .iter()
+        .find(|p| p.name == name && !p.disabled)?
+        .value
+        .as_array()
+}
+
 /// Feature prefix (`GSFeaturePrefix`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FeaturePrefix {
     /// Whether the code of the feature prefix is generated automatically.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -57,6 +193,7 @@ pub struct FeaturePrefix {
 
 /// Feature (`GSFeature`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Feature {
     /// Whether the code of the feature is generated automatically.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -79,6 +216,7 @@ pub struct Feature {
 
 /// Stylistic set label (`GSInfoValue`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct StylisticSetLabel {
     /// The language tag of the string value. The tag is based on the OpenType Language System Tags but omits trailing whitespace. Examples: `"dflt"`, `"DEU"`.
     pub language: String,
@@ -86,27 +224,165 @@ pub struct StylisticSetLabel {
     pub value: String,
 }
 
+impl StylisticSetLabel {
+    /// A label in a language
+    pub fn new(language: impl Into<String>, value: impl Into<String>) -> Self {
+        StylisticSetLabel {
+            language: language.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl Feature {
+    /// The label of the feature in a language, such as `"ENG"` or `"dflt"`
+    pub fn label_for_language(&self, language: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|label| label.language == language)
+            .map(|label| label.value.as_str())
+    }
+}
+
 /// Color representation
 ///
-/// Can be:
-/// - An RGB color with an alpha channel in the sRGB IEC61966-2.1 color space (4 components)
-/// - A gray color with an alpha channel in a perceptual generic gray color space with γ = 2.2 (2 components)
-/// - A CMYK color with an alpha channel, device-dependent color space (5 components)
-/// - An integer index of the color label
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(untagged)]
+/// Written as an integer for a color label, or as a tuple of components
+/// from 0 to 255 whose number gives the color space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Color {
-    /// The index of the color label.
-    ColorInt(u8),
-    /// Color tuple (RGB, Gray, or CMYK with alpha channel)
-    ColorTuple(Vec<u8>),
+    /// The index of a color label, as named by [`label_name`](Self::label_name)
+    Index(u8),
+    /// An RGB color with an alpha channel in the sRGB IEC61966-2.1 color space
+    Rgba(u8, u8, u8, u8),
+    /// A gray color with an alpha channel in a perceptual generic gray color
+    /// space with γ = 2.2
+    Graya(u8, u8),
+    /// A CMYK color with an alpha channel, in a device-dependent color space
+    Cmyka(u8, u8, u8, u8, u8),
+    /// Components in a number which fits none of the color spaces, kept as
+    /// they were read
+    Other(Vec<u8>),
+}
+
+// The names of Glyphs' color labels, in order of index
+const LABEL_NAMES: [&str; 12] = [
+    "red",
+    "orange",
+    "brown",
+    "yellow",
+    "light green",
+    "dark green",
+    "light blue",
+    "dark blue",
+    "purple",
+    "magenta",
+    "light gray",
+    "charcoal",
+];
+
+// The colors Glyphs draws its labels in, as glyphsLib has them
+const LABEL_COLORS: [(u8, u8, u8); 12] = [
+    (217, 66, 15),
+    (252, 158, 28),
+    (166, 122, 51),
+    (247, 255, 0),
+    (171, 242, 97),
+    (10, 145, 10),
+    (0, 171, 232),
+    (46, 41, 199),
+    (156, 0, 201),
+    (232, 92, 179),
+    (214, 214, 214),
+    (84, 84, 84),
+];
+
+impl Color {
+    /// The color label with a name such as `"red"` or `"light green"`
+    pub fn from_label_name(name: &str) -> Option<Self> {
+        let index = LABEL_NAMES.iter().position(|n| *n == name)?;
+        Some(Color::Index(index as u8))
+    }
+
+    /// The name of the color label, if this is one Glyphs knows
+    pub fn label_name(&self) -> Option<&'static str> {
+        match self {
+            Color::Index(index) => LABEL_NAMES.get(usize::from(*index)).copied(),
+            _ => None,
+        }
+    }
+
+    /// The color as sRGB components with alpha, converting gray and CMYK
+    /// naively and labels to the colors Glyphs shows them in
+    ///
+    /// Returns `None` for a label Glyphs does not know, and for other
+    /// components.
+    pub fn to_rgba(&self) -> Option<(u8, u8, u8, u8)> {
+        match *self {
+            Color::Index(index) => {
+                let (r, g, b) = *LABEL_COLORS.get(usize::from(index))?;
+                Some((r, g, b, 255))
+            }
+            Color::Rgba(r, g, b, a) => Some((r, g, b, a)),
+            Color::Graya(gray, a) => Some((gray, gray, gray, a)),
+            Color::Cmyka(c, m, y, k, a) => {
+                let channel = |v: u8| {
+                    let ink = 1.0 - f64::from(v) / 255.0;
+                    (255.0 * ink * (1.0 - f64::from(k) / 255.0)).round() as u8
+                };
+                Some((channel(c), channel(m), channel(y), a))
+            }
+            Color::Other(_) => None,
+        }
+    }
+
+    /// The color as a hex string such as `"#d9420fff"`, as for
+    /// [`to_rgba`](Self::to_rgba)
+    pub fn to_hex(&self) -> Option<String> {
+        let (r, g, b, a) = self.to_rgba()?;
+        Some(format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+    }
+
+    /// An RGB color from a hex string of six or eight digits, with or
+    /// without a leading `#`; six digits are opaque
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+        Some(Color::Rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+    }
+
+    // The components of a color written as a tuple
+    pub(crate) fn components(&self) -> Vec<u8> {
+        match *self {
+            Color::Index(index) => vec![index],
+            Color::Rgba(r, g, b, a) => vec![r, g, b, a],
+            Color::Graya(gray, a) => vec![gray, a],
+            Color::Cmyka(c, m, y, k, a) => vec![c, m, y, k, a],
+            Color::Other(ref components) => components.clone(),
+        }
+    }
+
+    // The color for a tuple of components, by their number
+    pub(crate) fn from_components(components: Vec<u8>) -> Self {
+        match *components {
+            [r, g, b, a] => Color::Rgba(r, g, b, a),
+            [gray, a] => Color::Graya(gray, a),
+            [c, m, y, k, a] => Color::Cmyka(c, m, y, k, a),
+            _ => Color::Other(components),
+        }
+    }
 }
 
 /// Kerning definition mapping master IDs to kerning definitions, which map glyph names or class names to kerning partners.
-pub type Kerning = BTreeMap<String, BTreeMap<String, BTreeMap<String, f32>>>;
+pub type Kerning = BTreeMap<String, BTreeMap<String, BTreeMap<String, f64>>>;
 
 /// Guide alignment (`GSElementOrientation`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Orientation {
     /// Left alignment
     #[default]
@@ -121,7 +397,8 @@ pub enum Orientation {
 }
 
 /// Node type for path nodes
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum NodeType {
     /// Line node
     #[serde(rename = "l")]
@@ -146,8 +423,212 @@ pub enum NodeType {
     QCurveSmooth,
 }
 
+impl NodeType {
+    /// Whether the node is an on-curve node with smooth handles
+    pub fn is_smooth(self) -> bool {
+        matches!(
+            self,
+            NodeType::LineSmooth | NodeType::CurveSmooth | NodeType::QCurveSmooth
+        )
+    }
+
+    /// Whether the node is an off-curve (control) point
+    pub fn is_off_curve(self) -> bool {
+        self == NodeType::OffCurve
+    }
+
+    /// The kind of segment the node ends, without its smoothness: `Line`,
+    /// `Curve`, `QCurve` or `OffCurve`
+    pub fn base_type(self) -> NodeType {
+        match self {
+            NodeType::LineSmooth => NodeType::Line,
+            NodeType::CurveSmooth => NodeType::Curve,
+            NodeType::QCurveSmooth => NodeType::QCurve,
+            other => other,
+        }
+    }
+
+    /// The type with the same kind of segment and the given smoothness
+    ///
+    /// Off-curve nodes cannot be smooth, so they stay as they are.
+    pub fn with_smooth(self, smooth: bool) -> NodeType {
+        match (self.base_type(), smooth) {
+            (NodeType::Line, true) => NodeType::LineSmooth,
+            (NodeType::Curve, true) => NodeType::CurveSmooth,
+            (NodeType::QCurve, true) => NodeType::QCurveSmooth,
+            (base, _) => base,
+        }
+    }
+}
+
+impl From<NodeType> for (NodeType, bool) {
+    /// Split a node type into its [base type](NodeType::base_type) and
+    /// whether it is smooth
+    fn from(node_type: NodeType) -> Self {
+        (node_type.base_type(), node_type.is_smooth())
+    }
+}
+
+impl From<(NodeType, bool)> for NodeType {
+    fn from((base, smooth): (NodeType, bool)) -> Self {
+        base.with_smooth(smooth)
+    }
+}
+
+/// A position in font units, such as that of an anchor or a component
+///
+/// This is written to files as the pair `(x, y)` the format has always used.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Point {
+    /// The horizontal coordinate
+    pub x: f64,
+    /// The vertical coordinate
+    pub y: f64,
+}
+
+impl Point {
+    /// The origin
+    pub const ZERO: Point = Point { x: 0.0, y: 0.0 };
+
+    /// A point at `x`, `y`
+    pub const fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<(f64, f64)> for Point {
+    fn from((x, y): (f64, f64)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for (f64, f64) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl std::ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl std::ops::AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl std::ops::SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl std::ops::Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl std::ops::Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: f64) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl std::ops::Mul<Scale> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: Scale) -> Point {
+        Point::new(self.x * scale.x, self.y * scale.y)
+    }
+}
+
+/// Horizontal and vertical scale factors, such as those of a component
+///
+/// The default is `(1, 1)`, which leaves things at their size. This is
+/// written to files as the pair `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Scale {
+    /// The horizontal factor
+    pub x: f64,
+    /// The vertical factor
+    pub y: f64,
+}
+
+impl Scale {
+    /// The scale which leaves things at their size
+    pub const IDENTITY: Scale = Scale { x: 1.0, y: 1.0 };
+
+    /// A scale by `x` horizontally and `y` vertically
+    pub const fn new(x: f64, y: f64) -> Self {
+        Scale { x, y }
+    }
+
+    /// The same scale in both directions
+    pub const fn uniform(factor: f64) -> Self {
+        Scale {
+            x: factor,
+            y: factor,
+        }
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Scale::IDENTITY
+    }
+}
+
+impl From<(f64, f64)> for Scale {
+    fn from((x, y): (f64, f64)) -> Self {
+        Scale { x, y }
+    }
+}
+
+impl From<Scale> for (f64, f64) {
+    fn from(scale: Scale) -> Self {
+        (scale.x, scale.y)
+    }
+}
+
+impl std::ops::Mul for Scale {
+    type Output = Scale;
+
+    fn mul(self, other: Scale) -> Scale {
+        Scale::new(self.x * other.x, self.y * other.y)
+    }
+}
+
+impl std::ops::Mul<f64> for Scale {
+    type Output = Scale;
+
+    fn mul(self, factor: f64) -> Scale {
+        Scale::new(self.x * factor, self.y * factor)
+    }
+}
+
 /// Version information
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Version {
     /// The major version number of the font.
     #[serde(default, rename = "versionMajor")]
@@ -157,19 +638,198 @@ pub struct Version {
     pub minor: i32,
 }
 
+/// The weight class of an instance, as Glyphs 2 names it or as a number
+///
+/// Files may give it either way; Glyphs 3 files are written with the
+/// number and Glyphs 2 files with the name. Names which mean the same
+/// weight, such as `Black` and `Heavy`, are kept apart so that they are
+/// written back as they were read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum WeightClass {
+    /// `Thin`, 100
+    Thin,
+    /// `ExtraLight`, 200
+    ExtraLight,
+    /// `UltraLight`, 200
+    UltraLight,
+    /// `Light`, 300
+    Light,
+    /// `Normal`, 400
+    Normal,
+    /// `Regular`, 400
+    Regular,
+    /// `Medium`, 500
+    Medium,
+    /// `SemiBold`, 600
+    SemiBold,
+    /// `DemiBold`, 600
+    DemiBold,
+    /// `Bold`, 700
+    Bold,
+    /// `ExtraBold`, 800
+    ExtraBold,
+    /// `UltraBold`, 800
+    UltraBold,
+    /// `Black`, 900
+    Black,
+    /// `Heavy`, 900
+    Heavy,
+    /// A weight with no name
+    Custom(u16),
+}
+
+const WEIGHT_CLASSES: [(WeightClass, &str, u16); 14] = [
+    (WeightClass::Thin, "Thin", 100),
+    (WeightClass::ExtraLight, "ExtraLight", 200),
+    (WeightClass::UltraLight, "UltraLight", 200),
+    (WeightClass::Light, "Light", 300),
+    (WeightClass::Regular, "Regular", 400),
+    (WeightClass::Normal, "Normal", 400),
+    (WeightClass::Medium, "Medium", 500),
+    (WeightClass::SemiBold, "SemiBold", 600),
+    (WeightClass::DemiBold, "DemiBold", 600),
+    (WeightClass::Bold, "Bold", 700),
+    (WeightClass::ExtraBold, "ExtraBold", 800),
+    (WeightClass::UltraBold, "UltraBold", 800),
+    (WeightClass::Black, "Black", 900),
+    (WeightClass::Heavy, "Heavy", 900),
+];
+
+impl WeightClass {
+    /// The weight class with this name, such as `SemiBold`
+    pub fn from_name(name: &str) -> Option<Self> {
+        WEIGHT_CLASSES
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(class, _, _)| *class)
+    }
+
+    /// The weight class of an `OS/2.usWeightClass` value, by its first
+    /// name if it has one
+    pub fn from_os2(value: u16) -> Self {
+        WEIGHT_CLASSES
+            .iter()
+            .find(|(_, _, v)| *v == value)
+            .map_or(WeightClass::Custom(value), |(class, _, _)| *class)
+    }
+
+    /// The name of the weight class, unless it is a custom one
+    pub fn name(&self) -> Option<&'static str> {
+        WEIGHT_CLASSES
+            .iter()
+            .find(|(class, _, _)| class == self)
+            .map(|(_, name, _)| *name)
+    }
+
+    /// The `OS/2.usWeightClass` value
+    pub fn to_os2(&self) -> u16 {
+        match self {
+            WeightClass::Custom(value) => *value,
+            _ => WEIGHT_CLASSES
+                .iter()
+                .find(|(class, _, _)| class == self)
+                .map_or(400, |(_, _, value)| *value),
+        }
+    }
+}
+
+/// The width class of an instance, as Glyphs 2 names it or as a number
+///
+/// Files may give it either way, as for [`WeightClass`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum WidthClass {
+    /// `Ultra Condensed`, 1
+    UltraCondensed,
+    /// `Extra Condensed`, 2
+    ExtraCondensed,
+    /// `Condensed`, 3
+    Condensed,
+    /// `Semi Condensed`, 4
+    SemiCondensed,
+    /// `Medium (normal)`, 5, which is also read from `Medium`, `Regular`
+    /// and `Normal`
+    Medium,
+    /// `Semi Expanded`, 6
+    SemiExpanded,
+    /// `Expanded`, 7
+    Expanded,
+    /// `Extra Expanded`, 8
+    ExtraExpanded,
+    /// `Ultra Expanded`, 9
+    UltraExpanded,
+    /// A width outside the range of `OS/2.usWidthClass`
+    Custom(u16),
+}
+
+const WIDTH_CLASSES: [(WidthClass, &str); 9] = [
+    (WidthClass::UltraCondensed, "Ultra Condensed"),
+    (WidthClass::ExtraCondensed, "Extra Condensed"),
+    (WidthClass::Condensed, "Condensed"),
+    (WidthClass::SemiCondensed, "Semi Condensed"),
+    (WidthClass::Medium, "Medium (normal)"),
+    (WidthClass::SemiExpanded, "Semi Expanded"),
+    (WidthClass::Expanded, "Expanded"),
+    (WidthClass::ExtraExpanded, "Extra Expanded"),
+    (WidthClass::UltraExpanded, "Ultra Expanded"),
+];
+
+impl WidthClass {
+    /// The width class with this name, such as `Semi Condensed`
+    pub fn from_name(name: &str) -> Option<Self> {
+        if matches!(name, "Medium" | "Regular" | "Normal") {
+            return Some(WidthClass::Medium);
+        }
+        WIDTH_CLASSES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(class, _)| *class)
+    }
+
+    /// The width class of an `OS/2.usWidthClass` value
+    pub fn from_os2(value: u16) -> Self {
+        match value {
+            1..=9 => WIDTH_CLASSES[value as usize - 1].0,
+            _ => WidthClass::Custom(value),
+        }
+    }
+
+    /// The name of the width class, unless it is a custom one
+    pub fn name(&self) -> Option<&'static str> {
+        WIDTH_CLASSES
+            .iter()
+            .find(|(class, _)| class == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// The `OS/2.usWidthClass` value
+    pub fn to_os2(&self) -> u16 {
+        match self {
+            WidthClass::Custom(value) => *value,
+            _ => WIDTH_CLASSES
+                .iter()
+                .position(|(class, _)| class == self)
+                .map_or(5, |ix| ix as u16 + 1),
+        }
+    }
+}
+
 /// Instance interpolation factors
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct InstanceFactors(
     #[serde(
         deserialize_with = "deserialize_commify",
         serialize_with = "serialize_commify",
         default
     )]
-    pub Vec<f32>,
+    pub Vec<f64>,
 );
 
 /// Smart component property setting (`GSPartProperty`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct SmartComponentSetting {
     /// The lower end of the value range of the property.
     #[serde(default, rename = "bottomValue")]
@@ -180,3 +840,199 @@ pub struct SmartComponentSetting {
     /// The name of the property.
     pub name: String,
 }
+
+/// The end of a smart component property a layer stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SmartComponentPole {
+    /// The bottom value of the property (`1` in `partSelection`)
+    Bottom,
+    /// The top value of the property (`2` in `partSelection`)
+    Top,
+}
+
+impl SmartComponentPole {
+    /// The pole of a `partSelection` value
+    pub fn from_part_selection(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(SmartComponentPole::Bottom),
+            2 => Some(SmartComponentPole::Top),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn parameter(file: &str, name: &str) -> CustomParameter {
+        let font = Font::load(path::Path::new("resources").join(file).as_path()).unwrap();
+        let font = font.upgrade();
+        let font = font.as_glyphs3().unwrap();
+        font.custom_parameters
+            .iter()
+            .chain(font.masters.iter().flat_map(|m| &m.custom_parameters))
+            .chain(font.instances.iter().flat_map(|i| &i.custom_parameters))
+            .find(|p| p.name == name)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_typed_parameter_values() {
+        let panose = parameter("MultiplePanose.glyphs", "panose");
+        assert_eq!(panose.as_panose(), Some([1, 2, 3, 4, 5, 6, 7, 8, 9, 10]));
+        let fs_type = parameter("GlobalMetrics_font_customParameters.glyphs", "fsType");
+        assert_eq!(fs_type.as_fs_type(), Some(0));
+        let location = parameter(
+            "GlobalMetrics_font_customParameters.glyphs",
+            "Axis Location",
+        );
+        assert_eq!(
+            location.as_axis_locations(),
+            Some(vec![AxisLocation {
+                axis: "Weight".to_string(),
+                location: 400.0
+            }])
+        );
+        let typo = parameter(
+            "GlobalMetrics_font_customParameters.glyphs",
+            "Use Typo Metrics",
+        );
+        assert_eq!(typo.as_bool(), Some(true));
+        assert_eq!(typo.as_panose(), None);
+    }
+
+    #[test]
+    fn test_typed_parameter_values_from_strings() {
+        let plist = Plist::parse(r#"{ name = fsType; value = ("2", 3); }"#).unwrap();
+        let deserializer = &mut openstep_plist::de::Deserializer::from_plist(&plist);
+        let fs_type: CustomParameter = serde_path_to_error::deserialize(deserializer).unwrap();
+        assert_eq!(fs_type.as_fs_type(), Some(0b1100));
+        assert_eq!(fs_type.as_number(), None);
+        let axes = CustomParameter {
+            value: Plist::parse(
+                "({ Name = Weight; Tag = wght; }, { Name = Foo; Tag = FOOO; Hidden = 1; })",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let axes = axes.as_axes().unwrap();
+        assert_eq!(axes[0].tag, "wght");
+        assert!(axes[1].hidden);
+    }
+
+    #[test]
+    fn test_weight_and_width_classes() {
+        let instance = |text: &str| -> crate::glyphs3::Instance {
+            openstep_plist::from_str(&format!("{{name = I; {text}}}")).unwrap()
+        };
+        let semibold = instance("weightClass = SemiBold; widthClass = \"Semi Condensed\";");
+        assert_eq!(semibold.weight_class, Some(WeightClass::SemiBold));
+        assert_eq!(semibold.width_class, Some(WidthClass::SemiCondensed));
+        let numbers = instance("weightClass = 600; widthClass = 4;");
+        assert_eq!(numbers, semibold);
+        let custom = instance("weightClass = 550; widthClass = Medium;");
+        assert_eq!(custom.weight_class, Some(WeightClass::Custom(550)));
+        assert_eq!(custom.width_class.map(|w| w.to_os2()), Some(5));
+        assert_eq!(WeightClass::Heavy.to_os2(), 900);
+        assert_eq!(WeightClass::from_os2(900), WeightClass::Black);
+
+        // Glyphs 3 writes numbers and Glyphs 2 names
+        let glyphs3 = openstep_plist::to_string(&semibold).unwrap();
+        assert!(glyphs3.contains("weightClass = 600;"));
+        assert!(glyphs3.contains("widthClass = 4;"));
+        let glyphs2 = openstep_plist::to_string(&crate::glyphs2::Instance {
+            weight_class: Some(WeightClass::Heavy),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(glyphs2.contains("weightClass = Heavy;"));
+    }
+
+    #[test]
+    fn test_node_type_smoothness() {
+        assert!(NodeType::CurveSmooth.is_smooth());
+        assert!(!NodeType::OffCurve.is_smooth());
+        assert!(NodeType::OffCurve.is_off_curve());
+        assert_eq!(NodeType::QCurveSmooth.base_type(), NodeType::QCurve);
+        assert_eq!(NodeType::Line.with_smooth(true), NodeType::LineSmooth);
+        assert_eq!(NodeType::LineSmooth.with_smooth(false), NodeType::Line);
+        assert_eq!(NodeType::OffCurve.with_smooth(true), NodeType::OffCurve);
+        assert_eq!(
+            <(NodeType, bool)>::from(NodeType::CurveSmooth),
+            (NodeType::Curve, true)
+        );
+        assert_eq!(
+            NodeType::from((NodeType::QCurve, true)),
+            NodeType::QCurveSmooth
+        );
+        let node = crate::glyphs3::Node {
+            x: 0.0,
+            y: 0.0,
+            node_type: NodeType::Curve,
+            user_data: None,
+        };
+        assert_eq!(node.with_smooth(true).node_type, NodeType::CurveSmooth);
+    }
+
+    #[test]
+    fn test_points_and_scales() {
+        let point = Point::new(10.0, 20.0);
+        assert_eq!(point + Point::new(1.0, 2.0), Point::new(11.0, 22.0));
+        assert_eq!(point - point, Point::ZERO);
+        assert_eq!(-point * 0.5, Point::new(-5.0, -10.0));
+        assert_eq!(point * Scale::new(2.0, -1.0), Point::new(20.0, -20.0));
+        assert_eq!(<(f64, f64)>::from(point), (10.0, 20.0));
+        assert_eq!(Scale::default(), Scale::uniform(1.0));
+
+        // Points are written as the pairs they always were
+        let plist = openstep_plist::ser::to_string(&point).unwrap();
+        assert_eq!(
+            plist,
+            openstep_plist::ser::to_string(&(10.0, 20.0)).unwrap()
+        );
+        let back: Point = openstep_plist::de::from_str(&plist).unwrap();
+        assert_eq!(back, point);
+    }
+
+    #[test]
+    fn test_colors() {
+        let red = Color::from_label_name("red").unwrap();
+        assert_eq!(red, Color::Index(0));
+        assert_eq!(Color::Index(11).label_name(), Some("charcoal"));
+        assert_eq!(Color::Index(99).label_name(), None);
+        assert_eq!(Color::Graya(128, 255).to_hex().unwrap(), "#808080ff");
+        assert_eq!(
+            Color::Cmyka(0, 255, 255, 0, 255).to_rgba(),
+            Some((255, 0, 0, 255))
+        );
+        assert_eq!(
+            Color::from_hex("#ff8000"),
+            Some(Color::Rgba(255, 128, 0, 255))
+        );
+        assert_eq!(
+            Color::from_hex("ff800080"),
+            Some(Color::Rgba(255, 128, 0, 128))
+        );
+        assert_eq!(Color::from_hex("#ff80"), None);
+
+        // Labels are written as integers, other colors as inline tuples
+        for (color, text) in [
+            (red, "0"),
+            (Color::Rgba(1, 2, 3, 4), "(1,2,3,4)"),
+            (Color::Graya(5, 6), "(5,6)"),
+            (Color::Cmyka(1, 2, 3, 4, 5), "(1,2,3,4,5)"),
+        ] {
+            assert_eq!(openstep_plist::ser::to_string(&color).unwrap(), text);
+            assert_eq!(openstep_plist::de::from_str::<Color>(text).unwrap(), color);
+        }
+        assert_eq!(
+            openstep_plist::de::from_str::<Color>("(1,2,3)").unwrap(),
+            Color::Other(vec![1, 2, 3])
+        );
+    }
+}
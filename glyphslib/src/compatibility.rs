@@ -0,0 +1,371 @@
+use std::{collections::BTreeSet, fmt};
+
+use crate::{
+    common::NodeType,
+    glyphs3::{Glyph, Glyphs3, Layer},
+};
+
+/// A way in which a layer differs from the glyph's reference layer, so that the two cannot interpolate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// The glyph has no layer for a master
+    MissingMasterLayer,
+    /// The layers have different numbers of paths
+    PathCount {
+        /// The number of paths of the reference layer
+        expected: usize,
+        /// The number of paths of this layer
+        found: usize,
+    },
+    /// A path has a different number of nodes
+    NodeCount {
+        /// The index of the path among the paths of the layer
+        path: usize,
+        /// The number of nodes of the path in the reference layer
+        expected: usize,
+        /// The number of nodes of the path in this layer
+        found: usize,
+    },
+    /// A node is of a different type; smooth and sharp nodes are alike
+    NodeType {
+        /// The index of the path among the paths of the layer
+        path: usize,
+        /// The index of the node in the path
+        node: usize,
+        /// The type of the node in the reference layer
+        expected: NodeType,
+        /// The type of the node in this layer
+        found: NodeType,
+    },
+    /// A path is open in one layer and closed in the other
+    Closedness {
+        /// The index of the path among the paths of the layer
+        path: usize,
+    },
+    /// The layers use different components, or use them in a different order
+    ComponentOrder {
+        /// The glyphs the components of the reference layer use
+        expected: Vec<String>,
+        /// The glyphs the components of this layer use
+        found: Vec<String>,
+    },
+    /// The layers have different anchors
+    Anchors {
+        /// Anchors of the reference layer which this layer lacks
+        missing: Vec<String>,
+        /// Anchors of this layer which the reference layer lacks
+        extra: Vec<String>,
+    },
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::MissingMasterLayer => write!(f, "no layer for this master"),
+            Incompatibility::PathCount { expected, found } => {
+                write!(f, "{found} paths, expected {expected}")
+            }
+            Incompatibility::NodeCount {
+                path,
+                expected,
+                found,
+            } => write!(f, "path {path} has {found} nodes, expected {expected}"),
+            Incompatibility::NodeType {
+                path,
+                node,
+                expected,
+                found,
+            } => write!(
+                f,
+                "node {node} of path {path} is {found:?}, expected {expected:?}"
+            ),
+            Incompatibility::Closedness { path } => {
+                write!(
+                    f,
+                    "path {path} is open in one layer and closed in the other"
+                )
+            }
+            Incompatibility::ComponentOrder { expected, found } => write!(
+                f,
+                "components ({}), expected ({})",
+                found.join(", "),
+                expected.join(", ")
+            ),
+            Incompatibility::Anchors { missing, extra } => {
+                write!(f, "anchors differ")?;
+                if !missing.is_empty() {
+                    write!(f, "; missing {}", missing.join(", "))?;
+                }
+                if !extra.is_empty() {
+                    write!(f, "; extra {}", extra.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The compatibility of the layers of one glyph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphCompatibility {
+    /// The name of the glyph
+    pub glyph: String,
+    /// The ID of the layer the others were compared with, that of the first master if it has one
+    pub reference_layer: Option<String>,
+    /// The problems found, with the ID of the layer (or master) each was found on
+    pub problems: Vec<(String, Incompatibility)>,
+}
+
+impl GlyphCompatibility {
+    /// Whether the layers of the glyph can interpolate
+    pub fn is_compatible(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The interpolation compatibility of the glyphs of a font
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// The compatibility of each glyph, in font order
+    pub glyphs: Vec<GlyphCompatibility>,
+}
+
+impl CompatibilityReport {
+    /// Whether every glyph can interpolate
+    pub fn is_compatible(&self) -> bool {
+        self.glyphs.iter().all(GlyphCompatibility::is_compatible)
+    }
+
+    /// The glyphs with problems
+    pub fn incompatible_glyphs(&self) -> impl Iterator<Item = &GlyphCompatibility> {
+        self.glyphs.iter().filter(|g| !g.is_compatible())
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for glyph in self.incompatible_glyphs() {
+            for (layer_id, problem) in &glyph.problems {
+                writeln!(f, "glyph {}, layer {layer_id}: {problem}", glyph.glyph)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check whether the master and intermediate layers of each glyph can interpolate
+///
+/// Each layer is compared with the glyph's layer for the first master: it
+/// must have the same number of paths, each with the same number, types and
+/// closedness of nodes, the same components in the same order, and the same
+/// anchors. Alternate (bracket) layers and backgrounds are not checked, and
+/// neither are glyphs which are not exported.
+pub fn check_compatibility(font: &Glyphs3) -> CompatibilityReport {
+    CompatibilityReport {
+        glyphs: font
+            .glyphs
+            .iter()
+            .filter(|g| g.export)
+            .map(|glyph| check_glyph(font, glyph))
+            .collect(),
+    }
+}
+
+fn check_glyph(font: &Glyphs3, glyph: &Glyph) -> GlyphCompatibility {
+    let mut problems = vec![];
+    let mut master_layers = vec![];
+    for master in &font.masters {
+        match glyph.layer_for_master(&master.id) {
+            Some(layer) => master_layers.push(layer),
+            None => problems.push((master.id.clone(), Incompatibility::MissingMasterLayer)),
+        }
+    }
+    let brace_layers = glyph
+        .layers
+        .iter()
+        .filter(|l| l.is_brace_layer() && font.masters.iter().all(|m| m.id != l.layer_id));
+    let mut layers = master_layers.into_iter().chain(brace_layers);
+    let reference = layers.next();
+    if let Some(reference) = reference {
+        for layer in layers {
+            for problem in compare(reference, layer) {
+                problems.push((layer.layer_id.clone(), problem));
+            }
+        }
+    }
+    GlyphCompatibility {
+        glyph: glyph.name.clone(),
+        reference_layer: reference.map(|l| l.layer_id.clone()),
+        problems,
+    }
+}
+
+pub(crate) fn compare(reference: &Layer, layer: &Layer) -> Vec<Incompatibility> {
+    let mut problems = vec![];
+    let (expected, found): (Vec<_>, Vec<_>) =
+        (reference.paths().collect(), layer.paths().collect());
+    if expected.len() != found.len() {
+        problems.push(Incompatibility::PathCount {
+            expected: expected.len(),
+            found: found.len(),
+        });
+    }
+    for (path, (a, b)) in expected.iter().zip(&found).enumerate() {
+        if a.closed != b.closed {
+            problems.push(Incompatibility::Closedness { path });
+        }
+        if a.nodes.len() != b.nodes.len() {
+            problems.push(Incompatibility::NodeCount {
+                path,
+                expected: a.nodes.len(),
+                found: b.nodes.len(),
+            });
+            continue;
+        }
+        let mismatch = a
+            .nodes
+            .iter()
+            .zip(&b.nodes)
+            .position(|(a, b)| a.node_type.base_type() != b.node_type.base_type());
+        if let Some(node) = mismatch {
+            problems.push(Incompatibility::NodeType {
+                path,
+                node,
+                expected: a.nodes[node].node_type,
+                found: b.nodes[node].node_type,
+            });
+        }
+    }
+
+    let components = |layer: &Layer| {
+        layer
+            .components()
+            .map(|component| component.component_glyph.clone())
+            .collect::<Vec<_>>()
+    };
+    let (expected, found) = (components(reference), components(layer));
+    if expected != found {
+        problems.push(Incompatibility::ComponentOrder { expected, found });
+    }
+
+    let anchors = |layer: &Layer| {
+        layer
+            .anchors
+            .iter()
+            .map(|a| a.name.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    let (expected, found) = (anchors(reference), anchors(layer));
+    if expected != found {
+        problems.push(Incompatibility::Anchors {
+            missing: expected.difference(&found).cloned().collect(),
+            extra: found.difference(&expected).cloned().collect(),
+        });
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::Point,
+        glyphs3::{Anchor, Shape},
+        Font,
+    };
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_compatible_font() {
+        let font = load();
+        let report = check_compatibility(&font);
+        assert_eq!(
+            report.glyphs.len(),
+            font.glyphs.iter().filter(|g| g.export).count()
+        );
+        assert!(report.is_compatible(), "{report}");
+    }
+
+    #[test]
+    fn test_incompatibilities() {
+        let mut font = load();
+        let bold = font.masters[1].id.clone();
+        let layer = |font: &mut Glyphs3, name: &str| {
+            font.glyph_mut(name)
+                .unwrap()
+                .layers
+                .iter_mut()
+                .find(|l| l.layer_id == bold)
+                .unwrap()
+                .clone()
+        };
+        // Drop a node from o, add an anchor to eacute and drop its components
+        let mut o = layer(&mut font, "o");
+        let Some(Shape::Path(path)) = o.shapes.first_mut() else {
+            unreachable!()
+        };
+        path.nodes.pop();
+        let mut eacute = layer(&mut font, "eacute");
+        eacute.anchors.push(Anchor {
+            locked: false,
+            name: "extra".to_string(),
+            orientation: Default::default(),
+            pos: Point::ZERO,
+            user_data: None,
+        });
+        eacute.shapes.retain(|s| matches!(s, Shape::Path(_)));
+        for (name, new) in [("o", o), ("eacute", eacute)] {
+            let glyph = font.glyph_mut(name).unwrap();
+            let existing = glyph
+                .layers
+                .iter_mut()
+                .find(|l| l.layer_id == bold)
+                .unwrap();
+            *existing = new;
+        }
+        font.glyph_mut("e")
+            .unwrap()
+            .layers
+            .retain(|l| l.layer_id != bold);
+
+        let report = check_compatibility(&font);
+        let problems = |name: &str| {
+            report
+                .glyphs
+                .iter()
+                .find(|g| g.glyph == name)
+                .unwrap()
+                .problems
+                .iter()
+                .map(|(_, p)| p.clone())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            problems("o")[..],
+            [Incompatibility::NodeCount { path: 0, .. }]
+        ));
+        let eacute = problems("eacute");
+        assert!(matches!(
+            &eacute[0],
+            Incompatibility::ComponentOrder { found, .. } if found.is_empty()
+        ));
+        assert_eq!(
+            eacute[1],
+            Incompatibility::Anchors {
+                missing: vec![],
+                extra: vec!["extra".to_string()]
+            }
+        );
+        assert_eq!(problems("e"), vec![Incompatibility::MissingMasterLayer]);
+        assert_eq!(report.incompatible_glyphs().count(), 3);
+        assert!(report.to_string().contains("glyph o, layer"));
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::glyphs3::{Glyph, Glyphs3, Layer};
+
+// The glyphs a glyph's layers use directly as components; backgrounds are
+// not counted
+fn direct_components(glyph: &Glyph) -> impl Iterator<Item = &str> {
+    glyph
+        .layers
+        .iter()
+        .flat_map(Layer::components)
+        .map(|component| component.component_glyph.as_str())
+}
+
+impl Glyphs3 {
+    /// The glyphs a glyph uses as components, directly or through other
+    /// components
+    ///
+    /// Components in every layer count, but not those in backgrounds. Names
+    /// of glyphs the font does not have are included, as components of them
+    /// are broken. The glyph itself is not included, even if it uses itself.
+    pub fn components_used_by(&self, name: &str) -> BTreeSet<&str> {
+        let mut used = BTreeSet::new();
+        let mut pending: Vec<&str> = self
+            .glyph(name)
+            .into_iter()
+            .flat_map(direct_components)
+            .collect();
+        while let Some(component) = pending.pop() {
+            if component == name || !used.insert(component) {
+                continue;
+            }
+            if let Some(glyph) = self.glyph(component) {
+                pending.extend(direct_components(glyph));
+            }
+        }
+        used
+    }
+
+    /// The glyphs which use a glyph as a component, directly or through
+    /// other components
+    ///
+    /// These are the glyphs whose outlines change when the glyph is edited,
+    /// and whose components break when it is deleted. As for
+    /// [`components_used_by`](Self::components_used_by), backgrounds are not
+    /// counted and the glyph itself is not included.
+    pub fn composites_using(&self, name: &str) -> BTreeSet<&str> {
+        let mut users: HashMap<&str, Vec<&str>> = HashMap::new();
+        for glyph in &self.glyphs {
+            for component in direct_components(glyph) {
+                users.entry(component).or_default().push(&glyph.name);
+            }
+        }
+        let mut using = BTreeSet::new();
+        let mut pending = users.get(name).cloned().unwrap_or_default();
+        while let Some(composite) = pending.pop() {
+            if composite == name || !using.insert(composite) {
+                continue;
+            }
+            pending.extend(users.get(composite).into_iter().flatten());
+        }
+        using
+    }
+
+    // The given glyphs along with all the glyphs they use as components
+    pub(crate) fn with_components<'a>(&'a self, names: &[&'a str]) -> BTreeSet<&'a str> {
+        let mut closure: BTreeSet<&str> = names.iter().copied().collect();
+        for name in names {
+            closure.extend(self.components_used_by(name));
+        }
+        closure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_component_closures() {
+        let font = Font::load(path::Path::new("resources/NestedComponent.glyphs")).unwrap();
+        let font = font.upgrade();
+        let font = font.as_glyphs3().unwrap();
+        assert_eq!(
+            Vec::from_iter(font.components_used_by("c3")),
+            ["c1", "c2", "period"]
+        );
+        assert_eq!(
+            Vec::from_iter(font.components_used_by("c2")),
+            ["c1", "period"]
+        );
+        assert!(font.components_used_by("period").is_empty());
+        assert!(font.components_used_by("nosuchglyph").is_empty());
+        assert_eq!(
+            Vec::from_iter(font.composites_using("period")),
+            ["c1", "c2", "c3"]
+        );
+        assert_eq!(Vec::from_iter(font.composites_using("c1")), ["c2", "c3"]);
+        assert!(font.composites_using("c3").is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    common::{NodeType, Scale},
+    geometry::Affine,
+    glyphs3::{Glyphs3, Hint, Layer, Node, Path, Shape},
+};
+
+/// Whether a corner component sits on a node or a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerKind {
+    /// A corner component (`Corner` hint, `_corner.*` glyph), which
+    /// replaces a node
+    Corner,
+    /// A cap component (`Cap` hint, `_cap.*` glyph), which replaces the
+    /// segment from a node to the next
+    Cap,
+}
+
+/// A corner or cap component on a path of a layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct CornerComponent {
+    /// Whether this is a corner or a cap
+    pub kind: CornerKind,
+    /// The name of the glyph drawing the corner or cap
+    pub glyph: String,
+    /// The index of the path among the shapes of the layer
+    pub path: usize,
+    /// The index of the node in the path
+    pub node: usize,
+    /// The scale of the component
+    pub scale: Scale,
+}
+
+impl Hint {
+    /// The corner or cap component this hint places, if it is one
+    pub fn corner_component(&self) -> Option<CornerComponent> {
+        let kind = match self.type_.as_str() {
+            "Corner" => CornerKind::Corner,
+            "Cap" => CornerKind::Cap,
+            _ => return None,
+        };
+        let (path, node) = self.origin?;
+        Some(CornerComponent {
+            kind,
+            glyph: self.name.clone()?,
+            path: path as usize,
+            node: node as usize,
+            scale: self.scale.unwrap_or_default(),
+        })
+    }
+}
+
+fn unit((x, y): (f64, f64)) -> Option<(f64, f64)> {
+    let length = x.hypot(y);
+    (length > 0.0).then(|| (x / length, y / length))
+}
+
+// The nodes of an open path the other way round. Each on-curve node takes
+// the type of the segment which now ends at it.
+fn reversed(nodes: &[Node]) -> Vec<Node> {
+    let mut result: Vec<Node> = nodes.iter().rev().cloned().collect();
+    let mut segment = NodeType::Line;
+    for (node, original) in result.iter_mut().zip(nodes.iter().rev()) {
+        if original.node_type != NodeType::OffCurve {
+            node.node_type = segment;
+            segment = original.node_type;
+        }
+    }
+    result
+}
+
+// The path a corner or cap glyph draws, in its own coordinates, with the
+// width of its layer
+fn corner_path(font: &Glyphs3, name: &str, master_id: &str) -> Option<(Vec<Node>, f64)> {
+    let layer = font.glyph(name)?.layer_for_master(master_id)?;
+    let path = layer.shapes.iter().find_map(|shape| match shape {
+        Shape::Path(path) if path.nodes.len() >= 2 => Some(path),
+        _ => None,
+    })?;
+    let mut nodes = path.nodes.clone();
+    if let Some(origin) = layer.anchors.iter().find(|a| a.name == "origin") {
+        for node in nodes.iter_mut() {
+            node.set_position(node.position() - origin.pos);
+        }
+    }
+    Some((nodes, layer.width))
+}
+
+// Place a corner or cap on a path, replacing the node it is on and, for a
+// cap, moving the next node to where the cap ends
+fn apply(path: &mut Path, corner: &CornerComponent, mut nodes: Vec<Node>, width: f64) -> bool {
+    let count = path.nodes.len();
+    if corner.node >= count || count < 2 {
+        return false;
+    }
+    let at = &path.nodes[corner.node];
+    let point = (at.x, at.y);
+    let previous = &path.nodes[(corner.node + count - 1) % count];
+    let next = &path.nodes[(corner.node + 1) % count];
+    let (start, end) = (&nodes[0], &nodes[nodes.len() - 1]);
+    let transform = match corner.kind {
+        // The corner is drawn at the origin, coming in down the y axis and
+        // going out along the x axis
+        CornerKind::Corner => {
+            let (Some(incoming), Some(outgoing)) = (
+                unit((at.x - previous.x, at.y - previous.y)),
+                unit((next.x - at.x, next.y - at.y)),
+            ) else {
+                return false;
+            };
+            if start.x.abs() > end.x.abs() {
+                nodes = reversed(&nodes);
+            }
+            Affine([
+                outgoing.0 * corner.scale.x,
+                outgoing.1 * corner.scale.x,
+                -incoming.0 * corner.scale.y,
+                -incoming.1 * corner.scale.y,
+                point.0,
+                point.1,
+            ])
+        }
+        // The cap is drawn across the end of the stroke from the origin to
+        // the width of its layer, outwards up the y axis
+        CornerKind::Cap => {
+            let across = (next.x - at.x, next.y - at.y);
+            let Some(outwards) = unit((across.1, -across.0)) else {
+                return false;
+            };
+            if width <= 0.0 {
+                return false;
+            }
+            if start.x > end.x {
+                nodes = reversed(&nodes);
+            }
+            Affine([
+                across.0 / width,
+                across.1 / width,
+                outwards.0 * corner.scale.y,
+                outwards.1 * corner.scale.y,
+                point.0,
+                point.1,
+            ])
+        }
+    };
+    for node in nodes.iter_mut() {
+        node.set_position(transform.apply(node.position()));
+    }
+    // The first node ends the segment which ended at the replaced node
+    nodes[0].node_type = path.nodes[corner.node].node_type;
+    if corner.kind == CornerKind::Cap {
+        let last = nodes.pop().expect("a corner path has two nodes or more");
+        let next = &mut path.nodes[(corner.node + 1) % count];
+        (next.x, next.y, next.node_type) = (last.x, last.y, last.node_type);
+    }
+    path.nodes.splice(corner.node..=corner.node, nodes);
+    true
+}
+
+impl Layer {
+    /// The corner and cap components on the paths of the layer
+    pub fn corner_components(&self) -> Vec<CornerComponent> {
+        self.hints
+            .iter()
+            .filter_map(Hint::corner_component)
+            .collect()
+    }
+
+    /// Draw the corner and cap components into the paths of the layer and
+    /// remove their hints
+    ///
+    /// The glyphs are looked up in the master of the layer, and the first
+    /// path of each is placed. A corner glyph is drawn at the origin (or
+    /// its `origin` anchor) for a corner whose incoming stroke runs down the
+    /// y axis and outgoing stroke runs along the x axis; it is turned and
+    /// skewed to fit the corner, and the path runs from one stroke to the
+    /// other in place of the node. A cap glyph is drawn across a stroke end
+    /// from the origin to its width, on the outside of the path, and is
+    /// stretched to the segment it replaces. The segments next to a corner
+    /// are not cut, so curves end where the corner path does. Components
+    /// whose glyph, path or node is missing are left as they are.
+    pub fn apply_corner_components(&mut self, font: &Glyphs3) {
+        let master_id = self
+            .associated_master_id
+            .clone()
+            .unwrap_or_else(|| self.layer_id.clone());
+        let mut corners: Vec<(usize, CornerComponent)> = self
+            .hints
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, hint)| Some((ix, hint.corner_component()?)))
+            .collect();
+        // Later nodes first, so placing a corner does not move the others
+        corners.sort_by_key(|(_, corner)| std::cmp::Reverse((corner.path, corner.node)));
+        let mut applied = vec![];
+        for (ix, corner) in corners {
+            let Some((nodes, width)) = corner_path(font, &corner.glyph, &master_id) else {
+                continue;
+            };
+            let Some(Shape::Path(path)) = self.shapes.get_mut(corner.path) else {
+                continue;
+            };
+            if apply(path, &corner, nodes, width) {
+                applied.push(ix);
+            }
+        }
+        let mut ix = 0;
+        self.hints.retain(|_| {
+            ix += 1;
+            !applied.contains(&(ix - 1))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::Point,
+        glyphs3::{Anchor, Glyph},
+    };
+
+    fn path(points: &[(f64, f64)], closed: bool) -> Shape {
+        Shape::Path(Path {
+            attr: Default::default(),
+            closed,
+            nodes: points
+                .iter()
+                .map(|&(x, y)| Node {
+                    x,
+                    y,
+                    node_type: NodeType::Line,
+                    user_data: None,
+                })
+                .collect(),
+        })
+    }
+
+    fn points(layer: &Layer) -> Vec<(f64, f64)> {
+        let Shape::Path(path) = &layer.shapes[0] else {
+            unreachable!()
+        };
+        path.nodes.iter().map(|n| (n.x, n.y)).collect()
+    }
+
+    fn hint(kind: &str, name: &str, node: f64) -> Hint {
+        Hint {
+            type_: kind.to_string(),
+            name: Some(name.to_string()),
+            origin: Some((0.0, node)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_corner_components() {
+        let file = std::path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let crate::Font::Glyphs3(mut font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let master_id = font.masters[0].id.clone();
+        let mut template = font.glyphs[0]
+            .layers
+            .iter()
+            .find(|l| l.layer_id == master_id)
+            .unwrap()
+            .clone();
+        template.hints = vec![];
+        template.anchors = vec![];
+        let mut bevel = template.clone();
+        // Drawn the other way round, and off the origin
+        bevel.shapes = vec![path(&[(25.0, 5.0), (5.0, 25.0)], false)];
+        bevel.anchors = vec![Anchor {
+            locked: false,
+            name: "origin".to_string(),
+            orientation: Default::default(),
+            pos: Point::new(5.0, 5.0),
+            user_data: None,
+        }];
+        let mut point = template.clone();
+        point.width = 100.0;
+        point.shapes = vec![path(&[(0.0, 0.0), (50.0, 30.0), (100.0, 0.0)], false)];
+        for (name, layer) in [("_corner.bevel", bevel), ("_cap.point", point)] {
+            font.glyphs.push(Glyph {
+                name: name.to_string(),
+                layers: vec![layer],
+                ..Default::default()
+            });
+        }
+
+        let mut layer = template.clone();
+        layer.shapes = vec![path(
+            &[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)],
+            true,
+        )];
+        layer.hints = vec![
+            hint("Corner", "_corner.bevel", 0.0),
+            hint("Corner", "_corner.bevel", 1.0),
+            hint("Stem", "", 0.0),
+            hint("Cap", "_cap.point", 2.0),
+        ];
+        assert_eq!(layer.corner_components().len(), 3);
+        layer.apply_corner_components(&font);
+        assert_eq!(
+            points(&layer),
+            [
+                (0.0, 20.0),
+                (20.0, 0.0),
+                (80.0, 0.0),
+                (100.0, 20.0),
+                (100.0, 100.0),
+                (50.0, 130.0),
+                (0.0, 100.0),
+            ]
+        );
+        assert_eq!(layer.hints.len(), 1);
+        assert_eq!(layer.hints[0].type_, "Stem");
+    }
+}
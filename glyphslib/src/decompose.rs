@@ -0,0 +1,312 @@
+use std::borrow::Cow;
+
+use crate::{
+    common::SmartComponentPole,
+    geometry::{Affine, MAX_COMPONENT_DEPTH},
+    glyphs3::{Component, Glyph, Glyphs3, Layer, Path, Shape},
+};
+
+impl Layer {
+    /// The outlines of the layer with all its components resolved into paths
+    ///
+    /// Components are looked up as for [`bounds_with_components`](Self::bounds_with_components),
+    /// with this layer's master standing in for layers a glyph does not have,
+    /// and nested components are resolved in turn. Automatically aligned
+    /// components are placed as [`resolved_component_positions`](Self::resolved_component_positions)
+    /// says. Smart components are
+    /// interpolated to their `piece` settings first. Paths keep their node
+    /// order, so a component flipped by its transform gives paths of the
+    /// opposite direction. Components of missing glyphs are left out.
+    pub fn decomposed(&self, font: &Glyphs3) -> Vec<Path> {
+        let master_id = self
+            .associated_master_id
+            .as_deref()
+            .unwrap_or(&self.layer_id);
+        let mut paths = vec![];
+        self.collect_paths(font, master_id, &Affine::IDENTITY, 0, &mut paths);
+        paths
+    }
+
+    /// Replace the components of the layer with their outlines
+    pub fn decompose_in_place(&mut self, font: &Glyphs3) {
+        self.shapes = self.decomposed(font).into_iter().map(Shape::Path).collect();
+    }
+
+    fn collect_paths(
+        &self,
+        font: &Glyphs3,
+        master_id: &str,
+        transform: &Affine,
+        depth: usize,
+        paths: &mut Vec<Path>,
+    ) {
+        if depth > MAX_COMPONENT_DEPTH {
+            return;
+        }
+        let mut placements = self.component_transforms(font).into_iter();
+        for shape in &self.shapes {
+            match shape {
+                Shape::Path(path) => paths.push(transform_path(path, transform)),
+                Shape::Component(component) => {
+                    let placement = placements.next().unwrap_or(Affine::IDENTITY);
+                    let Some(layer) = component_layer(font, component, &self.layer_id, master_id)
+                    else {
+                        continue;
+                    };
+                    layer.collect_paths(
+                        font,
+                        master_id,
+                        &transform.then_apply(&placement),
+                        depth + 1,
+                        paths,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Glyph {
+    /// Whether this is a smart glyph, whose components vary by properties
+    pub fn is_smart_glyph(&self) -> bool {
+        !self.smart_component_settings.is_empty()
+    }
+}
+
+impl Component {
+    /// The layer of the glyph this component uses for a master, interpolated
+    /// to the component's `piece` settings if the glyph is a smart glyph
+    ///
+    /// The layer is in the glyph's own coordinates, before the component's
+    /// transform. Returns `None` if the glyph or its master layer is missing.
+    pub fn smart_layer(&self, font: &Glyphs3, master_id: &str) -> Option<Layer> {
+        component_layer(font, self, master_id, master_id).map(Cow::into_owned)
+    }
+
+    /// The outlines this component draws in a master, with smart glyphs
+    /// interpolated to the component's `piece` settings, nested components
+    /// resolved, and the component's transform applied
+    ///
+    /// Returns `None` if the glyph or its master layer is missing.
+    pub fn resolve_smart(&self, font: &Glyphs3, master_id: &str) -> Option<Vec<Path>> {
+        let layer = component_layer(font, self, master_id, master_id)?;
+        let mut paths = vec![];
+        layer.collect_paths(font, master_id, &self.effective_transform(), 1, &mut paths);
+        Some(paths)
+    }
+}
+
+fn transform_path(path: &Path, transform: &Affine) -> Path {
+    let mut path = path.clone();
+    for node in path.nodes.iter_mut() {
+        node.set_position(transform.apply(node.position()));
+    }
+    path
+}
+
+// The layer of the glyph a component uses, interpolated if it is a smart component
+fn component_layer<'a>(
+    font: &'a Glyphs3,
+    component: &Component,
+    layer_id: &str,
+    master_id: &str,
+) -> Option<Cow<'a, Layer>> {
+    let glyph = font.glyph(&component.component_glyph)?;
+    let layer = glyph
+        .layers
+        .iter()
+        .find(|l| l.layer_id == layer_id)
+        .or_else(|| glyph.layer_for_master(master_id))?;
+    if !glyph.is_smart_glyph() {
+        return Some(Cow::Borrowed(layer));
+    }
+    Some(Cow::Owned(interpolate_smart(glyph, layer, component)))
+}
+
+// Where a layer sits on a smart component axis: 0 at the bottom, 1 at the top
+fn pole(layer: &Layer, axis: &str) -> f64 {
+    match layer.smart_component_pole(axis) {
+        Some(SmartComponentPole::Top) => 1.0,
+        _ => 0.0,
+    }
+}
+
+fn compatible(a: &Layer, b: &Layer) -> bool {
+    a.shapes.len() == b.shapes.len()
+        && a.shapes.iter().zip(&b.shapes).all(|pair| match pair {
+            (Shape::Path(a), Shape::Path(b)) => a.nodes.len() == b.nodes.len(),
+            (Shape::Component(_), Shape::Component(_)) => true,
+            _ => false,
+        })
+}
+
+// Smart components vary by one layer per axis, which differs from the
+// master layer in that axis only. The settings of a component add up the
+// differences of those layers from the master, each in proportion.
+fn interpolate_smart(glyph: &Glyph, master: &Layer, component: &Component) -> Layer {
+    let mut result = master.clone();
+    let poles: Vec<&Layer> = glyph
+        .layers
+        .iter()
+        .filter(|l| {
+            l.associated_master_id.as_deref() == Some(master.layer_id.as_str())
+                && !l.part_selection.is_empty()
+        })
+        .collect();
+    for setting in &glyph.smart_component_settings {
+        let axis = setting.name.as_str();
+        let (bottom, top) = (setting.bottom_value as f64, setting.top_value as f64);
+        let Some(value) = component.smart_component_location.get(axis) else {
+            continue;
+        };
+        let at = if top == bottom {
+            0.0
+        } else {
+            ((value - bottom) / (top - bottom)).clamp(0.0, 1.0)
+        };
+        let from = pole(master, axis);
+        let other = poles.iter().find(|layer| {
+            pole(layer, axis) != from
+                && glyph
+                    .smart_component_settings
+                    .iter()
+                    .filter(|s| s.name != axis)
+                    .all(|s| pole(layer, &s.name) == pole(master, &s.name))
+        });
+        let Some(other) = other.filter(|other| compatible(master, other)) else {
+            continue;
+        };
+        // How far towards the other layer to go, from the master's pole
+        let factor = (at - from) / (pole(other, axis) - from);
+        for (shape, (start, end)) in result
+            .shapes
+            .iter_mut()
+            .zip(master.shapes.iter().zip(&other.shapes))
+        {
+            let (Shape::Path(path), Shape::Path(start), Shape::Path(end)) = (shape, start, end)
+            else {
+                continue;
+            };
+            for (node, (a, b)) in path
+                .nodes
+                .iter_mut()
+                .zip(start.nodes.iter().zip(&end.nodes))
+            {
+                node.x += factor * (b.x - a.x);
+                node.y += factor * (b.y - a.y);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{
+        common::{NodeType, Point, SmartComponentSetting},
+        Font,
+    };
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    fn master_layer<'a>(font: &'a Glyphs3, name: &str) -> &'a Layer {
+        let master_id = &font.masters[0].id;
+        font.glyph(name)
+            .unwrap()
+            .layers
+            .iter()
+            .find(|l| &l.layer_id == master_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decomposed_matches_bounds() {
+        let font = load();
+        let eacute = master_layer(&font, "eacute");
+        let paths = eacute.decomposed(&font);
+        assert!(!paths.is_empty());
+        let mut decomposed = eacute.clone();
+        decomposed.decompose_in_place(&font);
+        assert!(decomposed
+            .shapes
+            .iter()
+            .all(|s| matches!(s, Shape::Path(_))));
+        assert_eq!(
+            decomposed.bounds(),
+            eacute.bounds_with_components(&font, &font.masters[0].id)
+        );
+    }
+
+    fn square(size: f64) -> Shape {
+        let node = |x, y| crate::glyphs3::Node {
+            x,
+            y,
+            node_type: NodeType::Line,
+            user_data: None,
+        };
+        Shape::Path(Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: vec![
+                node(0.0, 0.0),
+                node(size, 0.0),
+                node(size, size),
+                node(0.0, size),
+            ],
+        })
+    }
+
+    #[test]
+    fn test_smart_component_interpolation() {
+        let mut font = load();
+        let master_id = font.masters[0].id.clone();
+        let mut master = master_layer(&font, "eacute").clone();
+        master.shapes = vec![square(100.0)];
+        let mut wide = master.clone();
+        wide.layer_id = "wide".to_string();
+        wide.associated_master_id = Some(master_id.clone());
+        wide.part_selection = BTreeMap::from([("Width".to_string(), 2)]);
+        wide.shapes = vec![square(300.0)];
+        font.glyphs.push(Glyph {
+            name: "_part.square".to_string(),
+            layers: vec![master, wide],
+            smart_component_settings: vec![SmartComponentSetting {
+                bottom_value: 0,
+                top_value: 100,
+                name: "Width".to_string(),
+            }],
+            ..Default::default()
+        });
+        let mut layer = master_layer(&font, "eacute").clone();
+        layer.shapes = vec![Shape::Component(Component {
+            component_glyph: "_part.square".to_string(),
+            smart_component_location: BTreeMap::from([("Width".to_string(), 25.0)]),
+            position: Point::new(10.0, 0.0),
+            ..Default::default()
+        })];
+        let paths = layer.decomposed(&font);
+        let far = &paths[0].nodes[2];
+        assert_eq!((far.x, far.y), (160.0, 150.0));
+
+        let Shape::Component(component) = &layer.shapes[0] else {
+            unreachable!()
+        };
+        assert!(font.glyph("_part.square").unwrap().is_smart_glyph());
+        assert_eq!(component.resolve_smart(&font, &master_id), Some(paths));
+        let part = component.smart_layer(&font, &master_id).unwrap();
+        let Shape::Path(path) = &part.shapes[0] else {
+            unreachable!()
+        };
+        assert_eq!((path.nodes[2].x, path.nodes[2].y), (150.0, 150.0));
+    }
+}
@@ -0,0 +1,658 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use openstep_plist::{
+    diff::{diff, Difference},
+    Plist,
+};
+use serde::Serialize;
+
+use crate::{
+    common::{CustomParameter, Kerning, Point},
+    glyphs3::{Glyph, Glyphs3, Layer, Path, Shape},
+    validate::Location,
+};
+
+/// Which kerning of a font a pair belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KerningDirection {
+    /// `kerningLTR`
+    LeftToRight,
+    /// `kerningRTL`
+    RightToLeft,
+    /// `kerningVertical`
+    Vertical,
+}
+
+/// One way in which two fonts differ, as found by [`Font::diff`](crate::Font::diff)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A glyph only in the second font
+    GlyphAdded {
+        /// The name of the glyph
+        glyph: String,
+    },
+    /// A glyph only in the first font
+    GlyphRemoved {
+        /// The name of the glyph
+        glyph: String,
+    },
+    /// A layer only in the second font's glyph
+    LayerAdded {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+    },
+    /// A layer only in the first font's glyph
+    LayerRemoved {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+    },
+    /// A node which moved, in paths which are otherwise alike
+    NodeMoved {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+        /// The index of the path among the paths of the layer
+        path: usize,
+        /// The index of the node in the path
+        node: usize,
+        /// Where the node was in the first font
+        from: Point,
+        /// Where the node is in the second font
+        to: Point,
+    },
+    /// The paths of a layer differ in number, node counts, node types or
+    /// closedness, so their nodes cannot be compared one by one
+    PathsChanged {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+    },
+    /// The components of a layer differ in glyph, order or transformation
+    ComponentsChanged {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+    },
+    /// An anchor which was added, removed or moved
+    AnchorChanged {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+        /// The name of the anchor
+        anchor: String,
+        /// Where the anchor was in the first font, if it was there
+        from: Option<Point>,
+        /// Where the anchor is in the second font, if it is there
+        to: Option<Point>,
+    },
+    /// The advance width of a layer
+    WidthChanged {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer: String,
+        /// The width in the first font
+        from: f64,
+        /// The width in the second font
+        to: f64,
+    },
+    /// A kerning pair which was added, removed or changed
+    KerningChanged {
+        /// Which of the font's kerning the pair is in
+        direction: KerningDirection,
+        /// The ID of the master
+        master: String,
+        /// The left (or top) side of the pair, a glyph name or group key
+        left: String,
+        /// The right (or bottom) side of the pair, a glyph name or group key
+        right: String,
+        /// The value in the first font, if the pair was kerned
+        from: Option<f64>,
+        /// The value in the second font, if the pair is kerned
+        to: Option<f64>,
+    },
+    /// A custom parameter which was added, removed or changed
+    ParameterChanged {
+        /// The font, or the master or instance, the parameter belongs to
+        location: Location,
+        /// The name of the parameter
+        name: String,
+        /// The value in the first font, if it was set
+        from: Option<Plist>,
+        /// The value in the second font, if it is set
+        to: Option<Plist>,
+    },
+    /// Any other difference, such as in a glyph's Unicode values or a
+    /// master's metrics, with the path of the property as it is in the file
+    PropertyChanged {
+        /// The font, glyph or layer with the property
+        location: Location,
+        /// The difference between the two values of the property
+        difference: Difference,
+    },
+}
+
+fn point(pos: Point) -> String {
+    format!("({}, {})", pos.x, pos.y)
+}
+
+fn optional<T>(value: &Option<T>, show: impl Fn(&T) -> String) -> String {
+    value.as_ref().map_or("nothing".to_string(), show)
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::GlyphAdded { glyph } => write!(f, "glyph {glyph}: added"),
+            Change::GlyphRemoved { glyph } => write!(f, "glyph {glyph}: removed"),
+            Change::LayerAdded { glyph, layer } => {
+                write!(f, "glyph {glyph}, layer {layer}: added")
+            }
+            Change::LayerRemoved { glyph, layer } => {
+                write!(f, "glyph {glyph}, layer {layer}: removed")
+            }
+            Change::NodeMoved {
+                glyph,
+                layer,
+                path,
+                node,
+                from,
+                to,
+            } => write!(
+                f,
+                "glyph {glyph}, layer {layer}: node {node} of path {path} moved from {} to {}",
+                point(*from),
+                point(*to)
+            ),
+            Change::PathsChanged { glyph, layer } => {
+                write!(f, "glyph {glyph}, layer {layer}: paths changed")
+            }
+            Change::ComponentsChanged { glyph, layer } => {
+                write!(f, "glyph {glyph}, layer {layer}: components changed")
+            }
+            Change::AnchorChanged {
+                glyph,
+                layer,
+                anchor,
+                from,
+                to,
+            } => write!(
+                f,
+                "glyph {glyph}, layer {layer}: anchor {anchor} changed from {} to {}",
+                optional(from, |pos| point(*pos)),
+                optional(to, |pos| point(*pos))
+            ),
+            Change::WidthChanged {
+                glyph,
+                layer,
+                from,
+                to,
+            } => write!(
+                f,
+                "glyph {glyph}, layer {layer}: width changed from {from} to {to}"
+            ),
+            Change::KerningChanged {
+                direction,
+                master,
+                left,
+                right,
+                from,
+                to,
+            } => write!(
+                f,
+                "master {master}: {direction:?} kerning of {left} {right} changed from {} to {}",
+                optional(from, f64::to_string),
+                optional(to, f64::to_string)
+            ),
+            Change::ParameterChanged {
+                location,
+                name,
+                from,
+                to,
+            } => write!(
+                f,
+                "{location}: parameter {name} changed from {} to {}",
+                optional(from, |value| value.to_string().trim().to_string()),
+                optional(to, |value| value.to_string().trim().to_string())
+            ),
+            Change::PropertyChanged {
+                location,
+                difference,
+            } => write!(f, "{location}: {difference}"),
+        }
+    }
+}
+
+/// The differences between two fonts, as returned by [`Font::diff`](crate::Font::diff)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontDiff {
+    /// The changes from the first font to the second: those of the font's
+    /// own properties and parameters, then its kerning, then its glyphs in
+    /// the order of the first font, followed by the glyphs added
+    pub changes: Vec<Change>,
+}
+
+impl FontDiff {
+    /// Whether the fonts are alike
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for FontDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+// A value as a plist, to compare the properties without their own variants
+pub(crate) fn to_plist(value: &impl Serialize) -> Plist {
+    openstep_plist::to_string(value)
+        .ok()
+        .and_then(|s| Plist::parse(&s).ok())
+        .unwrap_or_default()
+}
+
+// Remove the keys compared elsewhere from a value as a plist
+fn remove_keys(plist: &mut Plist, keys: &[&str]) {
+    if let Plist::Dictionary(dict) = plist {
+        for key in keys {
+            dict.remove(*key);
+        }
+    }
+}
+
+// Compare the properties of two values, leaving out the keys compared elsewhere
+fn diff_properties(
+    left: &impl Serialize,
+    right: &impl Serialize,
+    skip: &[&str],
+    location: &Location,
+    changes: &mut Vec<Change>,
+) {
+    let [mut left, mut right] = [to_plist(left), to_plist(right)];
+    remove_keys(&mut left, skip);
+    remove_keys(&mut right, skip);
+    diff_plists(&left, &right, location, changes);
+}
+
+fn diff_plists(left: &Plist, right: &Plist, location: &Location, changes: &mut Vec<Change>) {
+    changes.extend(
+        diff(left, right)
+            .into_iter()
+            .map(|difference| Change::PropertyChanged {
+                location: location.clone(),
+                difference,
+            }),
+    );
+}
+
+fn diff_parameters(
+    left: &[CustomParameter],
+    right: &[CustomParameter],
+    location: Location,
+    changes: &mut Vec<Change>,
+) {
+    // The first of several parameters with the same name is the one used
+    let values = |parameters: &[CustomParameter]| {
+        let mut values = BTreeMap::new();
+        for parameter in parameters.iter().filter(|p| !p.disabled) {
+            values
+                .entry(parameter.name.clone())
+                .or_insert(parameter.value.clone());
+        }
+        values
+    };
+    let (mut left, mut right) = (values(left), values(right));
+    let names: BTreeSet<String> = left.keys().chain(right.keys()).cloned().collect();
+    for name in names {
+        let (from, to) = (left.remove(&name), right.remove(&name));
+        if from != to {
+            changes.push(Change::ParameterChanged {
+                location: location.clone(),
+                name,
+                from,
+                to,
+            });
+        }
+    }
+}
+
+fn diff_kerning(
+    left: &Kerning,
+    right: &Kerning,
+    direction: KerningDirection,
+    changes: &mut Vec<Change>,
+) {
+    let value = |kerning: &Kerning, master: &str, l: &str, r: &str| {
+        kerning.get(master)?.get(l)?.get(r).copied()
+    };
+    let mut pairs = BTreeSet::new();
+    for kerning in [left, right] {
+        for (master, lefts) in kerning {
+            for (l, rights) in lefts {
+                for r in rights.keys() {
+                    pairs.insert((master, l, r));
+                }
+            }
+        }
+    }
+    for (master, l, r) in pairs {
+        let (from, to) = (value(left, master, l, r), value(right, master, l, r));
+        if from != to {
+            changes.push(Change::KerningChanged {
+                direction,
+                master: master.clone(),
+                left: l.clone(),
+                right: r.clone(),
+                from,
+                to,
+            });
+        }
+    }
+}
+
+// Whether two paths have the same nodes apart from where they are
+fn same_structure(left: &Path, right: &Path) -> bool {
+    left.closed == right.closed
+        && left.nodes.len() == right.nodes.len()
+        && left
+            .nodes
+            .iter()
+            .zip(&right.nodes)
+            .all(|(l, r)| l.node_type == r.node_type)
+}
+
+fn diff_layer(glyph: &str, left: &Layer, right: &Layer, changes: &mut Vec<Change>) {
+    let layer = &left.layer_id;
+    let location = Location::Layer {
+        glyph: glyph.to_string(),
+        layer_id: layer.clone(),
+    };
+    diff_properties(
+        left,
+        right,
+        &["shapes", "anchors", "width"],
+        &location,
+        changes,
+    );
+    if left.width != right.width {
+        changes.push(Change::WidthChanged {
+            glyph: glyph.to_string(),
+            layer: layer.clone(),
+            from: left.width,
+            to: right.width,
+        });
+    }
+    let paths = |layer| Layer::paths(layer).collect::<Vec<&Path>>();
+    let (left_paths, right_paths) = (paths(left), paths(right));
+    if left_paths.len() != right_paths.len()
+        || left_paths
+            .iter()
+            .zip(&right_paths)
+            .any(|(l, r)| !same_structure(l, r))
+    {
+        changes.push(Change::PathsChanged {
+            glyph: glyph.to_string(),
+            layer: layer.clone(),
+        });
+    } else {
+        for (path, (l, r)) in left_paths.iter().zip(&right_paths).enumerate() {
+            for (node, (from, to)) in l.nodes.iter().zip(&r.nodes).enumerate() {
+                if (from.x, from.y) != (to.x, to.y) {
+                    changes.push(Change::NodeMoved {
+                        glyph: glyph.to_string(),
+                        layer: layer.clone(),
+                        path,
+                        node,
+                        from: from.position(),
+                        to: to.position(),
+                    });
+                }
+            }
+        }
+    }
+    let components = |layer: &Layer| {
+        layer
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape, Shape::Component(_)))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    if components(left) != components(right) {
+        changes.push(Change::ComponentsChanged {
+            glyph: glyph.to_string(),
+            layer: layer.clone(),
+        });
+    }
+    let anchors = |layer: &Layer| {
+        layer
+            .anchors
+            .iter()
+            .map(|anchor| (anchor.name.clone(), anchor.pos))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (mut left_anchors, mut right_anchors) = (anchors(left), anchors(right));
+    let names: BTreeSet<String> = left_anchors
+        .keys()
+        .chain(right_anchors.keys())
+        .cloned()
+        .collect();
+    for anchor in names {
+        let (from, to) = (left_anchors.remove(&anchor), right_anchors.remove(&anchor));
+        if from != to {
+            changes.push(Change::AnchorChanged {
+                glyph: glyph.to_string(),
+                layer: layer.clone(),
+                anchor,
+                from,
+                to,
+            });
+        }
+    }
+}
+
+fn diff_glyph(left: &Glyph, right: &Glyph, changes: &mut Vec<Change>) {
+    let glyph = &left.name;
+    diff_properties(
+        left,
+        right,
+        &["layers"],
+        &Location::Glyph(glyph.clone()),
+        changes,
+    );
+    for layer in &left.layers {
+        match right.layers.iter().find(|l| l.layer_id == layer.layer_id) {
+            Some(other) if other != layer => diff_layer(glyph, layer, other, changes),
+            Some(_) => {}
+            None => changes.push(Change::LayerRemoved {
+                glyph: glyph.clone(),
+                layer: layer.layer_id.clone(),
+            }),
+        }
+    }
+    for layer in &right.layers {
+        if !left.layers.iter().any(|l| l.layer_id == layer.layer_id) {
+            changes.push(Change::LayerAdded {
+                glyph: glyph.clone(),
+                layer: layer.layer_id.clone(),
+            });
+        }
+    }
+}
+
+// The font as a plist, without the parts which have their own changes
+fn font_properties(font: &Glyphs3) -> Plist {
+    let mut plist = to_plist(font);
+    remove_keys(
+        &mut plist,
+        &[
+            "glyphs",
+            "kerningLTR",
+            "kerningRTL",
+            "kerningVertical",
+            "customParameters",
+        ],
+    );
+    for key in ["fontMaster", "instances"] {
+        if let Some(Plist::Array(items)) = plist.pointer_mut(&[key.into()]) {
+            for item in items {
+                remove_keys(item, &["customParameters"]);
+            }
+        }
+    }
+    plist
+}
+
+impl Glyphs3 {
+    /// List the differences between this font and another
+    ///
+    /// The fonts are compared as fonts, not as text, so the layout and
+    /// number formatting of their files make no difference. Glyphs are
+    /// matched by name, layers by ID, masters by ID and instances by name.
+    /// The nodes of a layer's paths are compared one by one when the paths
+    /// have the same structure, and otherwise the paths are reported as
+    /// changed as a whole.
+    pub fn diff(&self, other: &Glyphs3) -> FontDiff {
+        let mut changes = vec![];
+        diff_plists(
+            &font_properties(self),
+            &font_properties(other),
+            &Location::Font,
+            &mut changes,
+        );
+        diff_parameters(
+            &self.custom_parameters,
+            &other.custom_parameters,
+            Location::Font,
+            &mut changes,
+        );
+        for master in &self.masters {
+            if let Some(other_master) = other.masters.iter().find(|m| m.id == master.id) {
+                diff_parameters(
+                    &master.custom_parameters,
+                    &other_master.custom_parameters,
+                    Location::Master(master.id.clone()),
+                    &mut changes,
+                );
+            }
+        }
+        for instance in &self.instances {
+            if let Some(other_instance) = other.instances.iter().find(|i| i.name == instance.name) {
+                diff_parameters(
+                    &instance.custom_parameters,
+                    &other_instance.custom_parameters,
+                    Location::Instance(instance.name.clone()),
+                    &mut changes,
+                );
+            }
+        }
+        for (left, right, direction) in [
+            (&self.kerning, &other.kerning, KerningDirection::LeftToRight),
+            (
+                &self.kerning_rtl,
+                &other.kerning_rtl,
+                KerningDirection::RightToLeft,
+            ),
+            (
+                &self.kerning_vertical,
+                &other.kerning_vertical,
+                KerningDirection::Vertical,
+            ),
+        ] {
+            diff_kerning(left, right, direction, &mut changes);
+        }
+        for glyph in &self.glyphs {
+            match other.glyph(&glyph.name) {
+                Some(other_glyph) if other_glyph != glyph => {
+                    diff_glyph(glyph, other_glyph, &mut changes)
+                }
+                Some(_) => {}
+                None => changes.push(Change::GlyphRemoved {
+                    glyph: glyph.name.clone(),
+                }),
+            }
+        }
+        for glyph in &other.glyphs {
+            if self.glyph(&glyph.name).is_none() {
+                changes.push(Change::GlyphAdded {
+                    glyph: glyph.name.clone(),
+                });
+            }
+        }
+        FontDiff { changes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{glyphs3::Node, Font};
+    use std::path;
+
+    #[test]
+    fn test_diff() {
+        let font = Font::load(path::Path::new("resources/WghtVar.glyphs")).unwrap();
+        assert!(font.diff(&font).is_empty());
+
+        let mut changed = font.clone();
+        let Font::Glyphs3(glyphs3) = &mut changed else {
+            unreachable!()
+        };
+        glyphs3.family_name = "Changed".to_string();
+        glyphs3.custom_parameters.remove(0);
+        glyphs3.set_kerning_pair("m01", "hyphen", "hyphen", -100.0);
+        glyphs3.glyphs.retain(|g| g.name != "space");
+        let exclam = glyphs3.glyph_mut("exclam").unwrap();
+        exclam.unicode = vec![0x21, 0xA1];
+        let Shape::Path(path) = &mut exclam.layers[0].shapes[1] else {
+            unreachable!()
+        };
+        path.nodes[2] = Node {
+            x: 240.0,
+            ..path.nodes[2].clone()
+        };
+        let Shape::Path(path) = &mut exclam.layers[1].shapes[0] else {
+            unreachable!()
+        };
+        path.nodes.pop();
+
+        let changes: Vec<String> = font
+            .diff(&changed)
+            .changes
+            .iter()
+            .map(Change::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "font: familyName: changed WghtVar to Changed",
+                "font: parameter Use Typo Metrics changed from 1 to nothing",
+                "master m01: LeftToRight kerning of hyphen hyphen changed from -150 to -100",
+                "glyph space: removed",
+                "glyph exclam: unicode: changed 33 to (33,161)",
+                "glyph exclam, layer m01: node 2 of path 1 moved from (238, 107) to (240, 107)",
+                "glyph exclam, layer E09E0C54-128D-4FEA-B209-1B70BEFE300B: paths changed",
+            ]
+        );
+
+        // A Glyphs 2 font is compared as it is upgraded
+        let glyphs2 = Font::load(path::Path::new("resources/An-Italic.glyphs")).unwrap();
+        assert!(glyphs2.diff(&glyphs2.upgrade()).is_empty());
+    }
+}
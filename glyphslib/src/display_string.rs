@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::glyphs3::Glyphs3;
+
+/// One entry of a [`GlyphRun`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayGlyph {
+    /// A glyph, given by name
+    ///
+    /// An escaped name is kept even if the font has no such glyph, since it
+    /// is what the string asks for.
+    Glyph(String),
+    /// A literal character which no glyph of the font encodes
+    Missing(char),
+}
+
+/// A line of an Edit View tab, as a sequence of glyphs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlyphRun {
+    /// The glyphs of the line, in order
+    pub glyphs: Vec<DisplayGlyph>,
+}
+
+/// Conversion between the strings of Edit View tabs and glyph sequences
+///
+/// A display string mixes literal characters, which stand for the glyph
+/// encoding them, with `/name` escapes for glyphs by name. An escape ends at
+/// a space, which is dropped, at the next `/`, or at the end of the line. A
+/// `/` not followed by a name is a literal slash. Each line of the string
+/// becomes one [`GlyphRun`].
+///
+/// ```
+/// use glyphslib::display_string::{DisplayGlyph, DisplayString};
+/// use glyphslib::glyphs3::{Glyph, Glyphs3};
+///
+/// let mut font = Glyphs3::default();
+/// for (name, unicode) in [("A", 0x41), ("A.ss01", 0)] {
+///     font.glyphs.push(Glyph {
+///         name: name.to_string(),
+///         unicode: if unicode == 0 { vec![] } else { vec![unicode] },
+///         ..Default::default()
+///     });
+/// }
+/// let runs = DisplayString::parse("A/A.ss01 A", &font);
+/// assert_eq!(runs[0].glyphs[1], DisplayGlyph::Glyph("A.ss01".to_string()));
+/// assert_eq!(DisplayString::build(&runs, &font), "A/A.ss01 A");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayString;
+
+impl DisplayString {
+    /// Split a display string into lines of glyphs
+    pub fn parse(text: &str, font: &Glyphs3) -> Vec<GlyphRun> {
+        let by_codepoint: HashMap<u32, &str> = font
+            .glyphs
+            .iter()
+            .rev() // so the first glyph wins if a code point is doubly encoded
+            .flat_map(|glyph| glyph.unicode.iter().map(|u| (*u, glyph.name.as_str())))
+            .collect();
+        text.split('\n')
+            .map(|line| {
+                let mut glyphs = vec![];
+                let mut chars = line.chars().peekable();
+                while let Some(c) = chars.next() {
+                    if c == '/' && chars.peek().is_some_and(|next| !matches!(next, ' ' | '/')) {
+                        let mut name = String::new();
+                        while let Some(next) = chars.next_if(|next| *next != '/') {
+                            if next == ' ' {
+                                break;
+                            }
+                            name.push(next);
+                        }
+                        glyphs.push(DisplayGlyph::Glyph(name));
+                        continue;
+                    }
+                    glyphs.push(match by_codepoint.get(&(c as u32)) {
+                        Some(name) => DisplayGlyph::Glyph(name.to_string()),
+                        None => DisplayGlyph::Missing(c),
+                    });
+                }
+                GlyphRun { glyphs }
+            })
+            .collect()
+    }
+
+    /// Write lines of glyphs back as a display string
+    ///
+    /// Glyphs which the font encodes are written as their first character,
+    /// and all others as `/name` escapes. The slash is always escaped, as
+    /// `/slash` even when the font has no glyph for it.
+    pub fn build(runs: &[GlyphRun], font: &Glyphs3) -> String {
+        let mut taken = HashSet::new();
+        let by_name: HashMap<&str, char> = font
+            .glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let c = char::from_u32(*glyph.unicode.first()?)?;
+                // A character can only stand for the glyph `parse` would find
+                taken.insert(c).then_some((glyph.name.as_str(), c))
+            })
+            .collect();
+        let mut text = String::new();
+        for (ix, run) in runs.iter().enumerate() {
+            if ix > 0 {
+                text.push('\n');
+            }
+            let mut after_escape = false;
+            for glyph in &run.glyphs {
+                let literal = match glyph {
+                    DisplayGlyph::Glyph(name) => by_name.get(name.as_str()).copied(),
+                    DisplayGlyph::Missing(c) => Some(*c),
+                };
+                match literal {
+                    Some(c) if c != '/' && c != '\n' => {
+                        if after_escape {
+                            text.push(' ');
+                        }
+                        text.push(c);
+                        after_escape = false;
+                    }
+                    _ => {
+                        let name = match glyph {
+                            DisplayGlyph::Glyph(name) => name.as_str(),
+                            DisplayGlyph::Missing(_) => "slash",
+                        };
+                        text.push('/');
+                        text.push_str(name);
+                        after_escape = true;
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    fn glyph(name: &str) -> DisplayGlyph {
+        DisplayGlyph::Glyph(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_escapes_and_literals() {
+        let font = load();
+        let runs = DisplayString::parse("AB/C/A.ss01 B\n/nosuch/ \u{10FFFF}", &font);
+        assert_eq!(
+            runs,
+            vec![
+                GlyphRun {
+                    glyphs: vec![
+                        glyph("A"),
+                        glyph("B"),
+                        glyph("C"),
+                        glyph("A.ss01"),
+                        glyph("B")
+                    ]
+                },
+                GlyphRun {
+                    glyphs: vec![
+                        glyph("nosuch"),
+                        glyph("slash"),
+                        glyph("space"),
+                        DisplayGlyph::Missing('\u{10FFFF}'),
+                    ]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_round_trips() {
+        let font = load();
+        for text in ["AB/C", "/A.ss01 A/nosuch\n\nB", "x/ y"] {
+            let runs = DisplayString::parse(text, &font);
+            let built = DisplayString::build(&runs, &font);
+            assert_eq!(DisplayString::parse(&built, &font), runs, "{text:?}");
+        }
+        let runs = DisplayString::parse("/A/B/C.ss01 A", &font);
+        assert_eq!(DisplayString::build(&runs, &font), "AB/C.ss01 A");
+    }
+
+    #[test]
+    fn test_font_display_strings_parse() {
+        let font = load();
+        for text in &font.display_strings {
+            let runs = DisplayString::parse(text, &font);
+            assert_eq!(runs.len(), text.split('\n').count());
+        }
+    }
+}
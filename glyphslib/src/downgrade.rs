@@ -0,0 +1,473 @@
+use openstep_plist::{Dictionary, Plist};
+
+use crate::{
+    common::{Color, CustomParameter, Kerning, Point, Scale},
+    glyphs2::{self, ColorLabel, CropRect, Transform},
+    glyphs3::{self, ExportType, LocalizedPropertyKey, SingularPropertyKey},
+};
+
+impl From<glyphs3::Node> for glyphs2::Node {
+    fn from(val: glyphs3::Node) -> Self {
+        glyphs2::Node {
+            x: val.x,
+            y: val.y,
+            node_type: val.node_type,
+        }
+    }
+}
+
+impl From<glyphs3::Guide> for glyphs2::Guide {
+    fn from(val: glyphs3::Guide) -> Self {
+        glyphs2::Guide {
+            alignment: val.orientation,
+            angle: val.angle,
+            filter: val.filter,
+            grid: val.grid,
+            length: val.length,
+            lock_angle: val.lock_angle,
+            locked: val.locked,
+            name: val.name,
+            show_measurement: val.show_measurement,
+            pos: val.pos,
+            scale: val.size.into(),
+            user_data: val.user_data.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<glyphs3::Anchor> for glyphs2::Anchor {
+    fn from(val: glyphs3::Anchor) -> Self {
+        glyphs2::Anchor {
+            name: val.name,
+            position: val.pos,
+        }
+    }
+}
+
+// The reverse of `upgrade::decompose`; skew is not carried over.
+fn compose(position: Point, scale: Scale, angle: f64) -> Transform {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    Transform {
+        m11: scale.x * cos,
+        m12: scale.x * sin,
+        m21: 0.0 - scale.y * sin, // rather than -0 when unrotated
+        m22: scale.y * cos,
+        t_x: position.x,
+        t_y: position.y,
+    }
+}
+
+impl From<glyphs3::BackgroundImage> for glyphs2::BackgroundImage {
+    fn from(val: glyphs3::BackgroundImage) -> Self {
+        let (x, y, width, height) = val.crop.unwrap_or_default();
+        glyphs2::BackgroundImage {
+            // The Glyphs 2 fields are named for the edges, but hold the
+            // `{{x, y}, {width, height}}` of the file in order, like the
+            // Glyphs 3 tuple
+            crop: CropRect {
+                top: x.round() as i32,
+                left: y.round() as i32,
+                bottom: width.round() as i32,
+                right: height.round() as i32,
+            },
+            image_path: val.image_path,
+            locked: val.locked,
+            transform: compose(val.pos, val.scale, val.angle),
+        }
+    }
+}
+
+impl From<glyphs3::Component> for glyphs2::Component {
+    fn from(val: glyphs3::Component) -> Self {
+        glyphs2::Component {
+            // An unset Glyphs 3 alignment is read as -1, but means the same as
+            // an unset Glyphs 2 one
+            alignment: if val.alignment == -1 && !val.alignment_explicit {
+                0
+            } else {
+                val.alignment
+            },
+            anchor: val.anchor,
+            locked: val.locked,
+            smart_component_location: val.smart_component_location,
+            component_glyph: val.component_glyph,
+            transform: compose(val.position, val.scale, val.angle),
+            disable_alignment: false,
+            user_data: val.user_data,
+        }
+    }
+}
+
+impl From<glyphs3::Path> for glyphs2::Path {
+    fn from(val: glyphs3::Path) -> Self {
+        glyphs2::Path {
+            closed: val.closed,
+            nodes: val.nodes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<glyphs3::Layer> for glyphs2::Layer {
+    fn from(val: glyphs3::Layer) -> Self {
+        let mut components = vec![];
+        let mut paths = vec![];
+        for shape in val.shapes {
+            match shape {
+                glyphs3::Shape::Component(component) => components.push(component.into()),
+                glyphs3::Shape::Path(path) => paths.push(path.into()),
+            }
+        }
+        glyphs2::Layer {
+            anchors: val.anchors.into_iter().map(Into::into).collect(),
+            annotations: val.annotations,
+            associated_master_id: val.associated_master_id,
+            background: val
+                .background
+                .map(|x| Box::new(std::convert::Into::<glyphs2::Layer>::into(*x))),
+            background_image: val.background_image.map(Into::into),
+            color: val.color.map(|color| match color {
+                Color::Index(index) => ColorLabel::IntColor(index.into()),
+                tuple => ColorLabel::ArrayColor(tuple),
+            }),
+            components,
+            guides: val.guides.into_iter().map(Into::into).collect(),
+            hints: vec![], // The upgrade drops them too
+            layer_id: val.layer_id,
+            metric_left: val.metric_left,
+            metric_right: val.metric_right,
+            metric_width: val.metric_width,
+            name: val.name,
+            paths,
+            user_data: val.user_data,
+            vert_width: val.vert_width,
+            width: val.width,
+            visible: val.visible,
+        }
+    }
+}
+
+impl From<glyphs3::Glyph> for glyphs2::Glyph {
+    fn from(val: glyphs3::Glyph) -> Self {
+        glyphs2::Glyph {
+            kern_bottom: val.kern_bottom,
+            metric_bottom: val.metric_bottom,
+            category: val.category,
+            color: val.color,
+            export: val.export,
+            name: val.name,
+            last_change: val.last_change,
+            layers: val.layers.into_iter().map(Into::into).collect(),
+            kern_left: val.kern_left,
+            metric_left: val.metric_left,
+            locked: val.locked,
+            note: val.note,
+            smart_component_settings: val.smart_component_settings,
+            production: val.production,
+            kern_right: val.kern_right,
+            metric_right: val.metric_right,
+            script: val.script,
+            subcategory: val.subcategory,
+            kern_top: val.kern_top,
+            metric_top: val.metric_top,
+            unicode: val.unicode,
+            user_data: val.user_data,
+            metric_vert_width: val.metric_vert_width,
+            metric_width: val.metric_width,
+        }
+    }
+}
+
+// Glyphs 2 keeps up to six axis locations in fixed fields: weight, width,
+// then the custom axes, in the order of the `Axes` custom parameter.
+fn axis_fields<T: Copy + Default>(values: &[T]) -> [T; 6] {
+    let mut fields = [T::default(); 6];
+    for (field, value) in fields.iter_mut().zip(values) {
+        *field = *value;
+    }
+    fields
+}
+
+impl glyphs3::Master {
+    fn to_glyphs2(&self, font: &glyphs3::Glyphs3) -> glyphs2::Master {
+        let alignment_zones = self.alignment_zones(font);
+        let mut horizontal_stems = vec![];
+        let mut vertical_stems = vec![];
+        for (stem, value) in font.stems.iter().zip(&self.stem_values) {
+            if stem.horizontal {
+                horizontal_stems.push(value.round() as i32);
+            } else {
+                vertical_stems.push(value.round() as i32);
+            }
+        }
+        let axes: Vec<i32> = self.axes_values.iter().map(|v| v.round() as i32).collect();
+        let [weight_value, width_value, custom_value, custom_value_1, custom_value_2, custom_value_3] =
+            axis_fields(&axes);
+        glyphs2::Master {
+            alignment_zones,
+            ascender: self.ascender(font),
+            cap_height: self.cap_height(font),
+            custom: (self.name != "Regular" && !self.name.is_empty()).then(|| self.name.clone()),
+            custom_value,
+            custom_value_1,
+            custom_value_2,
+            custom_value_3,
+            custom_parameters: self.custom_parameters.clone(),
+            descender: self.descender(font),
+            guides: self.guides.iter().cloned().map(Into::into).collect(),
+            horizontal_stems,
+            icon_name: self.icon_name.clone(),
+            id: self.id.clone(),
+            italic_angle: self.italic_angle(font),
+            name: self.name.clone(),
+            user_data: self.user_data.clone(),
+            vertical_stems,
+            visible: self.visible,
+            weight: String::new(),
+            weight_value: if axes.is_empty() { 100 } else { weight_value },
+            width: String::new(),
+            width_value: if axes.len() < 2 { 100 } else { width_value },
+            x_height: self.x_height(font),
+        }
+    }
+}
+
+impl glyphs3::Instance {
+    fn to_glyphs2(&self) -> glyphs2::Instance {
+        let [weight_value, width_value, custom_value, custom_value_1, custom_value_2, custom_value_3] =
+            axis_fields(&self.axes_values);
+        glyphs2::Instance {
+            custom_parameters: self.custom_parameters.clone(),
+            exports: self.exports,
+            custom_value,
+            custom_value_1,
+            custom_value_2,
+            custom_value_3,
+            weight_value,
+            width_value,
+            instance_interpolations: self.instance_interpolations.clone(),
+            is_bold: self.is_bold,
+            is_italic: self.is_italic,
+            link_style: self.link_style.clone(),
+            manual_interpolation: self.manual_interpolation,
+            name: self.name.clone(),
+            user_data: self.user_data.clone(),
+            weight_class: self.weight_class,
+            width_class: self.width_class,
+        }
+    }
+}
+
+impl glyphs3::Glyphs3 {
+    fn localized_property(&self, key: LocalizedPropertyKey) -> Option<String> {
+        self.get_property(key, "dflt").map(String::from)
+    }
+
+    fn singular_property(&self, key: SingularPropertyKey) -> Option<String> {
+        self.get_singular_property(key).map(String::from)
+    }
+
+    // The properties which Glyphs 2 keeps as custom parameters of the font,
+    // and the `Axes` parameter, which names the axes of the masters
+    fn glyphs2_custom_parameters(&self) -> Vec<CustomParameter> {
+        let mut parameters = self.custom_parameters.clone();
+        let mut add = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                if !parameters.iter().any(|p| p.name == name) {
+                    parameters.push(CustomParameter {
+                        disabled: false,
+                        name: name.to_string(),
+                        value: Plist::String(value),
+                    });
+                }
+            }
+        };
+        add(
+            "license",
+            self.localized_property(LocalizedPropertyKey::Licenses),
+        );
+        add(
+            "licenseURL",
+            self.singular_property(SingularPropertyKey::LicenseUrl),
+        );
+        add(
+            "trademark",
+            self.localized_property(LocalizedPropertyKey::Trademarks),
+        );
+        add(
+            "description",
+            self.localized_property(LocalizedPropertyKey::Descriptions),
+        );
+        add(
+            "sampleText",
+            self.localized_property(LocalizedPropertyKey::SampleTexts),
+        );
+        add(
+            "vendorID",
+            self.singular_property(SingularPropertyKey::VendorID),
+        );
+        add(
+            "versionString",
+            self.singular_property(SingularPropertyKey::VersionString),
+        );
+        add(
+            "uniqueID",
+            self.singular_property(SingularPropertyKey::UniqueID),
+        );
+        if !self.axes.is_empty() && !parameters.iter().any(|p| p.name == "Axes") {
+            let axes = self
+                .axes
+                .iter()
+                .map(|axis| {
+                    let mut dict = Dictionary::new();
+                    dict.insert("Name".into(), Plist::String(axis.name.clone()));
+                    dict.insert("Tag".into(), Plist::String(axis.tag.clone()));
+                    if axis.hidden {
+                        dict.insert("Hidden".into(), Plist::Integer(1));
+                    }
+                    Plist::Dictionary(dict)
+                })
+                .collect();
+            parameters.push(CustomParameter {
+                disabled: false,
+                name: "Axes".to_string(),
+                value: Plist::Array(axes),
+            });
+        }
+        parameters
+    }
+}
+
+// Glyphs 2 has no separate right-to-left kerning; its pairs live with the
+// rest, so they are merged in without overriding any left-to-right pair.
+fn merge_kerning(mut kerning: Kerning, rtl: Kerning) -> Kerning {
+    for (master_id, pairs) in rtl {
+        let master = kerning.entry(master_id).or_default();
+        for (left, rights) in pairs {
+            let entry = master.entry(left).or_default();
+            for (right, value) in rights {
+                entry.entry(right).or_insert(value);
+            }
+        }
+    }
+    kerning
+}
+
+impl From<glyphs3::Glyphs3> for glyphs2::Glyphs2 {
+    fn from(val: glyphs3::Glyphs3) -> Self {
+        let custom_parameters = val.glyphs2_custom_parameters();
+        let masters = val.masters.iter().map(|m| m.to_glyphs2(&val)).collect();
+        let instances = val
+            .instances
+            .iter()
+            .filter(|i| matches!(i.export_type, ExportType::Static))
+            .map(|i| i.to_glyphs2())
+            .collect();
+        glyphs2::Glyphs2 {
+            app_version: val.app_version.clone(),
+            display_strings: val.display_strings.clone(),
+            classes: val.classes.clone(),
+            copyright: val.localized_property(LocalizedPropertyKey::Copyrights),
+            custom_parameters,
+            date: val.date.clone(),
+            designer: val
+                .localized_property(LocalizedPropertyKey::Designers)
+                .or_else(|| val.singular_property(SingularPropertyKey::Designer)),
+            designer_url: val.singular_property(SingularPropertyKey::DesignerUrl),
+            disables_automatic_alignment: val.settings.disables_automatic_alignment,
+            disables_nice_names: val.settings.disables_nice_names,
+            family_name: val.family_name.clone(),
+            feature_prefixes: val.feature_prefixes.clone(),
+            features: val.features.clone(),
+            masters,
+            grid_length: val.settings.grid_length,
+            grid_sub_division: val.settings.grid_sub_division,
+            instances,
+            keep_alternates_together: val.settings.keep_alternates_together,
+            kerning_vertical: val.kerning_vertical.clone(),
+            keyboard_increment: val.settings.keyboard_increment,
+            keyboard_increment_big: val.settings.keyboard_increment_big,
+            keyboard_increment_huge: val.settings.keyboard_increment_huge,
+            manufacturer: val
+                .localized_property(LocalizedPropertyKey::Manufacturers)
+                .or_else(|| val.singular_property(SingularPropertyKey::Manufacturer)),
+            manufacturer_url: val.singular_property(SingularPropertyKey::ManufacturerUrl),
+            units_per_em: val.units_per_em,
+            user_data: val.user_data.clone(),
+            version: val.version.clone(),
+            kerning: merge_kerning(val.kerning, val.kerning_rtl),
+            glyphs: val.glyphs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Font;
+    use std::path;
+
+    fn load(file: &str) -> Font {
+        Font::load(path::Path::new("resources").join(file).as_path()).unwrap()
+    }
+
+    #[test]
+    fn test_downgrade_keeps_masters_and_glyphs() {
+        let font = load("RadioCanadaDisplay.glyphs");
+        let glyphs3 = font.as_glyphs3().unwrap();
+        let downgraded = font.downgrade();
+        let glyphs2 = downgraded.as_glyphs2().unwrap();
+        assert_eq!(glyphs2.family_name, glyphs3.family_name);
+        assert_eq!(glyphs2.glyphs.len(), glyphs3.glyphs.len());
+        assert_eq!(glyphs2.masters.len(), glyphs3.masters.len());
+        for (master2, master3) in glyphs2.masters.iter().zip(&glyphs3.masters) {
+            assert_eq!(
+                master2.vertical_stems.len() + master2.horizontal_stems.len(),
+                master3.stem_values.len()
+            );
+            assert_eq!(master2.weight_value as f64, master3.axes_values[0]);
+        }
+        assert!(glyphs2.custom_parameters.iter().any(|p| p.name == "Axes"));
+
+        // The result is a Glyphs 2 file which loads back as one
+        let serialized = downgraded.to_string().unwrap();
+        let reloaded = Font::load_str(&serialized).unwrap();
+        assert!(reloaded.as_glyphs2().is_some());
+        assert_eq!(reloaded.to_string().unwrap(), serialized);
+    }
+
+    #[test]
+    fn test_upgrade_downgrade_round_trip() {
+        let font = load("An-Italic.glyphs");
+        let original = font.as_glyphs2().unwrap();
+        let round_tripped = font.upgrade().downgrade();
+        let glyphs2 = round_tripped.as_glyphs2().unwrap();
+        assert_eq!(glyphs2.glyphs, original.glyphs);
+        for (after, before) in glyphs2.masters.iter().zip(&original.masters) {
+            assert_eq!(after.ascender, before.ascender);
+            assert_eq!(after.x_height, before.x_height);
+            assert_eq!(after.alignment_zones, before.alignment_zones);
+            assert_eq!(after.vertical_stems, before.vertical_stems);
+        }
+        assert_eq!(glyphs2.designer, original.designer);
+        assert_eq!(glyphs2.copyright, original.copyright);
+    }
+
+    #[test]
+    fn test_paths_written_as_glyphs_2_writes_them() {
+        // Saved by Glyphs 2, which writes one node per line
+        let text = std::fs::read_to_string("resources/RoadRage.glyphs").unwrap();
+        let font = load("RoadRage.glyphs");
+        let downgraded = font.upgrade().downgrade();
+        for font in [&font, &downgraded] {
+            let glyphs = &font.as_glyphs2().unwrap().glyphs;
+            let layers = glyphs.iter().flat_map(|g| &g.layers);
+            for layer in layers.filter(|l| !l.paths.is_empty()) {
+                let paths = openstep_plist::ser::to_string(&layer.paths).unwrap();
+                assert!(
+                    text.contains(&format!("\npaths = {paths};\n")),
+                    "layer {} paths were written differently:\n{paths}",
+                    layer.layer_id
+                );
+            }
+        }
+    }
+}
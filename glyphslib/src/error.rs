@@ -1,4 +1,7 @@
 use std::io;
+
+use openstep_plist::{events::offset_of, PathSegment};
+use serde_path_to_error::Segment;
 use thiserror::Error;
 
 /// Errors that can occur when working with Glyphs files.
@@ -16,9 +19,83 @@ pub enum Error {
     #[error("Serialization/Deserialization error: {0}")]
     Serde(#[from] serde_path_to_error::Error<openstep_plist::Error>),
 
-    /// Attempted to save a Glyphs 2 format file as a glyphspackage, which is not supported.
+    /// An error occurred while writing a font out as a property list.
+    #[error("Serialization error: {0}")]
+    Serialize(openstep_plist::Error),
+
+    /// A file which every glyphspackage has, such as `fontinfo.plist`, was missing.
+    #[error("Missing {0} in glyphspackage")]
+    MissingPackageFile(String),
+
+    /// Attempted to save a Glyphs 2 format file as a glyphspackage, which is not supported
+    /// unless [`SaveOptions::upgrade_glyphs2_packages`](crate::SaveOptions::upgrade_glyphs2_packages) is set.
     #[error("Glyphs 2 format files cannot be saved as a glyphspackage")]
     Glyphs2NoPackage,
+
+    /// A package without a `.formatVersion`, as Glyphs 2 wrote them, was opened
+    /// as a [`PackageFont`](crate::package::PackageFont), which only reads Glyphs 3 packages.
+    #[error("Only Glyphs 3 packages can be read lazily; use Font::load for Glyphs 2 packages")]
+    LazyGlyphs2Package,
+
+    /// A glyph database could not be read from its `GlyphData.xml` text.
+    #[error("Invalid GlyphData: {0}")]
+    GlyphData(String),
+
+    /// A kurbo `BezPath` could not be made into a path, as it was not a single contour.
+    #[error("Invalid BezPath: {0}")]
+    BezPath(String),
+
+    /// The font has no glyph with the given name.
+    #[error("Glyph {0} not found")]
+    UnknownGlyph(String),
+
+    /// The font already has a glyph with the given name.
+    #[error("Glyph {0} already exists")]
+    DuplicateGlyph(String),
+
+    /// A node was addressed by a layer, shape or node index which does not exist.
+    #[error("No node {node} in path {shape} of layer {layer_id} of glyph {glyph}")]
+    NodeOutOfRange {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer_id: String,
+        /// The index of the path among the layer's shapes
+        shape: usize,
+        /// The index of the node within the path
+        node: usize,
+    },
 }
 
+/// A `Result` whose error is a glyphslib [`Error`](enum@Error).
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    // Point a deserialization error at the place in the file text it came
+    // from, or as near to it as its path goes
+    pub(crate) fn located(self, s: &str) -> Self {
+        let Error::Serde(error) = self else {
+            return self;
+        };
+        let path: Vec<PathSegment> = error
+            .path()
+            .iter()
+            .map_while(|segment| match segment {
+                Segment::Map { key } => Some(PathSegment::Key(key)),
+                Segment::Seq { index } => Some(PathSegment::Index(*index)),
+                Segment::Enum { .. } | Segment::Unknown => None,
+            })
+            .collect();
+        let offset = (0..=path.len())
+            .rev()
+            .find_map(|len| offset_of(s, &path[..len]));
+        let Some(offset) = offset else {
+            return Error::Serde(error);
+        };
+        let path = error.path().clone();
+        Error::Serde(serde_path_to_error::Error::new(
+            path,
+            error.into_inner().located(s, offset),
+        ))
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use fea_rs::{
+    compile::{self, NopVariationInfo},
+    parse::{self, SourceLoadError},
+    DiagnosticSet, GlyphMap, Level, ParseTree,
+};
+
+use crate::{
+    common::Feature,
+    glyphs3::Glyphs3,
+    validate::{Diagnostic, Location},
+};
+
+const ROOT: &str = "features.fea";
+
+// The feature code of a font as one FEA text, remembering which prefix,
+// class or feature each part of it came from
+#[derive(Default)]
+struct Assembled {
+    text: String,
+    // Where each piece's own code starts in the text, and how long it is
+    pieces: Vec<(Location, usize, usize)>,
+}
+
+impl Assembled {
+    fn push(&mut self, location: Location, before: &str, code: &str, after: &str) {
+        self.text.push_str(before);
+        self.pieces.push((location, self.text.len(), code.len()));
+        self.text.push_str(code);
+        self.text.push_str(after);
+    }
+
+    fn push_feature(&mut self, feature: &Feature) {
+        let tag = &feature.tag;
+        self.push(
+            Location::Feature(tag.clone()),
+            &format!("feature {tag} {{\n"),
+            &feature.code,
+            &format!("\n}} {tag};\n\n"),
+        );
+    }
+
+    // The piece an offset in the text belongs to, and the line of its code
+    // that is on. Text added around a piece, such as the closing brace of a
+    // feature, counts as the nearest line of the piece before it.
+    fn locate(&self, offset: usize) -> Option<(Location, usize)> {
+        let ix = self
+            .pieces
+            .partition_point(|(_, start, _)| *start <= offset)
+            .checked_sub(1)?;
+        let (location, start, len) = &self.pieces[ix];
+        let end = (offset - start).min(*len);
+        let code = &self.text[*start..start + end];
+        Some((location.clone(), code.matches('\n').count() + 1))
+    }
+
+    fn parse(&self, glyph_map: &GlyphMap) -> (ParseTree, DiagnosticSet) {
+        let text: Arc<str> = self.text.as_str().into();
+        let resolver = move |path: &Path| {
+            if path == Path::new(ROOT) {
+                Ok(text.clone())
+            } else {
+                Err(SourceLoadError::new(
+                    path.to_path_buf(),
+                    "included files are not checked",
+                ))
+            }
+        };
+        // Every error, including one for a missing include, comes back as a
+        // diagnostic; only the root source can fail to load, and it cannot
+        parse::parse_root(PathBuf::from(ROOT), Some(glyph_map), Box::new(resolver))
+            .expect("the root source is always found")
+    }
+
+    // Parse the text, and validate it as well if asked, reporting problems
+    // at the pieces they were found in
+    fn check(&self, glyph_names: &[&str], validate: bool) -> Vec<Diagnostic> {
+        let Ok(glyph_map) = GlyphMap::new(glyph_names.iter().copied()) else {
+            return vec![Diagnostic::error(
+                Location::Font,
+                "too many glyphs to check the feature code".to_string(),
+            )];
+        };
+        let (tree, mut diagnostics) = self.parse(&glyph_map);
+        if validate && !diagnostics.has_errors() {
+            diagnostics = compile::validate::<NopVariationInfo>(&tree, &glyph_map, None);
+        }
+        diagnostics
+            .diagnostics()
+            .iter()
+            .filter_map(|diagnostic| {
+                let (location, line) = self.locate(diagnostic.span().start)?;
+                let message = format!("line {line}: {}", diagnostic.text());
+                Some(match diagnostic.level {
+                    Level::Error => Diagnostic::error(location, message),
+                    Level::Warning | Level::Info => Diagnostic::warning(location, message),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Feature {
+    /// Parse the code of the feature on its own with fea-rs, reporting
+    /// syntax errors
+    ///
+    /// The glyph names tell names such as `a-b` from ranges. Glyph and class
+    /// names are not checked, as classes may be defined elsewhere in the
+    /// font; see [`Glyphs3::check_features`].
+    pub fn check_syntax(&self, glyph_names: &[&str]) -> Vec<Diagnostic> {
+        let mut assembled = Assembled::default();
+        assembled.push_feature(self);
+        assembled.check(glyph_names, false)
+    }
+}
+
+impl Glyphs3 {
+    /// Parse and validate the feature code of the font with fea-rs
+    ///
+    /// The prefixes, classes and features are put together as they are
+    /// compiled, and any problem fea-rs finds, from a missing `;` to a glyph
+    /// or class which does not exist, is reported at the prefix, class or
+    /// feature it is in. Disabled code is left out, and included files are
+    /// not read. Validation only happens once the code parses.
+    pub fn check_features(&self) -> Vec<Diagnostic> {
+        let mut assembled = Assembled::default();
+        for prefix in self.feature_prefixes.iter().filter(|p| !p.disabled) {
+            let location = Location::FeaturePrefix(prefix.name.clone());
+            assembled.push(location, "", &prefix.code, "\n\n");
+        }
+        for class in self.classes.iter().filter(|c| !c.disabled) {
+            let location = Location::Class(class.name.clone());
+            // A comment at the end must not swallow the bracket
+            assembled.push(
+                location,
+                &format!("@{} = [", class.name),
+                &class.code,
+                "\n];\n",
+            );
+        }
+        for feature in self.features.iter().filter(|f| !f.disabled) {
+            assembled.push_feature(feature);
+        }
+        let glyph_names: Vec<&str> = self.glyphs.iter().map(|g| g.name.as_str()).collect();
+        assembled.check(&glyph_names, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FeatureClass, FeaturePrefix};
+
+    #[test]
+    fn test_check_features() {
+        let feature = |tag: &str, code: &str| Feature {
+            tag: tag.to_string(),
+            code: code.to_string(),
+            ..Default::default()
+        };
+        let glyphs = ["a", "b", "z", "a.sc", "a-b"];
+        let good = feature("smcp", "sub [a z] by @sc;\nsub a-b by b; # [nosuch\n");
+        assert!(good.check_syntax(&glyphs).is_empty());
+        let bad = feature("liga", "sub a by b;\nlookup x {\n  sub a by b\n} x;\n");
+        let messages: Vec<String> = bad
+            .check_syntax(&glyphs)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(messages, ["error: feature liga: line 3: Expected ';'"]);
+
+        let mut font = Glyphs3 {
+            glyphs: glyphs
+                .iter()
+                .map(|name| crate::glyphs3::Glyph {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            feature_prefixes: vec![FeaturePrefix {
+                name: "marks".to_string(),
+                code: "markClass [b] <anchor 0 0> @top;".to_string(),
+                ..Default::default()
+            }],
+            classes: vec![FeatureClass {
+                name: "sc".to_string(),
+                code: "a.sc # small caps".to_string(),
+                ..Default::default()
+            }],
+            features: vec![
+                good,
+                feature(
+                    "ss01",
+                    "@local = [a b];\nsub @local by z;\nsub @missing by a;",
+                ),
+            ],
+            ..Default::default()
+        };
+        let messages = |font: &Glyphs3| -> Vec<String> {
+            font.check_features()
+                .iter()
+                .map(|d| d.to_string())
+                .collect()
+        };
+        assert_eq!(
+            messages(&font),
+            ["error: feature ss01: line 3: undefined glyph class"]
+        );
+        font.classes[0].code = "a.sc b.sc".to_string();
+        assert_eq!(
+            messages(&font),
+            [
+                "error: class sc: line 1: glyph not in font",
+                "error: feature ss01: line 3: undefined glyph class",
+            ]
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    common::{Feature, Kerning},
+    glyphs3::{Glyph, Glyphs3},
+};
+
+/// Options for [`Glyphs3::to_fea`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeaOptions<'a> {
+    /// Regenerate the automatic prefixes, classes and features where this
+    /// crate can, and leave out those it cannot, instead of using the code
+    /// Glyphs last generated for them
+    ///
+    /// Only the `kern` feature can be generated; see
+    /// [`kerning`](Self::kerning).
+    pub regenerate_automatic: bool,
+    /// Add a `kern` feature generated by [`generate_kern`] from this kerning
+    pub kerning: Option<KerningSource<'a>>,
+}
+
+/// Which kerning [`generate_kern`] writes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KerningSource<'a> {
+    /// The kerning of the master with this ID
+    Master(&'a str),
+    /// The kerning of the instance with this name, interpolated from the
+    /// masters by its `instanceInterpolations`
+    ///
+    /// A pair which a master does not kern counts as zero in that master.
+    Instance(&'a str),
+}
+
+// One side of a kerning pair: a glyph, or a class of glyphs
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Side {
+    Glyph(String),
+    Class(String),
+}
+
+// The factors by which each master's kerning counts
+fn master_factors(font: &Glyphs3, source: KerningSource) -> Option<Vec<(String, f64)>> {
+    match source {
+        KerningSource::Master(id) => {
+            let master = font.masters.iter().find(|m| m.id == id)?;
+            Some(vec![(master.id.clone(), 1.0)])
+        }
+        KerningSource::Instance(name) => {
+            let instance = font.instances.iter().find(|i| i.name == name)?;
+            if !instance.instance_interpolations.is_empty() {
+                return Some(
+                    instance
+                        .instance_interpolations
+                        .iter()
+                        .filter_map(|(id, factors)| Some((id.clone(), *factors.0.first()?)))
+                        .collect(),
+                );
+            }
+            let master = font
+                .masters
+                .iter()
+                .find(|m| m.axes_values == instance.axes_values)?;
+            Some(vec![(master.id.clone(), 1.0)])
+        }
+    }
+}
+
+// The FEA name of a kerning group: `@kern1.` for groups on the left of pairs
+// and `@kern2.` for those on the right, as glyphsLib names them
+fn class_name(side: u8, group: &str) -> String {
+    let group: String = group
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("@kern{side}.{group}")
+}
+
+struct KernWriter<'a> {
+    glyphs: Vec<&'a Glyph>,
+    classes: BTreeMap<String, Vec<&'a str>>,
+}
+
+impl<'a> KernWriter<'a> {
+    // The side a key stands for, defining its class if it is a group with
+    // exported glyphs in it
+    fn side(&mut self, key: &str, left: bool) -> Option<Side> {
+        let (prefix, side) = if left { ("@MMK_L_", 1) } else { ("@MMK_R_", 2) };
+        let Some(group) = key.strip_prefix(prefix) else {
+            return self
+                .glyphs
+                .iter()
+                .any(|g| g.name == key)
+                .then(|| Side::Glyph(key.to_string()));
+        };
+        let members: Vec<&str> = self
+            .glyphs
+            .iter()
+            .filter(|g| {
+                let glyph_group = if left { &g.kern_right } else { &g.kern_left };
+                glyph_group.as_deref() == Some(group)
+            })
+            .map(|g| g.name.as_str())
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+        let name = class_name(side, group);
+        self.classes.insert(name.clone(), members);
+        Some(Side::Class(name))
+    }
+
+    fn pairs(
+        &mut self,
+        kerning: &Kerning,
+        factors: &[(String, f64)],
+    ) -> BTreeMap<(Side, Side), f64> {
+        let mut pairs = BTreeMap::new();
+        for (master, factor) in factors {
+            let Some(master_pairs) = kerning.get(master) else {
+                continue;
+            };
+            for (left, rights) in master_pairs {
+                let Some(left) = self.side(left, true) else {
+                    continue;
+                };
+                for (right, value) in rights {
+                    let Some(right) = self.side(right, false) else {
+                        continue;
+                    };
+                    *pairs.entry((left.clone(), right)).or_insert(0.0) += factor * value;
+                }
+            }
+        }
+        pairs
+    }
+}
+
+// A lookup of pairs in the order the kern writer of glyphsLib (and ufo2ft)
+// uses: glyph pairs, then exceptions of a glyph against a class, then pairs
+// of classes
+fn write_lookup(fea: &mut String, name: &str, pairs: &BTreeMap<(Side, Side), f64>, rtl: bool) {
+    if pairs.is_empty() {
+        return;
+    }
+    let _ = writeln!(fea, "lookup {name} {{");
+    let _ = writeln!(fea, "    lookupflag IgnoreMarks;");
+    let kind = |(left, right): &(Side, Side)| match (left, right) {
+        (Side::Glyph(_), Side::Glyph(_)) => 0,
+        (Side::Class(_), Side::Class(_)) => 2,
+        _ => 1,
+    };
+    for order in 0..3 {
+        for (pair, value) in pairs.iter().filter(|(pair, _)| kind(pair) == order) {
+            let text = |side: &Side| match side {
+                Side::Glyph(name) | Side::Class(name) => name.clone(),
+            };
+            let value = value.round() as i32;
+            let value = if rtl {
+                format!("<{value} 0 {value} 0>")
+            } else {
+                value.to_string()
+            };
+            let enumerate = if order == 1 { "enum " } else { "" };
+            let _ = writeln!(
+                fea,
+                "    {enumerate}pos {} {} {value};",
+                text(&pair.0),
+                text(&pair.1)
+            );
+        }
+    }
+    let _ = writeln!(fea, "}} {name};\n");
+}
+
+/// A `kern` feature in FEA syntax for the left-to-right and right-to-left
+/// kerning of a master or instance
+///
+/// Kerning groups become classes named `@kern1.` (for `@MMK_L_` groups)
+/// and `@kern2.` (for `@MMK_R_` groups) followed by the group name, with
+/// the exported glyphs in them. Pairs go in a `kern_ltr` and a `kern_rtl`
+/// lookup, glyph pairs first, then exceptions, then pairs of classes, so
+/// that exceptions take effect before the classes they are taken from.
+/// Pairs of glyphs which are not exported, or of groups with no exported
+/// glyphs, are left out. Values are rounded to whole units.
+///
+/// Returns `None` if there is no such master or instance, and an empty
+/// string if there is nothing to kern.
+pub fn generate_kern(font: &Glyphs3, source: KerningSource) -> Option<String> {
+    let factors = master_factors(font, source)?;
+    let mut writer = KernWriter {
+        glyphs: font.glyphs.iter().filter(|g| g.export).collect(),
+        classes: BTreeMap::new(),
+    };
+    let ltr = writer.pairs(&font.kerning, &factors);
+    let rtl = writer.pairs(&font.kerning_rtl, &factors);
+    let mut fea = String::new();
+    if ltr.is_empty() && rtl.is_empty() {
+        return Some(fea);
+    }
+    for (name, members) in &writer.classes {
+        let _ = writeln!(fea, "{name} = [{}];", members.join(" "));
+    }
+    if !writer.classes.is_empty() {
+        fea.push('\n');
+    }
+    write_lookup(&mut fea, "kern_ltr", &ltr, false);
+    write_lookup(&mut fea, "kern_rtl", &rtl, true);
+    fea.push_str("feature kern {\n");
+    for (name, pairs) in [("kern_ltr", &ltr), ("kern_rtl", &rtl)] {
+        if !pairs.is_empty() {
+            let _ = writeln!(fea, "    lookup {name};");
+        }
+    }
+    fea.push_str("} kern;\n");
+    Some(fea)
+}
+
+// The `featureNames` of a stylistic set, from its default and English labels
+fn feature_names(feature: &Feature) -> Option<String> {
+    let label = feature
+        .label_for_language("dflt")
+        .or_else(|| feature.label_for_language("ENG"))?;
+    let value = label.replace('"', "");
+    Some(format!("featureNames {{\n    name \"{value}\";\n}};\n"))
+}
+
+impl Glyphs3 {
+    /// The feature code of the font as one FEA file
+    ///
+    /// This is the prefixes, then the classes as `@name = [...];`, then the
+    /// features in `feature tag { ... } tag;` blocks, in the order of the
+    /// font. Disabled prefixes, classes and features are left out. The
+    /// English or default label of a stylistic set becomes its
+    /// `featureNames`.
+    pub fn to_fea(&self, options: &FeaOptions) -> String {
+        let include = |disabled: bool, automatic: bool| {
+            !(disabled || automatic && options.regenerate_automatic)
+        };
+        let mut fea = String::new();
+        for prefix in &self.feature_prefixes {
+            if include(prefix.disabled, prefix.automatic) {
+                let _ = writeln!(
+                    fea,
+                    "# Prefix: {}\n{}\n",
+                    prefix.name,
+                    prefix.code.trim_end()
+                );
+            }
+        }
+        for class in &self.classes {
+            if include(class.disabled, class.automatic) {
+                let code = class.code.trim();
+                // A comment at the end must not swallow the bracket
+                let end = if code.contains('#') { "\n" } else { "" };
+                let _ = writeln!(fea, "@{} = [{code}{end}];", class.name);
+            }
+        }
+        if !fea.is_empty() && !fea.ends_with("\n\n") {
+            fea.push('\n');
+        }
+        let regenerate_kern = options.regenerate_automatic && options.kerning.is_some();
+        for feature in &self.features {
+            let kern = feature.tag == "kern" && regenerate_kern;
+            if kern || !include(feature.disabled, feature.automatic) {
+                continue;
+            }
+            let tag = &feature.tag;
+            let _ = writeln!(fea, "feature {tag} {{");
+            if let Some(names) = feature_names(feature) {
+                fea.push_str(&names);
+            }
+            let _ = writeln!(fea, "{}\n}} {tag};\n", feature.code.trim_end());
+        }
+        if let Some(kerning) = options
+            .kerning
+            .and_then(|source| generate_kern(self, source))
+        {
+            fea.push_str(&kerning);
+        }
+        fea
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::{FeatureClass, FeaturePrefix, StylisticSetLabel},
+        Font,
+    };
+    use std::path;
+
+    #[test]
+    fn test_generate_kern() {
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let mut font = font.as_glyphs3().unwrap().subset(&["A", "Aacute", "V"]);
+        font.glyph_mut("V").unwrap().kern_left = Some("V".to_string());
+        font.glyph_mut("V").unwrap().kern_right = Some("V".to_string());
+        for glyph in ["A", "Aacute"] {
+            font.glyph_mut(glyph).unwrap().kern_right = Some("A".to_string());
+        }
+        let (first, second) = (font.masters[0].id.clone(), font.masters[1].id.clone());
+        font.set_kerning_pair(&first, "@MMK_L_A", "@MMK_R_V", -50.0);
+        font.set_kerning_pair(&first, "Aacute", "@MMK_R_V", -30.0);
+        font.set_kerning_pair(&first, "A", "V", -80.0);
+        font.set_kerning_pair(&first, "@MMK_L_nosuchgroup", "V", -10.0);
+        font.set_kerning_pair(&second, "@MMK_L_A", "@MMK_R_V", -100.0);
+
+        let fea = generate_kern(&font, KerningSource::Master(&first)).unwrap();
+        assert_eq!(
+            fea,
+            "@kern1.A = [A Aacute];\n\
+             @kern2.V = [V];\n\
+             \n\
+             lookup kern_ltr {\n    \
+                 lookupflag IgnoreMarks;\n    \
+                 pos A V -80;\n    \
+                 enum pos Aacute @kern2.V -30;\n    \
+                 pos @kern1.A @kern2.V -50;\n\
+             } kern_ltr;\n\
+             \n\
+             feature kern {\n    \
+                 lookup kern_ltr;\n\
+             } kern;\n"
+        );
+        assert_eq!(
+            generate_kern(&font, KerningSource::Master("nosuchmaster")),
+            None
+        );
+
+        font.instances[0].name = "Halfway".to_string();
+        font.instances[0].instance_interpolations = BTreeMap::from([
+            (first.clone(), crate::common::InstanceFactors(vec![0.5])),
+            (second.clone(), crate::common::InstanceFactors(vec![0.5])),
+        ]);
+        let fea = generate_kern(&font, KerningSource::Instance("Halfway")).unwrap();
+        assert!(fea.contains("pos @kern1.A @kern2.V -75;"));
+        assert!(fea.contains("pos A V -40;"));
+    }
+
+    #[test]
+    fn test_to_fea() {
+        let mut font = Glyphs3 {
+            feature_prefixes: vec![FeaturePrefix {
+                name: "Languagesystems".to_string(),
+                code: "languagesystem DFLT dflt;\n".to_string(),
+                automatic: true,
+                ..Default::default()
+            }],
+            classes: vec![
+                FeatureClass {
+                    name: "Uppercase".to_string(),
+                    code: "A B # capitals".to_string(),
+                    ..Default::default()
+                },
+                FeatureClass {
+                    name: "Off".to_string(),
+                    code: "C".to_string(),
+                    disabled: true,
+                    ..Default::default()
+                },
+            ],
+            features: vec![
+                Feature {
+                    tag: "ss01".to_string(),
+                    code: "sub A by A.ss01;".to_string(),
+                    labels: vec![StylisticSetLabel::new("dflt", "Alternate A")],
+                    ..Default::default()
+                },
+                Feature {
+                    tag: "kern".to_string(),
+                    code: "pos A B -10;".to_string(),
+                    automatic: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            font.to_fea(&FeaOptions::default()),
+            "# Prefix: Languagesystems\nlanguagesystem DFLT dflt;\n\n\
+             @Uppercase = [A B # capitals\n];\n\n\
+             feature ss01 {\nfeatureNames {\n    name \"Alternate A\";\n};\nsub A by A.ss01;\n} ss01;\n\n\
+             feature kern {\npos A B -10;\n} kern;\n\n"
+        );
+
+        font.glyphs = vec![Glyph {
+            name: "A".to_string(),
+            export: true,
+            ..Default::default()
+        }];
+        font.masters = vec![Default::default()];
+        font.set_kerning_pair("", "A", "A", -5.0);
+        let options = FeaOptions {
+            regenerate_automatic: true,
+            kerning: Some(KerningSource::Master("")),
+        };
+        let fea = font.to_fea(&options);
+        assert!(fea.starts_with("@Uppercase"));
+        assert!(!fea.contains("pos A B -10;"));
+        assert!(fea.contains("pos A A -5;"));
+    }
+}
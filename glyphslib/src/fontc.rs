@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use crate::glyphs3::{Glyphs3, Layer, Master};
+
+pub use crate::axes::Location;
+
+/// The extent of an axis in design coordinates, as covered by the masters
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisRange {
+    /// The user-facing name of the axis
+    pub name: String,
+    /// The OpenType tag of the axis
+    pub tag: String,
+    /// Whether the axis is hidden from the font user
+    pub hidden: bool,
+    /// The smallest master coordinate on this axis
+    pub min: f64,
+    /// The coordinate of the default master on this axis
+    pub default: f64,
+    /// The largest master coordinate on this axis
+    pub max: f64,
+}
+
+/// Font-wide information a compiler needs before looking at any glyph
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticMetadata {
+    /// The family name of the font
+    pub family_name: String,
+    /// The units per em of the font
+    pub units_per_em: i32,
+    /// The axes of the font with their ranges
+    pub axes: Vec<AxisRange>,
+    /// The ID of the master at the default location
+    pub default_master_id: String,
+    /// The names of the exported glyphs, in the order they should appear in the binary
+    pub glyph_order: Vec<String>,
+}
+
+/// A layer which contributes a source for a glyph at some location
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphSource<'a> {
+    /// Where this source sits in design space
+    pub location: Location,
+    /// The master the source belongs to
+    pub master_id: &'a str,
+    /// The layer holding the outlines of the source
+    pub layer: &'a Layer,
+}
+
+impl Glyphs3 {
+    /// The design space location of a master
+    pub fn master_location(&self, master: &Master) -> Location {
+        self.location_of(&master.axes_values)
+    }
+
+    fn location_of(&self, coordinates: &[f64]) -> Location {
+        self.axes
+            .iter()
+            .zip(coordinates)
+            .map(|(axis, value)| (axis.tag.clone(), *value))
+            .collect()
+    }
+
+    /// The names of the exported glyphs in compilation order
+    ///
+    /// Glyphs listed in the `glyphOrder` custom parameter come first, in that
+    /// order; the remaining glyphs follow in file order. Names in the parameter
+    /// which do not refer to an exported glyph are ignored.
+    pub fn glyph_order(&self) -> Vec<String> {
+        let exported: Vec<&str> = self
+            .glyphs
+            .iter()
+            .filter(|g| g.export)
+            .map(|g| g.name.as_str())
+            .collect();
+        let requested = self
+            .custom_parameters
+            .iter()
+            .find(|p| p.name == "glyphOrder" && !p.disabled)
+            .and_then(|p| p.as_glyph_order())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+        let mut order = vec![];
+        for name in requested {
+            if exported.contains(&name) && seen.insert(name) {
+                order.push(name.to_string());
+            }
+        }
+        for name in exported {
+            if seen.insert(name) {
+                order.push(name.to_string());
+            }
+        }
+        order
+    }
+
+    /// The ranges of the axes of the font, as spanned by its masters
+    pub fn axis_ranges(&self) -> Vec<AxisRange> {
+        let default = self.default_master();
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(ix, axis)| {
+                let values = self.masters.iter().filter_map(|m| m.axes_values.get(ix));
+                let (min, max) = values.fold((f64::MAX, f64::MIN), |(min, max), v| {
+                    (min.min(*v), max.max(*v))
+                });
+                let default = default
+                    .and_then(|m| m.axes_values.get(ix))
+                    .copied()
+                    .unwrap_or(min);
+                AxisRange {
+                    name: axis.name.clone(),
+                    tag: axis.tag.clone(),
+                    hidden: axis.hidden,
+                    min: min.min(default),
+                    default,
+                    max: max.max(default),
+                }
+            })
+            .collect()
+    }
+
+    /// Font-wide information for a compiler front end
+    pub fn static_metadata(&self) -> StaticMetadata {
+        StaticMetadata {
+            family_name: self.family_name.clone(),
+            units_per_em: self.units_per_em,
+            axes: self.axis_ranges(),
+            default_master_id: self
+                .default_master()
+                .map(|m| m.id.clone())
+                .unwrap_or_default(),
+            glyph_order: self.glyph_order(),
+        }
+    }
+
+    /// The sources of a glyph, one per master layer and intermediate layer
+    ///
+    /// Intermediate ("brace") layers take their location from the
+    /// `coordinates` layer attribute. Other non-master layers, such as backup
+    /// layers, are skipped. Returns `None` if there is no glyph with this name.
+    pub fn glyph_sources(&self, glyph_name: &str) -> Option<Vec<GlyphSource<'_>>> {
+        let glyph = self.glyph(glyph_name)?;
+        let mut sources = vec![];
+        for layer in &glyph.layers {
+            if let Some(master) = self.masters.iter().find(|m| m.id == layer.layer_id) {
+                sources.push(GlyphSource {
+                    location: self.master_location(master),
+                    master_id: &master.id,
+                    layer,
+                });
+                continue;
+            }
+            let (Some(master_id), Some(coordinates)) = (
+                layer.associated_master_id.as_deref(),
+                layer.brace_coordinates(),
+            ) else {
+                continue;
+            };
+            sources.push(GlyphSource {
+                location: self.location_of(coordinates),
+                master_id,
+                layer,
+            });
+        }
+        Some(sources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn load(file: &str) -> Glyphs3 {
+        let font = Font::load(path::Path::new(file)).unwrap();
+        font.as_glyphs3().unwrap().clone()
+    }
+
+    #[test]
+    fn test_custom_origin() {
+        let font = load("resources/WghtVar_3master_CustomOrigin.glyphs");
+        let metadata = font.static_metadata();
+        assert_eq!(
+            metadata.default_master_id,
+            "E09E0C54-128D-4FEA-B209-1B70BEFE300B"
+        );
+        let wght = &metadata.axes[0];
+        assert_eq!(wght.tag, "wght");
+        assert!(wght.min <= wght.default && wght.default <= wght.max);
+        assert_eq!(wght.default, font.default_master().unwrap().axes_values[0]);
+    }
+
+    #[test]
+    fn test_glyph_order_parameter() {
+        let font = load("resources/WghtVar_GlyphOrder.glyphs");
+        let order = font.glyph_order();
+        assert_eq!(&order[..2], &["hyphen", "space"]);
+        assert!(!order.contains(&"not-a-valid-name".to_string()));
+        assert_eq!(order.len(), font.glyphs.iter().filter(|g| g.export).count());
+    }
+
+    #[test]
+    fn test_intermediate_layer_sources() {
+        let font = load("resources/IntermediateLayer.glyphs");
+        let glyph = font
+            .glyphs
+            .iter()
+            .find(|g| g.layers.iter().any(|l| l.is_brace_layer()))
+            .unwrap();
+        let sources = font.glyph_sources(&glyph.name).unwrap();
+        assert!(sources.len() > font.masters.len());
+        let locations: Vec<&Location> = sources.iter().map(|s| &s.location).collect();
+        assert!(locations.iter().any(|l| l.values().any(|v| *v == 600.0)));
+        assert!(font.glyph_sources("no-such-glyph").is_none());
+    }
+}
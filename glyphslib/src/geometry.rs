@@ -0,0 +1,723 @@
+use crate::{
+    common::{NodeType, Point, Scale},
+    glyphs2,
+    glyphs3::{self, Glyphs3, Shape},
+};
+
+// Guard against component cycles in broken files.
+pub(crate) const MAX_COMPONENT_DEPTH: usize = 32;
+
+/// An axis-aligned rectangle, such as the bounding box of an outline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    /// The left edge
+    pub x_min: f64,
+    /// The bottom edge
+    pub y_min: f64,
+    /// The right edge
+    pub x_max: f64,
+    /// The top edge
+    pub y_max: f64,
+}
+
+impl Rect {
+    fn from_point((x, y): (f64, f64)) -> Self {
+        Rect {
+            x_min: x,
+            y_min: y,
+            x_max: x,
+            y_max: y,
+        }
+    }
+
+    fn include_point(&mut self, (x, y): (f64, f64)) {
+        self.x_min = self.x_min.min(x);
+        self.y_min = self.y_min.min(y);
+        self.x_max = self.x_max.max(x);
+        self.y_max = self.y_max.max(y);
+    }
+
+    /// The width of the rectangle
+    pub fn width(&self) -> f64 {
+        self.x_max - self.x_min
+    }
+
+    /// The height of the rectangle
+    pub fn height(&self) -> f64 {
+        self.y_max - self.y_min
+    }
+
+    /// The smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+}
+
+fn union_all(rects: impl Iterator<Item = Rect>) -> Option<Rect> {
+    rects.reduce(|a, b| a.union(&b))
+}
+
+/// A 2x3 affine matrix in the order `[xx, xy, yx, yy, dx, dy]`, the order
+/// of the Glyphs 2 `transform` of components and images
+///
+/// A point `(x, y)` goes to `(xx * x + yx * y + dx, xy * x + yy * y + dy)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine(pub [f64; 6]);
+
+impl Default for Affine {
+    fn default() -> Self {
+        Affine::IDENTITY
+    }
+}
+
+impl Affine {
+    /// The transform which leaves points where they are
+    pub const IDENTITY: Affine = Affine([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// A move by an offset
+    pub fn translate(Point { x, y }: Point) -> Self {
+        Affine([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    /// A rotation about the origin by an angle in degrees, counter-clockwise
+    pub fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Affine([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /// A scale about the origin
+    pub fn scale(Scale { x, y }: Scale) -> Self {
+        Affine([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    /// A skew by horizontal and vertical angles in degrees, as the `slant`
+    /// of a component
+    pub fn skew((x, y): (f64, f64)) -> Self {
+        Affine([
+            1.0,
+            y.to_radians().tan(),
+            x.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        ])
+    }
+
+    /// The transform applying `other` first, then `self`
+    pub fn then_apply(&self, other: &Affine) -> Affine {
+        let [a0, a1, a2, a3, a4, a5] = self.0;
+        let [b0, b1, b2, b3, b4, b5] = other.0;
+        Affine([
+            a0 * b0 + a2 * b1,
+            a1 * b0 + a3 * b1,
+            a0 * b2 + a2 * b3,
+            a1 * b2 + a3 * b3,
+            a0 * b4 + a2 * b5 + a4,
+            a1 * b4 + a3 * b5 + a5,
+        ])
+    }
+
+    /// Where the transform takes a point
+    pub fn apply(&self, Point { x, y }: Point) -> Point {
+        let [xx, xy, yx, yy, dx, dy] = self.0;
+        Point::new(xx * x + yx * y + dx, xy * x + yy * y + dy)
+    }
+
+    /// The offset the transform moves the origin by
+    pub fn translation(&self) -> Point {
+        Point::new(self.0[4], self.0[5])
+    }
+}
+
+impl From<glyphs2::Transform> for Affine {
+    fn from(transform: glyphs2::Transform) -> Self {
+        Affine([
+            transform.m11,
+            transform.m12,
+            transform.m21,
+            transform.m22,
+            transform.t_x,
+            transform.t_y,
+        ])
+    }
+}
+
+impl From<Affine> for glyphs2::Transform {
+    fn from(Affine([m11, m12, m21, m22, t_x, t_y]): Affine) -> Self {
+        glyphs2::Transform {
+            m11,
+            m12,
+            m21,
+            m22,
+            t_x,
+            t_y,
+        }
+    }
+}
+
+impl glyphs2::Component {
+    /// The transform that places the component's outlines in the containing
+    /// layer, as it is written
+    pub fn effective_transform(&self) -> Affine {
+        self.transform.clone().into()
+    }
+}
+
+impl glyphs3::Component {
+    /// The transform that places the component's outlines in the containing
+    /// layer: its position, rotation, slant and scale, applied in the
+    /// reverse order
+    ///
+    /// Automatic alignment is not applied; see
+    /// [`Layer::resolved_component_positions`](glyphs3::Layer::resolved_component_positions).
+    pub fn effective_transform(&self) -> Affine {
+        Affine::translate(self.position)
+            .then_apply(&Affine::rotate(self.angle))
+            .then_apply(&Affine::skew(self.slant))
+            .then_apply(&Affine::scale(self.scale))
+    }
+}
+
+// Extend `rect` by the extrema of a Bézier segment given by its control points.
+fn include_segment(rect: &mut Rect, points: &[(f64, f64)]) {
+    let end = points[points.len() - 1];
+    rect.include_point(end);
+    match points.len() {
+        3 => {
+            for t in quadratic_extrema(points[0], points[1], points[2]) {
+                rect.include_point(eval_quadratic(points[0], points[1], points[2], t));
+            }
+        }
+        4 => {
+            for t in cubic_extrema(points[0], points[1], points[2], points[3]) {
+                rect.include_point(eval_cubic(points[0], points[1], points[2], points[3], t));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn quadratic_extrema(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64)) -> Vec<f64> {
+    let root = |a: f64, b: f64, c: f64| {
+        let denominator = a - 2.0 * b + c;
+        if denominator == 0.0 {
+            None
+        } else {
+            Some((a - b) / denominator)
+        }
+    };
+    [root(p0.0, p1.0, p2.0), root(p0.1, p1.1, p2.1)]
+        .into_iter()
+        .flatten()
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .collect()
+}
+
+fn cubic_extrema(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), p3: (f64, f64)) -> Vec<f64> {
+    // Roots of the derivative a*t^2 + b*t + c in each dimension
+    let roots = |p0: f64, p1: f64, p2: f64, p3: f64| {
+        let a = 3.0 * (-p0 + 3.0 * p1 - 3.0 * p2 + p3);
+        let b = 6.0 * (p0 - 2.0 * p1 + p2);
+        let c = 3.0 * (p1 - p0);
+        if a.abs() < f64::EPSILON {
+            if b.abs() < f64::EPSILON {
+                return vec![];
+            }
+            return vec![-c / b];
+        }
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return vec![];
+        }
+        let sqrt = discriminant.sqrt();
+        vec![(-b + sqrt) / (2.0 * a), (-b - sqrt) / (2.0 * a)]
+    };
+    roots(p0.0, p1.0, p2.0, p3.0)
+        .into_iter()
+        .chain(roots(p0.1, p1.1, p2.1, p3.1))
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .collect()
+}
+
+fn eval_quadratic(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), t: f64) -> (f64, f64) {
+    let mt = 1.0 - t;
+    let f = |a: f64, b: f64, c: f64| mt * mt * a + 2.0 * mt * t * b + t * t * c;
+    (f(p0.0, p1.0, p2.0), f(p0.1, p1.1, p2.1))
+}
+
+fn eval_cubic(
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    p3: (f64, f64),
+    t: f64,
+) -> (f64, f64) {
+    let mt = 1.0 - t;
+    let f = |a: f64, b: f64, c: f64, d: f64| {
+        mt * mt * mt * a + 3.0 * mt * mt * t * b + 3.0 * mt * t * t * c + t * t * t * d
+    };
+    (f(p0.0, p1.0, p2.0, p3.0), f(p0.1, p1.1, p2.1, p3.1))
+}
+
+// Bounds of a path after applying `transform` to its nodes. Transforming the
+// control points before measuring gives tight bounds for rotated components.
+fn path_bounds(path: &glyphs3::Path, transform: &Affine) -> Option<Rect> {
+    let points: Vec<((f64, f64), NodeType)> = path
+        .nodes
+        .iter()
+        .map(|n| (transform.apply(n.position()).into(), n.node_type))
+        .collect();
+    let first_on_curve = points.iter().position(|(_, t)| !t.is_off_curve())?;
+    let mut rect = Rect::from_point(points[first_on_curve].0);
+    let count = points.len();
+    // A closed path wraps around from its last on-curve node; an open path
+    // starts at its first node.
+    let (start, len) = if path.closed {
+        (first_on_curve, count)
+    } else {
+        (0, count)
+    };
+    let mut segment = vec![points[start].0];
+    for offset in 1..=len {
+        if !path.closed && offset == len {
+            break;
+        }
+        let (point, node_type) = points[(start + offset) % count];
+        segment.push(point);
+        if node_type.is_off_curve() {
+            continue;
+        }
+        if node_type.base_type() == NodeType::QCurve && segment.len() > 3 {
+            // TrueType-style quadratic splines have implied on-curve points
+            // between consecutive off-curves.
+            let offs = &segment[1..segment.len() - 1];
+            let mut current = segment[0];
+            for (ix, off) in offs.iter().enumerate() {
+                let next = if ix + 1 < offs.len() {
+                    midpoint(*off, offs[ix + 1])
+                } else {
+                    point
+                };
+                include_segment(&mut rect, &[current, *off, next]);
+                current = next;
+            }
+        } else {
+            include_segment(&mut rect, &segment);
+        }
+        segment = vec![point];
+    }
+    Some(rect)
+}
+
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// Which way round a closed path goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathDirection {
+    /// Clockwise, as counters go in PostScript outlines
+    Clockwise,
+    /// Counter-clockwise, as outer contours go in PostScript outlines
+    CounterClockwise,
+}
+
+// Twice the signed area of the polygon through the nodes of a path, positive
+// when it goes counter-clockwise. The off-curve points are close enough to
+// the curves to tell which way a path goes.
+fn polygon_area(points: &[(f64, f64)]) -> f64 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum()
+}
+
+// Whether a point is inside a polygon, by the even-odd rule
+fn polygon_contains(points: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+        if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+impl glyphs3::Node {
+    /// The position of the node
+    pub fn position(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    /// Move the node to a position
+    pub fn set_position(&mut self, position: Point) {
+        (self.x, self.y) = (position.x, position.y);
+    }
+
+    /// The node with its type made smooth or sharp, as for
+    /// [`NodeType::with_smooth`]
+    pub fn with_smooth(self, smooth: bool) -> Self {
+        glyphs3::Node {
+            node_type: self.node_type.with_smooth(smooth),
+            ..self
+        }
+    }
+}
+
+impl glyphs3::Path {
+    /// The bounding box of the path, or `None` if it has no on-curve nodes
+    ///
+    /// The box is tight: curves are measured at their extrema rather than by
+    /// their off-curve points.
+    pub fn bounds(&self) -> Option<Rect> {
+        path_bounds(self, &Affine::IDENTITY)
+    }
+
+    fn points(&self) -> Vec<(f64, f64)> {
+        self.nodes.iter().map(|n| (n.x, n.y)).collect()
+    }
+
+    /// Which way round the path goes
+    ///
+    /// Returns `None` for open paths and paths which enclose no area.
+    pub fn direction(&self) -> Option<PathDirection> {
+        if !self.closed {
+            return None;
+        }
+        let area = polygon_area(&self.points());
+        if area > 0.0 {
+            Some(PathDirection::CounterClockwise)
+        } else if area < 0.0 {
+            Some(PathDirection::Clockwise)
+        } else {
+            None
+        }
+    }
+
+    /// Reverse the direction of the path
+    ///
+    /// Each on-curve node takes the kind of the segment which now ends at
+    /// it, keeping its own smoothness. A closed path keeps its start node;
+    /// an open path starts from its old end.
+    pub fn reverse(&mut self) {
+        let types: Vec<NodeType> = self.nodes.iter().map(|n| n.node_type).collect();
+        let count = types.len();
+        // The segment now ending at an on-curve node is the one which began
+        // at it: the one ending at the next on-curve node
+        let next_on_curve = |ix: usize| {
+            let ahead = if self.closed { count } else { count - ix - 1 };
+            (1..=ahead)
+                .map(|offset| types[(ix + offset) % count])
+                .find(|t| !t.is_off_curve())
+        };
+        let kinds: Vec<NodeType> = (0..count)
+            .map(|ix| match types[ix] {
+                NodeType::OffCurve => NodeType::OffCurve,
+                own => next_on_curve(ix)
+                    .map_or(NodeType::Line, NodeType::base_type)
+                    .with_smooth(own.is_smooth()),
+            })
+            .collect();
+        for (node, kind) in self.nodes.iter_mut().zip(kinds) {
+            node.node_type = kind;
+        }
+        self.nodes.reverse();
+        // Glyphs starts a closed path at its last node
+        if self.closed && count > 0 {
+            self.nodes.rotate_left(1);
+        }
+    }
+
+    /// Make the on-curve node at `index` the start of a closed path, which
+    /// Glyphs keeps as the last node
+    ///
+    /// Returns `false`, leaving the path as it is, if the path is open or
+    /// the node is missing or off-curve.
+    pub fn set_start_node(&mut self, index: usize) -> bool {
+        match self.nodes.get(index) {
+            Some(node) if self.closed && !node.node_type.is_off_curve() => {
+                self.nodes.rotate_left(index + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl glyphs3::Layer {
+    /// The bounding box of the paths of the layer, ignoring its components
+    pub fn bounds(&self) -> Option<Rect> {
+        union_all(self.paths().filter_map(glyphs3::Path::bounds))
+    }
+
+    /// Turn the closed paths of the layer to the PostScript convention, as
+    /// Glyphs' Correct Path Direction does: outer contours counter-clockwise,
+    /// the counters inside them clockwise, and so on alternately
+    ///
+    /// How deep a path is nested is the number of other paths around it.
+    /// Components are left alone.
+    pub fn correct_path_direction(&mut self) {
+        let outlines: Vec<Vec<(f64, f64)>> = self
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Path(path) if path.closed => Some(path.points()),
+                _ => None,
+            })
+            .collect();
+        let paths = self.shapes.iter_mut().filter_map(|shape| match shape {
+            Shape::Path(path) if path.closed => Some(path),
+            _ => None,
+        });
+        for (ix, path) in paths.enumerate() {
+            let Some(direction) = path.direction() else {
+                continue;
+            };
+            let Some(point) = outlines[ix].first() else {
+                continue;
+            };
+            let depth = outlines
+                .iter()
+                .enumerate()
+                .filter(|(other, outline)| *other != ix && polygon_contains(outline, *point))
+                .count();
+            let wanted = if depth % 2 == 0 {
+                PathDirection::CounterClockwise
+            } else {
+                PathDirection::Clockwise
+            };
+            if direction != wanted {
+                path.reverse();
+            }
+        }
+    }
+
+    /// The bounding box of the layer including the outlines of its components
+    ///
+    /// Components are resolved to the layer of the referenced glyph with the
+    /// same layer ID, falling back to the layer for `master_id`, and placed by
+    /// their transforms, with automatic alignment applied. Components of
+    /// missing glyphs are left out.
+    pub fn bounds_with_components(&self, font: &Glyphs3, master_id: &str) -> Option<Rect> {
+        self.transformed_bounds(font, master_id, &Affine::IDENTITY, 0)
+    }
+
+    fn transformed_bounds(
+        &self,
+        font: &Glyphs3,
+        master_id: &str,
+        transform: &Affine,
+        depth: usize,
+    ) -> Option<Rect> {
+        if depth > MAX_COMPONENT_DEPTH {
+            return None;
+        }
+        let mut placements = self.component_transforms(font).into_iter();
+        union_all(self.shapes.iter().filter_map(|shape| match shape {
+            Shape::Path(path) => path_bounds(path, transform),
+            Shape::Component(component) => {
+                let placement = placements.next().unwrap_or(Affine::IDENTITY);
+                let glyph = font.glyph(&component.component_glyph)?;
+                let layer = glyph
+                    .layers
+                    .iter()
+                    .find(|l| l.layer_id == self.layer_id)
+                    .or_else(|| glyph.layers.iter().find(|l| l.layer_id == master_id))?;
+                layer.transformed_bounds(
+                    font,
+                    master_id,
+                    &transform.then_apply(&placement),
+                    depth + 1,
+                )
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn node(x: f64, y: f64, node_type: NodeType) -> glyphs3::Node {
+        glyphs3::Node {
+            x,
+            y,
+            node_type,
+            user_data: None,
+        }
+    }
+
+    #[test]
+    fn test_curve_extrema() {
+        // A closed contour whose top is a curve bulging above its on-curve points
+        let path = glyphs3::Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: vec![
+                node(0.0, 0.0, NodeType::Line),
+                node(0.0, 100.0, NodeType::OffCurve),
+                node(100.0, 100.0, NodeType::OffCurve),
+                node(100.0, 0.0, NodeType::Curve),
+            ],
+        };
+        let bounds = path.bounds().unwrap();
+        assert_eq!(bounds.y_min, 0.0);
+        assert_eq!(bounds.y_max, 75.0);
+        assert_eq!((bounds.x_min, bounds.x_max), (0.0, 100.0));
+    }
+
+    fn square(x: f64, size: f64, clockwise: bool) -> glyphs3::Path {
+        let mut corners = vec![(x, 0.0), (x, size), (x + size, size), (x + size, 0.0)];
+        if !clockwise {
+            corners.reverse();
+        }
+        glyphs3::Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: corners
+                .into_iter()
+                .map(|(x, y)| node(x, y, NodeType::Line))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_reverse_path() {
+        let mut path = glyphs3::Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: vec![
+                node(0.0, 100.0, NodeType::OffCurve),
+                node(100.0, 100.0, NodeType::OffCurve),
+                node(100.0, 0.0, NodeType::CurveSmooth),
+                node(0.0, 0.0, NodeType::Line),
+            ],
+        };
+        assert_eq!(path.direction(), Some(PathDirection::Clockwise));
+        let original = path.clone();
+        path.reverse();
+        assert_eq!(path.direction(), Some(PathDirection::CounterClockwise));
+        let nodes: Vec<_> = path.nodes.iter().map(|n| (n.x, n.y, n.node_type)).collect();
+        assert_eq!(
+            nodes,
+            [
+                (100.0, 0.0, NodeType::LineSmooth),
+                (100.0, 100.0, NodeType::OffCurve),
+                (0.0, 100.0, NodeType::OffCurve),
+                (0.0, 0.0, NodeType::Curve),
+            ]
+        );
+        assert_eq!(path.bounds(), original.bounds());
+        path.reverse();
+        assert_eq!(path, original);
+
+        let mut open = glyphs3::Path {
+            closed: false,
+            ..original.clone()
+        };
+        open.nodes.rotate_right(1);
+        open.reverse();
+        assert_eq!(open.nodes[0].node_type, NodeType::LineSmooth);
+        assert_eq!(open.nodes[3].node_type, NodeType::Curve);
+        assert_eq!(open.direction(), None);
+
+        assert!(path.set_start_node(2));
+        assert_eq!((path.nodes[3].x, path.nodes[3].y), (100.0, 0.0));
+        assert!(!path.set_start_node(1));
+        assert!(!path.set_start_node(9));
+    }
+
+    #[test]
+    fn test_correct_path_direction() {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        let Font::Glyphs3(font) = Font::load(path::Path::new(file)).unwrap() else {
+            unreachable!()
+        };
+        let mut layer = font.glyphs[0].layers[0].clone();
+        layer.shapes = vec![
+            Shape::Path(square(0.0, 300.0, true)),
+            Shape::Path(square(100.0, 100.0, false)),
+            Shape::Path(square(500.0, 100.0, true)),
+        ];
+        layer.correct_path_direction();
+        let directions: Vec<_> = layer
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Path(path) => path.direction(),
+                Shape::Component(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            directions,
+            [
+                PathDirection::CounterClockwise,
+                PathDirection::Clockwise,
+                PathDirection::CounterClockwise,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_component_transforms() {
+        let component = glyphs3::Component {
+            component_glyph: "a".to_string(),
+            position: Point::new(10.0, 20.0),
+            scale: Scale::new(2.0, 1.0),
+            angle: 90.0,
+            ..Default::default()
+        };
+        let transform = component.effective_transform();
+        let moved = transform.apply(Point::new(1.0, 0.0));
+        assert!((moved.x - 10.0).abs() < 1e-5 && (moved.y - 22.0).abs() < 1e-5);
+        assert_eq!(transform.translation(), component.position);
+
+        // A Glyphs 2 component has the same transform
+        let glyphs2: glyphs2::Component = component.into();
+        let written = glyphs2.effective_transform();
+        for (a, b) in written.0.iter().zip(transform.0) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        let round_trip: glyphs2::Transform = written.into();
+        assert_eq!(round_trip, glyphs2.transform);
+        assert_eq!(Affine::default(), Affine::IDENTITY);
+    }
+
+    #[test]
+    fn test_component_bounds() {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        let font = Font::load(path::Path::new(file)).unwrap();
+        let glyphs3 = font.as_glyphs3().unwrap();
+        let master_id = &glyphs3.masters[0].id;
+        let layer_for = |name: &str| {
+            glyphs3
+                .glyphs
+                .iter()
+                .find(|g| g.name == name)
+                .unwrap()
+                .layers
+                .iter()
+                .find(|l| &l.layer_id == master_id)
+                .unwrap()
+        };
+        let eacute = layer_for("eacute");
+        let composite = eacute.bounds_with_components(glyphs3, master_id).unwrap();
+        let base = layer_for("e")
+            .bounds_with_components(glyphs3, master_id)
+            .unwrap();
+        assert!(composite.y_max > base.y_max);
+        assert_eq!(composite.y_min, base.y_min);
+        // The outlines of eacute are all in its components
+        assert_eq!(eacute.bounds(), None);
+        assert_eq!(layer_for("e").bounds(), Some(base));
+        assert_eq!(base.width(), base.x_max - base.x_min);
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    aglfn::AGLFN,
+    glyphs3::{Glyph, Glyphs3},
+    Error, Result,
+};
+
+/// What Glyphs knows about a glyph name: one `<glyph>` entry of `GlyphData.xml`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlyphInfo {
+    /// The nice name of the glyph, such as `Aacute`
+    pub name: String,
+    /// The code point of the glyph
+    pub unicode: Option<u32>,
+    /// The category, such as `Letter` or `Mark`
+    pub category: Option<String>,
+    /// The subcategory, such as `Uppercase` or `Nonspacing`
+    pub sub_category: Option<String>,
+    /// The script, such as `latin`
+    pub script: Option<String>,
+    /// The name used in exported fonts, if it is not the nice name
+    pub production: Option<String>,
+    /// Other names of the glyph, such as those of earlier Glyphs versions
+    pub alt_names: Vec<String>,
+}
+
+/// A database of glyph names, as in Glyphs' `GlyphData.xml`
+///
+/// The database is not bundled; load the `GlyphData.xml` which comes with
+/// Glyphs, or the one published in the GlyphsInfo repository, with
+/// [`from_xml`](Self::from_xml). Files of custom entries, like Glyphs'
+/// `GlyphData-*.xml` files, can be added on top with
+/// [`extend`](Self::extend).
+#[derive(Debug, Clone, Default)]
+pub struct GlyphData {
+    records: Vec<GlyphInfo>,
+    by_name: HashMap<String, usize>,
+    by_unicode: HashMap<u32, usize>,
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// The attributes of an element, from the text between its name and `>`
+fn attributes(mut text: &str) -> Result<HashMap<&str, String>> {
+    let mut attributes = HashMap::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() || text == "/" {
+            return Ok(attributes);
+        }
+        let (name, rest) = text
+            .split_once('=')
+            .ok_or_else(|| Error::GlyphData(format!("expected an attribute at {text:?}")))?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|q| *q == '"' || *q == '\'');
+        let quote =
+            quote.ok_or_else(|| Error::GlyphData(format!("unquoted attribute {}", name.trim())))?;
+        let end = rest[1..]
+            .find(quote)
+            .ok_or_else(|| Error::GlyphData(format!("unterminated attribute {}", name.trim())))?;
+        attributes.insert(name.trim(), unescape(&rest[1..end + 1]));
+        text = &rest[end + 2..];
+    }
+}
+
+// The code point of a `uniXXXX` or `uXXXXX` name
+fn codepoint_of_name(name: &str) -> Option<u32> {
+    let hex = name
+        .strip_prefix("uni")
+        .filter(|hex| hex.len() == 4)
+        .or_else(|| {
+            name.strip_prefix('u')
+                .filter(|hex| (4..=6).contains(&hex.len()))
+        })?;
+    if !hex
+        .chars()
+        .all(|ch| ch.is_ascii_hexdigit() && !ch.is_ascii_lowercase())
+    {
+        return None;
+    }
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|codepoint| char::from_u32(*codepoint).is_some())
+}
+
+// The production name of a code point, as Glyphs makes one
+fn codepoint_name(codepoint: u32) -> String {
+    if codepoint <= 0xFFFF {
+        format!("uni{codepoint:04X}")
+    } else {
+        format!("u{codepoint:05X}")
+    }
+}
+
+/// The code point a glyph name stands for, by the Adobe Glyph List For New
+/// Fonts or as a `uniXXXX` or `uXXXXX` name
+///
+/// Names with a suffix, like `a.sc`, and ligature names stand for no single
+/// code point, and give `None`.
+pub fn unicode_for_name(name: &str) -> Option<u32> {
+    AGLFN
+        .iter()
+        .find(|(_, aglfn)| *aglfn == name)
+        .map(|(codepoint, _)| *codepoint)
+        .or_else(|| codepoint_of_name(name))
+}
+
+/// A name for a glyph of the code point: its name in the Adobe Glyph List
+/// For New Fonts, or else `uniXXXX` or `uXXXXX`
+pub fn nice_name(codepoint: u32) -> String {
+    match AGLFN.binary_search_by_key(&codepoint, |(codepoint, _)| *codepoint) {
+        Ok(ix) => AGLFN[ix].1.to_string(),
+        Err(_) => codepoint_name(codepoint),
+    }
+}
+
+impl GlyphData {
+    /// Read a database from the text of a `GlyphData.xml` file
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let mut data = GlyphData::default();
+        data.add_xml(xml)?;
+        Ok(data)
+    }
+
+    /// Add the entries of another `GlyphData.xml` file, which take the place
+    /// of entries already here with the same name or code point
+    pub fn extend(&mut self, xml: &str) -> Result<()> {
+        self.add_xml(xml)
+    }
+
+    fn add_xml(&mut self, xml: &str) -> Result<()> {
+        let mut rest = xml;
+        while let Some(start) = rest.find("<glyph") {
+            rest = &rest[start + "<glyph".len()..];
+            // Skip `<glyphData>` and the like
+            if !rest.starts_with(|ch: char| ch.is_whitespace() || ch == '/' || ch == '>') {
+                continue;
+            }
+            let end = rest
+                .find('>')
+                .ok_or_else(|| Error::GlyphData("unterminated <glyph> element".to_string()))?;
+            let mut attributes = attributes(&rest[..end])?;
+            rest = &rest[end + 1..];
+            let name = attributes
+                .remove("name")
+                .ok_or_else(|| Error::GlyphData("<glyph> element without a name".to_string()))?;
+            let unicode = attributes
+                .remove("unicode")
+                .and_then(|hex| u32::from_str_radix(&hex, 16).ok());
+            self.insert(GlyphInfo {
+                name,
+                unicode,
+                category: attributes.remove("category"),
+                sub_category: attributes.remove("subCategory"),
+                script: attributes.remove("script"),
+                production: attributes.remove("production"),
+                alt_names: attributes
+                    .remove("altNames")
+                    .map(|names| names.split(',').map(|n| n.trim().to_string()).collect())
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, info: GlyphInfo) {
+        let ix = self.records.len();
+        for name in std::iter::once(&info.name).chain(&info.alt_names) {
+            self.by_name.insert(name.clone(), ix);
+        }
+        if let Some(codepoint) = info.unicode {
+            self.by_unicode.insert(codepoint, ix);
+        }
+        self.records.push(info);
+    }
+
+    /// The number of entries in the database
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the database has no entries
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The entry with the name, or one of its alternative names
+    pub fn get(&self, name: &str) -> Option<&GlyphInfo> {
+        self.by_name.get(name).map(|ix| &self.records[*ix])
+    }
+
+    /// The entry for the code point
+    pub fn get_by_unicode(&self, codepoint: u32) -> Option<&GlyphInfo> {
+        self.by_unicode.get(&codepoint).map(|ix| &self.records[*ix])
+    }
+
+    /// What is known about a glyph name, worked out as Glyphs does
+    ///
+    /// A name in the database, or else the code point, is looked up as it
+    /// is. Otherwise names like `uni00C1` are looked up by their code point;
+    /// a suffixed name like `a.ss01` is its base glyph's entry, with the
+    /// suffix added to its production name and, for `.sc`, `.smcp` and
+    /// `.c2sc`, the subcategory `Smallcaps`; and a ligature like `f_f_i`
+    /// takes its category and script from its first part, with the
+    /// subcategory `Ligature` and its parts' production names joined.
+    pub fn info(&self, name: &str, unicode: Option<u32>) -> Option<GlyphInfo> {
+        if let Some(info) = self.get(name) {
+            return Some(info.clone());
+        }
+        if let Some(info) = unicode.and_then(|u| self.get_by_unicode(u)) {
+            return Some(GlyphInfo {
+                name: name.to_string(),
+                ..info.clone()
+            });
+        }
+        if let Some(codepoint) = codepoint_of_name(name) {
+            let info = self.get_by_unicode(codepoint)?;
+            return Some(GlyphInfo {
+                name: name.to_string(),
+                production: Some(codepoint_name(codepoint)),
+                ..info.clone()
+            });
+        }
+        if let Some((base, suffix)) = name.split_once('.').filter(|(base, _)| !base.is_empty()) {
+            let base = self.info(base, None)?;
+            let smallcaps = matches!(suffix, "sc" | "smcp" | "c2sc");
+            return Some(GlyphInfo {
+                name: name.to_string(),
+                unicode: None,
+                sub_category: if smallcaps {
+                    Some("Smallcaps".to_string())
+                } else {
+                    base.sub_category
+                },
+                production: base.production.map(|p| format!("{p}.{suffix}")),
+                alt_names: vec![],
+                ..base
+            });
+        }
+        if name.contains('_') {
+            let parts: Vec<GlyphInfo> = name
+                .split('_')
+                .map(|part| self.info(part, None))
+                .collect::<Option<_>>()?;
+            let productions: Vec<String> = parts
+                .iter()
+                .map(|part| part.production.clone().unwrap_or(part.name.clone()))
+                .collect();
+            let production = if productions
+                .iter()
+                .all(|p| p.len() == 7 && p.starts_with("uni"))
+            {
+                format!(
+                    "uni{}",
+                    productions.iter().map(|p| &p[3..]).collect::<String>()
+                )
+            } else {
+                productions.join("_")
+            };
+            let first = parts.into_iter().next()?;
+            return Some(GlyphInfo {
+                name: name.to_string(),
+                unicode: None,
+                category: first.category,
+                sub_category: Some("Ligature".to_string()),
+                script: first.script,
+                production: (production != name).then_some(production),
+                alt_names: vec![],
+            });
+        }
+        None
+    }
+}
+
+impl Glyph {
+    fn info(&self, data: &GlyphData) -> Option<GlyphInfo> {
+        data.info(&self.name, self.unicode.first().copied())
+    }
+
+    /// The category of the glyph: the one set in the file, or else the one
+    /// the database gives its name
+    pub fn inferred_category(&self, data: &GlyphData) -> Option<String> {
+        self.category.clone().or_else(|| self.info(data)?.category)
+    }
+
+    /// The subcategory of the glyph: the one set in the file, or else the one
+    /// the database gives its name
+    pub fn inferred_sub_category(&self, data: &GlyphData) -> Option<String> {
+        self.subcategory
+            .clone()
+            .or_else(|| self.info(data)?.sub_category)
+    }
+
+    /// The script of the glyph: the one set in the file, or else the one the
+    /// database gives its name
+    pub fn inferred_script(&self, data: &GlyphData) -> Option<String> {
+        self.script.clone().or_else(|| self.info(data)?.script)
+    }
+
+    /// The name of the glyph in exported fonts
+    ///
+    /// This is the production name set in the file, or else the one the
+    /// database gives, or else `uniXXXX` for an encoded glyph, or else the
+    /// glyph's own name.
+    pub fn production_name(&self, data: &GlyphData) -> String {
+        if let Some(production) = &self.production {
+            return production.clone();
+        }
+        match self.info(data) {
+            Some(GlyphInfo {
+                production: Some(production),
+                ..
+            }) => production,
+            Some(_) => self.name.clone(),
+            None => self
+                .unicode
+                .first()
+                .map_or(self.name.clone(), |codepoint| codepoint_name(*codepoint)),
+        }
+    }
+}
+
+impl Glyphs3 {
+    /// Give glyphs with no code points the one their name stands for, as
+    /// [`unicode_for_name`] says
+    ///
+    /// This is for sources which only name their glyphs. Glyphs which already
+    /// have code points are left alone, and a code point another glyph has is
+    /// not given again. Returns the names of the glyphs which were given one.
+    pub fn assign_unicodes_from_names(&mut self) -> Vec<String> {
+        let mut used: HashSet<u32> = self
+            .glyphs
+            .iter()
+            .flat_map(|g| g.unicode.iter().copied())
+            .collect();
+        let mut assigned = vec![];
+        for glyph in &mut self.glyphs {
+            if !glyph.unicode.is_empty() {
+                continue;
+            }
+            let Some(codepoint) = unicode_for_name(&glyph.name) else {
+                continue;
+            };
+            if used.insert(codepoint) {
+                glyph.unicode = vec![codepoint];
+                assigned.push(glyph.name.clone());
+            }
+        }
+        assigned
+    }
+
+    /// Fill in the categories, subcategories, scripts and production names
+    /// which glyphs do not have from the database, as Glyphs does
+    ///
+    /// Nothing set in the file is changed, and a production name is only
+    /// set when it differs from the glyph's name.
+    pub fn apply_glyph_data(&mut self, data: &GlyphData) {
+        for glyph in &mut self.glyphs {
+            let Some(info) = glyph.info(data) else {
+                continue;
+            };
+            glyph.category = glyph.category.take().or(info.category);
+            glyph.subcategory = glyph.subcategory.take().or(info.sub_category);
+            glyph.script = glyph.script.take().or(info.script);
+            if glyph.production.is_none() {
+                glyph.production = info.production.filter(|p| *p != glyph.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyphData format="2">
+	<glyph unicode="0041" name="A" category="Letter" subCategory="Uppercase" script="latin" description="LATIN CAPITAL LETTER A" />
+	<glyph unicode="0066" name="f" category="Letter" subCategory="Lowercase" script="latin" />
+	<glyph unicode="0069" name="i" category="Letter" subCategory="Lowercase" script="latin" />
+	<glyph unicode="0301" name="acutecomb" category="Mark" subCategory="Nonspacing" production="uni0301" altNames="acute.comb" />
+	<glyph unicode="0644" name="lam-ar" category="Letter" script="arabic" production="uni0644" />
+	<glyph unicode="0627" name="alef-ar" category="Letter" script="arabic" production="uni0627" />
+	<glyph unicode="1F600" name="grinningFace" category="Symbol" production="u1F600" />
+</glyphData>"#;
+
+    fn glyph(name: &str, unicode: &[u32]) -> Glyph {
+        Glyph {
+            name: name.to_string(),
+            unicode: unicode.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_info() {
+        let data = GlyphData::from_xml(XML).unwrap();
+        assert_eq!(data.len(), 7);
+        assert_eq!(data.get("acute.comb").unwrap().name, "acutecomb");
+        let info = |name| data.info(name, None).unwrap();
+        assert_eq!(info("A").sub_category.as_deref(), Some("Uppercase"));
+        assert_eq!(info("uni0301").category.as_deref(), Some("Mark"));
+        assert_eq!(info("A.sc").sub_category.as_deref(), Some("Smallcaps"));
+        assert_eq!(
+            info("acutecomb.case").production.as_deref(),
+            Some("uni0301.case")
+        );
+        let ligature = info("f_f_i");
+        assert_eq!(ligature.sub_category.as_deref(), Some("Ligature"));
+        assert_eq!(ligature.script.as_deref(), Some("latin"));
+        assert_eq!(ligature.production, None);
+        assert_eq!(
+            info("lam-ar_alef-ar").production.as_deref(),
+            Some("uni06440627")
+        );
+        assert_eq!(data.info("f_nosuchglyph", None), None);
+        assert_eq!(
+            data.info("smiley", Some(0x1F600))
+                .unwrap()
+                .category
+                .as_deref(),
+            Some("Symbol")
+        );
+        assert!(GlyphData::from_xml("<glyph name=A />").is_err());
+    }
+
+    #[test]
+    fn test_names_and_unicodes() {
+        assert_eq!(unicode_for_name("Aacute"), Some(0xC1));
+        assert_eq!(unicode_for_name("Omega"), Some(0x2126));
+        assert_eq!(unicode_for_name("uni0414"), Some(0x414));
+        assert_eq!(unicode_for_name("u1F600"), Some(0x1F600));
+        assert_eq!(unicode_for_name("uniD800"), None);
+        assert_eq!(unicode_for_name("uni00e9"), None);
+        assert_eq!(unicode_for_name("a.sc"), None);
+        assert_eq!(nice_name(0x41), "A");
+        assert_eq!(nice_name(0x2014), "emdash");
+        assert_eq!(nice_name(0x414), "uni0414");
+        assert_eq!(nice_name(0x1F600), "u1F600");
+
+        let mut font = Glyphs3 {
+            glyphs: vec![
+                glyph("A", &[]),
+                glyph("B", &[0x41]),
+                glyph("uni0414", &[]),
+                glyph("A.alt", &[]),
+                glyph("eacute", &[0xE8]),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(font.assign_unicodes_from_names(), ["uni0414"]);
+        assert!(font.glyphs[0].unicode.is_empty());
+        assert_eq!(font.glyphs[2].unicode, [0x414]);
+        assert_eq!(font.glyphs[4].unicode, [0xE8]);
+    }
+
+    #[test]
+    fn test_apply_glyph_data() {
+        let data = GlyphData::from_xml(XML).unwrap();
+        let mut set = glyph("A", &[0x41]);
+        set.category = Some("Symbol".to_string());
+        let mut font = Glyphs3 {
+            glyphs: vec![
+                set,
+                glyph("acutecomb", &[0x301]),
+                glyph("smiley", &[0x1F601]),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            font.glyphs[1].inferred_category(&data).as_deref(),
+            Some("Mark")
+        );
+        assert_eq!(font.glyphs[2].production_name(&data), "u1F601");
+        font.apply_glyph_data(&data);
+        let [a, acutecomb, smiley] = &font.glyphs[..] else {
+            unreachable!()
+        };
+        assert_eq!(a.category.as_deref(), Some("Symbol"));
+        assert_eq!(a.subcategory.as_deref(), Some("Uppercase"));
+        assert_eq!(a.production, None);
+        assert_eq!(acutecomb.production.as_deref(), Some("uni0301"));
+        assert_eq!(smiley.category, None);
+    }
+}
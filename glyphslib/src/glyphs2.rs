@@ -1,10 +1,11 @@
 use std::collections::BTreeMap;
 
 use crate::{
-    common::{InstanceFactors, SmartComponentSetting},
+    common::{InstanceFactors, Point, Scale, SmartComponentSetting},
     serde::{
         anything_to_bool, bool_true, deserialize_comma_hexstring, deserialize_commify, is_default,
-        is_false, is_scale_unit, is_true, scale_unit, serialize_comma_hexstring, serialize_commify,
+        is_false, is_true, serialize_comma_hexstring, serialize_commify, serialize_nodes,
+        string_or_number,
     },
 };
 
@@ -15,18 +16,20 @@ use serde_with::serde_as;
 use crate::{
     common::{
         Color, CustomParameter, Feature, FeatureClass, FeaturePrefix, Kerning, NodeType,
-        Orientation, Version,
+        Orientation, Version, WeightClass, WidthClass,
     },
     serde::{is_one_hundred, one_hundred},
 };
 
 /// Glyphs file format version 2 document
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Glyphs2 {
     /// The build number of Glyphs used to save the file. Example: `"3210"`.
     #[serde(
         rename = ".appVersion",
         default,
+        deserialize_with = "string_or_number",
         skip_serializing_if = "String::is_empty"
     )]
     pub app_version: String,
@@ -125,19 +128,19 @@ pub struct Glyphs2 {
     pub kerning_vertical: Kerning,
     /// The standard keyboard increment.
     #[serde(rename = "keyboardIncrement", skip_serializing_if = "Option::is_none")]
-    pub keyboard_increment: Option<f32>,
+    pub keyboard_increment: Option<f64>,
     /// A bigger keyboard increment.
     #[serde(
         rename = "keyboardIncrementBig",
         skip_serializing_if = "Option::is_none"
     )]
-    pub keyboard_increment_big: Option<f32>,
+    pub keyboard_increment_big: Option<f64>,
     /// A huge keyboard increment.
     #[serde(
         rename = "keyboardIncrementHuge",
         skip_serializing_if = "Option::is_none"
     )]
-    pub keyboard_increment_huge: Option<f32>,
+    pub keyboard_increment_huge: Option<f64>,
     /// The name of the manufacturer.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub manufacturer: Option<String>,
@@ -161,6 +164,7 @@ pub struct Glyphs2 {
 
 /// Font master (`GSFontMaster`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Master {
     /// The alignment zones of the master.
     #[serde(
@@ -171,10 +175,10 @@ pub struct Master {
     pub alignment_zones: Vec<AlignmentZone>,
     /// The ascender metric of the master.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub ascender: Option<f32>,
+    pub ascender: Option<f64>,
     /// The cap height metric of the master.
     #[serde(rename = "capHeight", default, skip_serializing_if = "Option::is_none")]
-    pub cap_height: Option<f32>,
+    pub cap_height: Option<f64>,
     /// The custom name of the master.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom: Option<String>,
@@ -199,7 +203,7 @@ pub struct Master {
     pub custom_parameters: Vec<CustomParameter>,
     /// The descender metric of the master.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub descender: Option<f32>,
+    pub descender: Option<f64>,
     /// The global guides of the master.
     #[serde(rename = "guideLines", default, skip_serializing_if = "Vec::is_empty")]
     pub guides: Vec<Guide>,
@@ -217,7 +221,7 @@ pub struct Master {
     pub id: String,
     /// The italic angle of the master in degrees clockwise.
     #[serde(default, skip_serializing_if = "is_default", rename = "italicAngle")]
-    pub italic_angle: f32,
+    pub italic_angle: f64,
     /// The name of the master.
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
@@ -256,20 +260,22 @@ pub struct Master {
     pub width_value: i32,
     /// The x-height metric of the master.
     #[serde(default, rename = "xHeight", skip_serializing_if = "Option::is_none")]
-    pub x_height: Option<f32>,
+    pub x_height: Option<f64>,
 }
 
 /// Alignment zone with position and overshoot (e.g. "{800, 15}")
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AlignmentZone {
     /// The position of the zone.
-    pub position: f32,
+    pub position: f64,
     /// The overshoot of the zone.
-    pub overshoot: f32,
+    pub overshoot: f64,
 }
 
 /// Font instance (`GSInstance`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Instance {
     /// The custom parameters of the instance.
     #[serde(
@@ -287,42 +293,42 @@ pub struct Instance {
         skip_serializing_if = "is_default",
         rename = "interpolationCustom"
     )]
-    pub custom_value: f32,
+    pub custom_value: f64,
     /// The fourth interpolation coefficient of the instance for each master.
     #[serde(
         default,
         skip_serializing_if = "is_default",
         rename = "interpolationCustom1"
     )]
-    pub custom_value_1: f32,
+    pub custom_value_1: f64,
     /// The fifth interpolation coefficient of the instance for each master.
     #[serde(
         default,
         skip_serializing_if = "is_default",
         rename = "interpolationCustom2"
     )]
-    pub custom_value_2: f32,
+    pub custom_value_2: f64,
     /// The sixth interpolation coefficient of the instance for each master.
     #[serde(
         default,
         skip_serializing_if = "is_default",
         rename = "interpolationCustom3"
     )]
-    pub custom_value_3: f32,
+    pub custom_value_3: f64,
     /// The first interpolation coefficient of the instance for each master. The order of coefficients follows the order of masters.
     #[serde(
         default,
         skip_serializing_if = "is_default",
         rename = "interpolationWeight"
     )]
-    pub weight_value: f32,
+    pub weight_value: f64,
     /// The second interpolation coefficient of the instance for each master.
     #[serde(
         default,
         skip_serializing_if = "is_default",
         rename = "interpolationWidth"
     )]
-    pub width_value: f32,
+    pub width_value: f64,
     /// The interpolation weight for each master. Keys are master IDs, values are the factors for that master.
     #[serde(
         default,
@@ -357,19 +363,20 @@ pub struct Instance {
         rename = "weightClass",
         skip_serializing_if = "Option::is_none"
     )]
-    pub weight_class: Option<String>,
+    pub weight_class: Option<WeightClass>,
     /// The width class of the instance.
     #[serde(
         default,
         rename = "widthClass",
         skip_serializing_if = "Option::is_none"
     )]
-    pub width_class: Option<String>,
+    pub width_class: Option<WidthClass>,
 }
 
 /// Glyph (`GSGlyph`)
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Glyph {
     /// The bottom kerning group of the glyph.
     #[serde(rename = "bottomKerningGroup", skip_serializing_if = "is_default")]
@@ -482,6 +489,7 @@ pub struct Glyph {
 /// Layer (`GSLayer`)
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Layer {
     /// The anchors of the layer.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -550,10 +558,10 @@ pub struct Layer {
     pub user_data: Dictionary,
     /// The vertical width of the layer. Only stored if other than the default (ascender+descender).
     #[serde(rename = "vertWidth", default, skip_serializing_if = "is_default")]
-    pub vert_width: Option<f32>,
+    pub vert_width: Option<f64>,
     /// The width of the layer.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub width: f32,
+    pub width: f64,
     /// Whether the layer is visible in the editor. The visibility setting in the layer panel (the eye symbol).
     #[serde(default = "bool_true", skip_serializing_if = "is_true")]
     pub visible: bool,
@@ -561,6 +569,7 @@ pub struct Layer {
 
 /// Anchor (`GSAnchor`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Anchor {
     /// The name of the anchor.
     pub name: String,
@@ -569,11 +578,12 @@ pub struct Anchor {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub position: (f32, f32),
+    pub position: Point,
 }
 
 /// Background image (`GSBackgroundImage`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct BackgroundImage {
     /// The rectangle that defines the area to crop to in pixels, format: {{t,l},{b,r}}.
     #[serde(default)]
@@ -598,19 +608,20 @@ pub struct BackgroundImage {
 
 /// Affine transformation matrix in the form `{m11, m12, m21, m22, tX, tY}`
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Transform {
     /// m11 component of the transformation matrix.
-    pub m11: f32,
+    pub m11: f64,
     /// m12 component of the transformation matrix.
-    pub m12: f32,
+    pub m12: f64,
     /// m21 component of the transformation matrix.
-    pub m21: f32,
+    pub m21: f64,
     /// m22 component of the transformation matrix.
-    pub m22: f32,
+    pub m22: f64,
     /// Horizontal translation (tX) of the transformation matrix.
-    pub t_x: f32,
+    pub t_x: f64,
     /// Vertical translation (tY) of the transformation matrix.
-    pub t_y: f32,
+    pub t_y: f64,
 }
 
 impl Default for Transform {
@@ -624,6 +635,7 @@ impl Default for Transform {
 
 /// Crop rectangle with origin and size
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CropRect {
     /// Top coordinate of the crop rectangle.
     pub top: i32,
@@ -637,6 +649,7 @@ pub struct CropRect {
 
 /// Component (`GSComponent`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Component {
     /// The alignment of the component.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -653,7 +666,7 @@ pub struct Component {
     pub locked: bool,
     /// The Smart Component settings of the component, mapping property names to values.
     #[serde(rename = "piece", default, skip_serializing_if = "is_default")]
-    pub smart_component_location: BTreeMap<String, f32>,
+    pub smart_component_location: BTreeMap<String, f64>,
     /// The name of the glyph that the component references.
     #[serde(rename = "name")]
     pub component_glyph: String,
@@ -674,22 +687,23 @@ pub struct Component {
 
 /// Guide (`GSGuide`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Guide {
     /// The alignment of the guide.
     #[serde(default, skip_serializing_if = "is_default")]
     pub alignment: Orientation,
     /// The angle of the guide in degrees counter-clockwise.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub angle: f32,
+    pub angle: f64,
     /// The filter of the guide. The syntax is the description of [NSPredicate]
     #[serde(default, skip_serializing_if = "is_default")]
     pub filter: String,
     /// The grid of the guide.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub grid: f32,
+    pub grid: f64,
     /// The length of a line-type guide.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub length: f32,
+    pub length: f64,
     /// Whether the angle of the guide is locked.
     #[serde(
         default,
@@ -721,15 +735,15 @@ pub struct Guide {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub pos: (f32, f32),
+    pub pos: Point,
     /// The scale of the guide.
     #[serde(
-        default = "scale_unit",
+        default,
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify",
-        skip_serializing_if = "is_scale_unit"
+        skip_serializing_if = "is_default"
     )]
-    pub scale: (f32, f32),
+    pub scale: Scale,
     /// Custom data associated with the guide.
     #[serde(rename = "userData", default, skip_serializing_if = "is_default")]
     pub user_data: Dictionary,
@@ -737,6 +751,7 @@ pub struct Guide {
 
 /// PostScript hint (`GSHint`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Hint {
     /// Whether the hint is horizontal.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -754,7 +769,7 @@ pub struct Hint {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub origin: (f32, f32),
+    pub origin: (f64, f64),
     /// The target node of the hint.
     #[serde(default, skip_serializing_if = "is_default")]
     pub target: HintTarget,
@@ -765,7 +780,7 @@ pub struct Hint {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub other1: (f32, f32),
+    pub other1: (f64, f64),
     /// The second other position of the hint.
     #[serde(
         default,
@@ -773,7 +788,7 @@ pub struct Hint {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub other2: (f32, f32),
+    pub other2: (f64, f64),
     /// The scale of the hint.
     #[serde(
         default,
@@ -781,7 +796,7 @@ pub struct Hint {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    pub scale: (f32, f32),
+    pub scale: (f64, f64),
     /// Whether the hint is a stem.
     #[serde(default, skip_serializing_if = "is_default")]
     pub stem: bool,
@@ -794,6 +809,7 @@ pub struct Hint {
 ///
 /// Can be either a position coordinate or a label string.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(untagged)]
 pub enum HintTarget {
     /// Position as an x, y coordinate.
@@ -801,7 +817,7 @@ pub enum HintTarget {
         serialize_with = "serialize_commify",
         deserialize_with = "deserialize_commify"
     )]
-    Position((f32, f32)),
+    Position((f64, f64)),
     /// Label as a string.
     Label(String),
 }
@@ -813,28 +829,35 @@ impl Default for HintTarget {
 
 /// Path (`GSPath`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Path {
     /// Whether the path is closed.
     #[serde(default, skip_serializing_if = "is_false")]
     pub closed: bool,
     /// The nodes of the path.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_nodes"
+    )]
     pub nodes: Vec<Node>,
 }
 
 /// Node (`GSNode`)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Node {
     /// The x coordinate of the node.
-    pub x: f32,
+    pub x: f64,
     /// The y coordinate of the node.
-    pub y: f32,
+    pub y: f64,
     /// The type of the node.
     pub node_type: NodeType,
 }
 
 /// Color label (`GSColorLabel`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(untagged)]
 pub enum ColorLabel {
     /// The index of the color label. See also [the handbook entry on color labels](https://handbook.glyphsapp.com/glyph/#glyph/color-label).
@@ -7,11 +7,12 @@ use serde_with::{serde_as, OneOrMany};
 use crate::{
     common::{
         Color, CustomParameter, Feature, FeatureClass, FeaturePrefix, InstanceFactors, Kerning,
-        NodeType, Orientation, SmartComponentSetting, Version,
+        NodeType, Orientation, Point, Scale, SmartComponentPole, SmartComponentSetting, Version,
+        WeightClass, WidthClass,
     },
     serde::{
         bool_true, deserialize_export_type, int_to_bool, is_default, is_false, is_scale_unit,
-        is_true, scale_unit, SerializeAsTuple,
+        is_true, scale_unit, serialize_os2_weight, serialize_os2_width, SerializeAsTuple,
     },
 };
 
@@ -32,7 +33,7 @@ struct ComponentSerde {
     #[serde(default, rename = "anchorTo", skip_serializing_if = "Option::is_none")]
     anchor_to: Option<String>,
     #[serde(default, skip_serializing_if = "is_default")]
-    angle: f32,
+    angle: f64,
     #[serde(default, skip_serializing_if = "is_default")]
     attr: Dictionary,
     #[serde(
@@ -46,21 +47,23 @@ struct ComponentSerde {
     #[serde(default, skip_serializing_if = "is_default")]
     orientation: Orientation,
     #[serde(rename = "piece", default, skip_serializing_if = "is_default")]
-    smart_component_location: BTreeMap<String, f32>,
+    smart_component_location: BTreeMap<String, f64>,
     #[serde(default, rename = "pos", skip_serializing_if = "is_default")]
-    position: (f32, f32),
+    position: Point,
     #[serde(rename = "ref")]
     component_glyph: String,
-    #[serde(default = "scale_unit", skip_serializing_if = "is_scale_unit")]
-    scale: (f32, f32),
     #[serde(default, skip_serializing_if = "is_default")]
-    slant: (f32, f32),
+    scale: Scale,
+    #[serde(default, skip_serializing_if = "is_default")]
+    slant: (f64, f64),
     #[serde(default, rename = "userData", skip_serializing_if = "is_default")]
     user_data: Dictionary,
 }
 
 /// Glyphs file format version 3 document
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(remote = "Self")]
 pub struct Glyphs3 {
     /// The build number of Glyphs used to save the file. Example: `"3210"`.
     #[serde(
@@ -151,12 +154,16 @@ pub struct Glyphs3 {
     #[serde(rename = "userData", default, skip_serializing_if = "is_default")]
     pub user_data: Dictionary,
     /// Version information.
-    #[serde(flatten, default, skip_serializing_if = "is_default")]
+    #[serde(flatten, default, skip_serializing)]
     pub version: Version,
+    /// Any other keys of the font, kept so they are saved as they were read.
+    #[serde(flatten, default, skip_serializing)]
+    pub other: Dictionary,
 }
 
 /// Number metric
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Number {
     /// The name of the number.
     pub name: String,
@@ -164,6 +171,7 @@ pub struct Number {
 
 /// Metric definition (`GSMetric`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Metric {
     /// The filter of the metric limiting the scope of the metric to a subset of glyphs.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -178,6 +186,7 @@ pub struct Metric {
 
 /// Metric type
 #[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum MetricType {
     /// Ascender metric
     #[serde(rename = "ascender")]
@@ -216,6 +225,7 @@ pub enum MetricType {
 
 /// Font settings
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Settings {
     /// Whether automatic alignment of components is disabled.
     #[serde(
@@ -239,19 +249,19 @@ pub struct Settings {
     pub grid_sub_division: Option<i32>,
     /// The standard keyboard increment.
     #[serde(rename = "keyboardIncrement", skip_serializing_if = "Option::is_none")]
-    pub keyboard_increment: Option<f32>,
+    pub keyboard_increment: Option<f64>,
     /// The keyboard increment when holding the Shift key.
     #[serde(
         rename = "keyboardIncrementBig",
         skip_serializing_if = "Option::is_none"
     )]
-    pub keyboard_increment_big: Option<f32>,
+    pub keyboard_increment_big: Option<f64>,
     /// The keyboard increment when holding both the Shift and Command key.
     #[serde(
         rename = "keyboardIncrementHuge",
         skip_serializing_if = "Option::is_none"
     )]
-    pub keyboard_increment_huge: Option<f32>,
+    pub keyboard_increment_huge: Option<f64>,
     /// Whether to keep alternates glyphs together in Font View.
     #[serde(
         rename = "keepAlternatesTogether",
@@ -276,6 +286,7 @@ pub struct Settings {
 
 /// Axis definition (`GSAxis`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Axis {
     /// Whether the axis is considered to be hidden from the font user.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -288,10 +299,12 @@ pub struct Axis {
 
 /// Font master (`GSFontMaster`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(remote = "Self")]
 pub struct Master {
     /// The designspace location of the master.
     #[serde(rename = "axesValues", default, skip_serializing_if = "Vec::is_empty")]
-    pub axes_values: Vec<f32>,
+    pub axes_values: Vec<f64>,
     /// The custom parameters of the master.
     #[serde(
         rename = "customParameters",
@@ -323,13 +336,13 @@ pub struct Master {
         default,
         skip_serializing_if = "Vec::is_empty"
     )]
-    pub number_values: Vec<f32>,
+    pub number_values: Vec<f64>,
     /// The properties of the master.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub properties: Vec<Property>,
     /// The stem values of the master.
     #[serde(rename = "stemValues", default, skip_serializing_if = "Vec::is_empty")]
-    pub stem_values: Vec<f32>,
+    pub stem_values: Vec<f64>,
     /// Auto-save files only: Temporary data associated with the master.
     #[serde(rename = "tempData", default, skip_serializing_if = "is_default")]
     pub temp_data: Dictionary,
@@ -339,22 +352,28 @@ pub struct Master {
     /// Whether the master is visible in the preview.
     #[serde(default, skip_serializing_if = "is_default")]
     pub visible: bool,
+    /// Any other keys of the master, kept so they are saved as they were read.
+    #[serde(skip)]
+    pub other: Dictionary,
 }
 
 /// Metric value store (`GSMetricStore`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct MetricValue {
     /// The overshoot of the metric value.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub over: f32,
+    pub over: f64,
     /// The offset from the baseline of the metric value.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub pos: f32,
+    pub pos: f64,
 }
 
 /// Glyph definition (`GSGlyph`)
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(remote = "Self")]
 pub struct Glyph {
     /// The kerning group of the bottom side of the glyph.
     #[serde(rename = "kernBottom", skip_serializing_if = "Option::is_none")]
@@ -455,6 +474,43 @@ pub struct Glyph {
     /// Custom data associated with the glyph.
     #[serde(rename = "userData", default, skip_serializing_if = "is_default")]
     pub user_data: Dictionary,
+    /// Any other keys of the glyph, kept so they are saved as they were read.
+    #[serde(skip)]
+    pub other: Dictionary,
+}
+
+impl Glyph {
+    /// The master layer of the glyph for a master ID
+    pub fn layer_for_master(&self, master_id: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.layer_id == master_id)
+    }
+
+    /// The master layer of the glyph for a master ID, for editing
+    pub fn layer_for_master_mut(&mut self, master_id: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.layer_id == master_id)
+    }
+
+    /// The master layers of the glyph in the order of the masters of the
+    /// font, leaving out masters the glyph has no layer for
+    pub fn master_layers<'a>(&'a self, font: &'a Glyphs3) -> impl Iterator<Item = &'a Layer> {
+        font.masters
+            .iter()
+            .filter_map(|master| self.layer_for_master(&master.id))
+    }
+
+    /// The intermediate, alternate and color layers of the glyph, in the
+    /// order of the file
+    pub fn special_layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers
+            .iter()
+            .filter(|l| l.is_brace_layer() || l.is_bracket_layer() || l.is_color_layer())
+    }
+
+    /// The backgrounds of the layers of the glyph which have one, in the
+    /// order of the layers
+    pub fn backgrounds(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter().filter_map(|l| l.background.as_deref())
+    }
 }
 
 /// Layer definition (`GSLayer`)
@@ -462,6 +518,8 @@ pub struct Glyph {
 /// We manually serialize this because background layers serialize differently,
 /// and I don't want to have a separate BackgroundLayer struct.
 #[derive(Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(remote = "Self")]
 pub struct Layer {
     /// The anchors of the layer.
     #[serde(default)]
@@ -474,7 +532,7 @@ pub struct Layer {
     pub associated_master_id: Option<String>,
     /// The attributes of the layer.
     #[serde(default)]
-    pub attr: Dictionary,
+    pub attr: LayerAttributes,
     /// The background layer.
     #[serde(default)]
     pub background: Option<Box<Layer>>,
@@ -489,7 +547,7 @@ pub struct Layer {
     pub guides: Vec<Guide>,
     /// The hints of the layer.
     #[serde(default)]
-    pub hints: Vec<Dictionary>, // This thing's an absolute minefield
+    pub hints: Vec<Hint>,
     /// The unique ID of the layer. Matches the master ID when the layer is a master layer.
     #[serde(rename = "layerId", default)]
     // Not required for background layers
@@ -533,20 +591,311 @@ pub struct Layer {
     pub user_data: Dictionary,
     /// The vertical origin of the layer.
     #[serde(rename = "vertOrigin", default)]
-    pub vert_origin: Option<f32>,
+    pub vert_origin: Option<f64>,
     /// The vertical width of the layer.
     #[serde(rename = "vertWidth", default)]
-    pub vert_width: Option<f32>,
+    pub vert_width: Option<f64>,
     /// Whether the layer is visible.
     #[serde(default = "bool_true")]
     pub visible: bool,
     /// The width of the layer.
     #[serde(default)]
-    pub width: f32,
+    pub width: f64,
+    /// Any other keys of the layer, kept so they are saved as they were read.
+    #[serde(skip)]
+    pub other: Dictionary,
+}
+
+impl Layer {
+    // An empty, visible layer
+    pub(crate) fn empty(layer_id: String, width: f64) -> Self {
+        Layer {
+            anchors: vec![],
+            annotations: vec![],
+            associated_master_id: None,
+            attr: Default::default(),
+            background: None,
+            background_image: None,
+            color: None,
+            guides: vec![],
+            hints: vec![],
+            layer_id,
+            metric_bottom: None,
+            metric_left: None,
+            metric_right: None,
+            metric_top: None,
+            metric_vert_origin: None,
+            metric_vert_width: None,
+            metric_width: None,
+            name: None,
+            part_selection: BTreeMap::new(),
+            shapes: vec![],
+            user_data: Default::default(),
+            vert_origin: None,
+            vert_width: None,
+            visible: true,
+            width,
+            other: Default::default(),
+        }
+    }
+
+    /// Whether this is an intermediate ("brace") layer, placed at its own coordinates
+    pub fn is_brace_layer(&self) -> bool {
+        !self.attr.coordinates.is_empty()
+    }
+
+    /// The design space location of a brace layer, one value per axis
+    pub fn brace_coordinates(&self) -> Option<&[f64]> {
+        self.is_brace_layer()
+            .then_some(self.attr.coordinates.as_slice())
+    }
+
+    /// Whether this is an alternate ("bracket") layer, used within axis ranges
+    pub fn is_bracket_layer(&self) -> bool {
+        !self.attr.axis_rules.is_empty()
+    }
+
+    /// The axis ranges of a bracket layer, one rule per axis
+    pub fn bracket_axis_rules(&self) -> Option<&[AxisRule]> {
+        self.is_bracket_layer()
+            .then_some(self.attr.axis_rules.as_slice())
+    }
+
+    /// Whether this is one of the layers of a color glyph: a full color,
+    /// color palette, SVG or sbix layer
+    pub fn is_color_layer(&self) -> bool {
+        self.attr.color
+            || self.attr.svg
+            || self.attr.sbix_size.is_some()
+            || self.palette_index().is_some()
+    }
+
+    /// Which end of a smart component property this layer stands for, if
+    /// it is one of the layers of a smart glyph
+    pub fn smart_component_pole(&self, property: &str) -> Option<SmartComponentPole> {
+        SmartComponentPole::from_part_selection(*self.part_selection.get(property)?)
+    }
+
+    /// The paths of the layer, in order
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.shapes.iter().filter_map(Shape::as_path)
+    }
+
+    /// The paths of the layer, in order, for editing
+    pub fn paths_mut(&mut self) -> impl Iterator<Item = &mut Path> {
+        self.shapes.iter_mut().filter_map(Shape::as_path_mut)
+    }
+
+    /// The components of the layer, in order
+    pub fn components(&self) -> impl Iterator<Item = &Component> {
+        self.shapes.iter().filter_map(Shape::as_component)
+    }
+
+    /// The components of the layer, in order, for editing
+    pub fn components_mut(&mut self) -> impl Iterator<Item = &mut Component> {
+        self.shapes.iter_mut().filter_map(Shape::as_component_mut)
+    }
+}
+
+/// The range of one axis in which a bracket layer is used
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AxisRule {
+    /// The lowest location, if the range is bounded below.
+    pub min: Option<f64>,
+    /// The highest location, if the range is bounded above.
+    pub max: Option<f64>,
+}
+
+/// Layer attributes (the `attr` dictionary of `GSLayer`)
+///
+/// Values which are missing or not of the expected type are left in
+/// [`other`](LayerAttributes::other), so reading and writing the attributes
+/// never loses anything.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(from = "Dictionary", into = "Dictionary")]
+pub struct LayerAttributes {
+    /// The axis ranges of a bracket layer (`axisRules`).
+    pub axis_rules: Vec<AxisRule>,
+    /// Whether the layer is a full color layer (`color`).
+    pub color: bool,
+    /// The palette entry of a color palette layer (`colorPalette`).
+    pub color_palette: Option<i64>,
+    /// The location of a brace layer (`coordinates`).
+    pub coordinates: Vec<f64>,
+    /// The pixel size of an sbix bitmap layer (`sbixSize`).
+    pub sbix_size: Option<i64>,
+    /// Whether the layer is an SVG layer (`svg`).
+    pub svg: bool,
+    /// Attributes with no field of their own.
+    pub other: Dictionary,
+}
+
+impl LayerAttributes {
+    /// Whether the layer has no attributes at all
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl From<Dictionary> for LayerAttributes {
+    fn from(mut other: Dictionary) -> Self {
+        let mut take = |key: &str, convert: fn(&Plist) -> bool| {
+            other
+                .get(key)
+                .is_some_and(convert)
+                .then(|| other.remove(key))
+                .flatten()
+        };
+        let numbers = |plist: &Plist| {
+            plist
+                .as_array()
+                .is_some_and(|a| a.iter().all(|c| c.as_f64().is_some()))
+        };
+        let axis_rules = take("axisRules", |plist| {
+            plist
+                .as_array()
+                .is_some_and(|a| a.iter().all(|c| c.as_dict().is_some()))
+        });
+        let color = take("color", |plist| plist.as_i64().is_some());
+        let color_palette = take("colorPalette", |plist| plist.as_i64().is_some());
+        let coordinates = take("coordinates", numbers);
+        let sbix_size = take("sbixSize", |plist| plist.as_i64().is_some());
+        let svg = take("svg", |plist| plist.as_i64().is_some());
+        let bound = |rule: &Dictionary, key| rule.get(key).and_then(Plist::as_f64);
+        LayerAttributes {
+            axis_rules: axis_rules
+                .iter()
+                .flat_map(|rules| rules.as_array().unwrap_or_default())
+                .filter_map(Plist::as_dict)
+                .map(|rule| AxisRule {
+                    min: bound(rule, "min"),
+                    max: bound(rule, "max"),
+                })
+                .collect(),
+            color: color.and_then(|c| c.as_i64()).is_some_and(|c| c != 0),
+            color_palette: color_palette.and_then(|c| c.as_i64()),
+            coordinates: coordinates
+                .iter()
+                .flat_map(|c| c.as_array().unwrap_or_default())
+                .filter_map(Plist::as_f64)
+                .collect(),
+            sbix_size: sbix_size.and_then(|s| s.as_i64()),
+            svg: svg.and_then(|s| s.as_i64()).is_some_and(|s| s != 0),
+            other,
+        }
+    }
+}
+
+// Glyphs writes whole numbers without a fractional part
+fn number(value: f64) -> Plist {
+    if value.fract() == 0.0 {
+        Plist::Integer(value as i64)
+    } else {
+        Plist::Float(value)
+    }
+}
+
+impl From<LayerAttributes> for Dictionary {
+    fn from(val: LayerAttributes) -> Self {
+        let mut dict = val.other;
+        if !val.axis_rules.is_empty() {
+            let rules = val.axis_rules.iter().map(|rule| {
+                let mut bounds = Dictionary::new();
+                if let Some(min) = rule.min {
+                    bounds.insert("min".into(), number(min));
+                }
+                if let Some(max) = rule.max {
+                    bounds.insert("max".into(), number(max));
+                }
+                Plist::Dictionary(bounds)
+            });
+            dict.insert("axisRules".into(), Plist::Array(rules.collect()));
+        }
+        if val.color {
+            dict.insert("color".into(), Plist::Integer(1));
+        }
+        if let Some(palette) = val.color_palette {
+            dict.insert("colorPalette".into(), Plist::Integer(palette));
+        }
+        if !val.coordinates.is_empty() {
+            let coordinates = val.coordinates.iter().map(|c| number(*c));
+            dict.insert("coordinates".into(), Plist::Array(coordinates.collect()));
+        }
+        if let Some(size) = val.sbix_size {
+            dict.insert("sbixSize".into(), Plist::Integer(size));
+        }
+        if val.svg {
+            dict.insert("svg".into(), Plist::Integer(1));
+        }
+        dict
+    }
+}
+
+/// Hint (`GSHint`)
+///
+/// Glyphs 3 keeps PostScript and TrueType hints here, but also corner and
+/// cap components and a few other node-attached objects, each of which uses
+/// a different subset of the keys.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Hint {
+    /// Whether the hint is horizontal.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub horizontal: bool,
+    /// The name of the hint; for corner and cap hints, the glyph they use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The options of the hint, such as the alignment of a corner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<i64>,
+    /// The node the hint starts at, as a path index and a node index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<(f64, f64)>,
+    /// The first additional node of the hint, for interpolation and diagonal hints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other1: Option<(f64, f64)>,
+    /// The second additional node of the hint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other2: Option<(f64, f64)>,
+    /// The position of a hint which is not attached to a node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub place: Option<Point>,
+    /// The scale of a corner or cap component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Scale>,
+    /// The settings of the hint, such as the zones of a TrueType hint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Dictionary>,
+    /// The index of the master stem the hint uses; -1 chooses automatically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stem: Option<i64>,
+    /// The node or direction the hint ends at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<HintTarget>,
+    /// The type of the hint, such as `Stem`, `TTStem` or `Corner`.
+    #[serde(default, rename = "type", skip_serializing_if = "String::is_empty")]
+    pub type_: String,
+    /// Any other keys of the hint, kept so they are saved as they were read.
+    #[serde(flatten, default, skip_serializing_if = "is_default")]
+    pub other: Dictionary,
+}
+
+/// The end of a hint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(untagged)]
+pub enum HintTarget {
+    /// A node, as a path index and a node index.
+    Node((f64, f64)),
+    /// A direction, such as `up` or `down` for ghost hints.
+    Label(String),
 }
 
 /// Anchor definition (`GSAnchor`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Anchor {
     /// Whether the anchor is locked.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -558,7 +907,7 @@ pub struct Anchor {
     pub orientation: Orientation,
     #[serde(default, skip_serializing_if = "is_default")]
     /// The position of the anchor.
-    pub pos: (f32, f32),
+    pub pos: Point,
     /// Custom data associated with the anchor.
     #[serde(default, rename = "userData", skip_serializing_if = "is_default")]
     pub user_data: Option<Dictionary>,
@@ -566,13 +915,14 @@ pub struct Anchor {
 
 /// Background image (`GSImage`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct BackgroundImage {
     /// The rotation angle of the image in degrees clockwise.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub angle: f32,
+    pub angle: f64,
     /// The cropped frame of the image, specified as the crop origin X/Y and size width/height.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub crop: Option<(f32, f32, f32, f32)>,
+    pub crop: Option<(f64, f64, f64, f64)>,
     /// The file path of the image file relative to the document file.
     #[serde(rename = "imagePath")]
     pub image_path: String,
@@ -581,14 +931,15 @@ pub struct BackgroundImage {
     pub locked: bool,
     /// The position of the image.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub pos: (f32, f32),
+    pub pos: Point,
     /// The scale factor of the image.
-    #[serde(default = "scale_unit", skip_serializing_if = "is_scale_unit")]
-    pub scale: (f32, f32),
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub scale: Scale,
 }
 
 /// Guide type (`GSGuideType`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum GuideType {
     /// Line guide
     #[default]
@@ -601,19 +952,20 @@ pub enum GuideType {
 
 /// Guide definition (`GSGuide`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Guide {
     /// The angle at which the guide is drawn in degrees clockwise.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub angle: f32,
+    pub angle: f64,
     /// The filter of the guide. The syntax is the description of [NSPredicate]
     #[serde(default, skip_serializing_if = "is_default")]
     pub filter: String,
     /// The grid of the guide.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub grid: f32,
+    pub grid: f64,
     /// The length of a line-type guide.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub length: f32,
+    pub length: f64,
     /// Whether the angle of the guide is locked.
     #[serde(default, skip_serializing_if = "is_default", rename = "lockAngle")]
     pub lock_angle: bool,
@@ -628,7 +980,7 @@ pub struct Guide {
     pub orientation: Orientation,
     /// The position of the guide.
     #[serde(default, skip_serializing_if = "is_default")]
-    pub pos: (f32, f32),
+    pub pos: Point,
     /// Whether the measurement of the guide is shown
     #[serde(
         default,
@@ -638,7 +990,7 @@ pub struct Guide {
     pub show_measurement: bool,
     /// The size of the guide.
     #[serde(default = "scale_unit", skip_serializing_if = "is_scale_unit")]
-    pub size: (f32, f32),
+    pub size: (f64, f64),
     /// The type of the guide
     #[serde(default, skip_serializing_if = "is_default", rename = "type")]
     pub guide_type: GuideType,
@@ -649,6 +1001,7 @@ pub struct Guide {
 
 /// Shape - either a path or a component
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(untagged)]
 pub enum Shape {
     /// Component reference
@@ -657,8 +1010,43 @@ pub enum Shape {
     Path(Path),
 }
 
+impl Shape {
+    /// The path, if this is one
+    pub fn as_path(&self) -> Option<&Path> {
+        match self {
+            Shape::Path(path) => Some(path),
+            Shape::Component(_) => None,
+        }
+    }
+
+    /// The path, if this is one, for editing
+    pub fn as_path_mut(&mut self) -> Option<&mut Path> {
+        match self {
+            Shape::Path(path) => Some(path),
+            Shape::Component(_) => None,
+        }
+    }
+
+    /// The component, if this is one
+    pub fn as_component(&self) -> Option<&Component> {
+        match self {
+            Shape::Component(component) => Some(component),
+            Shape::Path(_) => None,
+        }
+    }
+
+    /// The component, if this is one, for editing
+    pub fn as_component_mut(&mut self) -> Option<&mut Component> {
+        match self {
+            Shape::Component(component) => Some(component),
+            Shape::Path(_) => None,
+        }
+    }
+}
+
 /// Path definition (`GSPath`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Path {
     /// The attributes of the path.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -672,11 +1060,12 @@ pub struct Path {
 
 /// Path node (`GSNode`)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Node {
     /// The x-coordinate of the node.
-    pub x: f32,
+    pub x: f64,
     /// The y-coordinate of the node.
-    pub y: f32,
+    pub y: f64,
     /// The type of the node.
     pub node_type: NodeType,
     /// Custom data associated with the node.
@@ -685,6 +1074,7 @@ pub struct Node {
 
 /// Component reference (`GSComponent`)
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Component {
     /// Controls the automatic alignment of the component. `-1`: disabled (no alignment), `0`: default (alignment is based on context), `1`: force alignment (align regardless of context), `3`: horizontal alignment (align horizontally, but allow for manual vertical placement).
     pub alignment: i8,
@@ -693,7 +1083,7 @@ pub struct Component {
     /// Undocumented anchor targeting.
     pub anchor_to: Option<String>,
     /// The rotation angle of the component in degrees clockwise.
-    pub angle: f32,
+    pub angle: f64,
     /// The attributes of the component.
     pub attr: Dictionary,
     /// Whether the component is locked.
@@ -703,15 +1093,15 @@ pub struct Component {
     /// The orientation of the component.
     pub orientation: Orientation,
     /// The Smart Component settings of the component, mapping property names to values.
-    pub smart_component_location: BTreeMap<String, f32>,
+    pub smart_component_location: BTreeMap<String, f64>,
     /// The position (translation transform) of the component.
-    pub position: (f32, f32),
+    pub position: Point,
     /// The name of the referenced glyph.
     pub component_glyph: String,
     /// The scale transform of the component.
-    pub scale: (f32, f32),
+    pub scale: Scale,
     /// The slant transform of the component.
-    pub slant: (f32, f32),
+    pub slant: (f64, f64),
     /// Custom data associated with the component.
     pub user_data: Dictionary,
 
@@ -781,10 +1171,12 @@ impl<'de> Deserialize<'de> for Component {
 
 /// Instance definition (`GSInstance`)
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(remote = "Self")]
 pub struct Instance {
     /// The internal axis locations of the instance. These values are also used for the external axis locations, if no external axis locations are specified separately.
     #[serde(default, rename = "axesValues", skip_serializing_if = "is_default")]
-    pub axes_values: Vec<f32>,
+    pub axes_values: Vec<f64>,
     /// The custom parameters of the instance.
     #[serde(
         default,
@@ -838,20 +1230,26 @@ pub struct Instance {
     #[serde(
         default,
         rename = "weightClass",
-        skip_serializing_if = "Option::is_none"
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_os2_weight"
     )]
-    pub weight_class: Option<i32>,
+    pub weight_class: Option<WeightClass>,
     /// The width class of the instance.
     #[serde(
         default,
         rename = "widthClass",
-        skip_serializing_if = "Option::is_none"
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_os2_width"
     )]
-    pub width_class: Option<i32>,
+    pub width_class: Option<WidthClass>,
+    /// Any other keys of the instance, kept so they are saved as they were read.
+    #[serde(skip)]
+    pub other: Dictionary,
 }
 
 /// Instance export type
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum ExportType {
     /// Static instance
     #[default]
@@ -864,9 +1262,23 @@ pub enum ExportType {
     Icon,
 }
 
-/// Font property (`GSInfoProperty`)
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
 #[serde(untagged)]
+enum PropertySerde {
+    SingularProperty {
+        key: SingularPropertyKey,
+        value: String,
+    },
+    LocalizedProperty {
+        key: LocalizedPropertyKey,
+        values: Vec<LocalizedValue>,
+    },
+    Junk(Plist),
+}
+
+/// Font property (`GSInfoProperty`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Property {
     /// Singular (non-localized) property
     SingularProperty {
@@ -882,11 +1294,100 @@ pub enum Property {
         /// Localized values
         values: Vec<LocalizedValue>,
     },
+    /// A property with a key this crate does not know, or a known key with
+    /// a value of the other kind, such as a localized `designer`
+    Unknown {
+        /// Property key, as written in the file
+        key: String,
+        /// The `value` of the property, or the array of its localized
+        /// `values`
+        value: Plist,
+    },
     /// Unrecognized property format
     Junk(Plist),
 }
 
+impl Serialize for Property {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.clone() {
+            Property::SingularProperty { key, value } => {
+                PropertySerde::SingularProperty { key, value }
+            }
+            Property::LocalizedProperty { key, values } => {
+                PropertySerde::LocalizedProperty { key, values }
+            }
+            Property::Unknown { key, value } => {
+                let name = if matches!(value, Plist::Array(_)) {
+                    "values"
+                } else {
+                    "value"
+                };
+                let dict =
+                    Dictionary::from([("key".into(), Plist::String(key)), (name.into(), value)]);
+                PropertySerde::Junk(Plist::Dictionary(dict))
+            }
+            Property::Junk(plist) => PropertySerde::Junk(plist),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Property {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match PropertySerde::deserialize(deserializer)? {
+            PropertySerde::SingularProperty { key, value } => {
+                Property::SingularProperty { key, value }
+            }
+            PropertySerde::LocalizedProperty { key, values } => {
+                Property::LocalizedProperty { key, values }
+            }
+            PropertySerde::Junk(plist) => Property::from_junk(plist),
+        })
+    }
+}
+
 impl Property {
+    // A property with a key and a value or values and nothing else, which
+    // can be written back the same, is unknown rather than junk
+    fn from_junk(plist: Plist) -> Self {
+        if let Plist::Dictionary(dict) = &plist {
+            if let (2, Some(Plist::String(key))) = (dict.len(), dict.get("key")) {
+                let value = match dict.get("values") {
+                    Some(values @ Plist::Array(_)) => Some(values),
+                    Some(_) => None,
+                    None => dict.get("value").filter(|v| !matches!(v, Plist::Array(_))),
+                };
+                if let Some(value) = value {
+                    return Property::Unknown {
+                        key: key.clone(),
+                        value: value.clone(),
+                    };
+                }
+            }
+        }
+        Property::Junk(plist)
+    }
+
+    /// The key of the property as written in the file, such as
+    /// `"familyNames"`, unless it is junk
+    pub fn key_name(&self) -> Option<String> {
+        match self {
+            Property::Unknown { key, .. } => Some(key.clone()),
+            Property::Junk(_) => None,
+            known => crate::diff::to_plist(known)
+                .as_dict()?
+                .get("key")?
+                .as_str()
+                .map(String::from),
+        }
+    }
+
     /// Create a singular property
     pub fn singular(key: SingularPropertyKey, value: String) -> Self {
         Property::SingularProperty { key, value }
@@ -895,16 +1396,58 @@ impl Property {
     pub fn localized_with_default(key: LocalizedPropertyKey, value: String) -> Self {
         Property::LocalizedProperty {
             key,
-            values: vec![LocalizedValue {
-                language: "dflt".to_string(),
-                value,
-            }],
+            values: vec![LocalizedValue::new("dflt", value)],
+        }
+    }
+
+    /// The value of the property in a language, such as `"ENG"` or `"dflt"`
+    ///
+    /// A singular property has the same value in every language. Junk
+    /// properties have none.
+    pub fn value_for_language(&self, language: &str) -> Option<&str> {
+        match self {
+            Property::SingularProperty { value, .. } => Some(value),
+            Property::LocalizedProperty { values, .. } => values
+                .iter()
+                .find(|v| v.language == language)
+                .map(|v| v.value.as_str()),
+            Property::Unknown {
+                value: Plist::String(value),
+                ..
+            } => Some(value),
+            Property::Unknown {
+                value: Plist::Array(values),
+                ..
+            } => values.iter().find_map(|v| {
+                let dict = v.as_dict()?;
+                (dict.get("language")?.as_str()? == language)
+                    .then_some(dict.get("value")?.as_str()?)
+            }),
+            Property::Unknown { .. } | Property::Junk(_) => None,
+        }
+    }
+
+    /// The value of the property in the default language, or else in the
+    /// first language it has
+    pub fn default_value(&self) -> Option<&str> {
+        match self {
+            Property::LocalizedProperty { values, .. } => self
+                .value_for_language("dflt")
+                .or(values.first().map(|v| v.value.as_str())),
+            Property::Unknown {
+                value: Plist::Array(values),
+                ..
+            } => self
+                .value_for_language("dflt")
+                .or_else(|| values.first()?.as_dict()?.get("value")?.as_str()),
+            _ => self.value_for_language("dflt"),
         }
     }
 }
 
 /// Localized property key
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum LocalizedPropertyKey {
     /// Family names
     #[serde(rename = "familyNames")]
@@ -943,6 +1486,7 @@ pub enum LocalizedPropertyKey {
 
 /// Singular (non-localized) property key
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum SingularPropertyKey {
     /// Designer name
     #[serde(rename = "designer")]
@@ -981,6 +1525,7 @@ pub enum SingularPropertyKey {
 
 /// Localized string value (`GSInfoValue`)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LocalizedValue {
     /// The language tag of the string value. The tag is based on the OpenType Language System Tags but omits trailing whitespace. Examples: `"dflt"`, `"DEU"`.
     pub language: String,
@@ -988,8 +1533,19 @@ pub struct LocalizedValue {
     pub value: String,
 }
 
+impl LocalizedValue {
+    /// A value in a language
+    pub fn new(language: impl Into<String>, value: impl Into<String>) -> Self {
+        LocalizedValue {
+            language: language.into(),
+            value: value.into(),
+        }
+    }
+}
+
 /// Stem definition
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Stem {
     /// Whether the stem is a horizontal stem.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -1030,10 +1586,303 @@ mod tests {
         assert!(guide.lock_angle);
         assert!(!guide.locked);
         assert_eq!(guide.orientation, Orientation::Left);
-        assert_eq!(guide.pos, (100.0, 200.0));
+        assert_eq!(guide.pos, Point::new(100.0, 200.0));
         assert!(guide.show_measurement);
         assert_eq!(guide.size, (300.0, 400.0));
         assert_eq!(guide.guide_type, GuideType::Line);
         assert!(guide.user_data.is_some());
     }
+
+    #[test]
+    fn test_coordinate_precision() {
+        // Coordinates of large fonts and scaled components keep more digits
+        // than f32 would
+        let plist = Plist::parse("(16383.0625,-123456.789,l)").unwrap();
+        let deserializer = &mut Deserializer::from_plist(&plist);
+        let node: Node = serde_path_to_error::deserialize(deserializer).unwrap();
+        assert_eq!((node.x, node.y), (16383.0625, -123456.789));
+        let written = openstep_plist::ser::to_string(&node).unwrap();
+        assert!(written.contains("-123456.789"), "{written}");
+    }
+
+    #[test]
+    fn test_shape_iterators() {
+        let file = std::path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let crate::Font::Glyphs3(mut font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let layer = &mut font
+            .glyphs
+            .iter_mut()
+            .find(|g| g.name == "eacute")
+            .unwrap()
+            .layers[0];
+        assert_eq!(layer.paths().count(), 0);
+        let names: Vec<&str> = layer
+            .components()
+            .map(|c| c.component_glyph.as_str())
+            .collect();
+        assert_eq!(names, ["e", "acutecomb"]);
+        assert!(layer.shapes[0].as_path().is_none());
+        let before: Vec<f64> = layer.components().map(|c| c.position.x).collect();
+        for component in layer.components_mut() {
+            component.position.x += 10.0;
+        }
+        let after: Vec<f64> = layer.components().map(|c| c.position.x - 10.0).collect();
+        assert_eq!(before, after);
+        layer.shapes.push(Shape::Path(Path {
+            attr: Default::default(),
+            closed: true,
+            nodes: vec![],
+        }));
+        layer.paths_mut().for_each(|p| p.closed = false);
+        assert!(!layer.shapes[2].as_path().unwrap().closed);
+    }
+
+    #[test]
+    fn test_glyph_layer_lookup() {
+        let file = std::path::Path::new("resources/IntermediateLayer.glyphs");
+        let crate::Font::Glyphs3(font) = crate::Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let mut glyph = font
+            .glyphs
+            .iter()
+            .find(|g| g.layers.iter().any(Layer::is_brace_layer))
+            .unwrap()
+            .clone();
+        let ids: Vec<&str> = glyph
+            .master_layers(&font)
+            .map(|l| l.layer_id.as_str())
+            .collect();
+        let master_ids: Vec<&str> = font.masters.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, master_ids);
+        assert!(glyph.layer_for_master(&font.masters[0].id).is_some());
+        assert!(glyph.layer_for_master("missing").is_none());
+        assert!(glyph.special_layers().all(Layer::is_brace_layer));
+        assert!(glyph.special_layers().count() > 0);
+
+        let master_id = font.masters[0].id.clone();
+        let mut background = glyph.layer_for_master(&master_id).unwrap().clone();
+        background.layer_id = "background".to_string();
+        let layer = glyph.layer_for_master_mut(&master_id).unwrap();
+        layer.background = Some(Box::new(background));
+        assert_eq!(glyph.backgrounds().count(), 1);
+    }
+
+    #[test]
+    fn test_hints() {
+        let data = r#"
+        (
+            {
+                horizontal = 1;
+                origin = (0,1);
+                stem = -2;
+                target = (0,4);
+                type = Stem;
+            },
+            {
+                origin = (1,0);
+                target = up;
+                type = TopGhost;
+            },
+            {
+                name = _corner.serif;
+                options = 1;
+                origin = (0,7);
+                scale = (1.2,1);
+                type = Corner;
+                futureKey = (1,2);
+            }
+        )
+        "#;
+        let plist = Plist::parse(data).expect("Failed to parse plist");
+        let deserializer = &mut Deserializer::from_plist(&plist);
+        let hints: Vec<Hint> =
+            serde_path_to_error::deserialize(deserializer).expect("Failed to deserialize hints");
+
+        assert!(hints[0].horizontal);
+        assert_eq!(hints[0].stem, Some(-2));
+        assert_eq!(hints[0].target, Some(HintTarget::Node((0.0, 4.0))));
+        assert_eq!(hints[1].target, Some(HintTarget::Label("up".to_string())));
+        assert_eq!(hints[2].name.as_deref(), Some("_corner.serif"));
+        assert_eq!(hints[2].scale, Some(Scale::new(1.2, 1.0)));
+        assert!(hints[2].other.contains_key("futureKey"));
+
+        let serialized = openstep_plist::ser::to_string(&hints).unwrap();
+        assert_eq!(Plist::parse(&serialized).unwrap(), plist);
+    }
+
+    #[test]
+    fn test_layer_attributes() {
+        let data = r#"
+        {
+            axisRules = ({ min = 600; }, {});
+            colorPalette = "*";
+            coordinates = (600, 400.5);
+            sbixSize = 128;
+            somethingNew = 1;
+        }
+        "#;
+        let plist = Plist::parse(data).expect("Failed to parse plist");
+        let deserializer = &mut Deserializer::from_plist(&plist);
+        let attr: LayerAttributes =
+            serde_path_to_error::deserialize(deserializer).expect("Failed to deserialize attr");
+
+        assert_eq!(
+            attr.axis_rules,
+            vec![
+                AxisRule {
+                    min: Some(600.0),
+                    max: None
+                },
+                AxisRule::default()
+            ]
+        );
+        assert_eq!(attr.coordinates, vec![600.0, 400.5]);
+        assert_eq!(attr.sbix_size, Some(128));
+        // Not a palette index, so kept as it was
+        assert_eq!(attr.color_palette, None);
+        assert!(attr.other.contains_key("colorPalette"));
+        assert!(attr.other.contains_key("somethingNew"));
+        assert!(!attr.is_empty());
+
+        let serialized = openstep_plist::ser::to_string(&attr).unwrap();
+        assert_eq!(Plist::parse(&serialized).unwrap(), plist);
+    }
+
+    #[test]
+    fn test_unknown_keys_round_trip() {
+        let data = r#"
+        {
+            .formatVersion = 3;
+            familyName = Test;
+            fontMaster = ({ id = m01; name = Regular; futureMasterKey = 1; });
+            futureFontKey = { nested = (1, 2); };
+            glyphs = (
+                {
+                    futureGlyphKey = "yes";
+                    glyphname = a;
+                    layers = ({
+                        background = { futureBackgroundKey = 2; };
+                        futureLayerKey = (a, b);
+                        layerId = m01;
+                        width = 500;
+                    });
+                }
+            );
+            instances = ({ futureInstanceKey = 3; name = Bold; });
+            unitsPerEm = 1000;
+            versionMajor = 1;
+            versionMinor = 2;
+            zFutureKey = 4;
+        }
+        "#;
+        let plist = Plist::parse(data).expect("Failed to parse plist");
+        let deserializer = &mut Deserializer::from_plist(&plist);
+        let font: Glyphs3 =
+            serde_path_to_error::deserialize(deserializer).expect("Failed to deserialize font");
+
+        assert!(font.other.contains_key("futureFontKey"));
+        assert!(font.masters[0].other.contains_key("futureMasterKey"));
+        assert!(font.instances[0].other.contains_key("futureInstanceKey"));
+        let glyph = &font.glyphs[0];
+        assert!(glyph.other.contains_key("futureGlyphKey"));
+        assert!(glyph.layers[0].other.contains_key("futureLayerKey"));
+        let background = glyph.layers[0].background.as_ref().unwrap();
+        assert!(background.other.contains_key("futureBackgroundKey"));
+        // Known keys are not duplicated
+        assert!(!font.other.contains_key("familyName"));
+        assert!(!glyph.layers[0].other.contains_key("width"));
+        assert_eq!(openstep_plist::from_str::<Glyphs3>(data).unwrap(), font);
+
+        let serialized = openstep_plist::ser::to_string(&font).unwrap();
+        let reloaded = Plist::parse(&serialized).unwrap();
+        let deserializer = &mut Deserializer::from_plist(&reloaded);
+        let reloaded: Glyphs3 = serde_path_to_error::deserialize(deserializer).unwrap();
+        assert_eq!(reloaded, font);
+        for key in ["futureFontKey", "futureMasterKey", "futureLayerKey"] {
+            assert!(serialized.contains(key), "{key} was not saved");
+        }
+        // Other keys are saved in order among the known ones
+        let order = [
+            "familyName",
+            "fontMaster",
+            "futureFontKey",
+            "futureGlyphKey",
+            "glyphname",
+            "futureLayerKey",
+            "layerId",
+            "futureInstanceKey",
+            "name = Bold;",
+            "unitsPerEm",
+            "versionMajor",
+            "versionMinor",
+            "zFutureKey",
+        ];
+        let positions: Vec<usize> = order
+            .iter()
+            .map(|key| serialized.find(key).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{serialized}");
+    }
+
+    #[test]
+    fn test_property_languages() {
+        let property = Property::LocalizedProperty {
+            key: LocalizedPropertyKey::FamilyNames,
+            values: vec![
+                LocalizedValue::new("DEU", "Schrift"),
+                LocalizedValue::new("ENG", "Font"),
+            ],
+        };
+        assert_eq!(property.value_for_language("ENG"), Some("Font"));
+        assert_eq!(property.value_for_language("FRA"), None);
+        assert_eq!(property.default_value(), Some("Schrift"));
+        let property = Property::localized_with_default(
+            LocalizedPropertyKey::FamilyNames,
+            "Default".to_string(),
+        );
+        assert_eq!(property.default_value(), Some("Default"));
+        let property = Property::singular(SingularPropertyKey::VendorID, "ABCD".to_string());
+        assert_eq!(property.value_for_language("ENG"), Some("ABCD"));
+        assert_eq!(Property::Junk(Plist::Integer(1)).default_value(), None);
+    }
+
+    #[test]
+    fn test_unknown_properties() {
+        let text = r#"(
+            {key = designers; value = "Misplaced";},
+            {key = designer; values = ({language = dflt; value = "Someone";});},
+            {key = futureKey; value = 3;},
+            {key = odd; value = 1; extra = 2;}
+        )"#;
+        let properties: Vec<Property> = openstep_plist::from_str(text).unwrap();
+        assert_eq!(
+            properties[0],
+            Property::Unknown {
+                key: "designers".to_string(),
+                value: Plist::String("Misplaced".to_string()),
+            }
+        );
+        assert_eq!(properties[0].default_value(), Some("Misplaced"));
+        assert_eq!(properties[1].key_name().as_deref(), Some("designer"));
+        assert_eq!(properties[1].value_for_language("dflt"), Some("Someone"));
+        assert!(matches!(
+            &properties[2],
+            Property::Unknown {
+                value: Plist::Integer(3),
+                ..
+            }
+        ));
+        assert!(matches!(properties[3], Property::Junk(_)));
+        assert_eq!(properties[3].key_name(), None);
+
+        let known = Property::singular(SingularPropertyKey::VendorID, "ABCD".to_string());
+        assert_eq!(known.key_name().as_deref(), Some("vendorID"));
+
+        let written = openstep_plist::ser::to_string(&properties).unwrap();
+        let reread: Vec<Property> = openstep_plist::from_str(&written).unwrap();
+        assert_eq!(reread, properties);
+    }
 }
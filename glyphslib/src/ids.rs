@@ -0,0 +1,139 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::glyphs3::{Glyph, Glyphs3, Layer, Master, MetricValue};
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Each `RandomState` is randomly keyed, and the counter and clock make
+// sure no two calls hash the same input.
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    if let Ok(time) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(time.as_nanos());
+    }
+    hasher.finish()
+}
+
+/// A fresh random (version 4) UUID in upper case, as Glyphs uses for the IDs
+/// of masters and layers
+pub fn new_id() -> String {
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&random_u64().to_be_bytes());
+    bytes[8..].copy_from_slice(&random_u64().to_be_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+impl Glyph {
+    /// Add a layer for a master to the glyph, returning it
+    ///
+    /// The layer is a copy of the first master layer in the glyph's list, as when a
+    /// master is added in Glyphs, so that the glyph stays compatible; a
+    /// glyph without one gets an empty layer 600 units wide. If the glyph
+    /// already has a layer for the master, that layer is returned instead.
+    pub fn add_master_layer(&mut self, master: &Master) -> &mut Layer {
+        if let Some(ix) = self.layers.iter().position(|l| l.layer_id == master.id) {
+            return &mut self.layers[ix];
+        }
+        let layer = match self
+            .layers
+            .iter()
+            .find(|l| l.associated_master_id.is_none())
+        {
+            Some(existing) => Layer {
+                layer_id: master.id.clone(),
+                name: None,
+                ..existing.clone()
+            },
+            None => Layer::empty(master.id.clone(), 600.0),
+        };
+        self.layers.push(layer);
+        self.layers.last_mut().unwrap()
+    }
+}
+
+impl Glyphs3 {
+    /// Add a master to the font, returning it, with a layer for it in every
+    /// glyph as [`Glyph::add_master_layer`] makes
+    ///
+    /// A master whose ID is empty or already taken is given a fresh one, and
+    /// its metric, axis, number and stem values are padded with zeros to
+    /// those of the font.
+    pub fn add_master(&mut self, mut master: Master) -> &mut Master {
+        if master.id.is_empty() || self.masters.iter().any(|m| m.id == master.id) {
+            master.id = new_id();
+        }
+        let metrics = self.metrics.len().max(master.metric_values.len());
+        master.metric_values.resize(metrics, MetricValue::default());
+        let axes = self.axes.len().max(master.axes_values.len());
+        master.axes_values.resize(axes, 0.0);
+        let numbers = self.numbers.len().max(master.number_values.len());
+        master.number_values.resize(numbers, 0.0);
+        let stems = self.stems.len().max(master.stem_values.len());
+        master.stem_values.resize(stems, 0.0);
+        for glyph in self.glyphs.iter_mut() {
+            glyph.add_master_layer(&master);
+        }
+        self.masters.push(master);
+        self.masters.last_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_add_master() {
+        let id = new_id();
+        assert_eq!(id.len(), 36);
+        assert_eq!(id, id.to_uppercase());
+        assert_eq!(id.as_bytes()[14], b'4');
+        assert_ne!(id, new_id());
+
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(mut font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let taken = font.masters[0].id.clone();
+        let master = font.add_master(Master {
+            name: "Black".to_string(),
+            ..font.masters[0].clone()
+        });
+        assert_ne!(master.id, taken);
+        assert_eq!(master.metric_values.len(), font.metrics.len());
+        let master = font.masters.last().unwrap().clone();
+        for glyph in &font.glyphs {
+            assert_eq!(
+                glyph.master_layers(&font).count(),
+                font.masters.len(),
+                "{}",
+                glyph.name
+            );
+        }
+        let a = font.glyph_mut("a").unwrap();
+        let layers = a.layers.len();
+        let first = a.layers.iter().find(|l| l.associated_master_id.is_none());
+        let shapes = first.unwrap().shapes.clone();
+        assert_eq!(a.add_master_layer(&master).shapes, shapes);
+        assert_eq!(a.layers.len(), layers);
+        assert!(crate::compatibility::check_compatibility(&font).is_compatible());
+    }
+}
@@ -0,0 +1,197 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{glyphs2, glyphs3};
+
+// Indexes of the glyphs of a few recently searched fonts, by name and by code
+// point, kept beside the fonts rather than in them. An index is found by the
+// address of the glyph list, which another list may come to have, and the
+// list may have been changed since, so a glyph the index gives is checked
+// against the key, and a key it does not have is looked for in the glyphs
+// themselves. Finding it there rebuilds the index.
+const CACHED_INDEXES: usize = 4;
+
+thread_local! {
+    static INDEXES: RefCell<Vec<(usize, Maps)>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Maps {
+    len: usize,
+    by_name: HashMap<String, usize>,
+    by_unicode: HashMap<u32, usize>,
+}
+
+pub(crate) trait IndexedGlyph {
+    fn name(&self) -> &str;
+    fn unicode(&self) -> &[u32];
+}
+
+impl IndexedGlyph for glyphs2::Glyph {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn unicode(&self) -> &[u32] {
+        &self.unicode
+    }
+}
+
+impl IndexedGlyph for glyphs3::Glyph {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn unicode(&self) -> &[u32] {
+        &self.unicode
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Key<'a> {
+    Name(&'a str),
+    Unicode(u32),
+}
+
+impl Key<'_> {
+    fn matches(&self, glyph: &impl IndexedGlyph) -> bool {
+        match self {
+            Key::Name(name) => glyph.name() == *name,
+            Key::Unicode(codepoint) => glyph.unicode().contains(codepoint),
+        }
+    }
+}
+
+impl Maps {
+    fn build<G: IndexedGlyph>(glyphs: &[G]) -> Self {
+        let mut maps = Maps {
+            len: glyphs.len(),
+            by_name: HashMap::new(),
+            by_unicode: HashMap::new(),
+        };
+        for (ix, glyph) in glyphs.iter().enumerate() {
+            // The first glyph with a name or code point wins, as with a scan
+            maps.by_name.entry(glyph.name().to_string()).or_insert(ix);
+            for codepoint in glyph.unicode() {
+                maps.by_unicode.entry(*codepoint).or_insert(ix);
+            }
+        }
+        maps
+    }
+
+    fn get(&self, key: Key) -> Option<usize> {
+        match key {
+            Key::Name(name) => self.by_name.get(name).copied(),
+            Key::Unicode(codepoint) => self.by_unicode.get(&codepoint).copied(),
+        }
+    }
+}
+
+// The position of the first glyph matching the key
+fn position<G: IndexedGlyph>(glyphs: &[G], key: Key) -> Option<usize> {
+    let address = glyphs.as_ptr() as usize;
+    INDEXES.with(|indexes| {
+        let mut indexes = indexes.borrow_mut();
+        let cached = indexes
+            .iter()
+            .position(|(at, maps)| *at == address && maps.len == glyphs.len());
+        if let Some(slot) = cached {
+            // Most recently used last
+            let entry = indexes.remove(slot);
+            let found = entry.1.get(key).filter(|ix| key.matches(&glyphs[*ix]));
+            indexes.push(entry);
+            if found.is_some() {
+                return found;
+            }
+        }
+        let found = glyphs.iter().position(|glyph| key.matches(glyph));
+        if cached.is_none() || found.is_some() {
+            indexes.retain(|(at, _)| *at != address);
+            if indexes.len() == CACHED_INDEXES {
+                indexes.remove(0);
+            }
+            indexes.push((address, Maps::build(glyphs)));
+        }
+        found
+    })
+}
+
+macro_rules! impl_glyph_lookup {
+    ($font:ty, $glyph:ty) => {
+        impl $font {
+            /// The first glyph with the given name
+            pub fn glyph(&self, name: &str) -> Option<&$glyph> {
+                self.glyphs.get(position(&self.glyphs, Key::Name(name))?)
+            }
+
+            /// The first glyph with the given name, to modify
+            pub fn glyph_mut(&mut self, name: &str) -> Option<&mut $glyph> {
+                let ix = position(&self.glyphs, Key::Name(name))?;
+                self.glyphs.get_mut(ix)
+            }
+
+            /// The first glyph which encodes the given code point
+            pub fn glyph_by_unicode(&self, codepoint: u32) -> Option<&$glyph> {
+                self.glyphs
+                    .get(position(&self.glyphs, Key::Unicode(codepoint))?)
+            }
+        }
+    };
+}
+
+impl_glyph_lookup!(glyphs2::Glyphs2, glyphs2::Glyph);
+impl_glyph_lookup!(glyphs3::Glyphs3, glyphs3::Glyph);
+
+#[cfg(test)]
+mod tests {
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_glyph_lookup() {
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let Font::Glyphs3(mut font) = font else {
+            unreachable!()
+        };
+        assert_eq!(font.glyph("A").unwrap().name, "A");
+        assert_eq!(font.glyph_by_unicode(0x42).unwrap().name, "B");
+        assert!(font.glyph("no-such-glyph").is_none());
+        assert!(font.glyph_by_unicode(0x10FFFF).is_none());
+
+        // Reordering, adding and removing glyphs is picked up
+        font.glyphs.swap(0, 1);
+        let first = font.glyphs[0].name.clone();
+        assert_eq!(font.glyph(&first).unwrap().name, first);
+
+        // So are edits through glyph_mut
+        font.glyph_mut(&first).unwrap().name = "renamed".to_string();
+        assert!(font.glyph(&first).is_none());
+        assert_eq!(font.glyph("renamed").unwrap().name, "renamed");
+        font.glyph_mut("renamed").unwrap().unicode = vec![0x10FFFF];
+        assert_eq!(font.glyph_by_unicode(0x10FFFF).unwrap().name, "renamed");
+        font.glyphs.remove(0);
+        assert!(font.glyph("renamed").is_none());
+
+        // And so are edits through the field
+        let old = font.glyphs[0].name.clone();
+        font.glyphs[0].name = "direct".to_string();
+        assert_eq!(font.glyph("direct").unwrap().name, "direct");
+        assert!(font.glyph(&old).is_none());
+        font.glyphs[1].unicode.push(0x10FFFE);
+        assert_eq!(font.glyph_by_unicode(0x10FFFE), Some(&font.glyphs[1]));
+
+        // Fonts can still be built field by field
+        let built = crate::glyphs3::Glyphs3 {
+            glyphs: font.glyphs[..2].to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(built.glyph("direct").unwrap().name, "direct");
+    }
+
+    #[test]
+    fn test_glyph_lookup_glyphs2() {
+        let font = Font::load(path::Path::new("resources/RoadRage.glyphs")).unwrap();
+        let Font::Glyphs2(font) = font else {
+            unreachable!()
+        };
+        let glyph = &font.glyphs[font.glyphs.len() / 2];
+        assert_eq!(font.glyph(&glyph.name), Some(glyph));
+        assert_eq!(font.clone().glyph(&glyph.name), Some(glyph));
+    }
+}
@@ -0,0 +1,398 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use crate::{
+    compatibility::compare,
+    glyphs3::{Glyph, Glyphs3, Layer, Shape},
+};
+
+// A location normalized as in OpenType variations, -1 to 1 on each axis
+// with 0 at the default master, keeping only the axes where it is not 0
+type Normalized = BTreeMap<usize, f64>;
+
+// The region of a source: lower, peak and upper on each axis
+type Support = BTreeMap<usize, (f64, f64, f64)>;
+
+// How much of a source's delta applies at a location, as in fontTools
+fn support_scalar(location: &Normalized, support: &Support) -> f64 {
+    let mut scalar = 1.0;
+    for (axis, &(lower, peak, upper)) in support {
+        if peak == 0.0 || lower > peak || peak > upper || (lower < 0.0 && upper > 0.0) {
+            continue;
+        }
+        let value = location.get(axis).copied().unwrap_or(0.0);
+        if value == peak {
+            continue;
+        }
+        if value <= lower || upper <= value {
+            return 0.0;
+        }
+        scalar *= if value < peak {
+            (value - lower) / (peak - lower)
+        } else {
+            (value - upper) / (peak - upper)
+        };
+    }
+    scalar
+}
+
+// The order fontTools gives sources: by how many axes they are off the
+// default on, with those on an axis of their own first, and then by axis
+// and direction
+fn sort_key(location: &Normalized, on_axis: &BTreeMap<usize, Vec<f64>>) -> impl Ord {
+    let on_point = location
+        .iter()
+        .filter(|(axis, value)| on_axis.get(axis).is_some_and(|v| v.contains(value)))
+        .count();
+    let axes: Vec<usize> = location.keys().copied().collect();
+    let signs: Vec<i8> = location.values().map(|v| v.signum() as i8).collect();
+    let magnitudes: Vec<u64> = location.values().map(|v| v.abs().to_bits()).collect();
+    (
+        location.len(),
+        std::cmp::Reverse(on_point),
+        axes,
+        signs,
+        magnitudes,
+    )
+}
+
+// A variation model over sources at normalized locations, as fontTools
+// builds one, giving each source's weight at any location
+struct Model {
+    // Indices of the sources, in the model's order
+    order: Vec<usize>,
+    supports: Vec<Support>,
+    // How each delta is made of the sources, in the model's order
+    deltas: Vec<Vec<f64>>,
+}
+
+impl Model {
+    fn new(locations: &[Normalized]) -> Option<Self> {
+        if !locations.iter().any(|l| l.is_empty()) {
+            return None;
+        }
+        let mut on_axis: BTreeMap<usize, Vec<f64>> = BTreeMap::new();
+        for location in locations.iter().filter(|l| l.len() == 1) {
+            let (axis, value) = location.iter().next()?;
+            on_axis
+                .entry(*axis)
+                .or_insert_with(|| vec![0.0])
+                .push(*value);
+        }
+        let mut order: Vec<usize> = (0..locations.len()).collect();
+        order.sort_by(|a, b| {
+            sort_key(&locations[*a], &on_axis).cmp(&sort_key(&locations[*b], &on_axis))
+        });
+        let sorted: Vec<&Normalized> = order.iter().map(|ix| &locations[*ix]).collect();
+
+        let (mut min, mut max) = (BTreeMap::new(), BTreeMap::new());
+        for location in &sorted {
+            for (axis, value) in location.iter() {
+                let low = min.entry(*axis).or_insert(*value);
+                *low = f64::min(*low, *value);
+                let high = max.entry(*axis).or_insert(*value);
+                *high = f64::max(*high, *value);
+            }
+        }
+        let mut supports: Vec<Support> = vec![];
+        for location in &sorted {
+            let mut region: Support = location
+                .iter()
+                .map(|(axis, value)| {
+                    let bounds = if *value > 0.0 {
+                        (0.0, *value, max[axis])
+                    } else {
+                        (min[axis], *value, 0.0)
+                    };
+                    (*axis, bounds)
+                })
+                .collect();
+            for previous in &sorted[..supports.len()] {
+                if !previous.keys().eq(region.keys()) {
+                    continue;
+                }
+                let relevant = region.iter().all(|(axis, (lower, peak, upper))| {
+                    let value = previous[axis];
+                    value == *peak || (*lower < value && value < *upper)
+                });
+                if !relevant {
+                    continue;
+                }
+                // Split the region where the previous source is, in the
+                // direction which leaves the most of it
+                let mut best: BTreeMap<usize, (f64, f64, f64)> = BTreeMap::new();
+                let mut best_ratio = -1.0;
+                for (axis, value) in previous.iter() {
+                    let (lower, peak, upper) = region[axis];
+                    let (bounds, ratio) = match value.partial_cmp(&peak) {
+                        Some(Ordering::Less) => {
+                            ((*value, peak, upper), (value - peak) / (lower - peak))
+                        }
+                        Some(Ordering::Greater) => {
+                            ((lower, peak, *value), (value - peak) / (upper - peak))
+                        }
+                        _ => continue,
+                    };
+                    if ratio > best_ratio {
+                        best.clear();
+                        best_ratio = ratio;
+                    }
+                    if ratio == best_ratio {
+                        best.insert(*axis, bounds);
+                    }
+                }
+                region.extend(best);
+            }
+            supports.push(region);
+        }
+
+        let mut deltas: Vec<Vec<f64>> = vec![];
+        for (ix, location) in sorted.iter().enumerate() {
+            let mut delta = vec![0.0; sorted.len()];
+            delta[ix] = 1.0;
+            for (previous, support) in supports[..ix].iter().enumerate() {
+                let scalar = support_scalar(location, support);
+                if scalar != 0.0 {
+                    for (d, p) in delta.iter_mut().zip(&deltas[previous]) {
+                        *d -= scalar * p;
+                    }
+                }
+            }
+            deltas.push(delta);
+        }
+        Some(Model {
+            order,
+            supports,
+            deltas,
+        })
+    }
+
+    // The weight of each source, in the order they were given, at a location
+    fn weights(&self, location: &Normalized) -> Vec<f64> {
+        let mut sorted = vec![0.0; self.order.len()];
+        for (support, delta) in self.supports.iter().zip(&self.deltas) {
+            let scalar = support_scalar(location, support);
+            for (weight, d) in sorted.iter_mut().zip(delta) {
+                *weight += scalar * d;
+            }
+        }
+        let mut weights = vec![0.0; self.order.len()];
+        for (ix, weight) in self.order.iter().zip(sorted) {
+            weights[*ix] = weight;
+        }
+        weights
+    }
+}
+
+// Add a weighted layer into the numbers of an interpolated one
+fn accumulate(result: &mut Layer, layer: &Layer, weight: f64) {
+    result.width += weight * layer.width;
+    for (anchor, other) in result.anchors.iter_mut().zip(&layer.anchors) {
+        anchor.pos += other.pos * weight;
+    }
+    for (shape, other) in result.shapes.iter_mut().zip(&layer.shapes) {
+        match (shape, other) {
+            (Shape::Path(path), Shape::Path(other)) => {
+                for (node, other) in path.nodes.iter_mut().zip(&other.nodes) {
+                    node.x += weight * other.x;
+                    node.y += weight * other.y;
+                }
+            }
+            (Shape::Component(component), Shape::Component(other)) => {
+                component.position += other.position * weight;
+                component.scale.x += weight * other.scale.x;
+                component.scale.y += weight * other.scale.y;
+                component.angle += weight * other.angle;
+            }
+            _ => {}
+        }
+    }
+}
+
+// A layer with all its interpolated numbers zeroed
+fn zeroed(layer: &Layer) -> Layer {
+    let mut result = layer.clone();
+    accumulate(&mut result, layer, -1.0);
+    result
+}
+
+impl Glyphs3 {
+    /// The intermediate ("brace") layers of the font, with their glyphs and
+    /// design space coordinates
+    ///
+    /// Glyphs treats each of these as a master of its glyph alone.
+    pub fn intermediate_layers(&self) -> Vec<(&Glyph, &[f64], &Layer)> {
+        self.glyphs
+            .iter()
+            .flat_map(|glyph| {
+                glyph.layers.iter().filter_map(move |layer| {
+                    let coordinates = layer.brace_coordinates()?;
+                    layer.associated_master_id.as_ref()?;
+                    Some((glyph, coordinates, layer))
+                })
+            })
+            .collect()
+    }
+
+    // The coordinates of the `Virtual Master` parameters, which extend the
+    // axes beyond the masters
+    fn virtual_masters(&self) -> Vec<Vec<f64>> {
+        self.custom_parameters
+            .iter()
+            .filter(|p| p.name == "Virtual Master" && !p.disabled)
+            .filter_map(|p| p.value.as_array())
+            .map(|locations| {
+                let mut coordinates = vec![0.0; self.axes.len()];
+                for location in locations.iter().filter_map(|l| l.as_dict()) {
+                    let axis = location.get("Axis").and_then(|a| a.as_str());
+                    let value = location.get("Location").and_then(|l| l.as_f64());
+                    if let (Some(axis), Some(value)) = (axis, value) {
+                        if let Some(ix) = self.axes.iter().position(|a| a.name == axis) {
+                            coordinates[ix] = value;
+                        }
+                    }
+                }
+                coordinates
+            })
+            .collect()
+    }
+
+    // The coordinates normalized between the least and greatest masters,
+    // virtual or not
+    fn normalize(&self, coordinates: &[f64], virtual_masters: &[Vec<f64>]) -> Normalized {
+        let default = self
+            .default_master()
+            .map(|m| m.axes_values.clone())
+            .unwrap_or_default();
+        let mut normalized = Normalized::new();
+        for (axis, value) in coordinates.iter().enumerate() {
+            let values = self
+                .masters
+                .iter()
+                .map(|m| &m.axes_values)
+                .chain(virtual_masters)
+                .filter_map(|values| values.get(axis));
+            let min = values.clone().copied().fold(f64::INFINITY, f64::min);
+            let max = values.copied().fold(f64::NEG_INFINITY, f64::max);
+            let Some(&default) = default.get(axis) else {
+                continue;
+            };
+            let value = if *value < default && min < default {
+                ((value - default) / (default - min)).max(-1.0)
+            } else if *value > default && max > default {
+                ((value - default) / (max - default)).min(1.0)
+            } else {
+                0.0
+            };
+            if value != 0.0 {
+                normalized.insert(axis, value);
+            }
+        }
+        normalized
+    }
+
+    /// The layer of a glyph at a location in the design space, interpolated
+    /// from its master layers and its intermediate layers
+    ///
+    /// As in a variable font, intermediate layers are masters of the glyph
+    /// at their own coordinates. The widths, nodes, anchors and component
+    /// positions, scales and angles are interpolated; everything else is as
+    /// in the layer of the default master. Locations outside the masters,
+    /// including those of `Virtual Master` parameters, are clamped to them.
+    ///
+    /// Returns `None` if there is no such glyph, it has no layer for the
+    /// default master, or its layers are not compatible.
+    pub fn interpolate_layer(&self, glyph_name: &str, coordinates: &[f64]) -> Option<Layer> {
+        let glyph = self.glyph(glyph_name)?;
+        let default = self.default_master()?;
+        let mut sources: Vec<(&Layer, &[f64])> = vec![];
+        for master in &self.masters {
+            if let Some(layer) = glyph.layers.iter().find(|l| l.layer_id == master.id) {
+                sources.push((layer, &master.axes_values));
+            }
+        }
+        sources.extend(
+            self.intermediate_layers()
+                .into_iter()
+                .filter(|(g, _, _)| g.name == glyph.name)
+                .map(|(_, coordinates, layer)| (layer, coordinates)),
+        );
+        let reference = glyph.layers.iter().find(|l| l.layer_id == default.id)?;
+        if sources
+            .iter()
+            .any(|(layer, _)| !compare(reference, layer).is_empty())
+        {
+            return None;
+        }
+        let virtual_masters = self.virtual_masters();
+        let locations: Vec<Normalized> = sources
+            .iter()
+            .map(|(_, coordinates)| self.normalize(coordinates, &virtual_masters))
+            .collect();
+        let model = Model::new(&locations)?;
+        let mut result = zeroed(reference);
+        for ((layer, _), weight) in sources
+            .iter()
+            .zip(model.weights(&self.normalize(coordinates, &virtual_masters)))
+        {
+            if weight != 0.0 {
+                accumulate(&mut result, layer, weight);
+            }
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_model_weights() {
+        let location = |values: &[(usize, f64)]| Normalized::from_iter(values.iter().copied());
+        // A default, the ends of two axes and a corner
+        let model = Model::new(&[
+            location(&[]),
+            location(&[(0, 1.0)]),
+            location(&[(1, 1.0)]),
+            location(&[(0, 1.0), (1, 1.0)]),
+        ])
+        .unwrap();
+        assert_eq!(model.weights(&location(&[])), [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(model.weights(&location(&[(0, 0.5)])), [0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(
+            model.weights(&location(&[(0, 0.5), (1, 0.5)])),
+            [0.25, 0.25, 0.25, 0.25]
+        );
+        // An intermediate source on an axis of its own
+        let model =
+            Model::new(&[location(&[(0, 1.0)]), location(&[]), location(&[(0, 0.5)])]).unwrap();
+        assert_eq!(model.weights(&location(&[(0, 0.75)])), [0.5, 0.0, 0.5]);
+        assert_eq!(model.weights(&location(&[(0, 0.25)])), [0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_interpolate_with_intermediate_layers() {
+        let font = Font::load(path::Path::new("resources/IntermediateLayer.glyphs")).unwrap();
+        let font = font.as_glyphs3().unwrap();
+        let intermediates = font.intermediate_layers();
+        assert!(!intermediates.is_empty());
+        let (glyph, coordinates, layer) = intermediates[0];
+        // At an intermediate layer's location, the glyph is that layer
+        let at = font.interpolate_layer(&glyph.name, coordinates).unwrap();
+        assert_eq!(at.width, layer.width);
+        assert_eq!(at.shapes, layer.shapes);
+        // At a master, it is the master layer
+        let master = &font.masters[0];
+        let at = font
+            .interpolate_layer(&glyph.name, &master.axes_values)
+            .unwrap();
+        let master_layer = glyph
+            .layers
+            .iter()
+            .find(|l| l.layer_id == master.id)
+            .unwrap();
+        assert_eq!(at.shapes, master_layer.shapes);
+    }
+}
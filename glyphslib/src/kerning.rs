@@ -0,0 +1,139 @@
+use crate::glyphs3::{Glyph, Glyphs3};
+
+// The prefixes of kerning groups in kerning keys. A glyph's right group is
+// used when it is on the left of a pair, and its left group on the right.
+const LEFT_GROUP: &str = "@MMK_L_";
+const RIGHT_GROUP: &str = "@MMK_R_";
+
+// Whether a kerning key is one of the glyphs, or a group one of them is in.
+// Left groups are the kernRight groups of glyphs and right groups their
+// kernLeft; other groups, such as those of vertical kerning, may be any.
+pub(crate) fn refers_to(key: &str, glyphs: &[&Glyph]) -> bool {
+    if let Some(group) = key.strip_prefix(LEFT_GROUP) {
+        glyphs
+            .iter()
+            .any(|g| g.kern_right.as_deref() == Some(group))
+    } else if let Some(group) = key.strip_prefix(RIGHT_GROUP) {
+        glyphs.iter().any(|g| g.kern_left.as_deref() == Some(group))
+    } else if let Some(group) = key.strip_prefix("@MMK_").and_then(|k| k.get(2..)) {
+        glyphs.iter().any(|g| {
+            [&g.kern_left, &g.kern_right, &g.kern_top, &g.kern_bottom]
+                .iter()
+                .any(|side| side.as_deref() == Some(group))
+        })
+    } else {
+        glyphs.iter().any(|g| g.name == key)
+    }
+}
+
+impl Glyphs3 {
+    /// The left-to-right kerning between two glyphs in a master
+    ///
+    /// As when Glyphs exports, the first of these entries which exists
+    /// applies: the pair of glyphs, the left glyph against the right glyph's
+    /// group, the left glyph's group against the right glyph, and then the
+    /// pair of groups. So exceptions override the kerning of their groups.
+    /// A glyph's group on the left of a pair is its `kernRight` group, and
+    /// on the right its `kernLeft`.
+    ///
+    /// Returns `None` if no entry applies, which means no kerning.
+    pub fn kerning_value(&self, master_id: &str, left: &str, right: &str) -> Option<f64> {
+        let pairs = self.kerning.get(master_id)?;
+        let left_group = self
+            .glyph(left)
+            .and_then(|g| g.kern_right.as_ref())
+            .map(|group| format!("{LEFT_GROUP}{group}"));
+        let right_group = self
+            .glyph(right)
+            .and_then(|g| g.kern_left.as_ref())
+            .map(|group| format!("{RIGHT_GROUP}{group}"));
+        let lefts = [Some(left), left_group.as_deref()];
+        let rights = [Some(right), right_group.as_deref()];
+        [(0, 0), (0, 1), (1, 0), (1, 1)]
+            .into_iter()
+            .filter_map(|(l, r)| Some((lefts[l]?, rights[r]?)))
+            .find_map(|(l, r)| pairs.get(l)?.get(r).copied())
+    }
+
+    /// Set the left-to-right kerning of a pair in a master
+    ///
+    /// Each side is a glyph name, or a group key such as `@MMK_L_A` for the
+    /// glyphs whose right group is `A`. Setting a glyph pair whose groups are
+    /// kerned makes an exception.
+    pub fn set_kerning_pair(&mut self, master_id: &str, left: &str, right: &str, value: f64) {
+        self.kerning
+            .entry(master_id.to_string())
+            .or_default()
+            .entry(left.to_string())
+            .or_default()
+            .insert(right.to_string(), value);
+    }
+
+    /// Remove the left-to-right kerning of a pair in a master, returning its value
+    ///
+    /// The sides are given as for [`set_kerning_pair`](Self::set_kerning_pair).
+    pub fn remove_kerning_pair(&mut self, master_id: &str, left: &str, right: &str) -> Option<f64> {
+        let pairs = self.kerning.get_mut(master_id)?;
+        let rights = pairs.get_mut(left)?;
+        let value = rights.remove(right)?;
+        if rights.is_empty() {
+            pairs.remove(left);
+        }
+        if pairs.is_empty() {
+            self.kerning.remove(master_id);
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_kerning_value_resolves_groups_and_exceptions() {
+        let mut font = load();
+        font.kerning.clear();
+        let master = font.masters[0].id.clone();
+        let kern_right = |font: &Glyphs3, name| font.glyph(name).unwrap().kern_right.clone();
+        let kern_left = |font: &Glyphs3, name| font.glyph(name).unwrap().kern_left.clone();
+        // Aacute kerns on both sides as A does
+        assert_eq!(kern_right(&font, "Aacute"), kern_right(&font, "A"));
+        assert_eq!(kern_left(&font, "Aacute"), kern_left(&font, "A"));
+        let left_group = format!("@MMK_L_{}", kern_right(&font, "A").unwrap());
+        let right_group = format!("@MMK_R_{}", kern_left(&font, "A").unwrap());
+
+        assert_eq!(font.kerning_value(&master, "A", "A"), None);
+        font.set_kerning_pair(&master, &left_group, &right_group, -10.0);
+        assert_eq!(font.kerning_value(&master, "Aacute", "A"), Some(-10.0));
+        font.set_kerning_pair(&master, "Aacute", &right_group, -20.0);
+        assert_eq!(font.kerning_value(&master, "Aacute", "Aacute"), Some(-20.0));
+        font.set_kerning_pair(&master, &left_group, "A", -30.0);
+        assert_eq!(font.kerning_value(&master, "Aacute", "A"), Some(-20.0));
+        assert_eq!(font.kerning_value(&master, "A", "A"), Some(-30.0));
+        font.set_kerning_pair(&master, "A", "A", -40.0);
+        assert_eq!(font.kerning_value(&master, "A", "A"), Some(-40.0));
+        assert_eq!(font.kerning_value(&font.masters[1].id, "A", "A"), None);
+
+        assert_eq!(font.remove_kerning_pair(&master, "A", "A"), Some(-40.0));
+        assert_eq!(font.kerning_value(&master, "A", "A"), Some(-30.0));
+        for (left, right) in [
+            ("Aacute", right_group.as_str()),
+            (&left_group, "A"),
+            (&left_group, &right_group),
+        ] {
+            assert!(font.remove_kerning_pair(&master, left, right).is_some());
+        }
+        assert!(font.kerning.is_empty());
+    }
+}
@@ -72,14 +72,14 @@
 //!     Font::Glyphs2(g2) => {
 //!         g2.designer = Some("Jane Doe".to_string());
 //!         // Modify a glyph
-//!         if let Some(glyph) = g2.glyphs.iter_mut().find(|g| g.name == "A") {
+//!         if let Some(glyph) = g2.glyph_mut("A") {
 //!             glyph.export = false;
 //!         }
 //!     }
 //!     Font::Glyphs3(g3) => {
 //!         g3.properties.push(Property::singular(SingularPropertyKey::Designer, "Jane Doe".to_string()));
 //!         // Modify a glyph
-//!         if let Some(glyph) = g3.glyphs.iter_mut().find(|g| g.name == "A") {
+//!         if let Some(glyph) = g3.glyph_mut("A") {
 //!             glyph.export = false;
 //!         }
 //!     }
@@ -118,26 +118,149 @@
 //! ```
 
 #![deny(missing_docs)]
+mod aglfn;
+mod alignment;
+/// Mappings between the user and design coordinates of axes
+pub mod axes;
+mod background;
+#[cfg(feature = "kurbo")]
+mod bezpath;
+/// Building fonts from scratch in code
+pub mod builder;
+/// Undoable changes to fonts
+pub mod changes;
+/// Color fonts: palettes, COLR layers, and SVG and sbix images
+pub mod colr;
 /// Common types and structures shared between Glyphs 2 and Glyphs 3 formats
 pub mod common;
+/// Checks that the layers of each glyph can interpolate
+pub mod compatibility;
+mod components;
+/// Corner and cap components, and drawing them into paths
+pub mod corners;
+mod decompose;
+/// Semantic comparison of fonts
+pub mod diff;
+/// Parsing the strings of Edit View tabs into glyphs
+pub mod display_string;
+mod downgrade;
+/// Errors returned when loading and saving fonts
+pub mod error;
+#[cfg(feature = "fea")]
+mod feature_syntax;
+/// OpenType feature code generated from fonts
+pub mod features;
+/// Design space queries for font compilers, such as fontc
+#[cfg(feature = "fontc")]
+pub mod fontc;
+/// Outline geometry, such as the bounding boxes of paths and layers
+pub mod geometry;
+/// Glyph names, code points, categories and scripts, from the Adobe Glyph
+/// List For New Fonts and Glyphs' GlyphData.xml
+pub mod glyphdata;
 /// Glyphs 2 file format structures
 pub mod glyphs2;
 /// Glyphs 3 file format structures
 pub mod glyphs3;
+/// Fresh IDs for masters and layers, and adding masters to fonts
+pub mod ids;
+mod index;
+/// Interpolating glyph layers at locations in the design space
+pub mod interpolate;
+mod kerning;
+mod load;
+mod merge;
+/// Font-wide metrics derived from masters, such as vertical metrics
+pub mod metrics;
+mod naming;
+/// OS/2 table bit fields computed from fonts
+pub mod os2;
+mod other_keys;
+/// Reading glyphspackages one glyph at a time
+#[cfg(not(target_arch = "wasm32"))]
+pub mod package;
+mod profiling;
+mod properties;
+#[cfg(not(target_arch = "wasm32"))]
+mod save;
+/// Scaling fonts to a new units per em, and rounding coordinates
+pub mod scale;
 mod serde;
+/// Sharing a font between threads, with copy-on-write editing
+pub mod shared;
+mod source;
+mod spacing;
+/// `STAT` and `fvar` data for variable fonts
+pub mod stat;
+/// Summary statistics about fonts
+pub mod stats;
+mod subset;
+/// Random, structurally valid fonts for property-based tests
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
+mod timestamps;
 mod traits;
+mod unicode_blocks;
 mod upgrade;
 mod utils;
-use std::{collections::HashMap, ffi::OsStr, fs, path};
+/// Consistency checks for fonts
+pub mod validate;
+/// Settings for exporting variable fonts
+pub mod variable;
+/// Watching font files for changes and reloading them
+#[cfg(all(feature = "watch", not(target_arch = "wasm32")))]
+pub mod watch;
+/// Alignment zones, and PostScript blue values from them
+pub mod zones;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+// There is no filesystem to load from or save to in the browser.
+#[cfg(not(target_arch = "wasm32"))]
+use std::{ffi::OsStr, fs, path};
 
+pub use error::{Error, Result};
+pub use load::{Encoding, LoadOptions, LoadWarning};
+pub use merge::{MasterMatching, MergeOptions};
+#[cfg(not(target_arch = "wasm32"))]
+pub use save::{Backup, SaveOptions};
+#[cfg(not(target_arch = "wasm32"))]
+pub use source::Directory;
+pub use source::PackageSource;
 pub use traits::GlyphsFile;
 
+use profiling::{record, record_package_file, Operation, OperationGuard, Stage};
+#[cfg(feature = "profiling")]
+pub use profiling::{CountingAllocator, LoadMetrics, SaveMetrics, StageMetrics};
+
 use glyphs2::Glyphs2;
 use glyphs3::Glyphs3;
-pub use openstep_plist::Plist;
-use openstep_plist::{de::Deserializer, Dictionary};
+use openstep_plist::{events::offset_of, Dictionary};
+pub use openstep_plist::{ser::SerializerOptions, Plist};
+
+use utils::glyph_file_names;
+pub use utils::{file_name_to_user_name, user_name_to_file_name};
 
-use utils::user_name_to_file_name;
+// The top-level dictionary of a package, without its glyphs
+fn package_toplevel(font_info: &str, ui_state: Option<&str>) -> Result<Dictionary> {
+    let mut toplevel = record(Stage::Parse, || Plist::parse(font_info))?.expect_dict()?;
+    if let Some(ui_state) = ui_state {
+        let ui_state_plist = record(Stage::Parse, || Plist::parse(ui_state))?;
+        // UIState.plist contains a dictionary with a key "displayStrings".
+        // However. the Glyphs3 non-package format has this key as "DisplayStrings" (with a capital 'D').
+        // So we can't just merge dictionaries, we have to rewrite the key.
+        toplevel.insert(
+            "DisplayStrings".into(),
+            ui_state_plist
+                .expect_dict()?
+                .get("displayStrings")
+                .cloned()
+                .unwrap_or(Plist::Array(vec![])),
+        );
+    }
+    Ok(toplevel)
+}
 
 fn is_glyphs3(plist: &Plist) -> bool {
     plist
@@ -151,6 +274,7 @@ fn is_glyphs3(plist: &Plist) -> bool {
 /// This enum allows working with both Glyphs file format versions through a unified interface.
 /// Use the [`GlyphsFile`] trait to access common properties regardless of version.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Font {
     /// A Glyphs 2 format font
     Glyphs2(Glyphs2),
@@ -161,7 +285,8 @@ impl Font {
     /// Load a Glyphs file from disk
     ///
     /// Supports both `.glyphs` files and `.glyphspackage` directories.
-    /// The file format version is automatically detected.
+    /// The file format version is automatically detected, as is the text
+    /// encoding of a `.glyphs` file; see [`LoadOptions::encoding`].
     ///
     /// # Examples
     ///
@@ -171,11 +296,13 @@ impl Font {
     ///
     /// let font = Font::load(Path::new("MyFont.glyphs")).unwrap();
     /// ```
-    pub fn load(glyphs_file: &path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(glyphs_file: &path::Path) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
         if glyphs_file.extension() == Some(OsStr::new("glyphspackage")) {
             return Font::load_package(glyphs_file);
         }
-        let raw_content = fs::read_to_string(glyphs_file)?;
+        let raw_content = load::decode(record(Stage::Read, || fs::read(glyphs_file))?, None)?;
         Self::load_str(&raw_content)
     }
 
@@ -186,9 +313,13 @@ impl Font {
     /// - `order.plist`
     /// - `UIState.plist` (optional)
     /// - `glyphs/<glyph-file-name>.glyph`
-    pub fn load_package_entries(
-        entries: &HashMap<String, String>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_package_entries(entries: &HashMap<String, String>) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
+        Self::from_plist(Self::package_plist(entries, false)?)
+    }
+
+    // Assemble the entries of a package into the plist of a single file
+    fn package_plist(entries: &HashMap<String, String>, parallel: bool) -> Result<Plist> {
         let normalized_entries: HashMap<String, String> = entries
             .iter()
             .map(|(path, contents)| {
@@ -204,43 +335,63 @@ impl Font {
 
         let raw_content = normalized_entries
             .get("fontinfo.plist")
-            .ok_or("Missing fontinfo.plist in glyphspackage entries")?;
-
-        let mut toplevel = Plist::parse(raw_content)?.expect_dict()?;
-
-        if let Some(ui_state) = normalized_entries.get("UIState.plist") {
-            let ui_state_plist = Plist::parse(ui_state)?;
-            // UIState.plist contains a dictionary with a key "displayStrings".
-            // However. the Glyphs3 non-package format has this key as "DisplayStrings" (with a capital 'D').
-            // So we can't just merge dictionaries, we have to rewrite the key.
-            toplevel.insert(
-                "DisplayStrings".into(),
-                ui_state_plist
-                    .expect_dict()?
-                    .get("displayStrings")
-                    .cloned()
-                    .unwrap_or(Plist::Array(vec![])),
-            );
-        }
+            .ok_or_else(|| Error::MissingPackageFile("fontinfo.plist".to_string()))?;
+
+        let mut toplevel = package_toplevel(
+            raw_content,
+            normalized_entries.get("UIState.plist").map(String::as_str),
+        )?;
 
         let glyph_order_plist = normalized_entries
             .get("order.plist")
-            .ok_or("Missing order.plist in glyphspackage entries")?;
-        let glyph_order = Plist::parse(glyph_order_plist).and_then(|p| p.expect_array())?;
-
-        let mut glyphs = vec![];
-        for glyph in glyph_order.iter() {
-            if let Some(name) = glyph.as_str() {
-                let glyph_path = format!("glyphs/{}.glyph", user_name_to_file_name(name));
-                if let Some(glyph_content) = normalized_entries.get(&glyph_path) {
-                    let glyph_plist = Plist::parse(glyph_content)?;
-                    glyphs.push(glyph_plist);
+            .ok_or_else(|| Error::MissingPackageFile("order.plist".to_string()))?;
+        let glyph_order = record(Stage::Parse, || Plist::parse(glyph_order_plist))
+            .and_then(|p| p.expect_array())?;
+
+        let names: Vec<&str> = glyph_order.iter().filter_map(Plist::as_str).collect();
+        let file_names = glyph_file_names(names.iter().copied());
+        let mut glyphs: Vec<Option<Plist>> = load::map(&file_names, parallel, |file_name| {
+            let glyph_path = format!("glyphs/{file_name}.glyph");
+            normalized_entries
+                .get(&glyph_path)
+                .map(|glyph_content| record(Stage::Parse, || Plist::parse(glyph_content)))
+                .transpose()
+        })
+        .into_iter()
+        .collect::<std::result::Result<_, _>>()?;
+        // Glyph files which another tool named differently are matched to the
+        // order by the name inside them. Files of glyphs not in the order,
+        // such as ones left behind after deleting a glyph, are ignored.
+        if glyphs.iter().any(Option::is_none) {
+            let expected: HashSet<String> = file_names
+                .iter()
+                .map(|file_name| format!("glyphs/{file_name}.glyph"))
+                .collect();
+            let mut unread: Vec<&String> = normalized_entries
+                .keys()
+                .filter(|path| path.starts_with("glyphs/") && path.ends_with(".glyph"))
+                .filter(|path| !expected.contains(*path))
+                .collect();
+            unread.sort();
+            for glyph_path in unread {
+                let glyph_plist = record(Stage::Parse, || {
+                    Plist::parse(&normalized_entries[glyph_path])
+                })?;
+                let name = glyph_plist
+                    .as_dict()
+                    .and_then(|glyph| glyph.get("glyphname"))
+                    .and_then(Plist::as_str);
+                if let Some(ix) = name.and_then(|name| names.iter().position(|n| *n == name)) {
+                    glyphs[ix].get_or_insert(glyph_plist);
                 }
             }
         }
 
-        toplevel.insert("glyphs".into(), Plist::Array(glyphs));
-        Self::from_plist(Plist::Dictionary(toplevel))
+        toplevel.insert(
+            "glyphs".into(),
+            Plist::Array(glyphs.into_iter().flatten().collect()),
+        );
+        Ok(Plist::Dictionary(toplevel))
     }
 
     /// Load a Glyphs file from a string
@@ -256,20 +407,161 @@ impl Font {
     /// let glyphs_data = std::fs::read_to_string("MyFont.glyphs").unwrap();
     /// let font = Font::load_str(&glyphs_data).unwrap();
     /// ```
-    pub fn load_str(raw_content: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let plist = Plist::parse(raw_content)?;
-        Font::from_plist(plist)
+    pub fn load_str(raw_content: &str) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
+        if let Some(font) = Font::from_text(raw_content) {
+            return Ok(font);
+        }
+        let plist = record(Stage::Parse, || Plist::parse(raw_content))?;
+        Font::from_plist(plist).map_err(|error| error.located(raw_content))
     }
 
-    fn from_plist(plist: Plist) -> Result<Self, Box<dyn std::error::Error>> {
-        let deserializer = &mut Deserializer::from_plist(&plist);
-        if is_glyphs3(&plist) {
-            let glyphs3: Glyphs3 = serde_path_to_error::deserialize(deserializer)?;
-            Ok(Font::Glyphs3(glyphs3))
-        } else {
-            let glyphs2: Glyphs2 = serde_path_to_error::deserialize(deserializer)?;
-            Ok(Font::Glyphs2(glyphs2))
+    /// Load a Glyphs file from a reader, such as an in-memory buffer or a
+    /// file inside an archive
+    ///
+    /// The whole of the reader is read, and its text encoding detected as by
+    /// [`load`](Self::load). Packages are made of many files, so
+    /// cannot be read this way; see
+    /// [`load_package_entries`](Self::load_package_entries).
+    pub fn from_reader(mut reader: impl io::Read) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
+        let mut bytes = vec![];
+        record(Stage::Read, || reader.read_to_end(&mut bytes))?;
+        Self::load_str(&load::decode(bytes, None)?)
+    }
+
+    /// Load a Glyphs file from disk with the given options
+    ///
+    /// Returns the font with any warnings about parts of the file which were
+    /// dropped; see [`LoadOptions::lenient`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use glyphslib::{Font, LoadOptions};
+    /// use std::path::Path;
+    ///
+    /// let options = LoadOptions {
+    ///     lenient: true,
+    ///     ..Default::default()
+    /// };
+    /// let (font, warnings) = Font::load_with_options(Path::new("MyFont.glyphs"), &options).unwrap();
+    /// for warning in warnings {
+    ///     eprintln!("{warning}");
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_with_options(
+        glyphs_file: &path::Path,
+        options: &LoadOptions,
+    ) -> Result<(Self, Vec<LoadWarning>)> {
+        let _profile = OperationGuard::start(Operation::Load);
+        if glyphs_file.extension() == Some(OsStr::new("glyphspackage")) {
+            let entries = Self::read_package(&Directory::new(glyphs_file))?;
+            let plist = Self::package_plist(&entries, options.parallel)?;
+            return Font::from_plist_with_options(plist, options);
+        }
+        let bytes = record(Stage::Read, || fs::read(glyphs_file))?;
+        let raw_content = load::decode(bytes, options.encoding)?;
+        Self::load_str_with_options(&raw_content, options)
+    }
+
+    /// Load a Glyphs file from a string with the given options
+    ///
+    /// See [`load_with_options`](Self::load_with_options).
+    pub fn load_str_with_options(
+        raw_content: &str,
+        options: &LoadOptions,
+    ) -> Result<(Self, Vec<LoadWarning>)> {
+        let _profile = OperationGuard::start(Operation::Load);
+        // Dropping values leniently and splitting off the glyphs both need the tree
+        if !options.lenient && !options.parallel {
+            if let Some(font) = Font::from_text(raw_content) {
+                return Ok((font, vec![]));
+            }
+        }
+        let plist = record(Stage::Parse, || Plist::parse(raw_content))?;
+        Font::from_plist_with_options(plist, options).map_err(|error| error.located(raw_content))
+    }
+
+    /// Load everything in a Glyphs file or package except its glyphs
+    ///
+    /// The font has its family name, axes, masters, instances, custom
+    /// parameters and so on, but no glyphs. Only the top level of a file is
+    /// deserialized, and a package's glyph files are not read at all, so this
+    /// is much faster than [`load`](Self::load) for large fonts.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use glyphslib::Font;
+    /// use std::path::Path;
+    ///
+    /// let font = Font::load_metadata(Path::new("MyFont.glyphs")).unwrap();
+    /// println!("{}", font.as_glyphs3().unwrap().family_name);
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_metadata(glyphs_file: &path::Path) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
+        if glyphs_file.extension() == Some(OsStr::new("glyphspackage")) {
+            return Ok(Font::Glyphs3(
+                package::PackageFont::open(glyphs_file)?.metadata()?,
+            ));
+        }
+        let raw_content = load::decode(record(Stage::Read, || fs::read(glyphs_file))?, None)?;
+        let mut plist = record(Stage::Parse, || Plist::parse(&raw_content))?;
+        if let Plist::Dictionary(toplevel) = &mut plist {
+            toplevel.remove("glyphs");
         }
+        Font::from_plist(plist)
+    }
+
+    // Deserialize straight from the text, without building the plist tree.
+    // Returns `None` if that fails, so the caller can parse the tree and
+    // report the error with its path.
+    fn from_text(raw_content: &str) -> Option<Self> {
+        record(Stage::Deserialize, || {
+            if offset_of(raw_content, &[".formatVersion".into()]).is_some() {
+                openstep_plist::from_str(raw_content)
+                    .ok()
+                    .map(Font::Glyphs3)
+            } else {
+                openstep_plist::from_str(raw_content)
+                    .ok()
+                    .map(Font::Glyphs2)
+            }
+        })
+    }
+
+    fn from_plist(plist: Plist) -> Result<Self> {
+        Ok(Font::from_plist_with_options(plist, &LoadOptions::default())?.0)
+    }
+
+    fn from_plist_with_options(
+        plist: Plist,
+        options: &LoadOptions,
+    ) -> Result<(Self, Vec<LoadWarning>)> {
+        record(Stage::Deserialize, || {
+            if options.parallel {
+                let font = if is_glyphs3(&plist) {
+                    load::deserialize_split(&plist, |font: &mut Glyphs3| &mut font.glyphs)
+                        .map(Font::Glyphs3)
+                } else {
+                    load::deserialize_split(&plist, |font: &mut Glyphs2| &mut font.glyphs)
+                        .map(Font::Glyphs2)
+                };
+                if let Some(font) = font {
+                    return Ok((font, vec![]));
+                }
+            }
+            if is_glyphs3(&plist) {
+                let (glyphs3, warnings) = load::deserialize(plist, options)?;
+                Ok((Font::Glyphs3(glyphs3), warnings))
+            } else {
+                let (glyphs2, warnings) = load::deserialize(plist, options)?;
+                Ok((Font::Glyphs2(glyphs2), warnings))
+            }
+        })
     }
 
     /// Get a reference to the font as a Glyphs 3 structure, if it is one
@@ -325,73 +617,212 @@ impl Font {
         *self = self.upgrade();
     }
 
-    /// Serializes the font to a a Plist in string format.
-    pub fn to_string(&self) -> Result<String, openstep_plist::error::Error> {
+    /// Returns a Glyphs2 version of the font.
+    ///
+    /// Glyphs 2 has no place for some Glyphs 3 data, which is dropped: hints,
+    /// variable and icon instances, the font note and number values, and
+    /// filtered metrics. Right-to-left kerning is merged into the kerning.
+    pub fn downgrade(&self) -> Self {
+        match self {
+            Font::Glyphs3(glyphs3) => Font::Glyphs2(Into::into(glyphs3.clone())),
+            Font::Glyphs2(_) => self.clone(),
+        }
+    }
+
+    /// Turns a Glyphs3 font into a Glyphs2 font in place.
+    pub fn downgrade_in_place(&mut self) {
+        *self = self.downgrade();
+    }
+
+    /// Lists the differences between this font and another, as
+    /// [`Glyphs3::diff`] does
+    ///
+    /// A Glyphs 2 font is compared as it would be upgraded to Glyphs 3, so
+    /// fonts of different versions can be compared.
+    pub fn diff(&self, other: &Font) -> diff::FontDiff {
+        match (self, other) {
+            (Font::Glyphs3(left), Font::Glyphs3(right)) => left.diff(right),
+            _ => self
+                .upgrade()
+                .as_glyphs3()
+                .unwrap()
+                .diff(other.upgrade().as_glyphs3().unwrap()),
+        }
+    }
+
+    /// Checks the font for problems, as [`validate::validate`] does
+    ///
+    /// A Glyphs 2 font is checked as it would be upgraded to Glyphs 3.
+    pub fn validate(&self) -> Vec<validate::Diagnostic> {
         match self {
+            Font::Glyphs3(glyphs3) => validate::validate(glyphs3),
+            Font::Glyphs2(glyphs2) => validate::validate(&glyphs2.clone().into()),
+        }
+    }
+
+    /// Timings and allocation counts for the most recent load on this thread
+    ///
+    /// Returns `None` if no font has been loaded on this thread yet. Allocation
+    /// counts are zero unless [`CountingAllocator`] is the global allocator.
+    #[cfg(feature = "profiling")]
+    pub fn last_load_metrics() -> Option<LoadMetrics> {
+        profiling::last_load_metrics()
+    }
+
+    /// Timings and allocation counts for the most recent save on this thread
+    ///
+    /// Returns `None` if no font has been saved or serialized on this thread yet.
+    #[cfg(feature = "profiling")]
+    pub fn last_save_metrics() -> Option<SaveMetrics> {
+        profiling::last_save_metrics()
+    }
+
+    /// Serializes the font to a a Plist in string format.
+    pub fn to_string(&self) -> std::result::Result<String, openstep_plist::error::Error> {
+        let _profile = OperationGuard::start(Operation::Save);
+        record(Stage::Serialize, || match self {
             Font::Glyphs2(glyphs2) => openstep_plist::ser::to_string(glyphs2),
             Font::Glyphs3(glyphs3) => openstep_plist::ser::to_string(glyphs3),
-        }
+        })
+    }
+
+    /// Serializes the font formatted exactly as Glyphs writes it
+    ///
+    /// Re-saving a file Glyphs wrote this way leaves it byte-for-byte the
+    /// same, so only real changes show up in version control; see
+    /// [`openstep_plist::ser::to_string_canonical`]. The one exception is
+    /// kerning, which is written in the order of the master IDs rather than
+    /// of the masters.
+    pub fn to_string_canonical(&self) -> std::result::Result<String, openstep_plist::error::Error> {
+        self.to_string_with_options(&SerializerOptions::canonical())
+    }
+
+    /// Serializes the font with control over how numbers are written
+    ///
+    /// Use this to keep more decimal places than [`to_string`](Self::to_string)
+    /// does, or to keep floats with no fractional part in user data and
+    /// custom parameters from being read back as integers.
+    pub fn to_string_with_options(
+        &self,
+        options: &SerializerOptions,
+    ) -> std::result::Result<String, openstep_plist::error::Error> {
+        let _profile = OperationGuard::start(Operation::Save);
+        record(Stage::Serialize, || match self {
+            Font::Glyphs2(glyphs2) => openstep_plist::ser::to_string_with_options(glyphs2, options),
+            Font::Glyphs3(glyphs3) => openstep_plist::ser::to_string_with_options(glyphs3, options),
+        })
     }
 
     /// Saves the font to a file.
-    pub fn save(&self, path: &path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// Saving over an existing `.glyphspackage` only rewrites the files
+    /// within it whose contents have changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &path::Path) -> Result<()> {
+        let _profile = OperationGuard::start(Operation::Save);
         if path.extension() == Some(OsStr::new("glyphspackage")) {
             return self.save_package(path);
         }
 
-        fs::write(path, self.to_string()?)?;
+        let contents = self.to_string().map_err(Error::Serialize)?;
+        record(Stage::Write, || fs::write(path, contents))?;
         Ok(())
     }
 
-    fn load_package(glyphs_file: &path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Write the font as a `.glyphs` file to a writer
+    ///
+    /// The file is written as it is serialized, without first being built
+    /// up in memory.
+    pub fn write_to(&self, writer: impl io::Write) -> Result<()> {
+        let _profile = OperationGuard::start(Operation::Save);
+        record(Stage::Serialize, || match self {
+            Font::Glyphs2(glyphs2) => openstep_plist::ser::to_writer(writer, glyphs2),
+            Font::Glyphs3(glyphs3) => openstep_plist::ser::to_writer(writer, glyphs3),
+        })
+        .map_err(Error::Serialize)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_package(glyphs_file: &path::Path) -> Result<Self> {
+        Self::load_package_from(&Directory::new(glyphs_file))
+    }
+
+    /// Load a Glyphs package from a [`PackageSource`], such as an archive
+    pub fn load_package_from(source: &impl PackageSource) -> Result<Self> {
+        let _profile = OperationGuard::start(Operation::Load);
+        Self::from_plist(Self::package_plist(&Self::read_package(source)?, false)?)
+    }
+
+    fn read_package(source: &impl PackageSource) -> Result<HashMap<String, String>> {
         let mut entries = HashMap::new();
 
-        entries.insert(
-            "fontinfo.plist".to_string(),
-            fs::read_to_string(glyphs_file.join("fontinfo.plist"))?,
-        );
+        let read_entry = |name: &str| record_package_file(name, || source.read_file(name));
 
-        if let Ok(ui_state) = fs::read_to_string(glyphs_file.join("UIState.plist")) {
+        entries.insert("fontinfo.plist".to_string(), read_entry("fontinfo.plist")?);
+
+        if let Ok(ui_state) = read_entry("UIState.plist") {
             entries.insert("UIState.plist".to_string(), ui_state);
         }
 
-        let glyph_order_plist = fs::read_to_string(glyphs_file.join("order.plist"))?;
+        let glyph_order_plist = read_entry("order.plist")?;
         entries.insert("order.plist".to_string(), glyph_order_plist.clone());
 
         let glyph_order = Plist::parse(&glyph_order_plist).and_then(|p| p.expect_array())?;
-        for glyph in glyph_order.iter() {
-            if let Some(name) = glyph.as_str() {
-                let glyph_file = glyphs_file
-                    .join("glyphs")
-                    .join(format!("{}.glyph", user_name_to_file_name(name)));
-                if let Ok(glyph_content) = fs::read_to_string(glyph_file) {
-                    entries.insert(
-                        format!("glyphs/{}.glyph", user_name_to_file_name(name)),
-                        glyph_content,
-                    );
+        let mut missing = false;
+        for file_name in glyph_file_names(glyph_order.iter().filter_map(Plist::as_str)) {
+            let glyph_file = format!("glyphs/{file_name}.glyph");
+            match read_entry(&glyph_file) {
+                Ok(glyph_content) => {
+                    entries.insert(glyph_file, glyph_content);
+                }
+                Err(_) => missing = true,
+            }
+        }
+        // Another tool may have named some glyph files differently
+        if missing {
+            for glyph_file in source.glyph_files()? {
+                if glyph_file.ends_with(".glyph") && !entries.contains_key(&glyph_file) {
+                    let glyph_content = read_entry(&glyph_file)?;
+                    entries.insert(glyph_file, glyph_content);
                 }
             }
         }
 
-        Self::load_package_entries(&entries)
+        Ok(entries)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_package(&self, glyphs_file: &path::Path) -> Result<()> {
+        if self.as_glyphs3().is_some() {
+            fs::create_dir_all(glyphs_file.join("glyphs"))?;
+        }
+        self.save_package_to(&mut Directory::new(glyphs_file))
     }
 
-    fn save_package(&self, glyphs_file: &path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    /// Save the font as a Glyphs package to a [`PackageSource`]
+    ///
+    /// Glyphs 2 fonts cannot be saved as packages; they must be
+    /// [upgraded](Self::upgrade) first.
+    pub fn save_package_to(&self, source: &mut impl PackageSource) -> Result<()> {
+        fn serialize<T: ::serde::Serialize>(value: &T) -> Result<String> {
+            record(Stage::Serialize, || openstep_plist::ser::to_string(value))
+                .map_err(Error::Serialize)
+        }
+        let mut write =
+            |file: &str, contents: &str| record(Stage::Write, || source.write_file(file, contents));
+
+        let _profile = OperationGuard::start(Operation::Save);
         if let Font::Glyphs3(glyphs3) = self {
-            let glyphs_dir = glyphs_file.join("glyphs");
-            fs::create_dir_all(&glyphs_dir)?;
             let mut glyph_order: Vec<Plist> = vec![];
-            for glyph in &glyphs3.glyphs {
+            let file_names = glyph_file_names(glyphs3.glyphs.iter().map(|g| g.name.as_str()));
+            let mut glyph_files = HashSet::new();
+            for (glyph, file_name) in glyphs3.glyphs.iter().zip(file_names) {
                 glyph_order.push(Plist::String(glyph.name.clone()));
-                let name = user_name_to_file_name(&glyph.name);
-                let glyph_file = glyphs_dir.join(format!("{name}.glyph"));
-                fs::write(glyph_file, openstep_plist::ser::to_string(glyph)?)?;
+                let glyph_file = format!("glyphs/{file_name}.glyph");
+                write(&glyph_file, &serialize(glyph)?)?;
+                glyph_files.insert(glyph_file);
             }
-            let glyphorder_file = glyphs_file.join("order.plist");
-            fs::write(
-                glyphorder_file,
-                openstep_plist::ser::to_string(&glyph_order)?.trim(),
-            )?;
+            write("order.plist", serialize(&glyph_order)?.trim())?;
             if !glyphs3.display_strings.is_empty() {
                 let mut dict = Dictionary::new();
                 dict.insert(
@@ -405,22 +836,22 @@ impl Font {
                     ),
                 );
                 let ui_state = Plist::Dictionary(dict);
-                fs::write(
-                    glyphs_file.join("UIState.plist"),
-                    openstep_plist::ser::to_string(&ui_state)?,
-                )?;
+                write("UIState.plist", &serialize(&ui_state)?)?;
             }
             // Drop the glyphs and UI state now we have saved them.
             let mut toplevel = glyphs3.clone();
             toplevel.glyphs.clear();
             toplevel.display_strings.clear();
-            fs::write(
-                glyphs_file.join("fontinfo.plist"),
-                openstep_plist::ser::to_string(&toplevel)?,
-            )?;
+            write("fontinfo.plist", &serialize(&toplevel)?)?;
+            // Files of renamed or deleted glyphs would be read back in
+            for glyph_file in source.glyph_files()? {
+                if glyph_file.ends_with(".glyph") && !glyph_files.contains(&glyph_file) {
+                    record(Stage::Write, || source.remove_file(&glyph_file))?;
+                }
+            }
             Ok(())
         } else {
-            Err("Saving Glyphs2 as package is not supported".into())
+            Err(Error::Glyphs2NoPackage)
         }
     }
 }
@@ -428,6 +859,7 @@ impl Font {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::{NodeType, Point};
     use glyphs3::Shape;
     use openstep_plist::de::Deserializer;
     // use pretty_assertions::assert_eq;
@@ -438,12 +870,20 @@ mod tests {
         if font.as_glyphs2().is_some() {
             let newfont = font.upgrade();
             let outdir = path::Path::new("resources/upgraded/");
+            fs::create_dir_all(outdir).unwrap();
             newfont
                 .save(&outdir.join(path.file_name().unwrap()))
                 .unwrap();
         }
     }
 
+    #[rstest]
+    fn test_load_from_text_matches_tree(#[files("resources/*glyphs")] path: PathBuf) {
+        let raw_content = load::decode(fs::read(&path).unwrap(), None).unwrap();
+        let tree = Font::from_plist(Plist::parse(&raw_content).unwrap()).unwrap();
+        assert_eq!(Font::from_text(&raw_content), Some(tree));
+    }
+
     #[test]
     fn test_component() {
         let file = "resources/RadioCanadaDisplay.glyphs";
@@ -462,7 +902,7 @@ mod tests {
             .unwrap()
         {
             assert_eq!(component.component_glyph, "acutecomb");
-            assert_eq!(component.position, (152.0, 0.0));
+            assert_eq!(component.position, Point::new(152.0, 0.0));
         }
     }
 
@@ -485,7 +925,7 @@ mod tests {
     fn test_component_manual_alignment_is_omitted_on_serialize() {
         let component = glyphs3::Component {
             component_glyph: "dotaccentcomb".to_string(),
-            position: (172.0, 0.0),
+            position: Point::new(172.0, 0.0),
             alignment: -1,
             ..Default::default()
         };
@@ -582,4 +1022,283 @@ mod tests {
         let new_plist = Plist::parse(&serialised).unwrap();
         pretty_assertions::assert_eq!(plist, new_plist);
     }
+
+    #[test]
+    fn test_error_kinds() {
+        let missing = Font::load(path::Path::new("resources/NoSuchFont.glyphs"));
+        assert!(matches!(missing, Err(Error::Io(_))));
+        assert!(matches!(Font::load_str("{ a = "), Err(Error::Parse(_))));
+        assert!(matches!(
+            Font::load_str("{ .formatVersion = 3; glyphs = 12; }"),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(
+            Font::load_package_entries(&HashMap::new()),
+            Err(Error::MissingPackageFile(file)) if file == "fontinfo.plist"
+        ));
+        let glyphs2 = Font::load(path::Path::new("resources/An-Italic.glyphs")).unwrap();
+        assert!(glyphs2.as_glyphs2().is_some());
+        let package = std::env::temp_dir().join("glyphslib-glyphs2.glyphspackage");
+        assert!(matches!(
+            glyphs2.save(&package),
+            Err(Error::Glyphs2NoPackage)
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_error_snippet() {
+        let contents = "{\n.formatVersion = 3;\nglyphs = (\n{\nglyphname = A;\nlayers = (\n{\nlayerId = m01;\nwidth = wide;\n}\n);\n}\n);\n}";
+        let Err(Error::Serde(error)) = Font::load_str(contents) else {
+            panic!("expected a deserialization error");
+        };
+        assert_eq!(error.path().to_string(), "glyphs[0].layers[0].width");
+        assert_eq!(error.inner().offset(), contents.find("wide"));
+        assert!(error
+            .to_string()
+            .ends_with("at line 9, column 9\n  9 | width = wide;\n    |         ^"));
+    }
+
+    #[test]
+    fn test_package_exotic_glyph_names() {
+        let mut font = Font::load(path::Path::new("resources/WghtVar.glyphs")).unwrap();
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        let template = glyphs3.glyphs[0].clone();
+        let names = [
+            "a/b".to_string(),
+            "a:b".to_string(),
+            "A*B".to_string(),
+            format!("{}.one", "long".repeat(80)),
+            format!("{}.two", "long".repeat(80)),
+        ];
+        for name in &names {
+            glyphs3.glyphs.push(glyphs3::Glyph {
+                name: name.clone(),
+                ..template.clone()
+            });
+        }
+        let package = std::env::temp_dir().join("glyphslib-exotic.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        assert_eq!(Font::load(&package).unwrap(), font);
+
+        // A glyph file named by another tool is still found
+        let glyphs_dir = package.join("glyphs");
+        let clashing = fs::read_dir(&glyphs_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains('~'))
+            .unwrap();
+        fs::rename(&clashing, glyphs_dir.join("a_b-renamed.glyph")).unwrap();
+        assert_eq!(Font::load(&package).unwrap(), font);
+        fs::remove_dir_all(&package).unwrap();
+    }
+
+    #[test]
+    fn test_glyphs1_quirks() {
+        let font = Font::load_str(
+            r#"{
+.appVersion = 895;
+familyName = Old;
+fontMaster = ({id = m01;});
+glyphs = (
+{glyphname = foursuperior; unicode = 2074; layers = ({layerId = m01; width = 400; paths = (
+{closed = 1; nodes = ("0 0 line", "200 400 curve smooth", "400 0 LINE");}
+);});},
+{glyphname = fi; unicode = "FB01, 0066"; layers = ();}
+);
+unitsPerEm = 1000;
+}"#,
+        )
+        .unwrap();
+        let font = font.as_glyphs2().unwrap();
+        assert_eq!(font.app_version, "895");
+        assert_eq!(font.glyphs[0].unicode, vec![0x2074]);
+        assert_eq!(font.glyphs[1].unicode, vec![0xFB01, 0x66]);
+        let node_types: Vec<_> = font.glyphs[0].layers[0].paths[0]
+            .nodes
+            .iter()
+            .map(|node| node.node_type)
+            .collect();
+        assert_eq!(
+            node_types,
+            [NodeType::Line, NodeType::CurveSmooth, NodeType::Line]
+        );
+    }
+
+    #[test]
+    fn test_canonical_resave() {
+        for file in [
+            "resources/RadioCanadaDisplay.glyphs",
+            "resources/Oswald-O.glyphs",
+            "resources/QCurve.glyphs",
+        ] {
+            let raw_content = fs::read_to_string(file).unwrap();
+            let font = Font::load_str(&raw_content).unwrap();
+            assert!(
+                font.to_string_canonical().unwrap() == raw_content,
+                "{file} changed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_data_bytes() {
+        let mut font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let thumbnail = Plist::Data(vec![0x89, 0x50, 0x4e, 0x47, 0x00, 0xff]);
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        glyphs3
+            .user_data
+            .insert("com.example.thumbnail".into(), thumbnail.clone());
+        let reloaded = Font::load_str(&font.to_string().unwrap()).unwrap();
+        let Font::Glyphs3(reloaded) = &reloaded else {
+            unreachable!()
+        };
+        assert_eq!(
+            reloaded.user_data.get("com.example.thumbnail"),
+            Some(&thumbnail)
+        );
+    }
+
+    #[test]
+    fn test_serializer_options() {
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        assert_eq!(
+            font.to_string_with_options(&SerializerOptions::default())
+                .unwrap(),
+            font.to_string().unwrap()
+        );
+
+        let options = SerializerOptions {
+            float_precision: 6,
+            preserve_integer_types: true,
+            ..Default::default()
+        };
+        let saved = font.to_string_with_options(&options).unwrap();
+        assert_eq!(Font::load_str(&saved).unwrap(), font);
+    }
+
+    #[test]
+    fn test_reader_and_writer() {
+        let path = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let font = Font::from_reader(fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(font, Font::load(path).unwrap());
+
+        let mut buffer = vec![];
+        font.write_to(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.clone()).unwrap(),
+            font.to_string().unwrap()
+        );
+        let reloaded = Font::from_reader(buffer.as_slice()).unwrap();
+        assert_eq!(
+            reloaded,
+            Font::load_str(&font.to_string().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_metadata() {
+        for file in [
+            "resources/RadioCanadaDisplay.glyphs",
+            "resources/Oswald-AE-comb.glyphs",
+        ] {
+            let path = path::Path::new(file);
+            let mut font = Font::load(path).unwrap();
+            match &mut font {
+                Font::Glyphs2(glyphs2) => glyphs2.glyphs.clear(),
+                Font::Glyphs3(glyphs3) => glyphs3.glyphs.clear(),
+            }
+            assert_eq!(Font::load_metadata(path).unwrap(), font);
+        }
+
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let package = std::env::temp_dir().join("glyphslib-metadata.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        let metadata = Font::load_metadata(&package).unwrap();
+        let metadata = metadata.as_glyphs3().unwrap();
+        assert!(metadata.glyphs.is_empty());
+        assert_eq!(metadata.masters, font.as_glyphs3().unwrap().masters);
+        fs::remove_dir_all(&package).unwrap();
+    }
+
+    #[test]
+    fn test_incremental_package_save() {
+        let mut font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let package = std::env::temp_dir().join("glyphslib-incremental.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+
+        let glyphs_dir = package.join("glyphs");
+        let long_ago = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000);
+        for entry in fs::read_dir(&glyphs_dir).unwrap() {
+            let file = fs::File::options()
+                .write(true)
+                .open(entry.unwrap().path())
+                .unwrap();
+            file.set_modified(long_ago).unwrap();
+        }
+        let modified = |name: &str| {
+            fs::metadata(glyphs_dir.join(name))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        let width = &mut glyphs3
+            .glyphs
+            .iter_mut()
+            .find(|g| g.name == "A")
+            .unwrap()
+            .layers[0]
+            .width;
+        *width += 10.0;
+        let width = *width;
+        font.save(&package).unwrap();
+        assert_ne!(modified("A_.glyph"), long_ago);
+        assert_eq!(modified("B_.glyph"), long_ago);
+        let reloaded = Font::load(&package).unwrap();
+        let a = reloaded.as_glyphs3().unwrap().glyph("A").unwrap();
+        assert_eq!(a.layers[0].width, width);
+        fs::remove_dir_all(&package).unwrap();
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_profiling_metrics() {
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let load = Font::last_load_metrics().unwrap();
+        assert_eq!(load.read.calls, 1);
+        // Deserialized straight from the text, with no tree
+        assert_eq!(load.parse.calls, 0);
+        assert_eq!(load.deserialize.calls, 1);
+        assert!(load.package_files.is_empty());
+
+        let package = std::env::temp_dir().join("glyphslib-profiling.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        let save = Font::last_save_metrics().unwrap();
+        let glyph_count = font.as_glyphs3().unwrap().glyphs.len() as u32;
+        // One file per glyph, plus the order and font info; there are no
+        // display strings, so no UI state
+        assert_eq!(save.write.calls, glyph_count + 2);
+        assert_eq!(save.serialize.calls, save.write.calls);
+
+        Font::load(&package).unwrap();
+        let load = Font::last_load_metrics().unwrap();
+        assert_eq!(load.package_files.len() as u32, load.read.calls);
+        assert!(load
+            .package_files
+            .iter()
+            .any(|(file, _)| file == "fontinfo.plist"));
+        assert_eq!(load.parse.calls, load.read.calls);
+        fs::remove_dir_all(&package).unwrap();
+    }
 }
@@ -0,0 +1,345 @@
+use std::{fmt, io};
+
+use openstep_plist::{de::Deserializer, Plist};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+
+use crate::error::Result;
+
+// Give up on a file with more problems than this; it is probably not a font
+const MAX_WARNINGS: usize = 1000;
+
+/// Options controlling how fonts are loaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Drop the parts of the file which cannot be read, rather than failing
+    ///
+    /// Each part which fails to deserialize, such as a metric of an unknown
+    /// type, a malformed hint or a stray property, is removed as deeply in
+    /// the file as possible, and loading is tried again. Every removal is
+    /// reported as a [`LoadWarning`]. Files which cannot be parsed as
+    /// property lists at all, or which lack something the font cannot do
+    /// without such as its family name, still fail.
+    pub lenient: bool,
+    /// Deserialize the glyphs of the font, and parse the glyph files of a
+    /// package, on all cores
+    ///
+    /// Only has an effect with the `rayon` feature. The font is the same as
+    /// one loaded without it; if any glyph fails to deserialize, the whole
+    /// font is deserialized again in order, so errors and warnings are also
+    /// the same.
+    pub parallel: bool,
+    /// The text encoding of the file, rather than detecting it
+    ///
+    /// Without this, a file is read as UTF-8 if it is valid UTF-8, and
+    /// otherwise as written by older Mac tools, in Mac OS Roman, unless it
+    /// has none of the characters only Mac OS Roman uses, in which case it is
+    /// read as Latin-1. A UTF-8 byte order mark is skipped.
+    pub encoding: Option<Encoding>,
+}
+
+/// A text encoding of a `.glyphs` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, as Glyphs writes; files which are not valid UTF-8 fail to load
+    Utf8,
+    /// ISO 8859-1
+    Latin1,
+    /// Mac OS Roman
+    MacRoman,
+}
+
+// The characters of bytes 0x80 to 0xFF in Mac OS Roman
+const MAC_ROMAN: [char; 128] = [
+    'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è', //
+    'ê', 'ë', 'í', 'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü', //
+    '†', '°', '¢', '£', '§', '•', '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø', //
+    '∞', '±', '≤', '≥', '¥', 'µ', '∂', '∑', '∏', 'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø', //
+    '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«', '»', '…', '\u{a0}', 'À', 'Ã', 'Õ', 'Œ', 'œ', //
+    '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ', 'Ÿ', '⁄', '€', '‹', '›', 'ﬁ', 'ﬂ', //
+    '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', 'Ó', 'Ô', //
+    '\u{f8ff}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚', '¸', '˝', '˛', 'ˇ', //
+];
+
+// Decode the contents of a file in the encoding, or detecting it
+pub(crate) fn decode(bytes: Vec<u8>, encoding: Option<Encoding>) -> io::Result<String> {
+    const BOM: &[u8] = b"\xef\xbb\xbf";
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(&bytes).is_ok() => Encoding::Utf8,
+        // Latin-1 has only control characters here, which text does not use
+        None if bytes.iter().any(|b| (0x80..0xa0).contains(b)) => Encoding::MacRoman,
+        None => Encoding::Latin1,
+    };
+    match encoding {
+        Encoding::Utf8 => {
+            let bytes = match bytes.strip_prefix(BOM) {
+                Some(rest) => rest.to_vec(),
+                None => bytes,
+            };
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+        Encoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+        Encoding::MacRoman => Ok(bytes
+            .into_iter()
+            .map(|b| match b {
+                0..=0x7f => char::from(b),
+                _ => MAC_ROMAN[usize::from(b - 0x80)],
+            })
+            .collect()),
+    }
+}
+
+/// Something which was dropped while loading a font leniently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadWarning {
+    /// Where in the file the dropped value was, such as `fontMaster[0].metricValues`
+    pub path: String,
+    /// Why the value could not be read
+    pub message: String,
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dropped {}: {}", self.path, self.message)
+    }
+}
+
+// Remove the value at the end of the path
+fn remove_last(plist: &mut Plist, path: &[&Segment]) -> bool {
+    let Some((last, parent)) = path.split_last() else {
+        return false;
+    };
+    let mut value = plist;
+    for segment in parent {
+        let child = match (value, segment) {
+            (Plist::Dictionary(dict), Segment::Map { key }) => dict.get_mut(key.as_str()),
+            (Plist::Array(array), Segment::Seq { index }) => array.get_mut(*index),
+            _ => None,
+        };
+        let Some(child) = child else {
+            return false;
+        };
+        value = child;
+    }
+    match (value, last) {
+        (Plist::Dictionary(dict), Segment::Map { key }) => dict.remove(key.as_str()).is_some(),
+        (Plist::Array(array), Segment::Seq { index }) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Remove the value at the path, or else the nearest value containing it
+// which the path leads to. Returns whether anything was removed.
+fn remove(plist: &mut Plist, path: &[&Segment]) -> bool {
+    // An enum variant or an unknown segment is inside a single plist value
+    let followable = path
+        .iter()
+        .position(|s| !matches!(s, Segment::Seq { .. } | Segment::Map { .. }))
+        .unwrap_or(path.len());
+    (1..=followable)
+        .rev()
+        .any(|len| remove_last(plist, &path[..len]))
+}
+
+// Map the function over the items, in parallel if asked to
+#[cfg(feature = "rayon")]
+pub(crate) fn map<T: Sync, U: Send>(
+    items: &[T],
+    parallel: bool,
+    f: impl Fn(&T) -> U + Sync + Send,
+) -> Vec<U> {
+    use rayon::prelude::*;
+    if parallel {
+        items.par_iter().map(f).collect()
+    } else {
+        items.iter().map(f).collect()
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+pub(crate) fn map<T, U>(items: &[T], _parallel: bool, f: impl Fn(&T) -> U) -> Vec<U> {
+    items.iter().map(f).collect()
+}
+
+// Deserialize the glyphs of a font separately from the rest of it, in
+// parallel. Returns `None` if anything fails to deserialize.
+pub(crate) fn deserialize_split<T, G>(
+    plist: &Plist,
+    glyphs: impl FnOnce(&mut T) -> &mut Vec<G>,
+) -> Option<T>
+where
+    T: DeserializeOwned,
+    G: DeserializeOwned + Send,
+{
+    let toplevel = plist.as_dict()?;
+    let glyph_plists = toplevel.get("glyphs").and_then(Plist::as_array)?;
+    let mut rest = toplevel.clone();
+    rest.remove("glyphs");
+    let mut font: T =
+        T::deserialize(&mut Deserializer::from_plist(&Plist::Dictionary(rest))).ok()?;
+    let deserialized = map(glyph_plists, true, |glyph| {
+        G::deserialize(&mut Deserializer::from_plist(glyph)).ok()
+    });
+    *glyphs(&mut font) = deserialized.into_iter().collect::<Option<_>>()?;
+    Some(font)
+}
+
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    mut plist: Plist,
+    options: &LoadOptions,
+) -> Result<(T, Vec<LoadWarning>)> {
+    let mut warnings = vec![];
+    loop {
+        let deserializer = &mut Deserializer::from_plist(&plist);
+        let error = match serde_path_to_error::deserialize(deserializer) {
+            Ok(value) => return Ok((value, warnings)),
+            Err(error) => error,
+        };
+        let segments: Vec<&Segment> = error.path().iter().collect();
+        if !options.lenient || warnings.len() >= MAX_WARNINGS || !remove(&mut plist, &segments) {
+            return Err(error.into());
+        }
+        warnings.push(LoadWarning {
+            path: error.path().to_string(),
+            message: error.inner().to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+
+    const DAMAGED: &str = r#"{
+.formatVersion = 3;
+familyName = Test;
+fontMaster = ({id = m01; name = Regular; metricValues = ({pos = 800;}, {pos = oops;});});
+glyphs = ({glyphname = a; layers = ({layerId = m01; width = 500; hints = ({horizontal = 1; place = (a, b);});});});
+metrics = ({type = ascender;}, {type = bogus;});
+properties = ({key = designers; values = ({language = dflt; value = Someone;});}, {key = familyNames; junk = 1;});
+unitsPerEm = 1000;
+}"#;
+
+    #[test]
+    fn test_lenient_load() {
+        assert!(Font::load_str(DAMAGED).is_err());
+        let (strict, warnings) = Font::load_str_with_options(
+            r#"{.formatVersion = 3; familyName = Test; unitsPerEm = 1000;}"#,
+            &LoadOptions::default(),
+        )
+        .unwrap();
+        assert!(strict.as_glyphs3().is_some() && warnings.is_empty());
+
+        let (font, warnings) = Font::load_str_with_options(
+            DAMAGED,
+            &LoadOptions {
+                lenient: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let Font::Glyphs3(font) = font else {
+            unreachable!()
+        };
+        let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths[0], "fontMaster[0].metricValues[1].pos");
+        assert_eq!(paths.last(), Some(&"metrics[1].type"));
+        assert!(warnings.last().unwrap().message.contains("bogus"));
+        assert!(paths.contains(&"glyphs[0].layers[0].hints[0].place"));
+        // Everything else is still there
+        assert_eq!(font.masters[0].metric_values[0].pos, 800.0);
+        assert_eq!(font.metrics.len(), 2);
+        assert_eq!(font.metrics[1].metric_type, None);
+        assert_eq!(font.glyphs[0].layers[0].hints.len(), 1);
+        assert_eq!(font.properties.len(), 2);
+    }
+
+    #[test]
+    fn test_parallel_load() {
+        let parallel = LoadOptions {
+            parallel: true,
+            ..Default::default()
+        };
+        for file in [
+            "resources/RadioCanadaDisplay.glyphs",
+            "resources/Oswald-AE-comb.glyphs",
+        ] {
+            let path = std::path::Path::new(file);
+            let (font, warnings) = Font::load_with_options(path, &parallel).unwrap();
+            assert!(warnings.is_empty());
+            assert_eq!(font, Font::load(path).unwrap());
+        }
+        let font = Font::load(std::path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let package = std::env::temp_dir().join("glyphslib-parallel.glyphspackage");
+        let _ = std::fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        let serial = Font::load(&package).unwrap();
+        assert_eq!(
+            Font::load_with_options(&package, &parallel).unwrap().0,
+            serial
+        );
+        std::fs::remove_dir_all(&package).unwrap();
+
+        let lenient = LoadOptions {
+            lenient: true,
+            ..parallel
+        };
+        let (font, warnings) = Font::load_str_with_options(DAMAGED, &lenient).unwrap();
+        let (serial_font, serial_warnings) = Font::load_str_with_options(
+            DAMAGED,
+            &LoadOptions {
+                lenient: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((font, warnings), (serial_font, serial_warnings));
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(
+            decode(b"\xef\xbb\xbfCaf\xc3\xa9".to_vec(), None).unwrap(),
+            "Café"
+        );
+        assert_eq!(decode(b"Caf\x8e \xa5".to_vec(), None).unwrap(), "Café •");
+        assert_eq!(decode(b"Caf\xe9".to_vec(), None).unwrap(), "Café");
+        let mac_roman = b"Caf\x8e".to_vec();
+        assert_eq!(
+            decode(mac_roman.clone(), Some(Encoding::Latin1)).unwrap(),
+            "Caf\u{8e}"
+        );
+        assert!(decode(mac_roman, Some(Encoding::Utf8)).is_err());
+
+        let path = std::env::temp_dir().join("glyphslib-mac-roman.glyphs");
+        std::fs::write(
+            &path,
+            b"{.formatVersion = 3; familyName = \"Caf\x8e\"; unitsPerEm = 1000;}",
+        )
+        .unwrap();
+        let font = Font::load(&path).unwrap();
+        assert_eq!(font.as_glyphs3().unwrap().family_name, "Café");
+        let latin1 = LoadOptions {
+            encoding: Some(Encoding::Latin1),
+            ..Default::default()
+        };
+        let (font, _) = Font::load_with_options(&path, &latin1).unwrap();
+        assert_eq!(font.as_glyphs3().unwrap().family_name, "Caf\u{8e}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unrecoverable() {
+        let options = LoadOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert!(Font::load_str_with_options("{.formatVersion = 3;}", &options).is_err());
+        assert!(Font::load_str_with_options("{ oops", &options).is_err());
+    }
+}
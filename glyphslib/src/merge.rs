@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    common::Kerning,
+    glyphs3::{Glyph, Glyphs3, Master, Shape},
+    kerning::refers_to,
+};
+
+/// How [`Glyphs3::merge_glyphs`] matches the masters of the other font with
+/// those of this one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MasterMatching {
+    /// Masters with the same name
+    #[default]
+    Name,
+    /// Masters at the same location on the axes, which are matched by tag
+    AxisPosition,
+}
+
+/// Options for [`Glyphs3::merge_glyphs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// How the masters of the two fonts are matched
+    pub match_masters: MasterMatching,
+    /// Also copy the kerning of the merged glyphs, in the matched masters
+    ///
+    /// Pairs are copied when one side is a merged glyph or one of its groups
+    /// and the other is in this font, replacing pairs already here.
+    pub kerning: bool,
+}
+
+// Where a master is on the axes, by axis tag
+fn location<'a>(font: &'a Glyphs3, master: &Master) -> BTreeMap<&'a str, f64> {
+    font.axes
+        .iter()
+        .zip(&master.axes_values)
+        .map(|(axis, value)| (axis.tag.as_str(), *value))
+        .collect()
+}
+
+// The IDs of this font's masters by the IDs of the other font's
+fn match_masters(
+    font: &Glyphs3,
+    other: &Glyphs3,
+    matching: MasterMatching,
+) -> HashMap<String, String> {
+    other
+        .masters
+        .iter()
+        .filter_map(|theirs| {
+            let ours = font.masters.iter().find(|ours| match matching {
+                MasterMatching::Name => ours.name == theirs.name,
+                MasterMatching::AxisPosition => location(font, ours) == location(other, theirs),
+            })?;
+            Some((theirs.id.clone(), ours.id.clone()))
+        })
+        .collect()
+}
+
+// The glyph with its layers moved to this font's masters, leaving out those
+// of masters with no match
+fn remap_glyph(glyph: &Glyph, masters: &HashMap<String, String>) -> Glyph {
+    let mut glyph = glyph.clone();
+    glyph.layers.retain_mut(|layer| {
+        let master = match &mut layer.associated_master_id {
+            Some(master) => master,
+            None => &mut layer.layer_id,
+        };
+        let Some(ours) = masters.get(master.as_str()) else {
+            return false;
+        };
+        *master = ours.clone();
+        for shape in &mut layer.shapes {
+            if let Shape::Component(component) = shape {
+                if let Some(master) = &mut component.master_id {
+                    if let Some(ours) = masters.get(master.as_str()) {
+                        *master = ours.clone();
+                    }
+                }
+            }
+        }
+        true
+    });
+    glyph
+}
+
+fn merge_kerning(
+    ours: &mut Kerning,
+    theirs: &Kerning,
+    masters: &HashMap<String, String>,
+    merged: &[&Glyph],
+    present: &[&Glyph],
+) {
+    for (their_master, pairs) in theirs {
+        let Some(our_master) = masters.get(their_master) else {
+            continue;
+        };
+        for (left, rights) in pairs {
+            for (right, value) in rights {
+                let involved = refers_to(left, merged) || refers_to(right, merged);
+                if involved && refers_to(left, present) && refers_to(right, present) {
+                    ours.entry(our_master.clone())
+                        .or_default()
+                        .entry(left.clone())
+                        .or_default()
+                        .insert(right.clone(), *value);
+                }
+            }
+        }
+    }
+}
+
+impl Glyphs3 {
+    /// Copy glyphs from another font into this one
+    ///
+    /// The glyphs the named glyphs use as components come too, unless this
+    /// font already has them. A named glyph replaces a glyph of the same
+    /// name in place; other glyphs are added to the end of the font. Layers
+    /// are moved to the masters matched as [`MergeOptions::match_masters`]
+    /// says, and layers of masters with no match are left out. Names of
+    /// glyphs the other font does not have are ignored.
+    ///
+    /// Returns the names of the glyphs copied, in the order of the other
+    /// font.
+    pub fn merge_glyphs(
+        &mut self,
+        other: &Glyphs3,
+        names: &[&str],
+        options: &MergeOptions,
+    ) -> Vec<String> {
+        let masters = match_masters(self, other, options.match_masters);
+        let wanted = other.with_components(names);
+        let merged: Vec<&Glyph> = other
+            .glyphs
+            .iter()
+            .filter(|g| wanted.contains(g.name.as_str()))
+            .filter(|g| names.contains(&g.name.as_str()) || self.glyph(&g.name).is_none())
+            .collect();
+        for glyph in &merged {
+            let glyph = remap_glyph(glyph, &masters);
+            match self.glyph_mut(&glyph.name) {
+                Some(existing) => *existing = glyph,
+                None => self.glyphs.push(glyph),
+            }
+        }
+        if options.kerning {
+            let present: Vec<&Glyph> = self.glyphs.iter().collect();
+            for (ours, theirs) in [
+                (&mut self.kerning, &other.kerning),
+                (&mut self.kerning_rtl, &other.kerning_rtl),
+                (&mut self.kerning_vertical, &other.kerning_vertical),
+            ] {
+                merge_kerning(ours, theirs, &masters, &merged, &present);
+            }
+        }
+        merged.iter().map(|g| g.name.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{glyphs3::Layer, Font};
+    use std::path;
+
+    fn load(file: &str) -> Glyphs3 {
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_merge_glyphs() {
+        let source = load("resources/RadioCanadaDisplay.glyphs");
+        let mut font = source.subset(&["B"]);
+        // The destination's masters have other IDs, and one is renamed
+        for master in &mut font.masters {
+            master.id = format!("new-{}", master.id);
+        }
+        font.masters[1].name = "Renamed".to_string();
+        font.glyphs.clear();
+        let (first, second) = (source.masters[0].id.clone(), source.masters[1].id.clone());
+        let mut source = source;
+        source.set_kerning_pair(&first, "Aacute", "B", -20.0);
+        source.set_kerning_pair(&first, "Aacute", "C", -30.0);
+
+        let options = MergeOptions {
+            kerning: true,
+            ..Default::default()
+        };
+        let merged = font.merge_glyphs(&source, &["Aacute", "B"], &options);
+        assert_eq!(merged, ["A", "Aacute", "B", "acutecomb.case"]);
+        let master_of = |layer: &Layer| {
+            layer
+                .associated_master_id
+                .clone()
+                .unwrap_or(layer.layer_id.clone())
+        };
+        let aacute = font.glyph("Aacute").unwrap();
+        assert!(!aacute.layers.is_empty());
+        assert!(aacute
+            .layers
+            .iter()
+            .all(|l| master_of(l) == format!("new-{first}")));
+        let pairs = &font.kerning[&format!("new-{first}")];
+        assert_eq!(pairs["Aacute"].len(), 1);
+        assert_eq!(pairs["Aacute"]["B"], -20.0);
+
+        // Masters at the same place match whatever they are called
+        let options = MergeOptions {
+            match_masters: MasterMatching::AxisPosition,
+            ..Default::default()
+        };
+        font.merge_glyphs(&source, &["Aacute"], &options);
+        let aacute = font.glyph("Aacute").unwrap();
+        assert!(aacute
+            .layers
+            .iter()
+            .any(|l| master_of(l) == format!("new-{second}")));
+        assert_eq!(font.glyphs.len(), 4);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{
+    common::parameter_value,
+    geometry::Rect,
+    glyphs3::{Glyphs3, Master, MetricType, MetricValue},
+};
+
+/// The OS/2 and hhea vertical metrics of a master
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VerticalMetrics {
+    /// `OS/2.sTypoAscender`
+    pub typo_ascender: f64,
+    /// `OS/2.sTypoDescender`, usually negative
+    pub typo_descender: f64,
+    /// `OS/2.sTypoLineGap`
+    pub typo_line_gap: f64,
+    /// `hhea.ascender`
+    pub hhea_ascender: f64,
+    /// `hhea.descender`, usually negative
+    pub hhea_descender: f64,
+    /// `hhea.lineGap`
+    pub hhea_line_gap: f64,
+    /// `OS/2.usWinAscent`
+    pub win_ascent: f64,
+    /// `OS/2.usWinDescent`, positive below the baseline
+    pub win_descent: f64,
+}
+
+// Master parameters override the font-wide ones.
+pub(crate) fn vertical_metric(font: &Glyphs3, master: &Master, name: &str) -> Option<f64> {
+    parameter_value(&master.custom_parameters, name)
+        .or_else(|| parameter_value(&font.custom_parameters, name))
+}
+
+impl Master {
+    /// The value of a metric of the font in this master
+    ///
+    /// Glyphs 3 keeps the metrics in the font and their values in each
+    /// master, in the same order. Metrics filtered to some glyphs are not
+    /// considered. Returns `None` if the font has no such metric or the
+    /// master no value for it.
+    pub fn metric(&self, font: &Glyphs3, metric_type: MetricType) -> Option<MetricValue> {
+        let ix = font
+            .metrics
+            .iter()
+            .position(|m| m.metric_type == Some(metric_type) && m.filter.is_none())?;
+        self.metric_values.get(ix).cloned()
+    }
+
+    /// The value in this master of the stem of the font with the name
+    ///
+    /// Stems are kept like metrics: names in the font and values in each
+    /// master, in the same order. If several stems share the name, the
+    /// first is used.
+    pub fn stem(&self, font: &Glyphs3, name: &str) -> Option<f64> {
+        let ix = font.stems.iter().position(|stem| stem.name == name)?;
+        self.stem_values.get(ix).copied()
+    }
+
+    /// The value in this master of the number of the font with the name
+    pub fn number(&self, font: &Glyphs3, name: &str) -> Option<f64> {
+        let ix = font.numbers.iter().position(|number| number.name == name)?;
+        self.number_values.get(ix).copied()
+    }
+
+    fn metric_position(&self, font: &Glyphs3, metric_type: MetricType) -> Option<f64> {
+        self.metric(font, metric_type).map(|value| value.pos)
+    }
+
+    /// The ascender of the master
+    pub fn ascender(&self, font: &Glyphs3) -> Option<f64> {
+        self.metric_position(font, MetricType::Ascender)
+    }
+
+    /// The descender of the master, usually negative
+    pub fn descender(&self, font: &Glyphs3) -> Option<f64> {
+        self.metric_position(font, MetricType::Descender)
+    }
+
+    /// The cap height of the master
+    pub fn cap_height(&self, font: &Glyphs3) -> Option<f64> {
+        self.metric_position(font, MetricType::CapHeight)
+    }
+
+    /// The x-height of the master
+    pub fn x_height(&self, font: &Glyphs3) -> Option<f64> {
+        self.metric_position(font, MetricType::XHeight)
+    }
+
+    /// The italic angle of the master in degrees, 0 if it has none
+    pub fn italic_angle(&self, font: &Glyphs3) -> f64 {
+        self.metric_position(font, MetricType::ItalicAngle)
+            .unwrap_or_default()
+    }
+
+    /// Resolve the vertical metrics of this master as Glyphs exports them
+    ///
+    /// Each value is taken from the custom parameter of the same name, such as
+    /// `typoAscender`, on the master or else on the font. Without one, the
+    /// ascenders and descenders come from the master's ascender and descender
+    /// metrics (800 and -200 if those are missing), and both line gaps make
+    /// the line height 1.2 times the em, but are never negative. `winAscent`
+    /// and `winDescent` cover the highest and lowest exported glyph of the
+    /// master, or the hhea ascender and descender if no glyph has outlines.
+    pub fn vertical_metrics(&self, font: &Glyphs3) -> VerticalMetrics {
+        let parameter = |name| vertical_metric(font, self, name);
+        let ascender = self.ascender(font).unwrap_or(800.0);
+        let descender = self.descender(font).unwrap_or(-200.0);
+        let line_gap = (1.2 * font.units_per_em as f64 - (ascender - descender)).max(0.0);
+
+        let typo_ascender = parameter("typoAscender").unwrap_or(ascender);
+        let typo_descender = parameter("typoDescender").unwrap_or(descender);
+        let hhea_ascender = parameter("hheaAscender").unwrap_or(ascender);
+        let hhea_descender = parameter("hheaDescender").unwrap_or(descender);
+        let win_ascent = parameter("winAscent");
+        let win_descent = parameter("winDescent");
+        let bounds = if win_ascent.is_none() || win_descent.is_none() {
+            self.export_bounds(font)
+        } else {
+            None
+        };
+        VerticalMetrics {
+            typo_ascender,
+            typo_descender,
+            typo_line_gap: parameter("typoLineGap").unwrap_or(line_gap),
+            hhea_ascender,
+            hhea_descender,
+            hhea_line_gap: parameter("hheaLineGap").unwrap_or(line_gap),
+            win_ascent: win_ascent
+                .unwrap_or_else(|| bounds.map_or(hhea_ascender, |b| b.y_max).max(0.0)),
+            win_descent: win_descent
+                .unwrap_or_else(|| bounds.map_or(-hhea_descender, |b| -b.y_min).max(0.0)),
+        }
+    }
+
+    // The union of the bounds of the exported glyphs in this master
+    fn export_bounds(&self, font: &Glyphs3) -> Option<Rect> {
+        font.glyphs
+            .iter()
+            .filter(|g| g.export)
+            .filter_map(|glyph| {
+                glyph
+                    .layers
+                    .iter()
+                    .find(|l| l.layer_id == self.id)?
+                    .bounds_with_components(font, &self.id)
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::CustomParameter, Font};
+    use openstep_plist::Plist;
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    fn parameter(name: &str, value: i64) -> CustomParameter {
+        CustomParameter {
+            name: name.to_string(),
+            value: Plist::Integer(value),
+            disabled: false,
+        }
+    }
+
+    // Leave the font with no vertical metrics parameters at all
+    fn strip_parameters(font: &mut Glyphs3) {
+        let is_vertical = |p: &CustomParameter| {
+            ["typo", "hhea", "win"]
+                .iter()
+                .any(|prefix| p.name.starts_with(prefix))
+        };
+        font.custom_parameters.retain(|p| !is_vertical(p));
+        for master in font.masters.iter_mut() {
+            master.custom_parameters.retain(|p| !is_vertical(p));
+        }
+    }
+
+    #[test]
+    fn test_master_metrics() {
+        let mut font = load();
+        let ix = font
+            .metrics
+            .iter()
+            .position(|m| m.metric_type == Some(MetricType::XHeight))
+            .unwrap();
+        let master = &font.masters[0];
+        assert_eq!(
+            master.metric(&font, MetricType::XHeight),
+            Some(master.metric_values[ix].clone())
+        );
+        assert_eq!(master.x_height(&font), Some(master.metric_values[ix].pos));
+        assert_eq!(master.italic_angle(&font), 0.0);
+        // Metrics filtered to some glyphs are not the master's metric
+        font.metrics[ix].filter = Some("script == \"greek\"".to_string());
+        assert_eq!(font.masters[0].x_height(&font), None);
+    }
+
+    #[test]
+    fn test_stems_and_numbers() {
+        let font = load();
+        let master = &font.masters[0];
+        assert_eq!(
+            master.stem(&font, &font.stems[0].name),
+            master.stem_values.first().copied()
+        );
+        assert_eq!(master.stem(&font, "no such stem"), None);
+
+        let file = path::Path::new("resources/number_value.glyphs");
+        let Font::Glyphs3(font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(font.masters[0].number(&font, "foo"), Some(12.4));
+        assert_eq!(font.masters[1].number(&font, "foo"), Some(0.0));
+        assert_eq!(font.masters[0].number(&font, "bar"), None);
+    }
+
+    #[test]
+    fn test_vertical_metrics_fallbacks() {
+        let mut font = load();
+        strip_parameters(&mut font);
+        let master = &font.masters[0];
+        let ascender = master.ascender(&font).unwrap();
+        let descender = master.descender(&font).unwrap();
+        let metrics = master.vertical_metrics(&font);
+        assert_eq!(metrics.typo_ascender, ascender);
+        assert_eq!(metrics.hhea_descender, descender);
+        assert_eq!(
+            metrics.typo_line_gap,
+            (1.2 * font.units_per_em as f64 - (ascender - descender)).max(0.0)
+        );
+        let bounds = master.export_bounds(&font).unwrap();
+        assert_eq!(metrics.win_ascent, bounds.y_max);
+        assert_eq!(metrics.win_descent, -bounds.y_min);
+    }
+
+    #[test]
+    fn test_vertical_metrics_parameters_override() {
+        let mut font = load();
+        strip_parameters(&mut font);
+        font.custom_parameters.push(parameter("typoLineGap", 123));
+        font.custom_parameters.push(parameter("winAscent", 1500));
+        font.masters[0]
+            .custom_parameters
+            .push(parameter("winAscent", 1400));
+        let metrics = font.masters[0].vertical_metrics(&font);
+        assert_eq!(metrics.typo_line_gap, 123.0);
+        assert_eq!(metrics.win_ascent, 1400.0);
+        assert_eq!(font.masters[1].vertical_metrics(&font).win_ascent, 1500.0);
+    }
+
+    #[test]
+    fn test_vertical_metrics_from_file() {
+        let font = load();
+        let master = &font.masters[0];
+        let metrics = master.vertical_metrics(&font);
+        let expected = |name| vertical_metric(&font, master, name).unwrap();
+        assert_eq!(metrics.typo_ascender, expected("typoAscender"));
+        assert_eq!(metrics.hhea_line_gap, expected("hheaLineGap"));
+        assert_eq!(metrics.win_descent, expected("winDescent"));
+    }
+}
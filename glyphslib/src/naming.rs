@@ -0,0 +1,236 @@
+use openstep_plist::Plist;
+
+use crate::{
+    common::parameter_str,
+    diff::to_plist,
+    glyphs3::{Glyphs3, Instance, Property},
+};
+
+// The value of a property by its key in the file, such as `familyNames`,
+// taking the default language of a localized one, or else its first. This
+// finds properties this crate has no key for, which are kept as junk, too.
+fn property(properties: &[Property], key: &str) -> Option<String> {
+    properties.iter().find_map(|property| {
+        let plist = match property {
+            Property::Junk(plist) => plist.clone(),
+            _ => to_plist(property),
+        };
+        let dict = plist.as_dict()?;
+        if dict.get("key")?.as_str()? != key {
+            return None;
+        }
+        if let Some(value) = dict.get("value") {
+            return value.as_str().map(String::from);
+        }
+        let values = dict.get("values")?.as_array()?;
+        let is_default = |value: &Plist| {
+            value
+                .as_dict()
+                .and_then(|dict| dict.get("language")?.as_str())
+                == Some("dflt")
+        };
+        values
+            .iter()
+            .find(|value| is_default(value))
+            .or(values.first())?
+            .as_dict()?
+            .get("value")?
+            .as_str()
+            .map(String::from)
+    })
+}
+
+// What is left of a style name linked to its regular, bold, italic or bold
+// italic: its last Regular, Bold or Italic, as the instance is, are taken
+// out. This is what glyphsLib does.
+fn linked_style(style_name: &str, is_bold: bool, is_italic: bool) -> String {
+    let (mut regular, mut bold, mut italic) = (!(is_bold || is_italic), is_bold, is_italic);
+    let mut parts: Vec<&str> = vec![];
+    for part in style_name.split_whitespace().rev() {
+        match part {
+            "Regular" if regular => regular = false,
+            "Bold" if bold => bold = false,
+            "Italic" if italic => italic = false,
+            _ => parts.insert(0, part),
+        }
+    }
+    parts.join(" ")
+}
+
+impl Instance {
+    // A name from a property of the instance, or else from the custom
+    // parameter Glyphs 2 kept it in
+    fn name_override(&self, property_key: &str, parameter: &str) -> Option<String> {
+        property(&self.properties, property_key)
+            .or_else(|| parameter_str(&self.custom_parameters, parameter).map(String::from))
+    }
+
+    /// The family name of the instance: its `familyNames` property or
+    /// `familyName` parameter, or else the family name of the font
+    pub fn family_name(&self, font: &Glyphs3) -> String {
+        self.name_override("familyNames", "familyName")
+            .unwrap_or_else(|| font.family_name.clone())
+    }
+
+    /// The style name of the instance: its `styleNames` property, or else
+    /// its name
+    pub fn style_name(&self) -> String {
+        property(&self.properties, "styleNames").unwrap_or_else(|| self.name.clone())
+    }
+
+    /// The typographic family name (name ID 16): the `preferredFamilyNames`
+    /// property or `preferredFamilyName` parameter, or else the family name
+    pub fn preferred_family_name(&self, font: &Glyphs3) -> String {
+        self.name_override("preferredFamilyNames", "preferredFamilyName")
+            .unwrap_or_else(|| self.family_name(font))
+    }
+
+    /// The typographic subfamily name (name ID 17): the
+    /// `preferredSubfamilyNames` property or `preferredSubfamilyName`
+    /// parameter, or else the style name
+    pub fn preferred_subfamily_name(&self) -> String {
+        self.name_override("preferredSubfamilyNames", "preferredSubfamilyName")
+            .unwrap_or_else(|| self.style_name())
+    }
+
+    /// The full name (name ID 4): the `compatibleFullNames` property or
+    /// `compatibleFullName` parameter, or else the family and style names
+    pub fn full_name(&self, font: &Glyphs3) -> String {
+        self.name_override("compatibleFullNames", "compatibleFullName")
+            .unwrap_or_else(|| format!("{} {}", self.family_name(font), self.style_name()))
+    }
+
+    /// The PostScript name (name ID 6): the `postscriptFontName` property
+    /// or parameter, or else the family and style names joined by a hyphen,
+    /// without spaces or characters PostScript names may not have
+    pub fn postscript_name(&self, font: &Glyphs3) -> String {
+        if let Some(name) = self.name_override("postscriptFontName", "postscriptFontName") {
+            return name;
+        }
+        let clean = |name: String| -> String {
+            name.chars()
+                .filter(|ch| ch.is_ascii_graphic() && !"[](){}<>/%".contains(*ch))
+                .collect()
+        };
+        format!(
+            "{}-{}",
+            clean(self.family_name(font)),
+            clean(self.style_name())
+        )
+    }
+
+    /// The PostScript full name: the `postscriptFullNames` property or
+    /// `postscriptFullName` parameter, or else the full name
+    pub fn postscript_full_name(&self, font: &Glyphs3) -> String {
+        self.name_override("postscriptFullNames", "postscriptFullName")
+            .unwrap_or_else(|| self.full_name(font))
+    }
+
+    /// The style-linked family name (name ID 1)
+    ///
+    /// This is the `styleMapFamilyNames` property or `styleMapFamilyName`
+    /// parameter, or else the family name followed by what the style name
+    /// has besides the Regular, Bold or Italic which `isBold` and `isItalic`
+    /// make it; a `linkStyle` other than Regular is used in place of the
+    /// style name. So `Light Italic`, linked as italic, is in the family
+    /// `Family Light`.
+    pub fn style_map_family_name(&self, font: &Glyphs3) -> String {
+        if let Some(name) = self.name_override("styleMapFamilyNames", "styleMapFamilyName") {
+            return name;
+        }
+        let family = self.family_name(font);
+        let linked = match self.link_style.as_deref() {
+            Some(link) if !link.is_empty() && link != "Regular" => link.to_string(),
+            _ => linked_style(&self.style_name(), self.is_bold, self.is_italic),
+        };
+        if linked.is_empty() {
+            family
+        } else {
+            format!("{family} {linked}")
+        }
+    }
+
+    /// The style-linked style name (name ID 2): the `styleMapStyleNames`
+    /// property or `styleMapStyleName` parameter, or else `regular`, `bold`,
+    /// `italic` or `bold italic` as `isBold` and `isItalic` say
+    pub fn style_map_style_name(&self) -> String {
+        if let Some(name) = self.name_override("styleMapStyleNames", "styleMapStyleName") {
+            return name;
+        }
+        match (self.is_bold, self.is_italic) {
+            (false, false) => "regular",
+            (true, false) => "bold",
+            (false, true) => "italic",
+            (true, true) => "bold italic",
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::CustomParameter,
+        glyphs3::{LocalizedPropertyKey, SingularPropertyKey},
+    };
+
+    #[test]
+    fn test_instance_names() {
+        let font = Glyphs3 {
+            family_name: "My Font".to_string(),
+            ..Default::default()
+        };
+        let mut instance = Instance {
+            name: "Light Italic".to_string(),
+            is_italic: true,
+            ..Default::default()
+        };
+        assert_eq!(instance.full_name(&font), "My Font Light Italic");
+        assert_eq!(instance.postscript_name(&font), "MyFont-LightItalic");
+        assert_eq!(instance.style_map_family_name(&font), "My Font Light");
+        assert_eq!(instance.style_map_style_name(), "italic");
+        assert_eq!(instance.preferred_family_name(&font), "My Font");
+        assert_eq!(instance.preferred_subfamily_name(), "Light Italic");
+
+        let bold = Instance {
+            name: "Bold".to_string(),
+            is_bold: true,
+            ..Default::default()
+        };
+        assert_eq!(bold.style_map_family_name(&font), "My Font");
+        assert_eq!(bold.style_map_style_name(), "bold");
+        let semibold = Instance {
+            name: "SemiBold".to_string(),
+            is_bold: true,
+            link_style: Some("SemiBold".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(semibold.style_map_family_name(&font), "My Font SemiBold");
+
+        instance.properties = vec![
+            Property::localized_with_default(
+                LocalizedPropertyKey::FamilyNames,
+                "Other Font".to_string(),
+            ),
+            Property::singular(
+                SingularPropertyKey::PostscriptFontName,
+                "Other-LtIt".to_string(),
+            ),
+            Property::Unknown {
+                key: "preferredFamilyNames".to_string(),
+                value: Plist::parse("({language = dflt; value = \"Other\";})").unwrap(),
+            },
+        ];
+        instance.custom_parameters = vec![CustomParameter {
+            name: "styleMapStyleName".to_string(),
+            value: Plist::String("regular".to_string()),
+            disabled: false,
+        }];
+        assert_eq!(instance.family_name(&font), "Other Font");
+        assert_eq!(instance.postscript_name(&font), "Other-LtIt");
+        assert_eq!(instance.preferred_family_name(&font), "Other");
+        assert_eq!(instance.style_map_family_name(&font), "Other Font Light");
+        assert_eq!(instance.style_map_style_name(), "regular");
+    }
+}
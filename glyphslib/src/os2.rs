@@ -0,0 +1,465 @@
+use std::collections::BTreeSet;
+
+use openstep_plist::Plist;
+
+use crate::{common::parameter_list, glyphs3::Glyphs3};
+
+// The blocks of each bit of OS/2.ulUnicodeRange1-4, from the OpenType
+// specification. Each entry is (bit, first code point, last code point).
+const UNICODE_RANGES: &[(u8, u32, u32)] = &[
+    (0, 0x0000, 0x007F),
+    (1, 0x0080, 0x00FF),
+    (2, 0x0100, 0x017F),
+    (3, 0x0180, 0x024F),
+    (4, 0x0250, 0x02AF),
+    (4, 0x1D00, 0x1D7F),
+    (4, 0x1D80, 0x1DBF),
+    (5, 0x02B0, 0x02FF),
+    (5, 0xA700, 0xA71F),
+    (6, 0x0300, 0x036F),
+    (6, 0x1DC0, 0x1DFF),
+    (7, 0x0370, 0x03FF),
+    (8, 0x2C80, 0x2CFF),
+    (9, 0x0400, 0x04FF),
+    (9, 0x0500, 0x052F),
+    (9, 0x2DE0, 0x2DFF),
+    (9, 0xA640, 0xA69F),
+    (10, 0x0530, 0x058F),
+    (11, 0x0590, 0x05FF),
+    (12, 0xA500, 0xA63F),
+    (13, 0x0600, 0x06FF),
+    (13, 0x0750, 0x077F),
+    (14, 0x07C0, 0x07FF),
+    (15, 0x0900, 0x097F),
+    (16, 0x0980, 0x09FF),
+    (17, 0x0A00, 0x0A7F),
+    (18, 0x0A80, 0x0AFF),
+    (19, 0x0B00, 0x0B7F),
+    (20, 0x0B80, 0x0BFF),
+    (21, 0x0C00, 0x0C7F),
+    (22, 0x0C80, 0x0CFF),
+    (23, 0x0D00, 0x0D7F),
+    (24, 0x0E00, 0x0E7F),
+    (25, 0x0E80, 0x0EFF),
+    (26, 0x10A0, 0x10FF),
+    (26, 0x2D00, 0x2D2F),
+    (27, 0x1B00, 0x1B7F),
+    (28, 0x1100, 0x11FF),
+    (29, 0x1E00, 0x1EFF),
+    (29, 0x2C60, 0x2C7F),
+    (29, 0xA720, 0xA7FF),
+    (30, 0x1F00, 0x1FFF),
+    (31, 0x2000, 0x206F),
+    (31, 0x2E00, 0x2E7F),
+    (32, 0x2070, 0x209F),
+    (33, 0x20A0, 0x20CF),
+    (34, 0x20D0, 0x20FF),
+    (35, 0x2100, 0x214F),
+    (36, 0x2150, 0x218F),
+    (37, 0x2190, 0x21FF),
+    (37, 0x27F0, 0x27FF),
+    (37, 0x2900, 0x297F),
+    (37, 0x2B00, 0x2BFF),
+    (38, 0x2200, 0x22FF),
+    (38, 0x2A00, 0x2AFF),
+    (38, 0x27C0, 0x27EF),
+    (38, 0x2980, 0x29FF),
+    (39, 0x2300, 0x23FF),
+    (40, 0x2400, 0x243F),
+    (41, 0x2440, 0x245F),
+    (42, 0x2460, 0x24FF),
+    (43, 0x2500, 0x257F),
+    (44, 0x2580, 0x259F),
+    (45, 0x25A0, 0x25FF),
+    (46, 0x2600, 0x26FF),
+    (47, 0x2700, 0x27BF),
+    (48, 0x3000, 0x303F),
+    (49, 0x3040, 0x309F),
+    (50, 0x30A0, 0x30FF),
+    (50, 0x31F0, 0x31FF),
+    (51, 0x3100, 0x312F),
+    (51, 0x31A0, 0x31BF),
+    (52, 0x3130, 0x318F),
+    (53, 0xA840, 0xA87F),
+    (54, 0x3200, 0x32FF),
+    (55, 0x3300, 0x33FF),
+    (56, 0xAC00, 0xD7AF),
+    (57, 0x10000, 0x10FFFF),
+    (58, 0x10900, 0x1091F),
+    (59, 0x4E00, 0x9FFF),
+    (59, 0x2E80, 0x2EFF),
+    (59, 0x2F00, 0x2FDF),
+    (59, 0x2FF0, 0x2FFF),
+    (59, 0x3400, 0x4DBF),
+    (59, 0x20000, 0x2A6DF),
+    (59, 0x3190, 0x319F),
+    (60, 0xE000, 0xF8FF),
+    (61, 0x31C0, 0x31EF),
+    (61, 0xF900, 0xFAFF),
+    (61, 0x2F800, 0x2FA1F),
+    (62, 0xFB00, 0xFB4F),
+    (63, 0xFB50, 0xFDFF),
+    (64, 0xFE20, 0xFE2F),
+    (65, 0xFE10, 0xFE1F),
+    (65, 0xFE30, 0xFE4F),
+    (66, 0xFE50, 0xFE6F),
+    (67, 0xFE70, 0xFEFF),
+    (68, 0xFF00, 0xFFEF),
+    (69, 0xFFF0, 0xFFFF),
+    (70, 0x0F00, 0x0FFF),
+    (71, 0x0700, 0x074F),
+    (72, 0x0780, 0x07BF),
+    (73, 0x0D80, 0x0DFF),
+    (74, 0x1000, 0x109F),
+    (75, 0x1200, 0x137F),
+    (75, 0x1380, 0x139F),
+    (75, 0x2D80, 0x2DDF),
+    (76, 0x13A0, 0x13FF),
+    (77, 0x1400, 0x167F),
+    (78, 0x1680, 0x169F),
+    (79, 0x16A0, 0x16FF),
+    (80, 0x1780, 0x17FF),
+    (80, 0x19E0, 0x19FF),
+    (81, 0x1800, 0x18AF),
+    (82, 0x2800, 0x28FF),
+    (83, 0xA000, 0xA48F),
+    (83, 0xA490, 0xA4CF),
+    (84, 0x1700, 0x171F),
+    (84, 0x1720, 0x173F),
+    (84, 0x1740, 0x175F),
+    (84, 0x1760, 0x177F),
+    (85, 0x10300, 0x1032F),
+    (86, 0x10330, 0x1034F),
+    (87, 0x10400, 0x1044F),
+    (88, 0x1D000, 0x1D0FF),
+    (88, 0x1D100, 0x1D1FF),
+    (88, 0x1D200, 0x1D24F),
+    (89, 0x1D400, 0x1D7FF),
+    (90, 0xF0000, 0xFFFFD),
+    (90, 0x100000, 0x10FFFD),
+    (91, 0xFE00, 0xFE0F),
+    (91, 0xE0100, 0xE01EF),
+    (92, 0xE0000, 0xE007F),
+    (93, 0x1900, 0x194F),
+    (94, 0x1950, 0x197F),
+    (95, 0x1980, 0x19DF),
+    (96, 0x1A00, 0x1A1F),
+    (97, 0x2C00, 0x2C5F),
+    (98, 0x2D30, 0x2D7F),
+    (99, 0x4DC0, 0x4DFF),
+    (100, 0xA800, 0xA82F),
+    (101, 0x10000, 0x1007F),
+    (101, 0x10080, 0x100FF),
+    (101, 0x10100, 0x1013F),
+    (102, 0x10140, 0x1018F),
+    (103, 0x10380, 0x1039F),
+    (104, 0x103A0, 0x103DF),
+    (105, 0x10450, 0x1047F),
+    (106, 0x10480, 0x104AF),
+    (107, 0x10800, 0x1083F),
+    (108, 0x10A00, 0x10A5F),
+    (109, 0x1D300, 0x1D35F),
+    (110, 0x12000, 0x123FF),
+    (110, 0x12400, 0x1247F),
+    (111, 0x1D360, 0x1D37F),
+    (112, 0x1B80, 0x1BBF),
+    (113, 0x1C00, 0x1C4F),
+    (114, 0x1C50, 0x1C7F),
+    (115, 0xA880, 0xA8DF),
+    (116, 0xA900, 0xA92F),
+    (117, 0xA930, 0xA95F),
+    (118, 0xAA00, 0xAA5F),
+    (119, 0x10190, 0x101CF),
+    (120, 0x101D0, 0x101FF),
+    (121, 0x102A0, 0x102DF),
+    (121, 0x10280, 0x1029F),
+    (121, 0x10920, 0x1093F),
+    (122, 0x1F030, 0x1F09F),
+    (122, 0x1F000, 0x1F02F),
+];
+
+// The bits of OS/2.ulCodePageRange1-2 by Windows code page number, as the
+// `codePageRanges` custom parameter names them
+const CODE_PAGES: [(i64, u8); 32] = [
+    (1252, 0),
+    (1250, 1),
+    (1251, 2),
+    (1253, 3),
+    (1254, 4),
+    (1255, 5),
+    (1256, 6),
+    (1257, 7),
+    (1258, 8),
+    (874, 16),
+    (932, 17),
+    (936, 18),
+    (949, 19),
+    (950, 20),
+    (1361, 21),
+    (869, 48),
+    (866, 49),
+    (865, 50),
+    (864, 51),
+    (863, 52),
+    (862, 53),
+    (861, 54),
+    (860, 55),
+    (857, 56),
+    (855, 57),
+    (852, 58),
+    (775, 59),
+    (737, 60),
+    (708, 61),
+    (850, 62),
+    (437, 63),
+    // Macintosh Roman, by its Windows code page number
+    (10000, 29),
+];
+
+fn set_bit<const N: usize>(bits: &mut [u32; N], bit: u8) {
+    if let Some(word) = bits.get_mut(usize::from(bit / 32)) {
+        *word |= 1 << (bit % 32);
+    }
+}
+
+/// Compute `OS/2.ulUnicodeRange1` to `ulUnicodeRange4` from code points
+///
+/// A bit is set if any code point falls in one of its blocks. The result is
+/// the four fields in order, with bit 0 of the first being Basic Latin.
+pub fn unicode_range_bits(codepoints: impl IntoIterator<Item = u32>) -> [u32; 4] {
+    let mut bits = [0; 4];
+    for codepoint in codepoints {
+        for (bit, start, end) in UNICODE_RANGES {
+            if (*start..=*end).contains(&codepoint) {
+                set_bit(&mut bits, *bit);
+            }
+        }
+    }
+    bits
+}
+
+/// Compute `OS/2.ulCodePageRange1` and `ulCodePageRange2` from code points
+///
+/// A code page is considered supported if the font has characters which
+/// are characteristic of it, following the checks ufo2ft makes. If no code
+/// page qualifies, bit 0 (Latin 1) is set.
+pub fn code_page_range_bits(codepoints: &BTreeSet<u32>) -> [u32; 2] {
+    let has = |c: char| codepoints.contains(&u32::from(c));
+    let has_ascii = (0x20..0x7E).all(|c| codepoints.contains(&c));
+    let has_lineart = has('┤');
+    let mut bits = [0; 2];
+    for &codepoint in codepoints {
+        let Some(c) = char::from_u32(codepoint) else {
+            continue;
+        };
+        match c {
+            'Þ' if has_ascii => set_bit(&mut bits, 0),
+            'Ľ' if has_ascii => {
+                set_bit(&mut bits, 1);
+                if has_lineart {
+                    set_bit(&mut bits, 58);
+                }
+            }
+            'Б' => {
+                set_bit(&mut bits, 2);
+                if has('Ѕ') && has_lineart {
+                    set_bit(&mut bits, 57);
+                }
+                if has('╜') && has_lineart {
+                    set_bit(&mut bits, 49);
+                }
+            }
+            'Ά' => {
+                set_bit(&mut bits, 3);
+                if has_lineart && has('½') {
+                    set_bit(&mut bits, 48);
+                }
+                if has_lineart && has('√') {
+                    set_bit(&mut bits, 60);
+                }
+            }
+            'İ' if has_ascii => {
+                set_bit(&mut bits, 4);
+                if has_lineart {
+                    set_bit(&mut bits, 56);
+                }
+            }
+            'א' => {
+                set_bit(&mut bits, 5);
+                if has_lineart && has('√') {
+                    set_bit(&mut bits, 53);
+                }
+            }
+            'ر' => {
+                set_bit(&mut bits, 6);
+                if has('√') {
+                    set_bit(&mut bits, 51);
+                }
+                if has_lineart {
+                    set_bit(&mut bits, 61);
+                }
+            }
+            'ŗ' if has_ascii => {
+                set_bit(&mut bits, 7);
+                if has_lineart {
+                    set_bit(&mut bits, 59);
+                }
+            }
+            '₫' if has_ascii => set_bit(&mut bits, 8),
+            'ๅ' => set_bit(&mut bits, 16),
+            'エ' => set_bit(&mut bits, 17),
+            'ㄅ' => set_bit(&mut bits, 18),
+            'ㄱ' => set_bit(&mut bits, 19),
+            '央' => set_bit(&mut bits, 20),
+            '곴' => set_bit(&mut bits, 21),
+            '♥' if has_ascii => set_bit(&mut bits, 30),
+            'þ' if has_ascii && has_lineart => set_bit(&mut bits, 54),
+            '╚' if has_ascii => {
+                set_bit(&mut bits, 62);
+                set_bit(&mut bits, 63);
+            }
+            'Å' if has_ascii && has_lineart && has('√') => set_bit(&mut bits, 50),
+            'é' if has_ascii && has_lineart && has('√') => set_bit(&mut bits, 52),
+            'õ' if has_ascii && has_lineart && has('√') => set_bit(&mut bits, 55),
+            _ => {}
+        }
+    }
+    if has_ascii && has('‰') && has('∑') {
+        set_bit(&mut bits, 29);
+    }
+    if bits == [0; 2] {
+        set_bit(&mut bits, 0);
+    }
+    bits
+}
+
+// A `codePageRanges` entry is a code page number, or `bit N` for bits
+// without one.
+fn code_page_bit(value: &Plist) -> Option<u8> {
+    let number = match value {
+        Plist::String(s) => match s.strip_prefix("bit ") {
+            Some(bit) => return bit.trim().parse().ok(),
+            None => s.parse().ok()?,
+        },
+        _ => value.as_i64()?,
+    };
+    CODE_PAGES
+        .iter()
+        .find(|(page, _)| *page == number)
+        .map(|(_, bit)| *bit)
+}
+
+impl Glyphs3 {
+    // The code points of the exported glyphs
+    fn exported_codepoints(&self) -> BTreeSet<u32> {
+        self.glyphs
+            .iter()
+            .filter(|g| g.export)
+            .flat_map(|g| g.unicode.iter().copied())
+            .collect()
+    }
+
+    /// The `OS/2.ulUnicodeRange1` to `ulUnicodeRange4` bits of the font
+    ///
+    /// The `unicodeRanges` custom parameter, a list of bit numbers, is used
+    /// if set; otherwise the bits are computed from the code points of the
+    /// exported glyphs by [`unicode_range_bits`].
+    pub fn os2_unicode_ranges(&self) -> [u32; 4] {
+        match parameter_list(&self.custom_parameters, "unicodeRanges") {
+            Some(values) => {
+                let mut bits = [0; 4];
+                for bit in values.iter().filter_map(Plist::as_i64) {
+                    if let Ok(bit) = u8::try_from(bit) {
+                        set_bit(&mut bits, bit);
+                    }
+                }
+                bits
+            }
+            None => unicode_range_bits(self.exported_codepoints()),
+        }
+    }
+
+    /// The `OS/2.ulCodePageRange1` and `ulCodePageRange2` bits of the font
+    ///
+    /// The `codePageRanges` custom parameter, a list of code page numbers
+    /// such as `1252`, is used if set; otherwise the bits are computed from
+    /// the code points of the exported glyphs by [`code_page_range_bits`].
+    pub fn os2_code_page_ranges(&self) -> [u32; 2] {
+        match parameter_list(&self.custom_parameters, "codePageRanges") {
+            Some(values) => {
+                let mut bits = [0; 2];
+                for bit in values.iter().filter_map(code_page_bit) {
+                    set_bit(&mut bits, bit);
+                }
+                bits
+            }
+            None => code_page_range_bits(&self.exported_codepoints()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::CustomParameter, Font};
+    use std::path;
+
+    fn load() -> Glyphs3 {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        match Font::load(path::Path::new(file)).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_unicode_range_bits() {
+        assert_eq!(unicode_range_bits([0x41, 0xE9]), [0b11, 0, 0, 0]);
+        // Phonetic Extensions shares bit 4 with IPA Extensions
+        assert_eq!(unicode_range_bits([0x1D00]), [1 << 4, 0, 0, 0]);
+        // Supplementary code points also set bit 57
+        assert_eq!(
+            unicode_range_bits([0x1F000]),
+            [0, 1 << (57 - 32), 0, 1 << (122 - 96)]
+        );
+    }
+
+    #[test]
+    fn test_code_page_range_bits() {
+        let mut codepoints: BTreeSet<u32> = (0x20..0x7F).collect();
+        assert_eq!(code_page_range_bits(&codepoints), [1, 0]);
+        codepoints.extend(['Þ', 'Б', 'Ľ'].map(u32::from));
+        assert_eq!(code_page_range_bits(&codepoints), [0b111, 0]);
+        codepoints.clear();
+        assert_eq!(code_page_range_bits(&codepoints), [1, 0]);
+    }
+
+    #[test]
+    fn test_font_ranges() {
+        let mut font = load();
+        let computed = font.os2_unicode_ranges();
+        assert_eq!(computed[0] & 1, 1);
+        font.custom_parameters.insert(
+            0,
+            CustomParameter {
+                name: "unicodeRanges".to_string(),
+                value: Plist::Array(vec![Plist::Integer(0), Plist::Integer(60)]),
+                disabled: false,
+            },
+        );
+        assert_eq!(font.os2_unicode_ranges(), [1, 1 << (60 - 32), 0, 0]);
+        font.custom_parameters.insert(
+            0,
+            CustomParameter {
+                name: "codePageRanges".to_string(),
+                value: Plist::Array(vec![
+                    Plist::Integer(1252),
+                    Plist::String("1251".to_string()),
+                    Plist::String("bit 29".to_string()),
+                ]),
+                disabled: false,
+            },
+        );
+        assert_eq!(font.os2_code_page_ranges(), [0b101 | 1 << 29, 0]);
+    }
+}
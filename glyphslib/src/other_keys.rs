@@ -0,0 +1,349 @@
+// Keeping the keys of a struct which it has no field for.
+//
+// Structs with an `other` dictionary derive their (de)serialization with
+// `#[serde(remote = "Self")]`, leaving `other` out of it, and wrap the derived
+// functions with the adaptors here. Unlike `#[serde(flatten)]`, this neither
+// buffers the struct through serde's `Content` on the way in, nor writes the
+// other keys after all the known ones on the way out: they are merged in key
+// order, so saved files stay sorted as Glyphs writes them.
+
+use std::{borrow::Cow, fmt, iter::Peekable};
+
+use openstep_plist::{Dictionary, Plist};
+use serde::{
+    de::{self, value::CowStrDeserializer, DeserializeSeed, MapAccess, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Impossible, SerializeMap, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    glyphs3::{self, Glyphs3},
+    serde::is_default,
+};
+
+/// The entries of a map, with the keys of a dictionary merged in among them
+///
+/// Entries must be given in key order.
+pub(crate) struct MergedMap<'a, M> {
+    map: M,
+    other: Peekable<<&'a Dictionary as IntoIterator>::IntoIter>,
+}
+
+impl<'a, M: SerializeMap> MergedMap<'a, M> {
+    pub(crate) fn new(map: M, other: &'a Dictionary) -> Self {
+        MergedMap {
+            map,
+            other: other.iter().peekable(),
+        }
+    }
+
+    pub(crate) fn entry<V>(&mut self, key: &str, value: &V) -> Result<(), M::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        while let Some((other_key, other_value)) = self.other.next_if(|(k, _)| k.as_str() < key) {
+            self.map.serialize_entry(other_key.as_str(), other_value)?;
+        }
+        self.map.serialize_entry(key, value)
+    }
+
+    pub(crate) fn finish(mut self) -> Result<M::Ok, M::Error> {
+        for (key, value) in self.other {
+            self.map.serialize_entry(key.as_str(), value)?;
+        }
+        self.map.end()
+    }
+}
+
+impl<M: SerializeMap> SerializeStruct for MergedMap<'_, M> {
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// A serializer for a derived struct, writing it as a map with the keys of
+/// `other` merged in
+pub(crate) struct WithOther<'a, S> {
+    serializer: S,
+    other: &'a Dictionary,
+}
+
+impl<'a, S> WithOther<'a, S> {
+    pub(crate) fn new(serializer: S, other: &'a Dictionary) -> Self {
+        WithOther { serializer, other }
+    }
+}
+
+macro_rules! only_structs {
+    ($($method:ident($($ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<$ret, Self::Error> {
+                Err(ser::Error::custom("only structs can have other keys"))
+            }
+        )*
+    };
+}
+
+impl<'a, S: Serializer> Serializer for WithOther<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Impossible<S::Ok, S::Error>;
+    type SerializeTuple = Impossible<S::Ok, S::Error>;
+    type SerializeTupleStruct = Impossible<S::Ok, S::Error>;
+    type SerializeTupleVariant = Impossible<S::Ok, S::Error>;
+    type SerializeMap = Impossible<S::Ok, S::Error>;
+    type SerializeStruct = MergedMap<'a, S::SerializeMap>;
+    type SerializeStructVariant = Impossible<S::Ok, S::Error>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let map = self.serializer.serialize_map(None)?;
+        Ok(MergedMap::new(map, self.other))
+    }
+
+    only_structs! {
+        serialize_bool(bool) -> S::Ok;
+        serialize_i8(i8) -> S::Ok;
+        serialize_i16(i16) -> S::Ok;
+        serialize_i32(i32) -> S::Ok;
+        serialize_i64(i64) -> S::Ok;
+        serialize_u8(u8) -> S::Ok;
+        serialize_u16(u16) -> S::Ok;
+        serialize_u32(u32) -> S::Ok;
+        serialize_u64(u64) -> S::Ok;
+        serialize_f32(f32) -> S::Ok;
+        serialize_f64(f64) -> S::Ok;
+        serialize_char(char) -> S::Ok;
+        serialize_str(&str) -> S::Ok;
+        serialize_bytes(&[u8]) -> S::Ok;
+        serialize_none() -> S::Ok;
+        serialize_unit() -> S::Ok;
+        serialize_unit_struct(&'static str) -> S::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> S::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom("only structs can have other keys"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom("only structs can have other keys"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom("only structs can have other keys"))
+    }
+}
+
+/// A deserializer for a derived struct, putting the keys it has no field
+/// for into `other` rather than handing them to it
+pub(crate) struct CollectOther<'o, D> {
+    deserializer: D,
+    other: &'o mut Dictionary,
+}
+
+impl<'o, D> CollectOther<'o, D> {
+    pub(crate) fn new(deserializer: D, other: &'o mut Dictionary) -> Self {
+        CollectOther {
+            deserializer,
+            other,
+        }
+    }
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for CollectOther<'_, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.deserializer.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.deserializer.deserialize_map(CollectVisitor {
+            visitor,
+            fields,
+            other: self.other,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct CollectVisitor<'o, V> {
+    visitor: V,
+    fields: &'static [&'static str],
+    other: &'o mut Dictionary,
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for CollectVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_map(CollectMap {
+            map,
+            fields: self.fields,
+            other: self.other,
+        })
+    }
+}
+
+struct CollectMap<'o, A> {
+    map: A,
+    fields: &'static [&'static str],
+    other: &'o mut Dictionary,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for CollectMap<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        while let Some(Key(key)) = self.map.next_key()? {
+            if self.fields.contains(&key.as_ref()) {
+                return seed.deserialize(CowStrDeserializer::new(key)).map(Some);
+            }
+            let value: Plist = self.map.next_value()?;
+            self.other.insert(key.as_ref().into(), value);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+}
+
+// A key, borrowed from the input where it can be
+struct Key<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string key")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Owned(v.to_string())))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+macro_rules! keep_other_keys {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    <$ty>::serialize(self, WithOther::new(serializer, &self.other))
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let mut other = Dictionary::new();
+                    let mut value = <$ty>::deserialize(CollectOther::new(deserializer, &mut other))?;
+                    value.other = other;
+                    Ok(value)
+                }
+            }
+        )*
+    };
+}
+
+keep_other_keys!(glyphs3::Master, glyphs3::Glyph, glyphs3::Instance);
+
+// Layers are serialized by hand, merging their other keys in as they go
+impl<'de> Deserialize<'de> for glyphs3::Layer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut other = Dictionary::new();
+        let mut layer = glyphs3::Layer::deserialize(CollectOther::new(deserializer, &mut other))?;
+        layer.other = other;
+        Ok(layer)
+    }
+}
+
+impl Serialize for Glyphs3 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The version is flattened too, so its keys are merged in with the rest
+        let mut other = self.other.clone();
+        if !is_default(&self.version) {
+            other.insert(
+                "versionMajor".into(),
+                Plist::Integer(self.version.major.into()),
+            );
+            other.insert(
+                "versionMinor".into(),
+                Plist::Integer(self.version.minor.into()),
+            );
+        }
+        Glyphs3::serialize(self, WithOther::new(serializer, &other))
+    }
+}
+
+// The font has few other keys, and reads them through flatten together with
+// its version
+impl<'de> Deserialize<'de> for Glyphs3 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Glyphs3::deserialize(deserializer)
+    }
+}
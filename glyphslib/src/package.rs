@@ -0,0 +1,228 @@
+use std::{collections::HashMap, io, path::Path, sync::OnceLock};
+
+use openstep_plist::{de::Deserializer, Plist};
+
+use crate::{
+    error::{Error, Result},
+    glyphs3::{Glyph, Glyphs3},
+    is_glyphs3, package_toplevel,
+    profiling::{record, record_package_file, Stage},
+    source::{Directory, PackageSource},
+    utils::glyph_file_names,
+    Font,
+};
+
+/// A `.glyphspackage` whose glyphs are read only when asked for
+///
+/// Opening a package reads its `fontinfo.plist`, `order.plist` and
+/// `UIState.plist`, but none of its glyph files; each glyph is read and
+/// parsed by [`load_glyph`](Self::load_glyph). This is much faster than
+/// [`Font::load`] for tools which only touch a few glyphs of a large font.
+/// Glyph files are found by name as Glyphs names them, or else by the name
+/// inside them, as when loading the whole package.
+///
+/// Only Glyphs 3 packages can be read this way.
+#[derive(Debug)]
+pub struct PackageFont<S> {
+    source: S,
+    toplevel: Plist,
+    glyph_order: Vec<String>,
+    file_names: HashMap<String, String>,
+    // The glyph files Glyphs would not have named as they are, by the name
+    // of the glyph inside, found the first time one is needed
+    renamed: OnceLock<HashMap<String, String>>,
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(plist: &Plist) -> Result<T> {
+    record(Stage::Deserialize, || {
+        let deserializer = &mut Deserializer::from_plist(plist);
+        Ok(serde_path_to_error::deserialize(deserializer)?)
+    })
+}
+
+impl PackageFont<Directory> {
+    /// Open a package in a directory, reading everything but its glyphs
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_from(Directory::new(path))
+    }
+}
+
+impl<S: PackageSource> PackageFont<S> {
+    /// Open a package from a [`PackageSource`], reading everything but its
+    /// glyphs
+    ///
+    /// Fails with [`Error::LazyGlyphs2Package`] if the package has no
+    /// `.formatVersion`, as Glyphs 2 packages do not.
+    pub fn open_from(source: S) -> Result<Self> {
+        let read = |name: &str| record_package_file(name, || source.read_file(name));
+        let font_info = read("fontinfo.plist")?;
+        let ui_state = read("UIState.plist").ok();
+        let toplevel = Plist::Dictionary(package_toplevel(&font_info, ui_state.as_deref())?);
+        if !is_glyphs3(&toplevel) {
+            return Err(Error::LazyGlyphs2Package);
+        }
+        let order = read("order.plist")?;
+        let glyph_order: Vec<String> = record(Stage::Parse, || Plist::parse(&order))?
+            .expect_array()?
+            .iter()
+            .filter_map(Plist::as_str)
+            .map(str::to_string)
+            .collect();
+        let file_names = glyph_order
+            .iter()
+            .cloned()
+            .zip(glyph_file_names(glyph_order.iter().map(String::as_str)))
+            .map(|(name, file_name)| (name, format!("glyphs/{file_name}.glyph")))
+            .collect();
+        Ok(PackageFont {
+            source,
+            toplevel,
+            glyph_order,
+            file_names,
+            renamed: OnceLock::new(),
+        })
+    }
+
+    /// The names of the glyphs of the font, in order
+    pub fn glyph_names(&self) -> impl Iterator<Item = &str> {
+        self.glyph_order.iter().map(String::as_str)
+    }
+
+    /// Everything in the font except its glyphs
+    pub fn metadata(&self) -> Result<Glyphs3> {
+        deserialize(&self.toplevel)
+    }
+
+    /// Read and parse the glyph with the given name
+    ///
+    /// Returns `None` if the glyph is not in the order of the font, or has
+    /// no glyph file.
+    pub fn load_glyph(&self, name: &str) -> Result<Option<Glyph>> {
+        let Some(glyph_file) = self.file_names.get(name) else {
+            return Ok(None);
+        };
+        match self.read_glyph(glyph_file) {
+            Err(Error::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                match self.renamed()?.get(name) {
+                    Some(glyph_file) => self.read_glyph(glyph_file).map(Some),
+                    None => Ok(None),
+                }
+            }
+            result => result.map(Some),
+        }
+    }
+
+    fn read_glyph(&self, glyph_file: &str) -> Result<Glyph> {
+        let contents = record_package_file(glyph_file, || self.source.read_file(glyph_file))?;
+        let plist = record(Stage::Parse, || Plist::parse(&contents))?;
+        deserialize(&plist)
+    }
+
+    fn renamed(&self) -> Result<&HashMap<String, String>> {
+        if let Some(renamed) = self.renamed.get() {
+            return Ok(renamed);
+        }
+        let expected: Vec<&String> = self.file_names.values().collect();
+        let mut unexpected: Vec<String> = self
+            .source
+            .glyph_files()?
+            .into_iter()
+            .filter(|glyph_file| glyph_file.ends_with(".glyph") && !expected.contains(&glyph_file))
+            .collect();
+        unexpected.sort();
+        let mut renamed = HashMap::new();
+        for glyph_file in unexpected {
+            let contents = record_package_file(&glyph_file, || self.source.read_file(&glyph_file))?;
+            let plist = record(Stage::Parse, || Plist::parse(&contents))?;
+            let glyph_name = plist
+                .as_dict()
+                .and_then(|glyph| glyph.get("glyphname"))
+                .and_then(Plist::as_str);
+            if let Some(glyph_name) = glyph_name {
+                renamed.entry(glyph_name.to_string()).or_insert(glyph_file);
+            }
+        }
+        Ok(self.renamed.get_or_init(|| renamed))
+    }
+
+    /// Read all the glyphs, giving the whole font
+    pub fn into_font(self) -> Result<Font> {
+        let mut font = self.metadata()?;
+        for name in &self.glyph_order {
+            if let Some(glyph) = self.load_glyph(name)? {
+                font.glyphs.push(glyph);
+            }
+        }
+        Ok(Font::Glyphs3(font))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_lazy_package() {
+        let font = Font::load(Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let package = std::env::temp_dir().join("glyphslib-lazy.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        let Font::Glyphs3(glyphs3) = &font else {
+            unreachable!()
+        };
+
+        let lazy = PackageFont::open(&package).unwrap();
+        assert_eq!(lazy.glyph_names().count(), glyphs3.glyphs.len());
+        assert_eq!(
+            lazy.load_glyph("Aacute").unwrap().as_ref(),
+            glyphs3.glyph("Aacute")
+        );
+        assert_eq!(lazy.load_glyph("no-such-glyph").unwrap(), None);
+        let metadata = lazy.metadata().unwrap();
+        assert!(metadata.glyphs.is_empty());
+        assert_eq!(metadata.masters, glyphs3.masters);
+
+        // A glyph file another tool named differently is still found
+        fs::rename(
+            package.join(&lazy.file_names["Aacute"]),
+            package.join("glyphs/renamed.glyph"),
+        )
+        .unwrap();
+        assert_eq!(
+            lazy.load_glyph("Aacute").unwrap().as_ref(),
+            glyphs3.glyph("Aacute")
+        );
+        assert_eq!(lazy.into_font().unwrap(), Font::load(&package).unwrap());
+        fs::remove_dir_all(&package).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_package_from_source() {
+        let font = Font::load(Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let mut entries = HashMap::new();
+        font.save_package_to(&mut entries).unwrap();
+        let file_name = PackageFont::open_from(entries.clone()).unwrap().file_names["A"].clone();
+        let contents = entries.remove(&file_name).unwrap();
+        entries.insert("glyphs/renamed-A.glyph".to_string(), contents);
+
+        let lazy = PackageFont::open_from(entries.clone()).unwrap();
+        assert_eq!(
+            lazy.load_glyph("A").unwrap().as_ref(),
+            font.as_glyphs3().unwrap().glyph("A")
+        );
+        assert_eq!(
+            lazy.into_font().unwrap(),
+            Font::load_package_from(&entries).unwrap()
+        );
+
+        // Glyphs 2 packages have no format version, and are not misread
+        let font_info = entries["fontinfo.plist"].replace(".formatVersion = 3;", "");
+        entries.insert("fontinfo.plist".to_string(), font_info);
+        assert!(matches!(
+            PackageFont::open_from(entries),
+            Err(Error::LazyGlyphs2Package)
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+// Load and save instrumentation. Without the `profiling` feature the hooks
+// below compile down to calling the wrapped closure.
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Stage {
+    Read,
+    Parse,
+    Deserialize,
+    Serialize,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Operation {
+    Load,
+    Save,
+}
+
+#[cfg(not(feature = "profiling"))]
+mod hooks {
+    use super::{Operation, Stage};
+
+    pub(crate) struct OperationGuard;
+
+    impl OperationGuard {
+        pub(crate) fn start(_operation: Operation) -> Self {
+            OperationGuard
+        }
+    }
+
+    pub(crate) fn record<T>(_stage: Stage, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    pub(crate) fn record_package_file<T, E>(
+        _file: &str,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        f()
+    }
+}
+
+#[cfg(feature = "profiling")]
+mod hooks {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::{Cell, RefCell},
+        sync::atomic::{AtomicU64, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use super::{Operation, Stage};
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+    /// A global allocator which counts allocations for [`StageMetrics`]
+    ///
+    /// Allocation counts are only recorded when this is installed as the
+    /// global allocator of the program:
+    ///
+    /// ```ignore
+    /// #[global_allocator]
+    /// static ALLOCATOR: glyphslib::CountingAllocator = glyphslib::CountingAllocator;
+    /// ```
+    ///
+    /// The counters are shared by all threads, so allocations made by other
+    /// threads during a load are included in its counts.
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            System.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+            System.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    /// Time and memory spent in one stage of loading or saving
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct StageMetrics {
+        /// Total wall-clock time spent in the stage
+        pub duration: Duration,
+        /// How many times the stage ran, such as once per file of a package
+        pub calls: u32,
+        /// Allocations made during the stage; zero unless [`CountingAllocator`] is installed
+        pub allocations: u64,
+        /// Bytes requested during the stage; zero unless [`CountingAllocator`] is installed
+        pub allocated_bytes: u64,
+    }
+
+    impl StageMetrics {
+        fn add(&mut self, other: &StageMetrics) {
+            self.duration += other.duration;
+            self.calls += other.calls;
+            self.allocations += other.allocations;
+            self.allocated_bytes += other.allocated_bytes;
+        }
+    }
+
+    /// Where the time went during the most recent load on this thread
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct LoadMetrics {
+        /// Reading files from disk, including every file of a package
+        pub read: StageMetrics,
+        /// Parsing the property list text into a tree
+        ///
+        /// A single `.glyphs` file is usually deserialized straight from its
+        /// text, which counts only under `deserialize`; it is parsed into a
+        /// tree when loading leniently or in parallel, or to report an error.
+        pub parse: StageMetrics,
+        /// Converting the property list into the font structures
+        pub deserialize: StageMetrics,
+        /// The read of each file of a package, by path relative to the package
+        pub package_files: Vec<(String, StageMetrics)>,
+    }
+
+    /// Where the time went during the most recent save on this thread
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct SaveMetrics {
+        /// Converting the font structures into property list text
+        pub serialize: StageMetrics,
+        /// Writing files to disk, including every file of a package
+        pub write: StageMetrics,
+    }
+
+    thread_local! {
+        static DEPTH: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+        static CURRENT_LOAD: RefCell<LoadMetrics> = RefCell::new(LoadMetrics::default());
+        static CURRENT_SAVE: RefCell<SaveMetrics> = RefCell::new(SaveMetrics::default());
+        static LAST_LOAD: RefCell<Option<LoadMetrics>> = const { RefCell::new(None) };
+        static LAST_SAVE: RefCell<Option<SaveMetrics>> = const { RefCell::new(None) };
+    }
+
+    pub(crate) fn last_load_metrics() -> Option<LoadMetrics> {
+        LAST_LOAD.with(|last| last.borrow().clone())
+    }
+
+    pub(crate) fn last_save_metrics() -> Option<SaveMetrics> {
+        LAST_SAVE.with(|last| last.borrow().clone())
+    }
+
+    // Public entry points call each other (`load` calls `load_str`, and so
+    // on), so only the outermost one starts and finishes a measurement.
+    pub(crate) struct OperationGuard(Operation);
+
+    fn depth_entry(depth: &mut (usize, usize), operation: Operation) -> &mut usize {
+        match operation {
+            Operation::Load => &mut depth.0,
+            Operation::Save => &mut depth.1,
+        }
+    }
+
+    impl OperationGuard {
+        pub(crate) fn start(operation: Operation) -> Self {
+            let mut depth = DEPTH.with(Cell::get);
+            let entry = depth_entry(&mut depth, operation);
+            if *entry == 0 {
+                match operation {
+                    Operation::Load => CURRENT_LOAD.with(|c| *c.borrow_mut() = Default::default()),
+                    Operation::Save => CURRENT_SAVE.with(|c| *c.borrow_mut() = Default::default()),
+                }
+            }
+            *entry += 1;
+            DEPTH.with(|d| d.set(depth));
+            OperationGuard(operation)
+        }
+    }
+
+    impl Drop for OperationGuard {
+        fn drop(&mut self) {
+            let mut depth = DEPTH.with(Cell::get);
+            let entry = depth_entry(&mut depth, self.0);
+            *entry -= 1;
+            if *entry == 0 {
+                match self.0 {
+                    Operation::Load => {
+                        let metrics = CURRENT_LOAD.with(|c| c.borrow().clone());
+                        LAST_LOAD.with(|last| *last.borrow_mut() = Some(metrics));
+                    }
+                    Operation::Save => {
+                        let metrics = CURRENT_SAVE.with(|c| c.borrow().clone());
+                        LAST_SAVE.with(|last| *last.borrow_mut() = Some(metrics));
+                    }
+                }
+            }
+            DEPTH.with(|d| d.set(depth));
+        }
+    }
+
+    fn measure<T>(f: impl FnOnce() -> T) -> (T, StageMetrics) {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let result = f();
+        let metrics = StageMetrics {
+            duration: start.elapsed(),
+            calls: 1,
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes,
+        };
+        (result, metrics)
+    }
+
+    pub(crate) fn record<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+        let (result, metrics) = measure(f);
+        match stage {
+            Stage::Read => CURRENT_LOAD.with(|c| c.borrow_mut().read.add(&metrics)),
+            Stage::Parse => CURRENT_LOAD.with(|c| c.borrow_mut().parse.add(&metrics)),
+            Stage::Deserialize => CURRENT_LOAD.with(|c| c.borrow_mut().deserialize.add(&metrics)),
+            Stage::Serialize => CURRENT_SAVE.with(|c| c.borrow_mut().serialize.add(&metrics)),
+            Stage::Write => CURRENT_SAVE.with(|c| c.borrow_mut().write.add(&metrics)),
+        }
+        result
+    }
+
+    // Optional files of a package which are missing are not counted.
+    pub(crate) fn record_package_file<T, E>(
+        file: &str,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let (result, metrics) = measure(f);
+        if result.is_ok() {
+            CURRENT_LOAD.with(|c| {
+                let mut current = c.borrow_mut();
+                current.read.add(&metrics);
+                current.package_files.push((file.to_string(), metrics));
+            });
+        }
+        result
+    }
+}
+
+#[cfg(feature = "profiling")]
+pub(crate) use hooks::{last_load_metrics, last_save_metrics};
+pub(crate) use hooks::{record, record_package_file, OperationGuard};
+#[cfg(feature = "profiling")]
+pub use hooks::{CountingAllocator, LoadMetrics, SaveMetrics, StageMetrics};
@@ -0,0 +1,208 @@
+use crate::{
+    common::parameter_str,
+    glyphs2::Glyphs2,
+    glyphs3::{Glyphs3, LocalizedPropertyKey, LocalizedValue, Property, SingularPropertyKey},
+    Font,
+};
+
+impl Glyphs3 {
+    /// The value of a localized property in a language, such as `"ENG"`
+    ///
+    /// As in the name table, a language the property has no value for falls
+    /// back to the default language (`"dflt"`), and then to the first
+    /// language given.
+    pub fn get_property(&self, key: LocalizedPropertyKey, language: &str) -> Option<&str> {
+        self.properties.iter().find_map(|property| match property {
+            Property::LocalizedProperty { key: k, .. } if *k == key => property
+                .value_for_language(language)
+                .or_else(|| property.default_value()),
+            _ => None,
+        })
+    }
+
+    /// Set the value of a localized property in a language, adding the
+    /// property if the font has none
+    pub fn set_property(
+        &mut self,
+        key: LocalizedPropertyKey,
+        language: &str,
+        value: impl Into<String>,
+    ) {
+        let value = value.into();
+        let values = self
+            .properties
+            .iter_mut()
+            .find_map(|property| match property {
+                Property::LocalizedProperty { key: k, values } if *k == key => Some(values),
+                _ => None,
+            });
+        let Some(values) = values else {
+            self.properties.push(Property::LocalizedProperty {
+                key,
+                values: vec![LocalizedValue::new(language, value)],
+            });
+            return;
+        };
+        match values.iter_mut().find(|v| v.language == language) {
+            Some(existing) => existing.value = value,
+            None => values.push(LocalizedValue::new(language, value)),
+        }
+    }
+
+    /// The value of a singular property
+    pub fn get_singular_property(&self, key: SingularPropertyKey) -> Option<&str> {
+        self.properties.iter().find_map(|property| match property {
+            Property::SingularProperty { key: k, value } if *k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Set the value of a singular property, adding the property if the
+    /// font has none
+    pub fn set_singular_property(&mut self, key: SingularPropertyKey, value: impl Into<String>) {
+        let value = value.into();
+        let existing = self
+            .properties
+            .iter_mut()
+            .find_map(|property| match property {
+                Property::SingularProperty { key: k, value } if *k == key => Some(value),
+                _ => None,
+            });
+        match existing {
+            Some(existing) => *existing = value,
+            None => self.properties.push(Property::singular(key, value)),
+        }
+    }
+
+    /// The family name of the font in a language, from its `familyNames`
+    /// property, or else its family name
+    pub fn family_name_localized(&self, language: &str) -> &str {
+        self.get_property(LocalizedPropertyKey::FamilyNames, language)
+            .unwrap_or(&self.family_name)
+    }
+
+    /// The copyright notice of the font, in the default language
+    pub fn copyright(&self) -> Option<&str> {
+        self.get_property(LocalizedPropertyKey::Copyrights, "dflt")
+    }
+
+    /// The designer of the font, in the default language
+    pub fn designer(&self) -> Option<&str> {
+        self.get_property(LocalizedPropertyKey::Designers, "dflt")
+            .or_else(|| self.get_singular_property(SingularPropertyKey::Designer))
+    }
+
+    /// The manufacturer of the font, in the default language
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.get_property(LocalizedPropertyKey::Manufacturers, "dflt")
+            .or_else(|| self.get_singular_property(SingularPropertyKey::Manufacturer))
+    }
+
+    /// The URL of the license of the font, from its property, or else the
+    /// custom parameter Glyphs 2 kept it in
+    pub fn license_url(&self) -> Option<&str> {
+        self.get_singular_property(SingularPropertyKey::LicenseUrl)
+            .or_else(|| parameter_str(&self.custom_parameters, "licenseURL"))
+    }
+}
+
+impl Glyphs2 {
+    /// The copyright notice of the font
+    pub fn copyright(&self) -> Option<&str> {
+        self.copyright.as_deref()
+    }
+
+    /// The designer of the font
+    pub fn designer(&self) -> Option<&str> {
+        self.designer.as_deref()
+    }
+
+    /// The manufacturer of the font
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.manufacturer.as_deref()
+    }
+
+    /// The URL of the license of the font, from its `licenseURL` parameter
+    pub fn license_url(&self) -> Option<&str> {
+        parameter_str(&self.custom_parameters, "licenseURL")
+    }
+}
+
+impl Font {
+    /// The family name of the font in a language, which Glyphs 2 files
+    /// have only one of
+    pub fn family_name_localized(&self, language: &str) -> &str {
+        match self {
+            Font::Glyphs3(font) => font.family_name_localized(language),
+            Font::Glyphs2(font) => &font.family_name,
+        }
+    }
+
+    /// The copyright notice of the font, in the default language
+    pub fn copyright(&self) -> Option<&str> {
+        match self {
+            Font::Glyphs3(font) => font.copyright(),
+            Font::Glyphs2(font) => font.copyright(),
+        }
+    }
+
+    /// The designer of the font, in the default language
+    pub fn designer(&self) -> Option<&str> {
+        match self {
+            Font::Glyphs3(font) => font.designer(),
+            Font::Glyphs2(font) => font.designer(),
+        }
+    }
+
+    /// The manufacturer of the font, in the default language
+    pub fn manufacturer(&self) -> Option<&str> {
+        match self {
+            Font::Glyphs3(font) => font.manufacturer(),
+            Font::Glyphs2(font) => font.manufacturer(),
+        }
+    }
+
+    /// The URL of the license of the font
+    pub fn license_url(&self) -> Option<&str> {
+        match self {
+            Font::Glyphs3(font) => font.license_url(),
+            Font::Glyphs2(font) => font.license_url(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() {
+        let mut font = Glyphs3 {
+            family_name: "Plain".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(font.family_name_localized("DEU"), "Plain");
+        assert_eq!(font.copyright(), None);
+
+        font.set_property(LocalizedPropertyKey::FamilyNames, "dflt", "Default");
+        font.set_property(LocalizedPropertyKey::FamilyNames, "DEU", "Schrift");
+        font.set_property(LocalizedPropertyKey::FamilyNames, "DEU", "Deutsch");
+        assert_eq!(font.properties.len(), 1);
+        assert_eq!(font.family_name_localized("DEU"), "Deutsch");
+        assert_eq!(font.family_name_localized("FRA"), "Default");
+
+        font.set_singular_property(SingularPropertyKey::Designer, "Someone");
+        assert_eq!(font.designer(), Some("Someone"));
+        font.set_property(LocalizedPropertyKey::Designers, "ENG", "Someone Else");
+        assert_eq!(font.designer(), Some("Someone Else"));
+        font.set_singular_property(SingularPropertyKey::LicenseUrl, "https://example.com");
+        assert_eq!(font.license_url(), Some("https://example.com"));
+
+        // Glyphs 2 files keep these at the top level, or in parameters
+        let downgraded = Font::Glyphs3(font.clone()).downgrade();
+        assert!(matches!(downgraded, Font::Glyphs2(_)));
+        assert_eq!(downgraded.designer(), Some("Someone Else"));
+        assert_eq!(downgraded.license_url(), Some("https://example.com"));
+        assert_eq!(downgraded.family_name_localized("DEU"), "Plain");
+    }
+}
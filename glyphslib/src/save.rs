@@ -0,0 +1,289 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Font, Result};
+
+/// Where to keep the previous contents of a file when saving over it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backup {
+    /// A sibling named like Glyphs.app's autosaves, such as `MyFont (Autosaved).glyphs`
+    Autosaved,
+    /// A sibling named by appending a suffix, such as `MyFont.glyphs.bak` for `".bak"`
+    Suffix(String),
+}
+
+impl Backup {
+    fn path_for(&self, path: &Path) -> PathBuf {
+        match self {
+            Backup::Autosaved => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let name = match path.extension() {
+                    Some(extension) => {
+                        format!("{stem} (Autosaved).{}", extension.to_string_lossy())
+                    }
+                    None => format!("{stem} (Autosaved)"),
+                };
+                path.with_file_name(name)
+            }
+            Backup::Suffix(suffix) => sibling(path, "", suffix),
+        }
+    }
+}
+
+/// Options for [`Font::save_with_options`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveOptions {
+    /// Keep the previous contents of the file, replacing any earlier backup
+    pub backup: Option<Backup>,
+    /// Upgrade Glyphs 2 fonts to Glyphs 3 when saving them as a
+    /// `.glyphspackage`, a format Glyphs 2 does not have
+    ///
+    /// Without this, saving a Glyphs 2 font as a package fails with
+    /// [`Error::Glyphs2NoPackage`](crate::Error::Glyphs2NoPackage). The font
+    /// itself is left as it is.
+    pub upgrade_glyphs2_packages: bool,
+}
+
+fn sibling(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(prefix);
+    name.push(path.file_name().unwrap_or_default());
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+impl Font {
+    /// Saves the font to a file or package without ever leaving a file half
+    /// written
+    ///
+    /// The font is written to a temporary sibling first, which is then
+    /// renamed into place, so a crash or full disk during the save leaves the
+    /// previous file as it was. With [`SaveOptions::backup`] set, the
+    /// previous file is moved aside to the backup rather than replaced.
+    ///
+    /// An existing package is saved in place instead, each changed file
+    /// through its own temporary sibling, so that unchanged files keep their
+    /// modification times. Its backup is a copy of it.
+    pub fn save_with_options(&self, path: &Path, options: &SaveOptions) -> Result<()> {
+        let upgraded;
+        let font = if options.upgrade_glyphs2_packages
+            && self.as_glyphs2().is_some()
+            && path.extension() == Some(OsStr::new("glyphspackage"))
+        {
+            upgraded = self.upgrade();
+            &upgraded
+        } else {
+            self
+        };
+        if path.is_dir() && path.extension() == Some(OsStr::new("glyphspackage")) {
+            if font.as_glyphs2().is_some() {
+                return Err(Error::Glyphs2NoPackage);
+            }
+            if let Some(backup) = &options.backup {
+                let aside = backup.path_for(path);
+                if aside.exists() {
+                    remove(&aside)?;
+                }
+                copy_dir(path, &aside)?;
+            }
+            return font.save(path);
+        }
+        let temp = sibling(path, ".saving-", "");
+        if temp.exists() {
+            remove(&temp)?;
+        }
+        if let Err(error) = font.save(&temp) {
+            let _ = remove(&temp);
+            return Err(error);
+        }
+        if path.exists() {
+            // A directory cannot be renamed over an existing one, so
+            // packages without a backup are moved aside and then removed.
+            let aside = match &options.backup {
+                Some(backup) => backup.path_for(path),
+                None if path.is_dir() => sibling(path, ".", ".old"),
+                None => path.to_path_buf(),
+            };
+            if aside != path {
+                if aside.exists() {
+                    remove(&aside)?;
+                }
+                fs::rename(path, &aside)?;
+            }
+            fs::rename(&temp, path)?;
+            if options.backup.is_none() && aside != path {
+                remove(&aside)?;
+            }
+        } else {
+            fs::rename(&temp, path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font_named(family_name: &str) -> Font {
+        let mut font = Font::load(Path::new("resources/WghtVar.glyphs")).unwrap();
+        if let Font::Glyphs3(glyphs3) = &mut font {
+            glyphs3.family_name = family_name.to_string();
+        }
+        font
+    }
+
+    fn family_name(path: &Path) -> String {
+        let font = Font::load(path).unwrap();
+        font.as_glyphs3().unwrap().family_name.clone()
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_save_with_suffix_backup() {
+        let dir = scratch("glyphslib-save-suffix");
+        let path = dir.join("Backed.glyphs");
+        let options = SaveOptions {
+            backup: Some(Backup::Suffix(".bak".to_string())),
+            ..Default::default()
+        };
+        font_named("First")
+            .save_with_options(&path, &options)
+            .unwrap();
+        assert!(!dir.join("Backed.glyphs.bak").exists());
+        font_named("Second")
+            .save_with_options(&path, &options)
+            .unwrap();
+        font_named("Third")
+            .save_with_options(&path, &options)
+            .unwrap();
+        assert_eq!(family_name(&path), "Third");
+        assert_eq!(family_name(&dir.join("Backed.glyphs.bak")), "Second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_package_with_autosaved_backup() {
+        let dir = scratch("glyphslib-save-autosaved");
+        let path = dir.join("Backed.glyphspackage");
+        let options = SaveOptions {
+            backup: Some(Backup::Autosaved),
+            ..Default::default()
+        };
+        font_named("First")
+            .save_with_options(&path, &options)
+            .unwrap();
+        font_named("Second")
+            .save_with_options(&path, &options)
+            .unwrap();
+        assert_eq!(family_name(&path), "Second");
+        assert_eq!(
+            family_name(&dir.join("Backed (Autosaved).glyphspackage")),
+            "First"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_package_keeps_unchanged_files() {
+        let dir = scratch("glyphslib-save-unchanged");
+        let path = dir.join("Unchanged.glyphspackage");
+        let options = SaveOptions {
+            backup: Some(Backup::Autosaved),
+            ..Default::default()
+        };
+        let font = font_named("First");
+        font.save_with_options(&path, &options).unwrap();
+        let long_ago = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000);
+        let glyph_files: Vec<PathBuf> = fs::read_dir(path.join("glyphs"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        for file in &glyph_files {
+            let file = fs::File::options().write(true).open(file).unwrap();
+            file.set_modified(long_ago).unwrap();
+        }
+
+        font_named("Second")
+            .save_with_options(&path, &options)
+            .unwrap();
+        for file in &glyph_files {
+            assert_eq!(fs::metadata(file).unwrap().modified().unwrap(), long_ago);
+        }
+        assert_eq!(family_name(&path), "Second");
+        assert_eq!(
+            family_name(&dir.join("Unchanged (Autosaved).glyphspackage")),
+            "First"
+        );
+        assert!(!path.join(".saving").exists());
+        assert!(!path.join("glyphs/.saving").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_package_without_backup_replaces() {
+        let dir = scratch("glyphslib-save-replace");
+        let path = dir.join("Replaced.glyphspackage");
+        let options = SaveOptions::default();
+        font_named("First")
+            .save_with_options(&path, &options)
+            .unwrap();
+        font_named("Second")
+            .save_with_options(&path, &options)
+            .unwrap();
+        assert_eq!(family_name(&path), "Second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_glyphs2_package_upgrades() {
+        let dir = scratch("glyphslib-save-glyphs2");
+        let path = dir.join("Upgraded.glyphspackage");
+        let font = Font::load(Path::new("resources/An-Italic.glyphs")).unwrap();
+        assert!(matches!(
+            font.save_with_options(&path, &SaveOptions::default()),
+            Err(crate::Error::Glyphs2NoPackage)
+        ));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let options = SaveOptions {
+            upgrade_glyphs2_packages: true,
+            ..Default::default()
+        };
+        font.save_with_options(&path, &options).unwrap();
+        assert_eq!(Font::load(&path).unwrap(), font.upgrade());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use openstep_plist::Plist;
+
+use crate::{
+    common::{CustomParameter, Kerning, Point},
+    glyphs3::{Glyphs3, Layer, MetricType, Shape},
+};
+
+/// How coordinates are rounded after they are changed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RoundingMode {
+    /// Keep coordinates as they come out
+    #[default]
+    None,
+    /// Round coordinates to whole units, as Glyphs does
+    Integer,
+    /// Round coordinates to multiples of a grid step; a step of 0 or less
+    /// leaves them as they are
+    Grid(f64),
+}
+
+impl RoundingMode {
+    /// Round a value
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::None => value,
+            RoundingMode::Integer => value.round(),
+            RoundingMode::Grid(step) if step > 0.0 => (value / step).round() * step,
+            RoundingMode::Grid(_) => value,
+        }
+    }
+}
+
+/// Which layers [`Glyphs3::round_to_grid`] leaves alone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GridRoundingOptions {
+    /// Leave intermediate ("brace") layers as they are
+    pub skip_brace_layers: bool,
+    /// Leave the backgrounds of layers as they are
+    pub skip_backgrounds: bool,
+}
+
+// The parameters whose values are in font units
+const SCALED_PARAMETERS: &[&str] = &[
+    "hheaAscender",
+    "hheaDescender",
+    "hheaLineGap",
+    "typoAscender",
+    "typoDescender",
+    "typoLineGap",
+    "winAscent",
+    "winDescent",
+    "underlinePosition",
+    "underlineThickness",
+    "strikeoutPosition",
+    "strikeoutSize",
+    "subscriptXOffset",
+    "subscriptXSize",
+    "subscriptYOffset",
+    "subscriptYSize",
+    "superscriptXOffset",
+    "superscriptXSize",
+    "superscriptYOffset",
+    "superscriptYSize",
+    "vheaVertAscender",
+    "vheaVertDescender",
+    "vheaVertLineGap",
+];
+
+fn scale_parameters(parameters: &mut [CustomParameter], factor: f64, rounding: RoundingMode) {
+    for parameter in parameters.iter_mut() {
+        if !SCALED_PARAMETERS.contains(&parameter.name.as_str()) {
+            continue;
+        }
+        let Some(value) = parameter.value.as_f64() else {
+            continue;
+        };
+        let scaled = rounding.apply(value * factor);
+        parameter.value = if scaled.fract() == 0.0 {
+            Plist::Integer(scaled as i64)
+        } else {
+            Plist::Float(scaled)
+        };
+    }
+}
+
+fn scale_kerning(kerning: &mut Kerning, factor: f64, rounding: RoundingMode) {
+    for value in kerning
+        .values_mut()
+        .flat_map(|pairs| pairs.values_mut())
+        .flat_map(|pairs| pairs.values_mut())
+    {
+        *value = rounding.apply(*value * factor);
+    }
+}
+
+impl Layer {
+    /// Round the nodes, anchors and component positions of the layer, but
+    /// not its background
+    pub fn round_coordinates(&mut self, rounding: RoundingMode) {
+        let round = |p: Point| Point::new(rounding.apply(p.x), rounding.apply(p.y));
+        for shape in self.shapes.iter_mut() {
+            match shape {
+                Shape::Path(path) => {
+                    for node in path.nodes.iter_mut() {
+                        node.set_position(round(node.position()));
+                    }
+                }
+                Shape::Component(component) => component.position = round(component.position),
+            }
+        }
+        for anchor in self.anchors.iter_mut() {
+            anchor.pos = round(anchor.pos);
+        }
+    }
+
+    /// Scale everything in the layer which is measured in font units: the
+    /// outlines, component positions, anchors, guides, hint positions,
+    /// background image placement, widths, and the background
+    pub fn scale(&mut self, factor: f64, rounding: RoundingMode) {
+        let scale =
+            |p: Point| Point::new(rounding.apply(p.x * factor), rounding.apply(p.y * factor));
+        for shape in self.shapes.iter_mut() {
+            match shape {
+                Shape::Path(path) => {
+                    for node in path.nodes.iter_mut() {
+                        node.set_position(scale(node.position()));
+                    }
+                }
+                Shape::Component(component) => component.position = scale(component.position),
+            }
+        }
+        for anchor in self.anchors.iter_mut() {
+            anchor.pos = scale(anchor.pos);
+        }
+        for guide in self.guides.iter_mut() {
+            guide.pos = scale(guide.pos);
+        }
+        for hint in self.hints.iter_mut() {
+            hint.place = hint.place.map(scale);
+        }
+        if let Some(image) = self.background_image.as_mut() {
+            image.pos = scale(image.pos);
+            image.scale = image.scale * factor;
+        }
+        self.width = rounding.apply(self.width * factor);
+        self.vert_width = self.vert_width.map(|w| rounding.apply(w * factor));
+        self.vert_origin = self.vert_origin.map(|o| rounding.apply(o * factor));
+        if let Some(background) = self.background.as_mut() {
+            background.scale(factor, rounding);
+        }
+    }
+}
+
+impl Glyphs3 {
+    /// The step of the font's coordinate grid: the grid length over its
+    /// subdivision, 1 unless the settings say otherwise
+    ///
+    /// A grid length of 0 turns the grid off, giving 0.
+    pub fn grid_step(&self) -> f64 {
+        let length = self.settings.grid_length.unwrap_or(1);
+        let subdivision = self.settings.grid_sub_division.unwrap_or(1).max(1);
+        length.max(0) as f64 / subdivision as f64
+    }
+
+    /// Round the nodes, anchors and component positions of every glyph
+    /// layer to the font's grid, as Glyphs' Round Coordinates does
+    ///
+    /// Does nothing if the grid is turned off.
+    pub fn round_to_grid(&mut self, options: GridRoundingOptions) {
+        let step = self.grid_step();
+        if step <= 0.0 {
+            return;
+        }
+        let rounding = RoundingMode::Grid(step);
+        for layer in self.glyphs.iter_mut().flat_map(|g| g.layers.iter_mut()) {
+            if options.skip_brace_layers && layer.is_brace_layer() {
+                continue;
+            }
+            layer.round_coordinates(rounding);
+            if let Some(background) = layer.background.as_mut() {
+                if !options.skip_backgrounds {
+                    background.round_coordinates(rounding);
+                }
+            }
+        }
+    }
+
+    /// Change the units per em of the font, scaling everything measured in
+    /// font units to match
+    ///
+    /// This scales the glyph layers as [`Layer::scale`] does, the kerning,
+    /// the metrics (but not the italic angle), stems and guides of the
+    /// masters, and the vertical metrics, underline, strikeout, subscript
+    /// and superscript parameters of the font and its masters.
+    pub fn scale_to_upm(&mut self, new_upm: u16, rounding: RoundingMode) {
+        if self.units_per_em <= 0 || i32::from(new_upm) == self.units_per_em {
+            return;
+        }
+        let factor = f64::from(new_upm) / self.units_per_em as f64;
+        for layer in self.glyphs.iter_mut().flat_map(|g| g.layers.iter_mut()) {
+            layer.scale(factor, rounding);
+        }
+        for kerning in [
+            &mut self.kerning,
+            &mut self.kerning_rtl,
+            &mut self.kerning_vertical,
+        ] {
+            scale_kerning(kerning, factor, rounding);
+        }
+        let metric_types: Vec<Option<MetricType>> =
+            self.metrics.iter().map(|m| m.metric_type).collect();
+        for master in self.masters.iter_mut() {
+            for (value, metric_type) in master.metric_values.iter_mut().zip(&metric_types) {
+                if *metric_type != Some(MetricType::ItalicAngle) {
+                    value.pos = rounding.apply(value.pos * factor);
+                    value.over = rounding.apply(value.over * factor);
+                }
+            }
+            for stem in master.stem_values.iter_mut() {
+                *stem = rounding.apply(*stem * factor);
+            }
+            for guide in master.guides.iter_mut() {
+                guide.pos = Point::new(
+                    rounding.apply(guide.pos.x * factor),
+                    rounding.apply(guide.pos.y * factor),
+                );
+            }
+            scale_parameters(&mut master.custom_parameters, factor, rounding);
+        }
+        scale_parameters(&mut self.custom_parameters, factor, rounding);
+        self.units_per_em = new_upm.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::{collections::BTreeMap, path};
+
+    #[test]
+    fn test_scale_to_upm() {
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(mut original) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let pair = BTreeMap::from([("a".to_string(), BTreeMap::from([("v".to_string(), -15.5)]))]);
+        original
+            .kerning
+            .insert(original.masters[0].id.clone(), pair);
+        let upm = original.units_per_em as f64;
+        let mut font = original.clone();
+        font.scale_to_upm((upm * 2.0) as u16, RoundingMode::None);
+        assert_eq!(font.units_per_em as f64, upm * 2.0);
+        let master_id = &font.masters[0].id;
+        let layer = |font: &Glyphs3| {
+            font.glyph("a")
+                .unwrap()
+                .layers
+                .iter()
+                .find(|l| &l.layer_id == master_id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(layer(&font).width, layer(&original).width * 2.0);
+        assert_eq!(
+            layer(&font).bounds().unwrap().x_max,
+            layer(&original).bounds().unwrap().x_max * 2.0
+        );
+        assert_eq!(
+            font.masters[0].x_height(&font).unwrap(),
+            original.masters[0].x_height(&original).unwrap() * 2.0
+        );
+        let pairs = |font: &Glyphs3| {
+            font.kerning
+                .values()
+                .flat_map(|k| k.values())
+                .flat_map(|p| p.values().copied())
+                .collect::<Vec<f64>>()
+        };
+        assert!(!pairs(&original).is_empty());
+        let doubled: Vec<f64> = pairs(&original).iter().map(|v| v * 2.0).collect();
+        assert_eq!(pairs(&font), doubled);
+
+        let mut font = original.clone();
+        font.scale_to_upm(2048, RoundingMode::Integer);
+        assert!(layer(&font)
+            .shapes
+            .iter()
+            .filter_map(|s| match s {
+                Shape::Path(path) => Some(path),
+                _ => None,
+            })
+            .flat_map(|p| &p.nodes)
+            .all(|n| n.x.fract() == 0.0 && n.y.fract() == 0.0));
+    }
+
+    #[test]
+    fn test_round_to_grid() {
+        assert_eq!(RoundingMode::Grid(0.5).apply(10.3), 10.5);
+        assert_eq!(RoundingMode::Grid(10.0).apply(-14.0), -10.0);
+        assert_eq!(RoundingMode::Grid(0.0).apply(10.3), 10.3);
+
+        let file = path::Path::new("resources/IntermediateLayer.glyphs");
+        let Font::Glyphs3(mut font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        font.settings.grid_length = Some(10);
+        font.settings.grid_sub_division = Some(2);
+        assert_eq!(font.grid_step(), 5.0);
+        let nodes = |font: &Glyphs3, brace: bool| -> Vec<(f64, f64)> {
+            font.glyphs
+                .iter()
+                .flat_map(|g| &g.layers)
+                .filter(|l| l.is_brace_layer() == brace)
+                .flat_map(|l| &l.shapes)
+                .filter_map(|s| match s {
+                    Shape::Path(path) => Some(path),
+                    _ => None,
+                })
+                .flat_map(|p| p.nodes.iter().map(|n| (n.x, n.y)))
+                .collect()
+        };
+        let braces = nodes(&font, true);
+        assert!(braces.iter().any(|(x, _)| x % 5.0 != 0.0));
+        font.round_to_grid(GridRoundingOptions {
+            skip_brace_layers: true,
+            ..Default::default()
+        });
+        assert!(nodes(&font, false)
+            .iter()
+            .all(|(x, y)| x % 5.0 == 0.0 && y % 5.0 == 0.0));
+        assert_eq!(nodes(&font, true), braces);
+    }
+}
@@ -1,19 +1,21 @@
 // Serde extensions for Glyphs data structures.
 
 use itertools::Itertools;
+use openstep_plist::ser::BlockArray;
 use std::fmt;
 
 use serde::{
     de::Visitor,
-    ser::{SerializeSeq, SerializeStruct, SerializeTuple},
+    ser::{SerializeSeq, SerializeTuple},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 use serde_with::SerializeAs;
 
 use crate::{
-    common::NodeType,
+    common::{Color, NodeType, Point, Scale, WeightClass, WidthClass},
     glyphs2::{self, AlignmentZone, CropRect},
     glyphs3::{self, MetricType},
+    other_keys::MergedMap,
 };
 
 pub(crate) fn is_false(b: &bool) -> bool {
@@ -26,11 +28,11 @@ pub(crate) fn bool_true() -> bool {
     true
 }
 
-pub(crate) fn scale_unit() -> (f32, f32) {
+pub(crate) fn scale_unit() -> (f64, f64) {
     (1.0, 1.0)
 }
 
-pub(crate) fn is_scale_unit(scale: &(f32, f32)) -> bool {
+pub(crate) fn is_scale_unit(scale: &(f64, f64)) -> bool {
     *scale == (1.0, 1.0)
 }
 pub(crate) fn is_default<T>(v: &T) -> bool
@@ -105,6 +107,74 @@ impl<'de> Visitor<'de> for SimpleNodeVisitor {
     }
 }
 
+// Points and scales are written as the pairs they used to be
+impl Serialize for Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (self.x, self.y).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <(f64, f64)>::deserialize(deserializer).map(Point::from)
+    }
+}
+
+impl Serialize for Scale {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (self.x, self.y).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scale {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <(f64, f64)>::deserialize(deserializer).map(Scale::from)
+    }
+}
+
+// Colors are label indices or inline tuples of components
+impl Serialize for Color {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Color::Index(index) => serializer.serialize_u8(*index),
+            color => serialize_inline(&color.components(), serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Encoded {
+            Index(u8),
+            Components(Vec<u8>),
+        }
+        match Encoded::deserialize(deserializer)? {
+            Encoded::Index(index) => Ok(Color::Index(index)),
+            Encoded::Components(components) => Ok(Color::from_components(components)),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for glyphs3::Node {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -136,22 +206,29 @@ impl Serialize for glyphs2::Node {
         S: serde::Serializer,
     {
         // string X Y (full node type) (SMOOTH)?
-        let node_type = match self.node_type {
-            NodeType::Line => "LINE",
+        let node_type = match self.node_type.base_type() {
             NodeType::Curve => "CURVE",
             NodeType::QCurve => "QCURVE",
             NodeType::OffCurve => "OFFCURVE",
-            NodeType::LineSmooth => "LINE SMOOTH",
-            NodeType::CurveSmooth => "CURVE SMOOTH",
-            NodeType::QCurveSmooth => "QCURVE SMOOTH",
+            _ => "LINE",
         };
-        let node_str = format!("{} {} {}", self.x, self.y, node_type);
-        let mut seq = serializer.serialize_seq(None)?;
-        seq.serialize_element(&node_str)?;
-        seq.end()
+        let smooth = if self.node_type.is_smooth() {
+            " SMOOTH"
+        } else {
+            ""
+        };
+        serializer.serialize_str(&format!("{} {} {node_type}{smooth}", self.x, self.y))
     }
 }
 
+// Glyphs 2 writes one node per line
+pub(crate) fn serialize_nodes<S>(nodes: &[glyphs2::Node], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    BlockArray(nodes).serialize(serializer)
+}
+
 impl<'de> Deserialize<'de> for glyphs2::Node {
     fn deserialize<D>(deserializer: D) -> Result<glyphs2::Node, D::Error>
     where
@@ -180,22 +257,21 @@ impl Visitor<'_> for NodeVisitor {
             return Err(E::custom("not enough parts"));
         }
         let x = parts[0]
-            .parse::<f32>()
+            .parse::<f64>()
             .map_err(|_| E::custom("could not parse x"))?;
         let y = parts[1]
-            .parse::<f32>()
+            .parse::<f64>()
             .map_err(|_| E::custom("could not parse y"))?;
-        let smooth = parts.len() > 3 && parts[3] == "SMOOTH";
-        let node_type = match (parts[2], smooth) {
-            ("LINE", false) => NodeType::Line,
-            ("CURVE", false) => NodeType::Curve,
-            ("QCURVE", false) => NodeType::QCurve,
+        // Glyphs 1 files are not always upper case
+        let smooth = parts.len() > 3 && parts[3].eq_ignore_ascii_case("SMOOTH");
+        let node_type = match (parts[2].to_ascii_uppercase().as_str(), smooth) {
+            ("LINE", _) => NodeType::Line,
+            ("CURVE", _) => NodeType::Curve,
+            ("QCURVE", _) => NodeType::QCurve,
             ("OFFCURVE", false) => NodeType::OffCurve,
-            ("LINE", true) => NodeType::LineSmooth,
-            ("CURVE", true) => NodeType::CurveSmooth,
-            ("QCURVE", true) => NodeType::QCurveSmooth,
             _ => return Err(E::custom("unknown node type")),
-        };
+        }
+        .with_smooth(smooth);
         Ok(glyphs2::Node { x, y, node_type })
     }
 }
@@ -205,81 +281,81 @@ impl Serialize for glyphs3::Layer {
     where
         S: Serializer,
     {
-        let fields = 24; // Nobody's actually counting.
-        let mut seq = serializer.serialize_struct("Layer", fields)?;
+        // A map rather than a struct, so that the other keys can be merged in
+        let mut seq = MergedMap::new(serializer.serialize_map(None)?, &self.other);
         if !self.anchors.is_empty() {
-            seq.serialize_field("anchors", &self.anchors)?;
+            seq.entry("anchors", &self.anchors)?;
         }
         if !self.annotations.is_empty() {
-            seq.serialize_field("annotations", &self.annotations)?;
+            seq.entry("annotations", &self.annotations)?;
         }
         if let Some(master_id) = &self.associated_master_id {
-            seq.serialize_field("associatedMasterId", master_id)?;
+            seq.entry("associatedMasterId", master_id)?;
         }
         if !self.attr.is_empty() {
-            seq.serialize_field("attr", &self.attr)?;
+            seq.entry("attr", &self.attr)?;
         }
         if let Some(background) = &self.background {
-            seq.serialize_field("background", background)?;
+            seq.entry("background", background)?;
         }
         if let Some(background_image) = &self.background_image {
-            seq.serialize_field("backgroundImage", background_image)?;
+            seq.entry("backgroundImage", background_image)?;
         }
         if let Some(color) = &self.color {
-            seq.serialize_field("color", color)?;
+            seq.entry("color", color)?;
         }
         if !self.guides.is_empty() {
-            seq.serialize_field("guides", &self.guides)?;
+            seq.entry("guides", &self.guides)?;
         }
         if !self.hints.is_empty() {
-            seq.serialize_field("hints", &self.hints)?;
+            seq.entry("hints", &self.hints)?;
         }
         if !self.layer_id.is_empty() {
-            seq.serialize_field("layerId", &self.layer_id)?;
+            seq.entry("layerId", &self.layer_id)?;
         }
         if let Some(metric_bottom) = &self.metric_bottom {
-            seq.serialize_field("metricBottom", metric_bottom)?;
+            seq.entry("metricBottom", metric_bottom)?;
         }
         if let Some(metric_left) = &self.metric_left {
-            seq.serialize_field("metricLeft", metric_left)?;
+            seq.entry("metricLeft", metric_left)?;
         }
         if let Some(metric_right) = &self.metric_right {
-            seq.serialize_field("metricRight", metric_right)?;
+            seq.entry("metricRight", metric_right)?;
         }
         if let Some(metric_top) = &self.metric_top {
-            seq.serialize_field("metricTop", metric_top)?;
+            seq.entry("metricTop", metric_top)?;
         }
         if let Some(metric_vert_width) = &self.metric_vert_width {
-            seq.serialize_field("metricVertWidth", metric_vert_width)?;
+            seq.entry("metricVertWidth", metric_vert_width)?;
         }
         if let Some(metric_width) = &self.metric_width {
-            seq.serialize_field("metricWidth", metric_width)?;
+            seq.entry("metricWidth", metric_width)?;
         }
         if let Some(name) = &self.name {
-            seq.serialize_field("name", name)?;
+            seq.entry("name", name)?;
         }
         if !self.part_selection.is_empty() {
-            seq.serialize_field("partSelection", &self.part_selection)?;
+            seq.entry("partSelection", &self.part_selection)?;
         }
         if !self.shapes.is_empty() {
-            seq.serialize_field("shapes", &self.shapes)?;
+            seq.entry("shapes", &self.shapes)?;
         }
         if !self.user_data.is_empty() {
-            seq.serialize_field("userData", &self.user_data)?;
+            seq.entry("userData", &self.user_data)?;
         }
         if let Some(vert_origin) = &self.vert_origin {
-            seq.serialize_field("vertOrigin", vert_origin)?;
+            seq.entry("vertOrigin", vert_origin)?;
         }
         if let Some(vert_width) = &self.vert_width {
-            seq.serialize_field("vertWidth", vert_width)?;
+            seq.entry("vertWidth", vert_width)?;
         }
         if !self.visible {
-            seq.serialize_field("visible", &self.visible)?;
+            seq.entry("visible", &self.visible)?;
         }
         if self.width != 0.0 || !self.layer_id.is_empty() {
-            seq.serialize_field("width", &self.width)?;
+            seq.entry("width", &self.width)?;
         }
-        seq.end()
+        seq.finish()
     }
 }
 
@@ -321,6 +397,130 @@ impl<'de> Visitor<'de> for AnythingToBoolVisitor {
     }
 }
 
+// Glyphs 1 files may have numbers where later versions have strings, such
+// as a bare `.appVersion = 895;`
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserializer.deserialize_any(StringOrNumberVisitor)
+}
+
+struct StringOrNumberVisitor;
+
+impl Visitor<'_> for StringOrNumberVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string or a number")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+}
+
+// Weight and width classes are names in Glyphs 2 files and numbers in
+// Glyphs 3 files, but either may be found in both
+macro_rules! os2_class_serde {
+    ($class:ident, $expecting:literal) => {
+        impl Serialize for $class {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self.name() {
+                    Some(name) => serializer.serialize_str(name),
+                    None => serializer.serialize_u16(self.to_os2()),
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $class {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct ClassVisitor;
+
+                impl Visitor<'_> for ClassVisitor {
+                    type Value = $class;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<$class, E> {
+                        if let Ok(value) = v.parse() {
+                            return self.visit_u64(value);
+                        }
+                        $class::from_name(v).ok_or_else(|| E::custom(format!("unknown {v}")))
+                    }
+
+                    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<$class, E> {
+                        u16::try_from(v)
+                            .map($class::from_os2)
+                            .map_err(|_| E::custom(format!("{v} is out of range")))
+                    }
+
+                    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<$class, E> {
+                        u64::try_from(v)
+                            .map_err(|_| E::custom(format!("{v} is out of range")))
+                            .and_then(|v| self.visit_u64(v))
+                    }
+                }
+
+                deserializer.deserialize_any(ClassVisitor)
+            }
+        }
+    };
+}
+
+os2_class_serde!(WeightClass, "a weight class name or number");
+os2_class_serde!(WidthClass, "a width class name or number");
+
+// Glyphs 3 writes weight and width classes as numbers
+pub(crate) fn serialize_os2_weight<S>(
+    value: &Option<WeightClass>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(class) => serializer.serialize_some(&class.to_os2()),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(crate) fn serialize_os2_width<S>(
+    value: &Option<WidthClass>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(class) => serializer.serialize_some(&class.to_os2()),
+        None => serializer.serialize_none(),
+    }
+}
+
 pub(crate) struct SerializeAsTuple<U> {
     _marker: std::marker::PhantomData<U>,
 }
@@ -350,6 +550,20 @@ where
     }
 }
 
+// Write a list inline, as Glyphs does for colors, rather than one element
+// per line
+pub(crate) fn serialize_inline<S, T>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    let mut tuple = serializer.serialize_tuple(values.len())?;
+    for value in values {
+        tuple.serialize_element(value)?;
+    }
+    tuple.end()
+}
+
 pub fn serialize_comma_hexstring<S>(value: &[u32], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -391,29 +605,22 @@ impl<'de> Visitor<'de> for CommaHexStringVisitor {
         E: serde::de::Error,
     {
         // If the value is a single integer - it isn't! It's a hex string
-        let s = format!("{value:04X}");
-
-        Ok(vec![
-            u32::from_str_radix(&s, 16).map_err(serde::de::Error::custom)?
-        ])
+        // whose digits happen to be decimal, such as `2074`
+        self.visit_str(&value.to_string())
     }
 
     fn visit_i64<E>(self, value: i64) -> Result<Vec<u32>, E>
     where
         E: serde::de::Error,
     {
-        // If the value is a single integer - it isn't! It's a hex string
-        let s = format!("{value:04X}");
-
-        Ok(vec![
-            u32::from_str_radix(&s, 16).map_err(serde::de::Error::custom)?
-        ])
+        self.visit_str(&value.to_string())
     }
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        let codepoints = v.split(',');
+        // Older files may put spaces after the commas
+        let codepoints = v.split(',').map(str::trim);
         let mut result = Vec::new();
         for codepoint in codepoints {
             result.push(u32::from_str_radix(codepoint, 16).map_err(serde::de::Error::custom)?);
@@ -429,8 +636,8 @@ pub(crate) trait CurlyBraceReceiver<T> {
         Self: Sized;
 }
 
-impl CurlyBraceReceiver<f32> for (f32, f32) {
-    fn try_from_parts(parts: impl Iterator<Item = f32>) -> Result<Self, String> {
+impl CurlyBraceReceiver<f64> for (f64, f64) {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
         let mut iter = parts.into_iter();
         let first = iter.next().ok_or_else(|| "Expected 2 parts".to_string())?;
         let second = iter.next().ok_or_else(|| "Expected 2 parts".to_string())?;
@@ -438,16 +645,28 @@ impl CurlyBraceReceiver<f32> for (f32, f32) {
     }
 }
 
+impl CurlyBraceReceiver<f64> for Point {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
+        <(f64, f64)>::try_from_parts(parts).map(Point::from)
+    }
+}
+
+impl CurlyBraceReceiver<f64> for Scale {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
+        <(f64, f64)>::try_from_parts(parts).map(Scale::from)
+    }
+}
+
 pub(crate) struct CurlyBraceVisitor<T>
 where
-    T: CurlyBraceReceiver<f32>, // Maybe there's an argument for being EVEN MORE GENERIC but I think we're quite generic enough
+    T: CurlyBraceReceiver<f64>, // Maybe there's an argument for being EVEN MORE GENERIC but I think we're quite generic enough
 {
     pub(crate) _marker: std::marker::PhantomData<T>,
 }
 
 impl<T> Default for CurlyBraceVisitor<T>
 where
-    T: CurlyBraceReceiver<f32>,
+    T: CurlyBraceReceiver<f64>,
 {
     fn default() -> Self {
         CurlyBraceVisitor {
@@ -458,7 +677,7 @@ where
 
 impl<T> Visitor<'_> for CurlyBraceVisitor<T>
 where
-    T: CurlyBraceReceiver<f32>,
+    T: CurlyBraceReceiver<f64>,
 {
     type Value = T;
 
@@ -471,11 +690,11 @@ where
         E: serde::de::Error,
     {
         let parts = value.trim_matches(|c| c == '{' || c == '}').split(',');
-        let parts_vec: Vec<f32> = parts
+        let parts_vec: Vec<f64> = parts
             .map(|s| {
                 s.trim()
-                    .parse::<f32>()
-                    .map_err(|e| E::custom(format!("failed to parse '{s}' as f32: {e}")))
+                    .parse::<f64>()
+                    .map_err(|e| E::custom(format!("failed to parse '{s}' as f64: {e}")))
             })
             .collect::<Result<Vec<_>, _>>()?;
         T::try_from_parts(parts_vec.into_iter())
@@ -488,13 +707,13 @@ where
     where
         E: serde::de::Error,
     {
-        T::try_from_parts(std::iter::once(value as f32)).map_err(|e| E::custom(e))
+        T::try_from_parts(std::iter::once(value)).map_err(|e| E::custom(e))
     }
     fn visit_i64<E>(self, value: i64) -> Result<T, E>
     where
         E: serde::de::Error,
     {
-        T::try_from_parts(std::iter::once(value as f32)).map_err(|e| E::custom(e))
+        T::try_from_parts(std::iter::once(value as f64)).map_err(|e| E::custom(e))
     }
 }
 
@@ -505,17 +724,33 @@ pub(crate) trait MyIntoIterator<'a> {
 
     fn into_iter(self) -> Self::IntoIter;
 }
-impl MyIntoIterator<'_> for &(f32, f32) {
-    type Item = f32;
-    type IntoIter = std::array::IntoIter<f32, 2>;
+impl MyIntoIterator<'_> for &(f64, f64) {
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 2>;
 
     fn into_iter(self) -> Self::IntoIter {
         [self.0, self.1].into_iter()
     }
 }
-impl<'a> MyIntoIterator<'a> for &'a Vec<f32> {
-    type Item = f32;
-    type IntoIter = std::iter::Copied<std::slice::Iter<'a, f32>>;
+impl MyIntoIterator<'_> for &Point {
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [self.x, self.y].into_iter()
+    }
+}
+impl MyIntoIterator<'_> for &Scale {
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [self.x, self.y].into_iter()
+    }
+}
+impl<'a> MyIntoIterator<'a> for &'a Vec<f64> {
+    type Item = f64;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, f64>>;
 
     fn into_iter(self) -> Self::IntoIter {
         std::iter::IntoIterator::into_iter(self).copied()
@@ -525,13 +760,13 @@ impl<'a> MyIntoIterator<'a> for &'a Vec<f32> {
 pub(crate) fn serialize_commify<'a, S, T>(value: &'a T, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
-    T: CurlyBraceReceiver<f32> + 'a,
-    &'a T: MyIntoIterator<'a, Item = f32>,
+    T: CurlyBraceReceiver<f64> + 'a,
+    &'a T: MyIntoIterator<'a, Item = f64>,
 {
-    let values: Vec<f32> = value.into_iter().collect();
+    let values: Vec<f64> = value.into_iter().collect();
     if values.len() == 1 {
         // Single value - serialize as a raw number, not a string with braces
-        return serializer.serialize_f32(values[0]);
+        return serializer.serialize_f64(values[0]);
     }
     let middle: String = values.iter().map(|x| x.to_string()).join(", ");
     serializer.serialize_str(&format!("{{{middle}}}"))
@@ -540,7 +775,7 @@ where
 pub(crate) fn deserialize_commify<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: serde::Deserializer<'de>,
-    T: CurlyBraceReceiver<f32>,
+    T: CurlyBraceReceiver<f64>,
 {
     deserializer.deserialize_any(CurlyBraceVisitor::<T>::default())
 }
@@ -625,17 +860,17 @@ impl Serialize for glyphs2::Transform {
     }
 }
 impl MyIntoIterator<'_> for &glyphs2::Transform {
-    type Item = f32;
-    type IntoIter = std::array::IntoIter<f32, 6>;
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 6>;
 
     fn into_iter(self) -> Self::IntoIter {
         [self.m11, self.m12, self.m21, self.m22, self.t_x, self.t_y].into_iter()
     }
 }
 
-impl CurlyBraceReceiver<f32> for glyphs2::Transform {
-    fn try_from_parts(parts: impl Iterator<Item = f32>) -> Result<Self, String> {
-        let parts: Vec<f32> = parts.into_iter().collect();
+impl CurlyBraceReceiver<f64> for glyphs2::Transform {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
+        let parts: Vec<f64> = parts.into_iter().collect();
         if parts.len() != 6 {
             return Err(format!(
                 "Expected exactly 6 parts for glyphs2::Transform, got {}",
@@ -654,8 +889,8 @@ impl CurlyBraceReceiver<f32> for glyphs2::Transform {
 }
 
 impl IntoIterator for &glyphs2::Transform {
-    type Item = f32;
-    type IntoIter = std::array::IntoIter<f32, 6>;
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 6>;
 
     fn into_iter(self) -> Self::IntoIter {
         [self.m11, self.m12, self.m21, self.m22, self.t_x, self.t_y].into_iter()
@@ -712,16 +947,16 @@ impl Serialize for AlignmentZone {
     }
 }
 impl MyIntoIterator<'_> for &AlignmentZone {
-    type Item = f32;
-    type IntoIter = std::array::IntoIter<f32, 2>;
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 2>;
 
     fn into_iter(self) -> Self::IntoIter {
         [self.position, self.overshoot].into_iter()
     }
 }
 
-impl CurlyBraceReceiver<f32> for AlignmentZone {
-    fn try_from_parts(parts: impl Iterator<Item = f32>) -> Result<Self, String> {
+impl CurlyBraceReceiver<f64> for AlignmentZone {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
         let mut iter = parts.into_iter();
         let position = iter.next().ok_or_else(|| "Expected 2 parts".to_string())?;
         let overshoot = iter.next().ok_or_else(|| "Expected 2 parts".to_string())?;
@@ -733,8 +968,8 @@ impl CurlyBraceReceiver<f32> for AlignmentZone {
 }
 
 impl IntoIterator for &AlignmentZone {
-    type Item = f32;
-    type IntoIter = std::array::IntoIter<f32, 2>;
+    type Item = f64;
+    type IntoIter = std::array::IntoIter<f64, 2>;
 
     fn into_iter(self) -> Self::IntoIter {
         [self.position, self.overshoot].into_iter()
@@ -759,8 +994,8 @@ where
     })
 }
 
-impl CurlyBraceReceiver<f32> for Vec<f32> {
-    fn try_from_parts(parts: impl Iterator<Item = f32>) -> Result<Self, String> {
+impl CurlyBraceReceiver<f64> for Vec<f64> {
+    fn try_from_parts(parts: impl Iterator<Item = f64>) -> Result<Self, String> {
         Ok(parts.collect())
     }
 }
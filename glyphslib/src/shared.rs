@@ -0,0 +1,288 @@
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use crate::{
+    glyphs3::{Glyph, Glyphs3},
+    Font,
+};
+
+/// An immutable view of a [`SharedFont`] at one moment
+///
+/// Snapshots are cheap to take and to hold: glyphs are reference counted and
+/// shared with other snapshots until an edit changes them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    info: Arc<Glyphs3>,
+    glyphs: Vec<Arc<Glyph>>,
+}
+
+impl Snapshot {
+    fn new(mut font: Glyphs3) -> Self {
+        let glyphs = std::mem::take(&mut font.glyphs)
+            .into_iter()
+            .map(Arc::new)
+            .collect();
+        Snapshot {
+            info: Arc::new(font),
+            glyphs,
+        }
+    }
+
+    /// The font-wide data, such as masters, axes and kerning
+    ///
+    /// Glyphs are held separately, so the `glyphs` field of this is empty.
+    pub fn info(&self) -> &Glyphs3 {
+        &self.info
+    }
+
+    /// The glyphs of the font, in order
+    pub fn glyphs(&self) -> impl Iterator<Item = &Glyph> {
+        self.glyphs.iter().map(|glyph| glyph.as_ref())
+    }
+
+    /// Get a glyph by name
+    pub fn glyph(&self, name: &str) -> Option<&Glyph> {
+        self.glyphs().find(|glyph| glyph.name == name)
+    }
+
+    /// Whether the named glyph is the very same, unchanged, in both snapshots
+    ///
+    /// This is a pointer comparison, so it tells a renderer which glyphs it
+    /// need not redraw without comparing their contents.
+    pub fn glyph_unchanged(&self, other: &Snapshot, name: &str) -> bool {
+        let find = |snapshot: &Snapshot| {
+            snapshot
+                .glyphs
+                .iter()
+                .find(|glyph| glyph.name == name)
+                .map(Arc::as_ptr)
+        };
+        match (find(self), find(other)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Assemble a standalone copy of the font
+    pub fn to_glyphs3(&self) -> Glyphs3 {
+        Glyphs3 {
+            glyphs: self.glyphs().cloned().collect(),
+            ..self.info().clone()
+        }
+    }
+}
+
+/// A set of changes to a [`SharedFont`], made visible together on commit
+///
+/// Only the parts of the font which are changed are copied: the first
+/// mutable access to a glyph clones that glyph alone.
+#[derive(Debug)]
+pub struct Transaction {
+    working: Snapshot,
+}
+
+impl Transaction {
+    /// The font as changed so far
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.working
+    }
+
+    /// Mutable access to the font-wide data
+    ///
+    /// Glyphs pushed onto its `glyphs` field are appended to the font on commit.
+    pub fn info_mut(&mut self) -> &mut Glyphs3 {
+        Arc::make_mut(&mut self.working.info)
+    }
+
+    /// Mutable access to a glyph by name
+    pub fn glyph_mut(&mut self, name: &str) -> Option<&mut Glyph> {
+        self.working
+            .glyphs
+            .iter_mut()
+            .find(|glyph| glyph.name == name)
+            .map(Arc::make_mut)
+    }
+
+    /// Add a glyph to the end of the font
+    pub fn push_glyph(&mut self, glyph: Glyph) {
+        self.working.glyphs.push(Arc::new(glyph));
+    }
+
+    /// Remove a glyph by name, returning it if it was present
+    pub fn remove_glyph(&mut self, name: &str) -> Option<Glyph> {
+        let ix = self
+            .working
+            .glyphs
+            .iter()
+            .position(|glyph| glyph.name == name)?;
+        let glyph = self.working.glyphs.remove(ix);
+        Some(Arc::try_unwrap(glyph).unwrap_or_else(|shared| (*shared).clone()))
+    }
+
+    fn finish(mut self) -> Snapshot {
+        if !self.working.info.glyphs.is_empty() {
+            let added = std::mem::take(&mut Arc::make_mut(&mut self.working.info).glyphs);
+            self.working.glyphs.extend(added.into_iter().map(Arc::new));
+        }
+        self.working
+    }
+}
+
+/// A Glyphs 3 font which many threads can read while one edits it
+///
+/// Cloning a `SharedFont` gives another handle to the same font. Readers take
+/// a [`Snapshot`], which never changes under them; editors make changes in a
+/// [`Transaction`], which replaces the current snapshot in one step when it
+/// commits. Edits are applied one at a time, so no change is lost to a
+/// concurrent edit.
+///
+/// ```
+/// use glyphslib::{glyphs3::Glyphs3, shared::SharedFont};
+///
+/// let font = SharedFont::new(Glyphs3::default());
+/// let before = font.snapshot();
+/// font.edit(|edit| edit.info_mut().family_name = "Renamed".to_string());
+/// assert_eq!(before.info().family_name, "");
+/// assert_eq!(font.snapshot().info().family_name, "Renamed");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedFont {
+    current: Arc<RwLock<Arc<Snapshot>>>,
+    writer: Arc<Mutex<()>>,
+}
+
+impl SharedFont {
+    /// Share a font
+    pub fn new(font: Glyphs3) -> Self {
+        SharedFont {
+            current: Arc::new(RwLock::new(Arc::new(Snapshot::new(font)))),
+            writer: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// The font as of the most recent commit
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        // A panic elsewhere cannot leave the snapshot half-written, as it is
+        // only ever replaced whole, so a poisoned lock is still safe to use.
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Make changes to the font and commit them
+    pub fn edit<R>(&self, f: impl FnOnce(&mut Transaction) -> R) -> R {
+        match self.try_edit(|edit| Ok::<_, std::convert::Infallible>(f(edit))) {
+            Ok(result) => result,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Make changes to the font, committing them only if `f` succeeds
+    pub fn try_edit<T, E>(&self, f: impl FnOnce(&mut Transaction) -> Result<T, E>) -> Result<T, E> {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut transaction = Transaction {
+            working: (*self.snapshot()).clone(),
+        };
+        let result = f(&mut transaction)?;
+        let committed = Arc::new(transaction.finish());
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = committed;
+        Ok(result)
+    }
+
+    /// A standalone copy of the font as of the most recent commit
+    pub fn to_font(&self) -> Font {
+        Font::Glyphs3(self.snapshot().to_glyphs3())
+    }
+}
+
+impl From<Glyphs3> for SharedFont {
+    fn from(font: Glyphs3) -> Self {
+        SharedFont::new(font)
+    }
+}
+
+impl From<Font> for SharedFont {
+    /// Share a font, upgrading it first if it is in Glyphs 2 format
+    fn from(font: Font) -> Self {
+        match font {
+            Font::Glyphs3(glyphs3) => SharedFont::new(glyphs3),
+            Font::Glyphs2(_) => match font.upgrade() {
+                Font::Glyphs3(glyphs3) => SharedFont::new(glyphs3),
+                Font::Glyphs2(_) => unreachable!("upgrade always gives a Glyphs 3 font"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{path, thread};
+
+    fn load() -> SharedFont {
+        Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs"))
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_edit_copies_only_changed_glyphs() {
+        let font = load();
+        let before = font.snapshot();
+        font.edit(|edit| {
+            edit.glyph_mut("A").unwrap().layers[0].width = 1234.0;
+        });
+        let after = font.snapshot();
+        assert_ne!(before.glyph("A").unwrap().layers[0].width, 1234.0);
+        assert_eq!(after.glyph("A").unwrap().layers[0].width, 1234.0);
+        assert!(!before.glyph_unchanged(&after, "A"));
+        assert!(before.glyph_unchanged(&after, "B"));
+    }
+
+    #[test]
+    fn test_failed_edit_is_not_committed() {
+        let font = load();
+        let result: Result<(), &str> = font.try_edit(|edit| {
+            edit.remove_glyph("A");
+            Err("changed my mind")
+        });
+        assert!(result.is_err());
+        assert!(font.snapshot().glyph("A").is_some());
+    }
+
+    #[test]
+    fn test_round_trip_through_shared() {
+        let original = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let shared = SharedFont::from(original.clone());
+        assert_eq!(shared.to_font(), original);
+    }
+
+    #[test]
+    fn test_concurrent_readers_see_whole_commits() {
+        let font = load();
+        let count = font.snapshot().glyphs().count();
+        font.edit(|edit| edit.info_mut().family_name = "Edited 0".to_string());
+        let reader = {
+            let font = font.clone();
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    let snapshot = font.snapshot();
+                    let n = snapshot.glyphs().count();
+                    // Each edit below adds a glyph and renames the family together
+                    assert_eq!(snapshot.info().family_name, format!("Edited {}", n - count));
+                }
+            })
+        };
+        for ix in 1..=50 {
+            font.edit(|edit| {
+                edit.push_glyph(Glyph {
+                    name: format!("new{ix}"),
+                    ..Default::default()
+                });
+                edit.info_mut().family_name = format!("Edited {ix}");
+            });
+        }
+        reader.join().unwrap();
+        assert_eq!(font.snapshot().glyphs().count(), count + 50);
+    }
+}
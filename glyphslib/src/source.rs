@@ -0,0 +1,182 @@
+use std::{collections::HashMap, io};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Somewhere the files of a `.glyphspackage` are kept
+///
+/// Paths are relative to the root of the package and separated by `/`, such
+/// as `fontinfo.plist` or `glyphs/A_.glyph`. Implement this to load and save
+/// packages held in archives, version control or anywhere else; see
+/// [`Font::load_package_from`](crate::Font::load_package_from) and
+/// [`Font::save_package_to`](crate::Font::save_package_to).
+pub trait PackageSource {
+    /// Read the file at the path
+    ///
+    /// A missing file must be an error of kind [`io::ErrorKind::NotFound`].
+    fn read_file(&self, path: &str) -> io::Result<String>;
+
+    /// Write the file at the path, replacing any file already there
+    fn write_file(&mut self, path: &str, contents: &str) -> io::Result<()>;
+
+    /// Remove the file at the path
+    ///
+    /// Saving removes the files of glyphs which have been renamed or deleted.
+    fn remove_file(&mut self, path: &str) -> io::Result<()>;
+
+    /// The paths of the files in the `glyphs` directory of the package
+    fn glyph_files(&self) -> io::Result<Vec<String>>;
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no {path} in package"))
+}
+
+/// A package held in memory, keyed by path
+impl PackageSource for HashMap<String, String> {
+    fn read_file(&self, path: &str) -> io::Result<String> {
+        self.get(path).cloned().ok_or_else(|| not_found(path))
+    }
+
+    fn write_file(&mut self, path: &str, contents: &str) -> io::Result<()> {
+        self.insert(path.to_string(), contents.to_string());
+        Ok(())
+    }
+
+    fn remove_file(&mut self, path: &str) -> io::Result<()> {
+        self.remove(path).map(drop).ok_or_else(|| not_found(path))
+    }
+
+    fn glyph_files(&self) -> io::Result<Vec<String>> {
+        Ok(self
+            .keys()
+            .filter(|path| path.starts_with("glyphs/"))
+            .cloned()
+            .collect())
+    }
+}
+
+/// A package in a directory on disk
+///
+/// Files whose contents would not change are not written, so they keep
+/// their modification times for build systems watching them. The others are
+/// written to a temporary sibling and renamed into place.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directory {
+    root: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Directory {
+    /// The package in the given directory, which need not exist yet
+    pub fn new(root: &Path) -> Self {
+        Directory {
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PackageSource for Directory {
+    fn read_file(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(self.root.join(path))
+    }
+
+    fn write_file(&mut self, path: &str, contents: &str) -> io::Result<()> {
+        let file = self.root.join(path);
+        if fs::read(&file).is_ok_and(|existing| existing == contents.as_bytes()) {
+            return Ok(());
+        }
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Written whole to a sibling first, so the file is never left half
+        // written. The sibling's name is short, as glyph file names can
+        // already be as long as names can be.
+        let temp = file.with_file_name(".saving");
+        if let Err(error) = fs::write(&temp, contents).and_then(|()| fs::rename(&temp, &file)) {
+            let _ = fs::remove_file(&temp);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&mut self, path: &str) -> io::Result<()> {
+        fs::remove_file(self.root.join(path))
+    }
+
+    fn glyph_files(&self) -> io::Result<Vec<String>> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(self.root.join("glyphs"))? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            paths.push(format!("glyphs/{file_name}"));
+        }
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+
+    #[test]
+    fn test_in_memory_package() {
+        let font = Font::load(Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let mut entries = HashMap::new();
+        font.save_package_to(&mut entries).unwrap();
+        assert!(entries.contains_key("fontinfo.plist"));
+        assert!(entries.contains_key("glyphs/A_.glyph"));
+
+        let package = std::env::temp_dir().join("glyphslib-source.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        let from_disk = Font::load(&package).unwrap();
+        assert_eq!(Font::load_package_from(&entries).unwrap(), from_disk);
+        assert_eq!(Font::load_package_entries(&entries).unwrap(), from_disk);
+        fs::remove_dir_all(&package).unwrap();
+
+        // Renamed glyph files are found by listing the glyphs directory
+        let contents = entries.remove("glyphs/A_.glyph").unwrap();
+        entries.insert("glyphs/renamed.glyph".to_string(), contents);
+        assert_eq!(Font::load_package_from(&entries).unwrap(), from_disk);
+    }
+
+    #[test]
+    fn test_save_removes_old_glyph_files() {
+        let mut font = Font::load(Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let package = std::env::temp_dir().join("glyphslib-source-rename.glyphspackage");
+        let _ = fs::remove_dir_all(&package);
+        font.save(&package).unwrap();
+        fs::write(package.join("glyphs/notes.txt"), "not a glyph").unwrap();
+
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        glyphs3.glyph_mut("A").unwrap().name = "A.alt".to_string();
+        glyphs3.glyphs.retain(|g| g.name != "B");
+        font.save(&package).unwrap();
+        assert!(!package.join("glyphs/A_.glyph").exists());
+        assert!(!package.join("glyphs/B_.glyph").exists());
+        assert!(package.join("glyphs/A_.alt.glyph").exists());
+        assert!(package.join("glyphs/notes.txt").exists());
+        let names = |font: &Font| -> Vec<String> {
+            let glyphs = font.as_glyphs3().unwrap().glyphs.iter();
+            glyphs.map(|g| g.name.clone()).collect()
+        };
+        assert_eq!(names(&Font::load(&package).unwrap()), names(&font));
+        fs::remove_dir_all(&package).unwrap();
+
+        let mut entries = HashMap::new();
+        entries.insert("glyphs/old.glyph".to_string(), String::new());
+        font.save_package_to(&mut entries).unwrap();
+        assert!(!entries.contains_key("glyphs/old.glyph"));
+        assert_eq!(
+            names(&Font::load_package_from(&entries).unwrap()),
+            names(&font)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    common::Point,
+    geometry::Rect,
+    glyphs3::{Glyphs3, Layer, Shape},
+};
+
+impl Layer {
+    // The master whose layers stand in for those a component's glyph lacks
+    fn master_id(&self) -> &str {
+        self.associated_master_id
+            .as_deref()
+            .unwrap_or(&self.layer_id)
+    }
+
+    /// The left sidebearing of the layer, from the bounds of its outlines
+    /// and components
+    ///
+    /// Returns `None` if the layer draws nothing.
+    pub fn lsb(&self, font: &Glyphs3) -> Option<f64> {
+        let bounds = self.bounds_with_components(font, self.master_id())?;
+        Some(bounds.x_min)
+    }
+
+    /// The right sidebearing of the layer, from the bounds of its outlines
+    /// and components
+    ///
+    /// Returns `None` if the layer draws nothing.
+    pub fn rsb(&self, font: &Glyphs3) -> Option<f64> {
+        let bounds = self.bounds_with_components(font, self.master_id())?;
+        Some(self.width - bounds.x_max)
+    }
+
+    /// Move the paths, components and anchors of the layer sideways
+    pub fn shift_horizontally(&mut self, dx: f64) {
+        for shape in self.shapes.iter_mut() {
+            match shape {
+                Shape::Path(path) => {
+                    for node in path.nodes.iter_mut() {
+                        node.x += dx;
+                    }
+                }
+                Shape::Component(component) => component.position.x += dx,
+            }
+        }
+        for anchor in self.anchors.iter_mut() {
+            anchor.pos.x += dx;
+        }
+    }
+
+    /// Set the left sidebearing, moving the contents of the layer and
+    /// changing its width so that the right sidebearing stays as it is
+    ///
+    /// Does nothing if the layer draws nothing.
+    pub fn set_lsb(&mut self, font: &Glyphs3, lsb: f64) {
+        let Some(current) = self.lsb(font) else {
+            return;
+        };
+        let dx = lsb - current;
+        self.shift_horizontally(dx);
+        self.width += dx;
+    }
+
+    /// Set the right sidebearing by changing the width of the layer
+    ///
+    /// Does nothing if the layer draws nothing.
+    pub fn set_rsb(&mut self, font: &Glyphs3, rsb: f64) {
+        if let Some(current) = self.rsb(font) {
+            self.width += rsb - current;
+        }
+    }
+
+    /// Set the width of the layer, leaving the contents where they are so
+    /// that only the right sidebearing changes
+    pub fn set_width_keep_lsb(&mut self, width: f64) {
+        self.width = width;
+    }
+
+    /// Skew the paths, components and anchors of the layer horizontally by
+    /// an angle in degrees, keeping the height `pivot` in place
+    ///
+    /// A positive angle leans the layer to the right, as an italic does.
+    /// Components are moved, not skewed themselves.
+    pub fn skew(&mut self, angle: f64, pivot: f64) {
+        let slope = angle.to_radians().tan();
+        let skew = |p: Point| Point::new(p.x + (p.y - pivot) * slope, p.y);
+        for shape in self.shapes.iter_mut() {
+            match shape {
+                Shape::Path(path) => {
+                    for node in path.nodes.iter_mut() {
+                        node.set_position(skew(node.position()));
+                    }
+                }
+                Shape::Component(component) => component.position = skew(component.position),
+            }
+        }
+        for anchor in self.anchors.iter_mut() {
+            anchor.pos = skew(anchor.pos);
+        }
+    }
+
+    // The master of the layer, with its italic angle and the height Glyphs
+    // slants around: half the x-height
+    fn italic_slant(&self, font: &Glyphs3) -> Option<(f64, f64)> {
+        let master = font.masters.iter().find(|m| m.id == self.master_id())?;
+        let pivot = master.x_height(font).unwrap_or_default() / 2.0;
+        Some((master.italic_angle(font), pivot))
+    }
+
+    /// Skew the layer by the italic angle of its master around half its
+    /// x-height, as Glyphs slants upright drawings
+    ///
+    /// Does nothing if the master is missing.
+    pub fn skew_to_italic(&mut self, font: &Glyphs3) {
+        if let Some((angle, pivot)) = self.italic_slant(font) {
+            self.skew(angle, pivot);
+        }
+    }
+
+    // The bounds of the outlines with components resolved, leaned back
+    // upright by the italic angle
+    fn upright_bounds(&self, font: &Glyphs3) -> Option<Rect> {
+        let (angle, pivot) = self.italic_slant(font)?;
+        let mut upright = self.clone();
+        upright.shapes = self.decomposed(font).into_iter().map(Shape::Path).collect();
+        upright.skew(-angle, pivot);
+        upright.bounds()
+    }
+
+    /// The left sidebearing measured as Glyphs does in italic masters: with
+    /// the outlines leaned back upright around half the x-height
+    ///
+    /// This is the plain sidebearing in an upright master. Returns `None` if
+    /// the layer draws nothing or its master is missing.
+    pub fn italic_lsb(&self, font: &Glyphs3) -> Option<f64> {
+        Some(self.upright_bounds(font)?.x_min)
+    }
+
+    /// The right sidebearing measured as Glyphs does in italic masters, as
+    /// for [`italic_lsb`](Self::italic_lsb)
+    pub fn italic_rsb(&self, font: &Glyphs3) -> Option<f64> {
+        Some(self.width - self.upright_bounds(font)?.x_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        glyphs3::{Metric, MetricType, MetricValue},
+        Font,
+    };
+    use std::path;
+
+    #[test]
+    fn test_sidebearings() {
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let master_id = &font.masters[0].id;
+        for name in ["a", "eacute"] {
+            let glyph = font.glyph(name).unwrap();
+            let mut layer = glyph
+                .layers
+                .iter()
+                .find(|l| &l.layer_id == master_id)
+                .unwrap()
+                .clone();
+            let (lsb, rsb) = (layer.lsb(&font).unwrap(), layer.rsb(&font).unwrap());
+            layer.set_lsb(&font, lsb + 10.0);
+            assert!((layer.lsb(&font).unwrap() - (lsb + 10.0)).abs() < 0.01);
+            assert!((layer.rsb(&font).unwrap() - rsb).abs() < 0.01);
+            layer.set_rsb(&font, 0.0);
+            assert!(layer.rsb(&font).unwrap().abs() < 0.01);
+            let width = layer.width;
+            layer.set_width_keep_lsb(width + 50.0);
+            assert!((layer.lsb(&font).unwrap() - (lsb + 10.0)).abs() < 0.01);
+            assert!((layer.rsb(&font).unwrap() - 50.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_italic_sidebearings() {
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(mut font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let master_id = font.masters[0].id.clone();
+        let layer = font
+            .glyph("a")
+            .unwrap()
+            .layers
+            .iter()
+            .find(|l| l.layer_id == master_id)
+            .unwrap()
+            .clone();
+        // Upright, italic sidebearings are the plain ones
+        assert_eq!(layer.italic_lsb(&font), layer.lsb(&font));
+
+        // Slanting an upright drawing keeps its italic sidebearings
+        let ix = font
+            .metrics
+            .iter()
+            .position(|m| m.metric_type == Some(MetricType::ItalicAngle) && m.filter.is_none());
+        let ix = ix.unwrap_or_else(|| {
+            font.metrics.push(Metric {
+                metric_type: Some(MetricType::ItalicAngle),
+                ..Default::default()
+            });
+            for master in font.masters.iter_mut() {
+                master
+                    .metric_values
+                    .resize(font.metrics.len(), MetricValue::default());
+            }
+            font.metrics.len() - 1
+        });
+        font.masters[0].metric_values[ix].pos = 12.0;
+        let mut italic = layer.clone();
+        italic.skew_to_italic(&font);
+        assert_ne!(italic.lsb(&font), layer.lsb(&font));
+        let close = |a: Option<f64>, b: Option<f64>| (a.unwrap() - b.unwrap()).abs() < 0.01;
+        assert!(close(italic.italic_lsb(&font), layer.lsb(&font)));
+        assert!(close(italic.italic_rsb(&font), layer.rsb(&font)));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    common::CustomParameter,
+    glyphs3::{ExportType, Glyphs3, Instance},
+    variable::NamedInstance,
+};
+
+/// An axis as the `STAT` table records it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatAxis {
+    /// The OpenType tag of the axis
+    pub tag: String,
+    /// The user-facing name of the axis
+    pub name: String,
+    /// The place of the axis in the order of the font's axes
+    pub ordering: u16,
+}
+
+/// A named value of an axis, for the axis value tables of `STAT`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisValue {
+    /// The tag of the axis
+    pub axis: String,
+    /// The name of the value, such as `Bold`
+    pub name: String,
+    /// The user coordinate of the value
+    pub value: f64,
+    /// The user coordinate of the style-linked bold of the value, for a
+    /// format 3 axis value table
+    pub linked_value: Option<f64>,
+    /// Whether the name may be left out of the full style name, as
+    /// `Regular` usually is
+    pub elidable: bool,
+}
+
+/// The `STAT` and `fvar` data of a variable font, in user coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatPlan {
+    /// The design axes
+    pub axes: Vec<StatAxis>,
+    /// The named values of the axes, by axis and then by value
+    pub values: Vec<AxisValue>,
+    /// The name of the font at the default location when every name there
+    /// is elided
+    pub elided_fallback_name: String,
+    /// The named instances of `fvar`
+    pub instances: Vec<NamedInstance>,
+}
+
+// The axis tags a parameter lists, whether one tag or a list of them
+fn axis_tags(parameters: &[CustomParameter], name: &str) -> Vec<String> {
+    parameters
+        .iter()
+        .filter(|p| p.name == name && !p.disabled)
+        .flat_map(|p| match p.value.as_array() {
+            Some(tags) => tags.iter().filter_map(|t| t.as_str()).collect(),
+            None => p.value.as_str().into_iter().collect::<Vec<_>>(),
+        })
+        .map(String::from)
+        .collect()
+}
+
+impl Glyphs3 {
+    fn exported_statics(&self) -> impl Iterator<Item = &Instance> {
+        self.instances
+            .iter()
+            .filter(|i| i.exports && matches!(i.export_type, ExportType::Static))
+    }
+
+    // The value on an axis of the instance, named as the instance is
+    fn axis_value(&self, instance: &Instance, axis: &str) -> Option<AxisValue> {
+        let value = *self.instance_user_location(instance).get(axis)?;
+        // The bold an instance is linked to from this one
+        let linked_value = self
+            .exported_statics()
+            .find(|bold| bold.is_bold && bold.link_style.as_deref() == Some(&instance.name))
+            .and_then(|bold| self.instance_user_location(bold).get(axis).copied())
+            .filter(|linked| *linked != value);
+        let elidable = axis_tags(&instance.custom_parameters, "Elidable STAT Axis Value Name")
+            .iter()
+            .any(|tag| tag == axis);
+        Some(AxisValue {
+            axis: axis.to_string(),
+            name: instance.name.clone(),
+            value,
+            linked_value,
+            elidable,
+        })
+    }
+
+    /// The `STAT` axis records and axis values, and the `fvar` named
+    /// instances, of the variable font
+    ///
+    /// Axis values come from the exported static instances with a `Style
+    /// Name as STAT entry` parameter, which names the axes on which the
+    /// instance's name is the name of its coordinate; the `Elidable STAT
+    /// Axis Value Name` parameter marks the axes on which it is elidable.
+    /// The coordinate of a bold instance style-linked to one gives the
+    /// linked value. A font with one axis and no such parameters has a
+    /// value for each exported static instance, with `Regular` elidable.
+    pub fn stat_plan(&self) -> StatPlan {
+        let axes: Vec<StatAxis> = self
+            .axes
+            .iter()
+            .enumerate()
+            .map(|(ix, axis)| StatAxis {
+                tag: axis.tag.clone(),
+                name: axis.name.clone(),
+                ordering: ix as u16,
+            })
+            .collect();
+        let mut values = vec![];
+        for instance in self.exported_statics() {
+            for tag in axis_tags(&instance.custom_parameters, "Style Name as STAT entry") {
+                values.extend(self.axis_value(instance, &tag));
+            }
+        }
+        if values.is_empty() && axes.len() == 1 {
+            for instance in self.exported_statics() {
+                if let Some(mut value) = self.axis_value(instance, &axes[0].tag) {
+                    value.elidable |= value.name == "Regular";
+                    values.push(value);
+                }
+            }
+        }
+        let ordering = |tag: &str| axes.iter().position(|a| a.tag == tag);
+        values.sort_by(|a, b| {
+            ordering(&a.axis)
+                .cmp(&ordering(&b.axis))
+                .then(a.value.total_cmp(&b.value))
+        });
+        values.dedup_by(|a, b| a.axis == b.axis && a.value == b.value);
+        StatPlan {
+            axes,
+            values,
+            elided_fallback_name: "Regular".to_string(),
+            instances: self.named_instances(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyphs3::Axis;
+    use openstep_plist::Plist;
+
+    fn instance(name: &str, weight: f64, parameters: &[(&str, &str)]) -> Instance {
+        Instance {
+            name: name.to_string(),
+            axes_values: vec![weight],
+            exports: true,
+            custom_parameters: parameters
+                .iter()
+                .map(|(name, value)| CustomParameter {
+                    name: name.to_string(),
+                    value: Plist::String(value.to_string()),
+                    disabled: false,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stat_plan() {
+        let mut font = Glyphs3 {
+            family_name: "Test".to_string(),
+            axes: vec![Axis {
+                name: "Weight".to_string(),
+                tag: "wght".to_string(),
+                hidden: false,
+            }],
+            instances: vec![
+                instance("Regular", 400.0, &[]),
+                instance("Light", 300.0, &[]),
+                instance("Bold", 700.0, &[]),
+            ],
+            ..Default::default()
+        };
+        font.instances[2].is_bold = true;
+        font.instances[2].link_style = Some("Regular".to_string());
+
+        let plan = font.stat_plan();
+        assert_eq!(plan.axes.len(), 1);
+        let summary: Vec<_> = plan
+            .values
+            .iter()
+            .map(|v| (v.name.as_str(), v.value, v.linked_value, v.elidable))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Light", 300.0, None, false),
+                ("Regular", 400.0, Some(700.0), true),
+                ("Bold", 700.0, None, false),
+            ]
+        );
+        assert_eq!(plan.instances[2].postscript_name, "Test-Bold");
+        assert_eq!(plan.instances[2].location["wght"], 700.0);
+
+        // Once parameters name the entries, only those are used
+        font.instances[0] = instance(
+            "Regular",
+            400.0,
+            &[
+                ("Style Name as STAT entry", "wght"),
+                ("Elidable STAT Axis Value Name", "wght"),
+            ],
+        );
+        let plan = font.stat_plan();
+        assert_eq!(plan.values.len(), 1);
+        assert!(plan.values[0].elidable);
+    }
+}
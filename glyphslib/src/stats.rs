@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    common::Kerning,
+    glyphs3::{Glyphs3, Shape},
+    unicode_blocks::UNICODE_BLOCKS,
+};
+
+/// How many code points of a Unicode block a font encodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCoverage {
+    /// The name of the block, such as `Latin Extended-A`
+    pub name: &'static str,
+    /// The first code point of the block
+    pub start: u32,
+    /// The last code point of the block
+    pub end: u32,
+    /// The number of code points in the block which the font encodes
+    pub covered: usize,
+}
+
+impl BlockCoverage {
+    /// The number of code points in the block
+    pub fn size(&self) -> usize {
+        (self.end - self.start + 1) as usize
+    }
+}
+
+/// Summary statistics about a font, as returned by [`Glyphs3::stats`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontStats {
+    /// The total number of glyphs
+    pub glyph_count: usize,
+    /// Glyph counts by explicitly set category; `None` counts glyphs without one
+    pub glyphs_by_category: BTreeMap<Option<String>, usize>,
+    /// Glyph counts by explicitly set script; `None` counts glyphs without one
+    pub glyphs_by_script: BTreeMap<Option<String>, usize>,
+    /// The number of glyphs with at least one Unicode code point
+    pub encoded_glyphs: usize,
+    /// The number of glyphs with no Unicode code point
+    pub unencoded_glyphs: usize,
+    /// The Unicode blocks with at least one encoded code point, in code point order
+    pub unicode_blocks: Vec<BlockCoverage>,
+    /// Kerning pair counts by master ID, summed over all writing directions
+    pub kerning_pairs: BTreeMap<String, usize>,
+    /// Glyphs whose master layers contain only components
+    pub component_glyphs: usize,
+    /// Glyphs whose master layers contain only paths
+    pub outline_glyphs: usize,
+    /// Glyphs whose master layers contain both paths and components
+    pub mixed_glyphs: usize,
+    /// Glyphs whose master layers are all empty
+    pub empty_glyphs: usize,
+    /// The mean number of path nodes in a master layer
+    pub average_nodes: f64,
+}
+
+fn count_pairs(kerning: &Kerning, counts: &mut BTreeMap<String, usize>) {
+    for (master_id, pairs) in kerning {
+        *counts.entry(master_id.clone()).or_default() +=
+            pairs.values().map(|rights| rights.len()).sum::<usize>();
+    }
+}
+
+impl Glyphs3 {
+    /// Collect summary statistics about the font
+    ///
+    /// Categories and scripts are those set in the file; no glyph data lookup
+    /// is made for glyphs without them. Outline and node statistics consider
+    /// master layers only.
+    pub fn stats(&self) -> FontStats {
+        let mut stats = FontStats {
+            glyph_count: self.glyphs.len(),
+            ..Default::default()
+        };
+        let mut codepoints = BTreeSet::new();
+        let (mut node_total, mut layer_total) = (0, 0);
+        for glyph in &self.glyphs {
+            *stats
+                .glyphs_by_category
+                .entry(glyph.category.clone())
+                .or_default() += 1;
+            *stats
+                .glyphs_by_script
+                .entry(glyph.script.clone())
+                .or_default() += 1;
+            if glyph.unicode.is_empty() {
+                stats.unencoded_glyphs += 1;
+            } else {
+                stats.encoded_glyphs += 1;
+                codepoints.extend(glyph.unicode.iter().copied());
+            }
+            let (mut has_paths, mut has_components) = (false, false);
+            for layer in glyph
+                .layers
+                .iter()
+                .filter(|l| self.masters.iter().any(|m| m.id == l.layer_id))
+            {
+                layer_total += 1;
+                for shape in &layer.shapes {
+                    match shape {
+                        Shape::Path(path) => {
+                            has_paths = true;
+                            node_total += path.nodes.len();
+                        }
+                        Shape::Component(_) => has_components = true,
+                    }
+                }
+            }
+            match (has_paths, has_components) {
+                (true, true) => stats.mixed_glyphs += 1,
+                (true, false) => stats.outline_glyphs += 1,
+                (false, true) => stats.component_glyphs += 1,
+                (false, false) => stats.empty_glyphs += 1,
+            }
+        }
+        if layer_total > 0 {
+            stats.average_nodes = node_total as f64 / layer_total as f64;
+        }
+        for (start, end, name) in UNICODE_BLOCKS {
+            let covered = codepoints.range(start..=end).count();
+            if covered > 0 {
+                stats.unicode_blocks.push(BlockCoverage {
+                    name,
+                    start: *start,
+                    end: *end,
+                    covered,
+                });
+            }
+        }
+        for kerning in [&self.kerning, &self.kerning_rtl, &self.kerning_vertical] {
+            count_pairs(kerning, &mut stats.kerning_pairs);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_stats() {
+        let file = "resources/RadioCanadaDisplay.glyphs";
+        let font = Font::load(path::Path::new(file)).unwrap();
+        let glyphs3 = font.as_glyphs3().unwrap();
+        let stats = glyphs3.stats();
+        assert_eq!(stats.glyph_count, 477);
+        assert_eq!(stats.encoded_glyphs + stats.unencoded_glyphs, 477);
+        assert_eq!(
+            stats.component_glyphs + stats.outline_glyphs + stats.mixed_glyphs + stats.empty_glyphs,
+            477
+        );
+        assert!(stats.component_glyphs > 0 && stats.outline_glyphs > 0);
+        let basic_latin = &stats.unicode_blocks[0];
+        assert_eq!(basic_latin.name, "Basic Latin");
+        assert!(basic_latin.covered <= basic_latin.size());
+        assert_eq!(stats.glyphs_by_category.values().sum::<usize>(), 477);
+    }
+
+    #[test]
+    fn test_kerning_pairs() {
+        let file = "resources/KernFloats.glyphs";
+        let font = Font::load(path::Path::new(file)).unwrap();
+        let stats = font.as_glyphs3().unwrap().stats();
+        assert_eq!(stats.kerning_pairs.get("m01"), Some(&1));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use openstep_plist::Plist;
+
+use crate::{
+    common::Kerning,
+    glyphs3::{Glyph, Glyphs3},
+    kerning::refers_to,
+};
+
+fn prune_kerning(kerning: &mut Kerning, kept: &[&Glyph]) {
+    for pairs in kerning.values_mut() {
+        pairs.retain(|left, rights| {
+            rights.retain(|right, _| refers_to(right, kept));
+            refers_to(left, kept) && !rights.is_empty()
+        });
+    }
+    kerning.retain(|_, pairs| !pairs.is_empty());
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '.' | '_' | '-')
+}
+
+// The glyph names in a piece of feature code, as byte ranges, with whether
+// each is inside a `[...]` class. Names of classes (`@name`) are left out,
+// and a leading `\` is not part of the range.
+fn names(code: &str) -> Vec<(usize, usize, bool)> {
+    let mut names = vec![];
+    let mut in_class = false;
+    let mut chars = code.char_indices().peekable();
+    while let Some((ix, ch)) = chars.next() {
+        match ch {
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '@' => while chars.next_if(|(_, ch)| is_name_char(*ch)).is_some() {},
+            ch if is_name_char(ch) => {
+                let mut end = ix + ch.len_utf8();
+                while let Some((next, ch)) = chars.next_if(|(_, ch)| is_name_char(*ch)) {
+                    end = next + ch.len_utf8();
+                }
+                names.push((ix, end, in_class));
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+// The code with comments blanked out, so they are neither split into
+// statements nor searched for names
+fn without_comments(code: &str) -> String {
+    let mut in_comment = false;
+    code.chars()
+        .map(|ch| match ch {
+            '#' => {
+                in_comment = true;
+                ' '
+            }
+            '\n' => {
+                in_comment = false;
+                ch
+            }
+            _ if in_comment => ' ',
+            _ => ch,
+        })
+        .collect()
+}
+
+// Remove the dropped glyphs from feature code: from `[...]` classes, and
+// then whole statements which still refer to one or are left with an empty
+// class. Anything which does not name a dropped glyph is left as it was.
+fn prune_code(code: &str, dropped: &HashSet<&str>) -> String {
+    let masked = without_comments(code);
+    let mut removals = vec![];
+    let mut start = 0;
+    for (end, ch) in masked.char_indices() {
+        if !matches!(ch, ';' | '{' | '}') {
+            continue;
+        }
+        let statement = &masked[start..end];
+        let names = names(statement);
+        let is_dropped = |(from, to, _): &&(usize, usize, bool)| {
+            dropped.contains(statement[*from..*to].trim_start_matches('\\'))
+        };
+        let outside = names
+            .iter()
+            .filter(is_dropped)
+            .any(|(_, _, in_class)| !in_class);
+        let emptied = statement.match_indices('[').any(|(open, _)| {
+            let close = statement[open..]
+                .find(']')
+                .map_or(statement.len(), |c| open + c);
+            let inside: Vec<_> = names
+                .iter()
+                .filter(|(from, _, _)| (open..close).contains(from))
+                .collect();
+            !inside.is_empty() && inside.iter().all(is_dropped)
+        });
+        if ch == ';' && (outside || emptied) {
+            let first = statement.len() - statement.trim_start().len();
+            removals.push((start + first, end + 1));
+        } else {
+            removals.extend(
+                names
+                    .iter()
+                    .filter(|name| name.2 && is_dropped(name))
+                    .map(|(from, to, _)| (start + from, start + to)),
+            );
+        }
+        start = end + 1;
+    }
+    let mut code = code.to_string();
+    for (from, to) in removals.into_iter().rev() {
+        code.replace_range(from..to, "");
+    }
+    code
+}
+
+impl Glyphs3 {
+    /// A copy of the font with only the given glyphs and the glyphs their
+    /// components use
+    ///
+    /// Kerning is kept only between the glyphs kept and the groups they
+    /// are in, and the `glyphOrder` parameter lists only the glyphs kept.
+    /// The glyphs left out are removed from the classes and from the `[...]`
+    /// classes of feature code and prefixes, and statements which still
+    /// refer to one are removed, so that the code still compiles. Names of
+    /// glyphs the font does not have are ignored.
+    pub fn subset(&self, names: &[&str]) -> Glyphs3 {
+        let keep = self.with_components(names);
+        let kept: Vec<&Glyph> = self
+            .glyphs
+            .iter()
+            .filter(|g| keep.contains(g.name.as_str()))
+            .collect();
+        let dropped: HashSet<&str> = self
+            .glyphs
+            .iter()
+            .map(|g| g.name.as_str())
+            .filter(|name| !keep.contains(name))
+            .collect();
+        let mut font = Glyphs3 {
+            glyphs: vec![],
+            ..self.clone()
+        };
+        for kerning in [
+            &mut font.kerning,
+            &mut font.kerning_rtl,
+            &mut font.kerning_vertical,
+        ] {
+            prune_kerning(kerning, &kept);
+        }
+        for class in &mut font.classes {
+            class.code = prune_code(&class.code, &dropped);
+        }
+        for prefix in &mut font.feature_prefixes {
+            prefix.code = prune_code(&prefix.code, &dropped);
+        }
+        for feature in &mut font.features {
+            feature.code = prune_code(&feature.code, &dropped);
+        }
+        for parameter in &mut font.custom_parameters {
+            if let (true, Plist::Array(order)) =
+                (parameter.name == "glyphOrder", &mut parameter.value)
+            {
+                order.retain(|name| !name.as_str().is_some_and(|n| dropped.contains(n)));
+            }
+        }
+        font.glyphs = kept.into_iter().cloned().collect();
+        font
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_prune_code() {
+        let dropped = HashSet::from(["b", "c.alt"]);
+        let code = "sub a by a.alt;\nsub b by b.alt; # b\nsub [a b c] by [x y z];\nsub [b] by x;\nlookup l {\n  pos a c.alt -10;\n} l;";
+        assert_eq!(
+            prune_code(code, &dropped),
+            "sub a by a.alt;\n # b\nsub [a  c] by [x y z];\n\nlookup l {\n  \n} l;"
+        );
+        assert_eq!(prune_code("@b = [a b];", &dropped), "@b = [a ];");
+    }
+
+    #[test]
+    fn test_subset() {
+        let font = Font::load(path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        let mut font = font.as_glyphs3().unwrap().clone();
+        let master = font.masters[0].id.clone();
+        font.set_kerning_pair(&master, "@MMK_L_A", "@MMK_R_A", -10.0);
+        font.set_kerning_pair(&master, "Aacute", "B", -20.0);
+        font.set_kerning_pair(&master, "A", "C", -30.0);
+        font.set_kerning_pair(&master, "@MMK_L_C", "B", -40.0);
+        let subset = font.subset(&["Aacute", "B", "nosuchglyph"]);
+        let names: Vec<&str> = subset.glyphs.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["A", "Aacute", "B", "acutecomb.case"]);
+        let pairs = &subset.kerning[&master];
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs["@MMK_L_A"]["@MMK_R_A"], -10.0);
+        assert_eq!(pairs["Aacute"]["B"], -20.0);
+        assert_eq!(subset.masters, font.masters);
+    }
+}
@@ -0,0 +1,473 @@
+use proptest::{
+    collection::vec,
+    prelude::*,
+    sample::{select, subsequence},
+};
+
+use crate::{
+    common::{Kerning, NodeType, Point},
+    glyphs3::{Anchor, Axis, Component, Glyph, Glyphs3, Layer, Master, Node, Path, Shape},
+};
+
+const AXES: [(&str, &str, f64, f64); 2] = [
+    ("Weight", "wght", 100.0, 900.0),
+    ("Width", "wdth", 75.0, 125.0),
+];
+
+const ANCHOR_NAMES: [&str; 3] = ["top", "bottom", "_top"];
+
+const MAX_MASTERS: usize = 3;
+const MAX_GLYPHS: usize = 12;
+const MAX_SEGMENTS: usize = 6;
+const UNITS_PER_EM: [i32; 3] = [1000, 1024, 2048];
+
+/// Generates small, structurally valid random fonts
+///
+/// Every font has at least one master, and every glyph has one layer per
+/// master. Those layers are compatible: they hold the same paths with the
+/// same node types, the same components and the same anchors, and differ
+/// only in coordinates. Components only refer to glyphs earlier in the
+/// font, so there are no cycles, and kerning pairs only name glyphs which
+/// exist. Coordinates and kerning values are whole numbers, so they are not
+/// rounded when the font is serialized.
+///
+/// The same seed always gives the same font. For property tests, [`fonts`]
+/// generates the same kind of fonts as a proptest strategy, which can shrink
+/// a failing font.
+#[derive(Debug, Clone)]
+pub struct FontGenerator {
+    state: u64,
+    /// The largest number of masters to generate
+    pub max_masters: usize,
+    /// The largest number of glyphs to generate
+    pub max_glyphs: usize,
+    /// The largest number of segments in a path
+    pub max_segments: usize,
+}
+
+impl FontGenerator {
+    /// Create a generator from a seed
+    pub fn new(seed: u64) -> Self {
+        FontGenerator {
+            state: seed,
+            max_masters: MAX_MASTERS,
+            max_glyphs: MAX_GLYPHS,
+            max_segments: MAX_SEGMENTS,
+        }
+    }
+
+    // SplitMix64, which is plenty for test data and needs no dependency
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn between(&mut self, low: i32, high: i32) -> f64 {
+        (low + self.below((high - low + 1) as usize) as i32) as f64
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    /// Generate the next font
+    pub fn font(&mut self) -> Glyphs3 {
+        let axes = axes(1 + self.below(AXES.len()));
+        let masters: Vec<Master> = (0..1 + self.below(self.max_masters.max(1)))
+            .map(|ix| {
+                let axes_values = AXES[..axes.len()]
+                    .iter()
+                    .map(|(_, _, min, max)| self.between(*min as i32, *max as i32))
+                    .collect();
+                master(ix, axes_values)
+            })
+            .collect();
+
+        let mut pool: Vec<char> = ('A'..='Z').chain('a'..='z').collect();
+        let count = 1 + self.below(self.max_glyphs.clamp(1, pool.len()));
+        let mut names = Vec::with_capacity(count);
+        for _ in 0..count {
+            names.push(pool.remove(self.below(pool.len())));
+        }
+
+        let mut glyphs: Vec<Glyph> = Vec::with_capacity(names.len());
+        for (ix, name) in names.iter().enumerate() {
+            let glyph = self.glyph(*name, &names[..ix], &masters);
+            glyphs.push(glyph);
+        }
+        let kerning = self.kerning(&names, &masters);
+
+        Glyphs3 {
+            format_version: 3,
+            family_name: format!("Random {}", self.below(10_000)),
+            units_per_em: UNITS_PER_EM[self.below(UNITS_PER_EM.len())],
+            axes,
+            masters,
+            glyphs,
+            kerning,
+            ..Default::default()
+        }
+    }
+
+    fn glyph(&mut self, name: char, earlier: &[char], masters: &[Master]) -> Glyph {
+        // Decide the structure once, then realise it for every master
+        let segments: Vec<Vec<bool>> = (0..self.below(3))
+            .map(|_| {
+                (0..1 + self.below(self.max_segments.max(1)))
+                    .map(|_| self.chance(50))
+                    .collect()
+            })
+            .collect();
+        let components: Vec<char> = if earlier.is_empty() {
+            vec![]
+        } else {
+            (0..self.below(3))
+                .map(|_| earlier[self.below(earlier.len())])
+                .collect()
+        };
+        let anchors: Vec<&str> = ANCHOR_NAMES
+            .iter()
+            .copied()
+            .filter(|_| self.chance(30))
+            .collect();
+
+        let layers = masters
+            .iter()
+            .map(|master| {
+                let mut shapes: Vec<Shape> = segments
+                    .iter()
+                    .map(|curves| {
+                        Shape::Path(contour(curves, || {
+                            (self.between(-100, 1100), self.between(-300, 900))
+                        }))
+                    })
+                    .collect();
+                shapes.extend(components.iter().map(|base| {
+                    component(*base, (self.between(-200, 200), self.between(-200, 200)))
+                }));
+                Layer {
+                    anchors: anchors
+                        .iter()
+                        .map(|name| anchor(name, (self.between(0, 600), self.between(-200, 800))))
+                        .collect(),
+                    shapes,
+                    ..Layer::empty(master.id.clone(), self.between(0, 1000))
+                }
+            })
+            .collect();
+
+        glyph(name, self.chance(80), layers)
+    }
+
+    fn kerning(&mut self, names: &[char], masters: &[Master]) -> Kerning {
+        let pairs: Vec<(char, char)> = (0..self.below(names.len() * 2))
+            .map(|_| {
+                (
+                    names[self.below(names.len())],
+                    names[self.below(names.len())],
+                )
+            })
+            .collect();
+        let mut kerning = Kerning::new();
+        if pairs.is_empty() {
+            return kerning;
+        }
+        for master in masters {
+            let by_left = kerning.entry(master.id.clone()).or_default();
+            for (left, right) in &pairs {
+                by_left
+                    .entry(left.to_string())
+                    .or_default()
+                    .insert(right.to_string(), self.between(-150, 50));
+            }
+        }
+        kerning
+    }
+}
+
+/// Generate the font for a seed with the default [`FontGenerator`] limits
+pub fn random_font(seed: u64) -> Glyphs3 {
+    FontGenerator::new(seed).font()
+}
+
+/// A [proptest](https://docs.rs/proptest) strategy for fonts like those of
+/// [`FontGenerator`] with its default limits
+///
+/// A failing font shrinks towards fewer masters, glyphs, contours and
+/// kerning pairs, and smaller coordinates.
+///
+/// ```
+/// use glyphslib::testutil::fonts;
+/// use proptest::prelude::*;
+///
+/// proptest!(|(font in fonts())| {
+///     prop_assert!(!font.masters.is_empty());
+/// });
+/// ```
+pub fn fonts() -> impl Strategy<Value = Glyphs3> {
+    let names: Vec<char> = ('A'..='Z').chain('a'..='z').collect();
+    (
+        1..=AXES.len(),
+        1..=MAX_MASTERS,
+        subsequence(names, 1..=MAX_GLYPHS),
+    )
+        .prop_flat_map(|(axis_count, master_count, names)| {
+            let axes_values: Vec<_> = AXES[..axis_count]
+                .iter()
+                .map(|(_, _, min, max)| *min as i32..=*max as i32)
+                .collect();
+            let glyphs: Vec<_> = (0..names.len())
+                .map(|ix| glyph_strategy(names[ix], names[..ix].to_vec(), master_count))
+                .collect();
+            let pair = (select(names.clone()), select(names.clone()));
+            let kerning = vec((pair, vec(-150..=50, master_count)), 0..names.len() * 2);
+            (
+                vec(axes_values, master_count),
+                glyphs,
+                kerning,
+                0..10_000,
+                select(&UNITS_PER_EM[..]),
+            )
+        })
+        .prop_map(|(axes_values, glyphs, pairs, family, units_per_em)| {
+            let masters: Vec<Master> = axes_values
+                .into_iter()
+                .enumerate()
+                .map(|(ix, values)| master(ix, values.into_iter().map(f64::from).collect()))
+                .collect();
+            let mut kerning = Kerning::new();
+            for ((left, right), values) in pairs {
+                for (master, value) in masters.iter().zip(values) {
+                    kerning
+                        .entry(master.id.clone())
+                        .or_default()
+                        .entry(left.to_string())
+                        .or_default()
+                        .insert(right.to_string(), value as f64);
+                }
+            }
+            Glyphs3 {
+                format_version: 3,
+                family_name: format!("Random {family}"),
+                units_per_em,
+                axes: axes(masters[0].axes_values.len()),
+                masters,
+                glyphs,
+                kerning,
+                ..Default::default()
+            }
+        })
+}
+
+// A glyph, with a layer for each master which is compatible with the others
+fn glyph_strategy(
+    name: char,
+    earlier: Vec<char>,
+    master_count: usize,
+) -> impl Strategy<Value = Glyph> {
+    let components = if earlier.is_empty() {
+        Just(vec![]).boxed()
+    } else {
+        vec(select(earlier), 0..3).boxed()
+    };
+    let anchors = subsequence(ANCHOR_NAMES.to_vec(), 0..=ANCHOR_NAMES.len());
+    let segments = vec(vec(any::<bool>(), 1..=MAX_SEGMENTS), 0..3);
+    (segments, components, anchors, prop::bool::weighted(0.8))
+        .prop_flat_map(move |(segments, components, anchors, unicode)| {
+            let nodes: usize = segments
+                .iter()
+                .flatten()
+                .map(|&curve| if curve { 3 } else { 1 })
+                .sum();
+            let layer = (
+                vec((-100..=1100, -300..=900), nodes),
+                vec((-200..=200, -200..=200), components.len()),
+                vec((0..=600, -200..=800), anchors.len()),
+                0..=1000,
+            );
+            (
+                Just((segments, components, anchors, unicode)),
+                vec(layer, master_count),
+            )
+        })
+        .prop_map(move |((segments, components, anchors, unicode), layers)| {
+            let layers = layers
+                .into_iter()
+                .enumerate()
+                .map(|(ix, (nodes, offsets, positions, width))| {
+                    let mut nodes = nodes.into_iter().map(float_point);
+                    let mut shapes: Vec<Shape> = segments
+                        .iter()
+                        .map(|curves| Shape::Path(contour(curves, || nodes.next().unwrap())))
+                        .collect();
+                    shapes.extend(
+                        components
+                            .iter()
+                            .zip(offsets)
+                            .map(|(base, offset)| component(*base, float_point(offset))),
+                    );
+                    Layer {
+                        anchors: anchors
+                            .iter()
+                            .zip(positions)
+                            .map(|(name, pos)| anchor(name, float_point(pos)))
+                            .collect(),
+                        shapes,
+                        ..Layer::empty(master_id(ix), width as f64)
+                    }
+                })
+                .collect();
+            glyph(name, unicode, layers)
+        })
+}
+
+fn float_point((x, y): (i32, i32)) -> (f64, f64) {
+    (x as f64, y as f64)
+}
+
+fn axes(count: usize) -> Vec<Axis> {
+    AXES[..count]
+        .iter()
+        .map(|(name, tag, _, _)| Axis {
+            name: name.to_string(),
+            tag: tag.to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn master_id(ix: usize) -> String {
+    format!("m{:02}", ix + 1)
+}
+
+fn master(ix: usize, axes_values: Vec<f64>) -> Master {
+    Master {
+        id: master_id(ix),
+        name: format!("Master {}", ix + 1),
+        axes_values,
+        ..Default::default()
+    }
+}
+
+fn glyph(name: char, unicode: bool, layers: Vec<Layer>) -> Glyph {
+    Glyph {
+        name: name.to_string(),
+        export: true,
+        unicode: if unicode { vec![name as u32] } else { vec![] },
+        layers,
+        ..Default::default()
+    }
+}
+
+// A closed contour; each segment is a curve if `true` and a line otherwise
+fn contour(curves: &[bool], mut position: impl FnMut() -> (f64, f64)) -> Path {
+    let mut node = |node_type| {
+        let (x, y) = position();
+        Node {
+            x,
+            y,
+            node_type,
+            user_data: None,
+        }
+    };
+    let mut nodes = vec![];
+    for &curve in curves {
+        if curve {
+            for _ in 0..2 {
+                nodes.push(node(NodeType::OffCurve));
+            }
+            nodes.push(node(NodeType::Curve));
+        } else {
+            nodes.push(node(NodeType::Line));
+        }
+    }
+    Path {
+        attr: Default::default(),
+        closed: true,
+        nodes,
+    }
+}
+
+fn component(base: char, (x, y): (f64, f64)) -> Shape {
+    Shape::Component(Component {
+        alignment: -1,
+        component_glyph: base.to_string(),
+        position: Point::new(x, y),
+        ..Default::default()
+    })
+}
+
+fn anchor(name: &str, (x, y): (f64, f64)) -> Anchor {
+    Anchor {
+        locked: false,
+        name: name.to_string(),
+        orientation: Default::default(),
+        pos: Point::new(x, y),
+        user_data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+
+    #[test]
+    fn test_random_fonts_roundtrip() {
+        for seed in 0..64 {
+            let font = Font::Glyphs3(random_font(seed));
+            let serialized = font.to_string().unwrap();
+            let reloaded = Font::load_str(&serialized).unwrap();
+            assert_eq!(font, reloaded, "seed {seed} did not round trip");
+        }
+    }
+
+    #[test]
+    fn test_random_fonts_are_compatible() {
+        for seed in 0..64 {
+            let font = random_font(seed);
+            assert!(!font.masters.is_empty());
+            for glyph in &font.glyphs {
+                assert_eq!(glyph.layers.len(), font.masters.len());
+                let structure = |layer: &Layer| -> Vec<Vec<NodeType>> {
+                    layer
+                        .shapes
+                        .iter()
+                        .map(|shape| match shape {
+                            Shape::Path(path) => path.nodes.iter().map(|n| n.node_type).collect(),
+                            Shape::Component(_) => vec![],
+                        })
+                        .collect()
+                };
+                let first = structure(&glyph.layers[0]);
+                assert!(glyph.layers.iter().all(|l| structure(l) == first));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn test_strategy_fonts_roundtrip(font in fonts()) {
+            for glyph in &font.glyphs {
+                prop_assert_eq!(glyph.layers.len(), font.masters.len());
+            }
+            let font = Font::Glyphs3(font);
+            let reloaded = Font::load_str(&font.to_string().unwrap()).unwrap();
+            prop_assert_eq!(font, reloaded);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_font() {
+        assert_eq!(random_font(7), random_font(7));
+        assert_ne!(random_font(7), random_font(8));
+    }
+}
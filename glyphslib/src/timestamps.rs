@@ -0,0 +1,145 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "time")]
+use time::{
+    format_description::BorrowedFormatItem, macros::format_description, OffsetDateTime, UtcOffset,
+};
+
+use crate::glyphs3::Glyph;
+#[cfg(feature = "time")]
+use crate::glyphs3::Glyphs3;
+
+#[cfg(feature = "time")]
+const FORMAT: &[BorrowedFormatItem<'_>] = format_description!(
+    "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+);
+
+#[cfg(feature = "time")]
+fn parse(timestamp: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(timestamp, FORMAT).ok()
+}
+
+// Times are written in UTC, as Glyphs writes them
+#[cfg(feature = "time")]
+fn format(time: OffsetDateTime) -> String {
+    time.to_offset(UtcOffset::UTC)
+        .format(FORMAT)
+        .expect("a four digit year")
+}
+
+// The civil date in UTC of a day counted from 1970-01-01, after Howard
+// Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Format a time in seconds since the Unix epoch as Glyphs writes
+/// `lastChange` and `date`: `2024-07-17 03:14:15 +0000`, in UTC
+pub(crate) fn format_timestamp(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} +0000",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+// The current time as Glyphs writes it
+pub(crate) fn now() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    format_timestamp(seconds)
+}
+
+impl Glyph {
+    /// Mark the glyph as changed now, setting its `lastChange` as Glyphs
+    /// does when a glyph is edited
+    pub fn touch(&mut self) {
+        self.last_change = Some(now());
+    }
+
+    /// The time the glyph was last changed, if it has one Glyphs can read
+    #[cfg(feature = "time")]
+    pub fn last_change_parsed(&self) -> Option<OffsetDateTime> {
+        parse(self.last_change.as_deref()?)
+    }
+
+    /// Set the time the glyph was last changed, written in UTC
+    #[cfg(feature = "time")]
+    pub fn set_last_change(&mut self, time: OffsetDateTime) {
+        self.last_change = Some(format(time));
+    }
+}
+
+#[cfg(feature = "time")]
+impl Glyphs3 {
+    /// The creation date of the font, if it is one Glyphs can read
+    pub fn date_parsed(&self) -> Option<OffsetDateTime> {
+        parse(&self.date)
+    }
+
+    /// Set the creation date of the font, written in UTC
+    pub fn set_date(&mut self, time: OffsetDateTime) {
+        self.date = format(time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 +0000");
+        assert_eq!(format_timestamp(1_721_186_055), "2024-07-17 03:14:15 +0000");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 +0000");
+        assert_eq!(format_timestamp(-1), "1969-12-31 23:59:59 +0000");
+
+        let mut glyph = Glyph::default();
+        glyph.touch();
+        let stamp = glyph.last_change.unwrap();
+        assert_eq!(stamp.len(), 25);
+        assert!(stamp.ends_with(" +0000"));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_parsed_timestamps() {
+        let mut glyph = Glyph {
+            last_change: Some("2024-07-17 03:14:15 +0000".to_string()),
+            ..Default::default()
+        };
+        let time = glyph.last_change_parsed().unwrap();
+        assert_eq!(time.unix_timestamp(), 1_721_186_055);
+        glyph.touch();
+        assert!(glyph.last_change_parsed().is_some());
+        glyph.set_last_change(time.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap()));
+        assert_eq!(
+            glyph.last_change.as_deref(),
+            Some("2024-07-17 03:14:15 +0000")
+        );
+
+        let mut font = Glyphs3 {
+            date: "2019-03-01 10:00:00 +0100".to_string(),
+            ..Default::default()
+        };
+        let date = font.date_parsed().unwrap();
+        assert_eq!(date.offset().whole_hours(), 1);
+        font.set_date(date);
+        assert_eq!(font.date, "2019-03-01 09:00:00 +0000");
+        font.date = "yesterday".to_string();
+        assert!(font.date_parsed().is_none());
+    }
+}
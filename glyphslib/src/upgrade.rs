@@ -1,9 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{
-    common::Orientation,
+    common::{Orientation, Point, Scale},
     glyphs2,
-    glyphs3::{self, Axis, LocalizedPropertyKey, Metric, MetricType, MetricValue, Property, Stem},
+    glyphs3::{self, Axis, LocalizedPropertyKey, Metric, MetricType, Property, Stem},
 };
 
 impl From<glyphs2::Node> for glyphs3::Node {
@@ -23,7 +23,7 @@ impl From<glyphs2::Guide> for glyphs3::Guide {
             angle: val.angle,
             locked: val.locked,
             pos: val.pos,
-            size: val.scale,
+            size: val.scale.into(),
             filter: val.filter,
             grid: val.grid,
             length: val.length,
@@ -61,15 +61,15 @@ impl From<glyphs2::BackgroundImage> for glyphs3::BackgroundImage {
 }
 
 struct DecomposedAffine {
-    translation: (f32, f32),
-    scale: (f32, f32),
-    rotation: f32,
+    translation: Point,
+    scale: Scale,
+    rotation: f64,
     // I don't care about skew
 }
 
 fn decompose(t: &glyphs2::Transform) -> DecomposedAffine {
     let delta = t.m11 * t.m22 - t.m12 * t.m21;
-    let translation = (t.t_x, t.t_y);
+    let translation = Point::new(t.t_x, t.t_y);
     let (rotation, scale) = if t.m11 != 0.0 || t.m12 != 0.0 {
         let r = (t.m11 * t.m11 + t.m12 * t.m12).sqrt();
         let angle = if t.m12 > 0.0 {
@@ -77,7 +77,7 @@ fn decompose(t: &glyphs2::Transform) -> DecomposedAffine {
         } else {
             -(t.m11 / r).acos()
         };
-        (angle, (r, delta / r))
+        (angle, Scale::new(r, delta / r))
     } else if t.m21 != 0.0 || t.m22 != 0.0 {
         let s = (t.m21 * t.m21 + t.m22 * t.m22).sqrt();
         let angle = if t.m22 > 0.0 {
@@ -85,9 +85,12 @@ fn decompose(t: &glyphs2::Transform) -> DecomposedAffine {
         } else {
             -(t.m22 / s).asin()
         };
-        ((std::f32::consts::PI / 2.0) - angle, (delta / s, s))
+        (
+            (std::f64::consts::PI / 2.0) - angle,
+            Scale::new(delta / s, s),
+        )
     } else {
-        (0.0, (0.0, 0.0))
+        (0.0, Scale::new(0.0, 0.0))
     };
     DecomposedAffine {
         translation,
@@ -98,7 +101,6 @@ fn decompose(t: &glyphs2::Transform) -> DecomposedAffine {
 
 impl From<glyphs2::Layer> for glyphs3::Layer {
     fn from(val: glyphs2::Layer) -> Self {
-        let attrs = BTreeMap::new();
         let shapes = val
             .components
             .into_iter()
@@ -115,7 +117,7 @@ impl From<glyphs2::Layer> for glyphs3::Layer {
             anchors: val.anchors.into_iter().map(Into::into).collect(),
             annotations: val.annotations,
             associated_master_id: val.associated_master_id,
-            attr: attrs,
+            attr: Default::default(),
             background: val
                 .background
                 .map(|x| Box::new(std::convert::Into::<glyphs3::Layer>::into(*x))),
@@ -139,6 +141,7 @@ impl From<glyphs2::Layer> for glyphs3::Layer {
             vert_width: val.vert_width,
             visible: val.visible,
             width: val.width,
+            other: Default::default(),
         }
     }
 }
@@ -247,6 +250,7 @@ impl From<glyphs2::Glyphs2> for glyphs3::Glyphs3 {
             units_per_em: val.units_per_em,
             user_data: val.user_data,
             version: val.version,
+            other: Default::default(),
         };
         font.axes = axes;
         font
@@ -284,44 +288,7 @@ impl glyphs2::Master {
     }
 
     fn to_glyphs3(&self, axes: &[Axis], metrics: &[Metric], _stems: &[Stem]) -> glyphs3::Master {
-        let alignment_to_overshoot: Vec<(f32, f32)> = self
-            .alignment_zones
-            .iter()
-            .map(|z| (z.position, z.overshoot))
-            .collect();
-        let find_overshoot = |v| {
-            alignment_to_overshoot
-                .iter()
-                .find(|(pos, _)| *pos == v)
-                .map(|(_, over)| *over)
-                .unwrap_or(0.0)
-        };
-        let metric_values = metrics
-            .iter()
-            .map(|m| match m.metric_type {
-                Some(MetricType::Ascender) => MetricValue {
-                    pos: self.ascender.unwrap_or_default(),
-                    over: find_overshoot(self.ascender.unwrap_or_default()),
-                },
-                Some(MetricType::Baseline) => MetricValue {
-                    pos: 0.0,
-                    over: find_overshoot(0.0),
-                },
-                Some(MetricType::CapHeight) => MetricValue {
-                    pos: self.cap_height.unwrap_or_default(),
-                    over: find_overshoot(self.cap_height.unwrap_or_default()),
-                },
-                Some(MetricType::Descender) => MetricValue {
-                    pos: self.descender.unwrap_or_default(),
-                    over: find_overshoot(self.descender.unwrap_or_default()),
-                },
-                Some(MetricType::XHeight) => MetricValue {
-                    pos: self.x_height.unwrap_or_default(),
-                    over: find_overshoot(self.x_height.unwrap_or_default()),
-                },
-                _ => panic!("Can't happen"),
-            })
-            .collect();
+        let metric_values = self.metric_values(metrics);
 
         let mut name_particles = vec![];
         let axis_tags: BTreeSet<&str> = axes.iter().map(|a| a.tag.as_str()).collect();
@@ -346,7 +313,7 @@ impl glyphs2::Master {
                 .axis_values(axes.len())
                 .iter()
                 .copied()
-                .map(|x| x as f32)
+                .map(|x| x as f64)
                 .collect(),
             custom_parameters: self.custom_parameters.clone(),
             guides: self.guides.iter().cloned().map(Into::into).collect(),
@@ -360,7 +327,7 @@ impl glyphs2::Master {
                 .iter()
                 .chain(self.vertical_stems.iter())
                 .copied()
-                .map(|x| x as f32)
+                .map(|x| x as f64)
                 .collect(),
             visible: self.visible,
             ..Default::default()
@@ -371,27 +338,13 @@ impl glyphs2::Master {
 impl glyphs2::Glyphs2 {
     fn determine_axes(&self) -> Vec<glyphs3::Axis> {
         // If we have an Axes custom parameter, start with that.
-        if let Some(axes_param) = self.custom_parameters.iter().find(|x| x.name == "Axes") {
-            if let Some(axes_cp) = axes_param.value.as_array() {
-                let axes = axes_cp
-                    .iter()
-                    .flat_map(|x| x.as_dict())
-                    .map(|d| glyphs3::Axis {
-                        name: d
-                            .get("Name")
-                            .and_then(|x| x.as_str())
-                            .unwrap_or_default()
-                            .to_string(),
-                        tag: d
-                            .get("Tag")
-                            .and_then(|x| x.as_str())
-                            .unwrap_or_default()
-                            .to_string(),
-                        hidden: d.contains_key("Hidden"),
-                    })
-                    .collect::<Vec<_>>();
-                return axes;
-            }
+        if let Some(axes) = self
+            .custom_parameters
+            .iter()
+            .find(|x| x.name == "Axes")
+            .and_then(|x| x.as_axes())
+        {
+            return axes;
         }
         // Else we only have one or two "default" axes (weight/width/both); work it out the hard way
         let mut axes = vec![];
@@ -526,28 +479,16 @@ impl glyphs2::Glyphs2 {
 }
 
 impl glyphs2::Instance {
-    fn axis_values(&self, axes: &[Axis]) -> Vec<f32> {
-        if let Some(cp) = self
+    fn axis_values(&self, axes: &[Axis]) -> Vec<f64> {
+        if let Some(locations) = self
             .custom_parameters
             .iter()
             .find(|x| x.name == "Axis Location")
-            .and_then(|x| x.value.as_array())
+            .and_then(|x| x.as_axis_locations())
         {
-            let location = cp
+            let location = locations
                 .iter()
-                .flat_map(|x| x.as_dict())
-                .filter_map(|d| {
-                    let name = d.get("Axis")?.as_str()?;
-                    // Sometimes it's a string. :-/
-                    let loc = d.get("Location")?;
-                    if let Some(loc_str) = loc.as_str() {
-                        if let Ok(loc_num) = loc_str.parse::<f32>() {
-                            return Some((name, loc_num));
-                        }
-                    }
-                    let value = loc.as_f64()? as f32;
-                    Some((name, value))
-                })
+                .map(|l| (l.axis.as_str(), l.location))
                 .collect::<BTreeMap<_, _>>();
             // 0.0 here looks bad. If we have an incomplete location, we should probably
             // fill it with the default for that axis, but we don't know what the default is yet.
@@ -587,32 +528,6 @@ impl glyphs2::Instance {
     }
 
     fn to_glyphs3(&self, axes: &[Axis]) -> glyphs3::Instance {
-        let weight_value = match self.weight_class.as_deref() {
-            Some("Thin") => Some(100),
-            Some("ExtraLight") => Some(200),
-            Some("Light") => Some(300),
-            Some("Regular") => Some(400),
-            Some("Medium") => Some(500),
-            Some("SemiBold") => Some(600),
-            Some("Bold") => Some(700),
-            Some("ExtraBold") => Some(800),
-            Some("Black") => Some(900),
-            _ => None,
-        };
-        let width_value = match self.width_class.as_deref() {
-            Some("Ultra Condensed") => Some(1),
-            Some("Extra Condensed") => Some(2),
-            Some("Condensed") => Some(3),
-            Some("Semi Condensed") => Some(4),
-            Some("Regular") => Some(5),
-            Some("Medium") => Some(5),
-            Some("Medium (normal)") => Some(5),
-            Some("Semi Expanded") => Some(6),
-            Some("Expanded") => Some(7),
-            Some("Extra Expanded") => Some(8),
-            Some("Ultra Expanded") => Some(9),
-            _ => None,
-        };
         glyphs3::Instance {
             axes_values: self.axis_values(axes),
             custom_parameters: self.custom_parameters.clone(),
@@ -623,8 +538,8 @@ impl glyphs2::Instance {
             name: self.name.clone(),
             properties: vec![],
             user_data: self.user_data.clone(),
-            weight_class: weight_value,
-            width_class: width_value,
+            weight_class: self.weight_class,
+            width_class: self.width_class,
             ..Default::default()
         }
     }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 const ILLEGAL_CHARACTERS: [char; 45] = [
     '"', '*', '+', '/', ':', '<', '>', '?', '[', '\\', ']', '|', '\0', // null character
     '\x01', '\x02', '\x03', '\x04', '\x05', '\x06', '\x07', '\x08', '\t', '\n', '\x0B', '\x0C',
@@ -9,8 +11,36 @@ const RESERVED_FILE_NAMES: [&str; 13] = [
     "a:-z:", // That one doesn't look right
 ];
 const MAX_FILE_NAME_LENGTH: usize = 255;
+const GLYPH_FILE_EXTENSION: &str = ".glyph";
+// A tilde and eight hex digits
+const CLASH_SUFFIX_LENGTH: usize = 9;
+
+fn clip(s: &mut String, max: usize) {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+}
+
+// FNV-1a, which unlike the std hasher gives the same value on every Rust release
+fn clash_hash(name: &str, salt: u32) -> u32 {
+    name.bytes()
+        .chain(salt.to_le_bytes())
+        .fold(0x811c_9dc5, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        })
+}
 
-pub(crate) fn user_name_to_file_name(name: &str) -> String {
+/// Convert a glyph name to the file name used for it in a `.glyphspackage`
+///
+/// This is the scheme of Glyphs.app and ufoLib: characters which are not
+/// allowed in file names become `_`, uppercase letters are followed by `_`
+/// so that names differing only in case do not clash on case-insensitive
+/// file systems, and reserved names such as `con` gain a `_` prefix.
+pub fn user_name_to_file_name(name: &str) -> String {
     // replace an initial period with an _
     let mut user_name = name.to_string();
     if user_name.starts_with('.') {
@@ -29,7 +59,7 @@ pub(crate) fn user_name_to_file_name(name: &str) -> String {
         }
     }
     // Clip to 255
-    filtered_username.truncate(MAX_FILE_NAME_LENGTH);
+    clip(&mut filtered_username, MAX_FILE_NAME_LENGTH);
     // Test for illegal file names
     let mut parts = vec![];
     for part in filtered_username.split('.') {
@@ -42,6 +72,68 @@ pub(crate) fn user_name_to_file_name(name: &str) -> String {
     parts.join(".")
 }
 
+/// Recover a glyph name from a `.glyphspackage` file name, as far as possible
+///
+/// This undoes [`user_name_to_file_name`] and the clash suffixes added when
+/// saving packages, but characters replaced by `_` cannot be recovered, so
+/// `_notdef` stays as it is rather than becoming `.notdef`. The name stored
+/// inside a glyph file is authoritative.
+pub fn file_name_to_user_name(file_name: &str) -> String {
+    let mut stem = file_name
+        .strip_suffix(GLYPH_FILE_EXTENSION)
+        .unwrap_or(file_name);
+    if let Some((base, hash)) = stem.rsplit_once('~') {
+        if hash.len() == CLASH_SUFFIX_LENGTH - 1 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            stem = base;
+        }
+    }
+    let mut parts = vec![];
+    for part in stem.split('.') {
+        let part = match part.strip_prefix('_') {
+            Some(rest) if RESERVED_FILE_NAMES.contains(&rest.to_lowercase().as_str()) => rest,
+            _ => part,
+        };
+        let mut user_name = String::new();
+        let mut chars = part.chars().peekable();
+        while let Some(c) = chars.next() {
+            user_name.push(c);
+            if c.to_lowercase().to_string() != c.to_string() && chars.peek() == Some(&'_') {
+                chars.next();
+            }
+        }
+        parts.push(user_name);
+    }
+    parts.join(".")
+}
+
+/// The file names, without extension, of the glyph files of a package
+///
+/// Names are given out in order. When a name would clash with an earlier
+/// one, ignoring case, after filtering or clipping to the file name length
+/// limit, it is clipped further and given a suffix from a hash of the glyph
+/// name, as ufoLib does with a counter. Loading and saving both use this,
+/// so any set of glyph names round-trips.
+pub(crate) fn glyph_file_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let max_length = MAX_FILE_NAME_LENGTH - GLYPH_FILE_EXTENSION.len();
+    let mut used = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let base = user_name_to_file_name(name);
+            let mut file_name = base.clone();
+            clip(&mut file_name, max_length);
+            let mut salt = 0;
+            while !used.insert(file_name.to_lowercase()) {
+                file_name = base.clone();
+                clip(&mut file_name, max_length - CLASH_SUFFIX_LENGTH);
+                file_name.push_str(&format!("~{:08x}", clash_hash(name, salt)));
+                salt += 1;
+            }
+            file_name
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +163,51 @@ mod tests {
         assert_eq!(user_name_to_file_name("con.alt"), "_con.alt");
         assert_eq!(user_name_to_file_name("alt.con"), "alt._con");
     }
+
+    #[test]
+    fn test_file_name_to_user_name() {
+        for name in [
+            "a",
+            "A",
+            "AE",
+            "A.Alt",
+            "T_H",
+            "F_F_I",
+            "f_f_i",
+            "Aacute_V.swash",
+            "con",
+            "CON",
+            "con.alt",
+            "alt.con",
+        ] {
+            assert_eq!(file_name_to_user_name(&user_name_to_file_name(name)), name);
+        }
+        assert_eq!(file_name_to_user_name("A_.ss01.glyph"), "A.ss01");
+        assert_eq!(file_name_to_user_name("a_b~0123abcd.glyph"), "a_b");
+    }
+
+    #[test]
+    fn test_glyph_file_names_clash() {
+        let long_a = format!("{}a", "x".repeat(300));
+        let long_b = format!("{}b", "x".repeat(300));
+        let names = [
+            "a/b",
+            "a:b",
+            "a_b",
+            long_a.as_str(),
+            long_b.as_str(),
+            "é".repeat(200).as_str(),
+        ]
+        .map(String::from);
+        let file_names = glyph_file_names(names.iter().map(String::as_str));
+        assert_eq!(file_names[0], "a_b");
+        assert!(file_names[1].starts_with("a_b~"));
+        assert!(file_names[2].starts_with("a_b~"));
+        assert_ne!(file_names[1], file_names[2]);
+        assert_eq!(file_names[3].len(), 249);
+        assert!(file_names[4].starts_with("xxx") && file_names[4].contains('~'));
+        let unique: HashSet<_> = file_names.iter().map(|f| f.to_lowercase()).collect();
+        assert_eq!(unique.len(), names.len());
+        assert!(file_names.iter().all(|f| f.len() + ".glyph".len() <= 255));
+    }
 }
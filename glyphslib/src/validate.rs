@@ -0,0 +1,603 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    glyphs3::{Glyphs3, Shape},
+    metrics::vertical_metric,
+};
+
+/// How serious a problem found during validation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Probably a mistake, but the font can still be built
+    Warning,
+    /// The font is broken or will produce broken binaries
+    Error,
+}
+
+/// Where in the font a problem was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// The font as a whole
+    Font,
+    /// A master, identified by its ID
+    Master(String),
+    /// An instance, identified by its name
+    Instance(String),
+    /// A glyph, identified by its name
+    Glyph(String),
+    /// A layer of a glyph
+    Layer {
+        /// The name of the glyph
+        glyph: String,
+        /// The ID of the layer
+        layer_id: String,
+    },
+    /// A feature prefix, identified by its name
+    FeaturePrefix(String),
+    /// A class of the feature code, identified by its name
+    Class(String),
+    /// A feature, identified by its tag
+    Feature(String),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Font => write!(f, "font"),
+            Location::Master(id) => write!(f, "master {id}"),
+            Location::Instance(name) => write!(f, "instance {name}"),
+            Location::Glyph(name) => write!(f, "glyph {name}"),
+            Location::Layer { glyph, layer_id } => write!(f, "glyph {glyph}, layer {layer_id}"),
+            Location::FeaturePrefix(name) => write!(f, "prefix {name}"),
+            Location::Class(name) => write!(f, "class {name}"),
+            Location::Feature(tag) => write!(f, "feature {tag}"),
+        }
+    }
+}
+
+/// A problem found during validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Where the problem was found
+    pub location: Location,
+    /// A human-readable description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn warning(location: Location, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            location,
+            message,
+        }
+    }
+
+    pub(crate) fn error(location: Location, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            location,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}: {}", self.location, self.message)
+    }
+}
+
+const VERTICAL_METRICS_PARAMETERS: [&str; 8] = [
+    "typoAscender",
+    "typoDescender",
+    "typoLineGap",
+    "hheaAscender",
+    "hheaDescender",
+    "hheaLineGap",
+    "winAscent",
+    "winDescent",
+];
+
+/// Check the `typo*`, `hhea*` and `win*` vertical metrics custom parameters
+///
+/// Reports parameters which are set on some masters but not others, values which
+/// do not change monotonically along an axis (and so cannot interpolate cleanly),
+/// and `winAscent`/`winDescent` values which would clip the tallest or deepest
+/// exported glyph of a master.
+pub fn check_vertical_metrics(font: &Glyphs3) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for name in VERTICAL_METRICS_PARAMETERS {
+        let values: Vec<Option<f64>> = font
+            .masters
+            .iter()
+            .map(|m| vertical_metric(font, m, name))
+            .collect();
+        if values.iter().all(Option::is_none) {
+            continue;
+        }
+        for (master, value) in font.masters.iter().zip(values.iter()) {
+            if value.is_none() {
+                diagnostics.push(Diagnostic::warning(
+                    Location::Master(master.id.clone()),
+                    format!(
+                        "{name} is set on other masters but not on master '{}'",
+                        master.name
+                    ),
+                ));
+            }
+        }
+        check_monotonic(font, name, &values, &mut diagnostics);
+    }
+    check_win_metrics_cover_bounds(font, &mut diagnostics);
+    diagnostics
+}
+
+/// Run all the checks on a font: its structure and its vertical metrics, and
+/// with the `fea` feature its feature code
+pub fn validate(font: &Glyphs3) -> Vec<Diagnostic> {
+    let mut diagnostics = check_structure(font);
+    diagnostics.extend(check_vertical_metrics(font));
+    #[cfg(feature = "fea")]
+    diagnostics.extend(font.check_features());
+    diagnostics
+}
+
+/// Check that the parts of the font which refer to each other agree
+///
+/// Reports masters whose IDs are not unique, or whose axis coordinates or
+/// metric values do not match the axes and metrics of the font; glyphs whose
+/// names or code points are not unique, or which lack a layer for some
+/// master; layers which belong to no master; components of glyphs the font
+/// does not have; and left-to-right kerning of unknown masters, glyphs or
+/// groups.
+pub fn check_structure(font: &Glyphs3) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    check_masters(font, &mut diagnostics);
+    check_glyphs(font, &mut diagnostics);
+    check_kerning(font, &mut diagnostics);
+    diagnostics
+}
+
+fn check_masters(font: &Glyphs3, diagnostics: &mut Vec<Diagnostic>) {
+    let mut ids = HashSet::new();
+    for master in &font.masters {
+        let location = || Location::Master(master.id.clone());
+        if !ids.insert(master.id.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                location(),
+                format!("master '{}' has the same ID as another master", master.name),
+            ));
+        }
+        if master.axes_values.len() != font.axes.len() {
+            diagnostics.push(Diagnostic::error(
+                location(),
+                format!(
+                    "master '{}' has {} axis coordinates but the font has {} axes",
+                    master.name,
+                    master.axes_values.len(),
+                    font.axes.len()
+                ),
+            ));
+        }
+        let (values, metrics) = (master.metric_values.len(), font.metrics.len());
+        if values > metrics {
+            diagnostics.push(Diagnostic::error(
+                location(),
+                format!(
+                    "master '{}' has {values} metric values but the font has {metrics} metrics",
+                    master.name
+                ),
+            ));
+        } else if values < metrics {
+            diagnostics.push(Diagnostic::warning(
+                location(),
+                format!(
+                    "master '{}' has {values} metric values for the {metrics} metrics of the font; the rest are at 0",
+                    master.name
+                ),
+            ));
+        }
+    }
+}
+
+fn check_glyphs(font: &Glyphs3, diagnostics: &mut Vec<Diagnostic>) {
+    let masters: HashSet<&str> = font.masters.iter().map(|m| m.id.as_str()).collect();
+    let mut names = HashSet::new();
+    let mut encoded: HashMap<u32, &str> = HashMap::new();
+    for glyph in &font.glyphs {
+        let location = || Location::Glyph(glyph.name.clone());
+        if !names.insert(glyph.name.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                location(),
+                format!("there is more than one glyph named '{}'", glyph.name),
+            ));
+        }
+        for codepoint in &glyph.unicode {
+            match encoded.get(codepoint) {
+                Some(other) if *other != glyph.name => diagnostics.push(Diagnostic::error(
+                    location(),
+                    format!("U+{codepoint:04X} is also encoded by glyph '{other}'"),
+                )),
+                Some(_) => {}
+                None => {
+                    encoded.insert(*codepoint, &glyph.name);
+                }
+            }
+        }
+        for master in &font.masters {
+            if !glyph.layers.iter().any(|l| l.layer_id == master.id) {
+                diagnostics.push(Diagnostic::warning(
+                    location(),
+                    format!("glyph has no layer for master '{}'", master.name),
+                ));
+            }
+        }
+        for layer in &glyph.layers {
+            let location = || Location::Layer {
+                glyph: glyph.name.clone(),
+                layer_id: layer.layer_id.clone(),
+            };
+            if !masters.contains(layer.layer_id.as_str()) {
+                match &layer.associated_master_id {
+                    None => diagnostics.push(Diagnostic::error(
+                        location(),
+                        "layer is neither a master layer nor associated with a master".to_string(),
+                    )),
+                    Some(id) if !masters.contains(id.as_str()) => {
+                        diagnostics.push(Diagnostic::error(
+                            location(),
+                            format!("layer is associated with master {id}, which does not exist"),
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+            for shape in &layer.shapes {
+                if let Shape::Component(component) = shape {
+                    if font.glyph(&component.component_glyph).is_none() {
+                        diagnostics.push(Diagnostic::error(
+                            location(),
+                            format!(
+                                "component refers to glyph '{}', which does not exist",
+                                component.component_glyph
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_kerning(font: &Glyphs3, diagnostics: &mut Vec<Diagnostic>) {
+    // A glyph's right group is used on the left of a pair, and its left group on the right
+    let left_groups: BTreeSet<&str> = font
+        .glyphs
+        .iter()
+        .filter_map(|g| g.kern_right.as_deref())
+        .collect();
+    let right_groups: BTreeSet<&str> = font
+        .glyphs
+        .iter()
+        .filter_map(|g| g.kern_left.as_deref())
+        .collect();
+    let known = |key: &str, prefix: &str, groups: &BTreeSet<&str>| match key.strip_prefix(prefix) {
+        Some(group) => groups.contains(group),
+        None => font.glyph(key).is_some(),
+    };
+    for (master_id, pairs) in &font.kerning {
+        let location = || Location::Master(master_id.clone());
+        if !font.masters.iter().any(|m| &m.id == master_id) {
+            diagnostics.push(Diagnostic::warning(
+                location(),
+                "kerning is for a master which does not exist".to_string(),
+            ));
+            continue;
+        }
+        let mut unknown = BTreeSet::new();
+        for (left, rights) in pairs {
+            if !known(left, "@MMK_L_", &left_groups) {
+                unknown.insert(left.as_str());
+            }
+            for right in rights.keys() {
+                if !known(right, "@MMK_R_", &right_groups) {
+                    unknown.insert(right.as_str());
+                }
+            }
+        }
+        for key in unknown {
+            diagnostics.push(Diagnostic::warning(
+                location(),
+                format!("kerning refers to '{key}', which is not a glyph or a kerning group"),
+            ));
+        }
+    }
+}
+
+// A master's position on one axis, its metric value and its index
+type LinePoint = (f64, f64, usize);
+
+// Along each axis, masters which share all their other coordinates form a
+// line through the designspace; the metric must not change direction along it.
+fn check_monotonic(
+    font: &Glyphs3,
+    name: &str,
+    values: &[Option<f64>],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (axis_index, axis) in font.axes.iter().enumerate() {
+        let mut lines: Vec<(Vec<f64>, Vec<LinePoint>)> = vec![];
+        for (master_index, master) in font.masters.iter().enumerate() {
+            let (Some(position), Some(value)) =
+                (master.axes_values.get(axis_index), values[master_index])
+            else {
+                continue;
+            };
+            let others: Vec<f64> = master
+                .axes_values
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix != axis_index)
+                .map(|(_, v)| *v)
+                .collect();
+            let entry = (*position, value, master_index);
+            match lines.iter_mut().find(|(key, _)| *key == others) {
+                Some((_, line)) => line.push(entry),
+                None => lines.push((others, vec![entry])),
+            }
+        }
+        for (_, mut line) in lines {
+            line.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut direction = 0.0;
+            for pair in line.windows(2) {
+                let delta = pair[1].1 - pair[0].1;
+                if delta == 0.0 {
+                    continue;
+                }
+                if direction * delta < 0.0 {
+                    let master = &font.masters[pair[0].2];
+                    diagnostics.push(Diagnostic::warning(
+                        Location::Master(master.id.clone()),
+                        format!(
+                            "{name} changes direction along the {} axis at master '{}' and will not interpolate monotonically",
+                            axis.name, master.name
+                        ),
+                    ));
+                    break;
+                }
+                direction = delta;
+            }
+        }
+    }
+}
+
+fn check_win_metrics_cover_bounds(font: &Glyphs3, diagnostics: &mut Vec<Diagnostic>) {
+    for master in &font.masters {
+        let win_ascent = vertical_metric(font, master, "winAscent");
+        let win_descent = vertical_metric(font, master, "winDescent");
+        if win_ascent.is_none() && win_descent.is_none() {
+            continue;
+        }
+        let mut highest: Option<(f64, &str)> = None;
+        let mut lowest: Option<(f64, &str)> = None;
+        for glyph in font.glyphs.iter().filter(|g| g.export) {
+            let Some(bounds) = glyph
+                .layers
+                .iter()
+                .find(|l| l.layer_id == master.id)
+                .and_then(|l| l.bounds_with_components(font, &master.id))
+            else {
+                continue;
+            };
+            if highest.map_or(true, |(y, _)| bounds.y_max > y) {
+                highest = Some((bounds.y_max, &glyph.name));
+            }
+            if lowest.map_or(true, |(y, _)| bounds.y_min < y) {
+                lowest = Some((bounds.y_min, &glyph.name));
+            }
+        }
+        if let (Some(win_ascent), Some((y_max, glyph))) = (win_ascent, highest) {
+            if y_max > win_ascent {
+                diagnostics.push(Diagnostic::error(
+                    Location::Master(master.id.clone()),
+                    format!(
+                        "winAscent {win_ascent} of master '{}' clips glyph '{glyph}', which reaches {y_max}",
+                        master.name
+                    ),
+                ));
+            }
+        }
+        if let (Some(win_descent), Some((y_min, glyph))) = (win_descent, lowest) {
+            if -y_min > win_descent {
+                diagnostics.push(Diagnostic::error(
+                    Location::Master(master.id.clone()),
+                    format!(
+                        "winDescent {win_descent} of master '{}' clips glyph '{glyph}', which reaches {y_min}",
+                        master.name
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::CustomParameter,
+        glyphs3::{Axis, Glyph, Master},
+    };
+    use itertools::Itertools;
+    use openstep_plist::{de::Deserializer, Plist};
+
+    fn master(id: &str, weight: f64, parameters: &[(&str, i64)]) -> Master {
+        Master {
+            id: id.to_string(),
+            name: id.to_string(),
+            axes_values: vec![weight],
+            custom_parameters: parameters
+                .iter()
+                .map(|(name, value)| CustomParameter {
+                    name: name.to_string(),
+                    value: Plist::Integer(*value),
+                    disabled: false,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn square_glyph(name: &str, master_ids: &[&str], bottom: f64, top: f64) -> Glyph {
+        let layers = master_ids
+            .iter()
+            .map(|id| {
+                format!(
+                    "{{layerId = {id}; shapes = ({{closed = 1; nodes = ((0,{bottom},l),(0,{top},l),(100,{top},l),(100,{bottom},l));}}); width = 100;}}"
+                )
+            })
+            .join(",");
+        let plist = Plist::parse(&format!("{{glyphname = {name}; layers = ({layers});}}")).unwrap();
+        serde_path_to_error::deserialize(&mut Deserializer::from_plist(&plist)).unwrap()
+    }
+
+    fn weight_font(masters: Vec<Master>) -> Glyphs3 {
+        Glyphs3 {
+            axes: vec![Axis {
+                hidden: false,
+                name: "Weight".to_string(),
+                tag: "wght".to_string(),
+            }],
+            masters,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_consistent_metrics_pass() {
+        let mut font = weight_font(vec![
+            master("light", 300.0, &[("winAscent", 1000), ("winDescent", 300)]),
+            master("bold", 700.0, &[("winAscent", 1100), ("winDescent", 300)]),
+        ]);
+        font.glyphs = vec![square_glyph("H", &["light", "bold"], -200.0, 900.0)];
+        assert_eq!(check_vertical_metrics(&font), vec![]);
+    }
+
+    #[test]
+    fn test_missing_and_non_monotonic() {
+        let font = weight_font(vec![
+            master(
+                "light",
+                300.0,
+                &[("typoAscender", 800), ("hheaAscender", 900)],
+            ),
+            master("regular", 400.0, &[("typoAscender", 850)]),
+            master(
+                "bold",
+                700.0,
+                &[("typoAscender", 820), ("hheaAscender", 900)],
+            ),
+        ]);
+        let diagnostics = check_vertical_metrics(&font);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.location == Location::Master("regular".to_string())));
+        assert!(diagnostics[0].message.contains("typoAscender"));
+        assert!(diagnostics[0].message.contains("monotonically"));
+        assert!(diagnostics[1].message.contains("hheaAscender"));
+    }
+
+    #[test]
+    fn test_win_metrics_clip() {
+        let mut font = weight_font(vec![master(
+            "regular",
+            400.0,
+            &[("winAscent", 800), ("winDescent", 250)],
+        )]);
+        font.glyphs = vec![
+            square_glyph("H", &["regular"], 0.0, 700.0),
+            square_glyph("Aring", &["regular"], 0.0, 950.0),
+            square_glyph("g", &["regular"], -260.0, 500.0),
+        ];
+        let diagnostics = check_vertical_metrics(&font);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+        assert!(diagnostics[0].message.contains("'Aring'"));
+        assert!(diagnostics[1].message.contains("'g'"));
+    }
+
+    #[test]
+    fn test_structure_of_sample_font() {
+        let font =
+            crate::Font::load(std::path::Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap();
+        assert_eq!(check_structure(font.as_glyphs3().unwrap()), vec![]);
+        // The tallest glyphs of the bold master go above its winAscent
+        assert!(font
+            .validate()
+            .iter()
+            .any(|d| d.severity == Severity::Error && d.message.contains("winAscent")));
+    }
+
+    #[test]
+    fn test_structural_problems() {
+        use crate::builder::{GlyphBuilder, Glyphs3Builder, MasterBuilder};
+        use crate::glyphs3::Component;
+
+        let mut font = Glyphs3Builder::new("Test")
+            .master(MasterBuilder::new("Regular").id("m01"))
+            .glyph(GlyphBuilder::new("A").unicode(0x41))
+            .glyph(GlyphBuilder::new("A.alt"))
+            .glyph(
+                GlyphBuilder::new("Aacute").shape(Shape::Component(Component {
+                    component_glyph: "A".to_string(),
+                    ..Default::default()
+                })),
+            )
+            .build();
+        assert_eq!(check_structure(&font), vec![]);
+
+        font.masters[0].metric_values.pop();
+        font.glyph_mut("A.alt").unwrap().unicode = vec![0x41];
+        let Shape::Component(component) =
+            &mut font.glyph_mut("Aacute").unwrap().layers[0].shapes[0]
+        else {
+            unreachable!()
+        };
+        component.component_glyph = "acutecomb".to_string();
+        let mut stray = font.glyphs[0].layers[0].clone();
+        stray.layer_id = "stray".to_string();
+        font.glyphs[0].layers.push(stray);
+        font.glyph_mut("A").unwrap().kern_right = Some("A".to_string());
+        font.set_kerning_pair("m01", "@MMK_L_A", "@MMK_R_A", -10.0);
+        let diagnostics: Vec<(Severity, Location)> = check_structure(&font)
+            .into_iter()
+            .map(|d| (d.severity, d.location))
+            .collect();
+        let layer = |glyph: &str, layer_id: &str| Location::Layer {
+            glyph: glyph.to_string(),
+            layer_id: layer_id.to_string(),
+        };
+        assert_eq!(
+            diagnostics,
+            vec![
+                (Severity::Warning, Location::Master("m01".to_string())),
+                (Severity::Error, layer("A", "stray")),
+                (Severity::Error, Location::Glyph("A.alt".to_string())),
+                (Severity::Error, layer("Aacute", "m01")),
+                (Severity::Warning, Location::Master("m01".to_string())),
+            ]
+        );
+    }
+}
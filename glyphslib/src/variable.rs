@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    axes::{AxisMapping, Location},
+    common::{parameter_str, CustomParameter},
+    glyphs3::{AxisRule, ExportType, Glyphs3, Instance, Master},
+};
+
+/// An axis of a variable font, in user coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableAxis {
+    /// The OpenType tag of the axis
+    pub tag: String,
+    /// The user-facing name of the axis
+    pub name: String,
+    /// Whether the axis is hidden from the font user
+    pub hidden: bool,
+    /// The least user coordinate of the masters
+    pub minimum: f64,
+    /// The user coordinate of the origin master
+    pub default: f64,
+    /// The greatest user coordinate of the masters
+    pub maximum: f64,
+    /// The mapping between user and design coordinates
+    pub mapping: AxisMapping,
+}
+
+/// A variable font to export, as a variable instance of the font sets it up
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableFont {
+    /// The name of the variable instance, or `None` for the font Glyphs
+    /// exports when there is no variable instance
+    pub name: Option<String>,
+    /// The IDs of the masters the font is made of
+    pub masters: Vec<String>,
+    /// The axes of the font, which are the same for each font but for
+    /// their ranges, as masters may be disabled
+    pub axes: Vec<VariableAxis>,
+    /// The enabled custom parameters of the variable instance, which apply
+    /// to this font on top of those of the font
+    pub custom_parameters: Vec<CustomParameter>,
+}
+
+/// A named instance of the variable fonts
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedInstance {
+    /// The name of the instance
+    pub name: String,
+    /// The PostScript name of the instance
+    pub postscript_name: String,
+    /// Where the instance is, in user coordinates by axis tag
+    pub location: Location,
+}
+
+/// The range of one axis in which a substitution rule applies, in design
+/// coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    /// The OpenType tag of the axis
+    pub axis: String,
+    /// The lowest location, if the range is bounded below
+    pub minimum: Option<f64>,
+    /// The highest location, if the range is bounded above
+    pub maximum: Option<f64>,
+}
+
+/// Glyphs substituted by alternates within a region of the design space
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionRule {
+    /// The axis ranges which all must hold for the rule to apply
+    pub conditions: Vec<Condition>,
+    /// The alternate glyph for each glyph
+    pub substitutions: BTreeMap<String, String>,
+}
+
+/// The feature variations made of the alternate ("bracket") layers
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureVariations {
+    /// The feature the rules go in: `rvrn` unless the `Feature for Feature
+    /// Variations` parameter says otherwise
+    pub feature: String,
+    /// The rules, one for each distinct set of axis ranges
+    pub rules: Vec<SubstitutionRule>,
+}
+
+/// Everything needed to export the variable fonts of a font
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableFontPlan {
+    /// The ID of the master at the default location, as the
+    /// `Variable Font Origin` parameter says
+    pub origin: String,
+    /// The names of the masters the `Disable Masters` parameter of the
+    /// font leaves out of all variable fonts
+    pub disabled_masters: Vec<String>,
+    /// The fonts to export: one for each exported variable instance, or
+    /// one for the whole font if there are none
+    pub fonts: Vec<VariableFont>,
+    /// The exported static instances, which become named instances
+    pub named_instances: Vec<NamedInstance>,
+    /// The alternate glyphs and where they are used
+    pub feature_variations: FeatureVariations,
+}
+
+// The master names a `Disable Masters` parameter lists
+fn disabled_masters(parameters: &[CustomParameter]) -> Vec<String> {
+    parameters
+        .iter()
+        .filter(|p| p.name == "Disable Masters" && !p.disabled)
+        .filter_map(|p| p.value.as_array())
+        .flatten()
+        .filter_map(|name| name.as_str().map(String::from))
+        .collect()
+}
+
+impl Glyphs3 {
+    // The axes of a variable font made of these masters
+    fn variable_axes(&self, masters: &[&Master], origin: &Location) -> Vec<VariableAxis> {
+        let locations: Vec<Location> = masters
+            .iter()
+            .map(|m| self.master_user_location(m))
+            .collect();
+        self.axes
+            .iter()
+            .zip(self.axis_mappings())
+            .map(|(axis, mapping)| {
+                let values = locations.iter().filter_map(|l| l.get(&axis.tag).copied());
+                let default = origin.get(&axis.tag).copied().unwrap_or_default();
+                VariableAxis {
+                    tag: axis.tag.clone(),
+                    name: axis.name.clone(),
+                    hidden: axis.hidden,
+                    minimum: values.clone().fold(default, f64::min),
+                    default,
+                    maximum: values.fold(default, f64::max),
+                    mapping,
+                }
+            })
+            .collect()
+    }
+
+    fn variable_font(
+        &self,
+        instance: Option<&Instance>,
+        disabled: &[String],
+        origin: &Location,
+    ) -> VariableFont {
+        let parameters: Vec<CustomParameter> = instance
+            .map(|i| i.custom_parameters.iter())
+            .into_iter()
+            .flatten()
+            .filter(|p| !p.disabled)
+            .cloned()
+            .collect();
+        let mut disabled = disabled.to_vec();
+        disabled.extend(disabled_masters(&parameters));
+        let masters: Vec<&Master> = self
+            .masters
+            .iter()
+            .filter(|m| !disabled.contains(&m.name))
+            .collect();
+        VariableFont {
+            name: instance.map(|i| i.name.clone()),
+            masters: masters.iter().map(|m| m.id.clone()).collect(),
+            axes: self.variable_axes(&masters, origin),
+            custom_parameters: parameters,
+        }
+    }
+
+    /// What Glyphs would export as variable fonts
+    ///
+    /// Returns `None` if the font has no masters.
+    pub fn variable_font_plan(&self) -> Option<VariableFontPlan> {
+        let origin = self.default_master()?;
+        let origin_location = self.master_user_location(origin);
+        let disabled = disabled_masters(&self.custom_parameters);
+        let variable: Vec<&Instance> = self
+            .instances
+            .iter()
+            .filter(|i| i.exports && matches!(i.export_type, ExportType::Variable))
+            .collect();
+        let fonts = if variable.is_empty() {
+            vec![self.variable_font(None, &disabled, &origin_location)]
+        } else {
+            variable
+                .into_iter()
+                .map(|i| self.variable_font(Some(i), &disabled, &origin_location))
+                .collect()
+        };
+        Some(VariableFontPlan {
+            origin: origin.id.clone(),
+            disabled_masters: disabled,
+            fonts,
+            named_instances: self.named_instances(),
+            feature_variations: self.feature_variations(),
+        })
+    }
+
+    /// The exported static instances, as named instances of a variable font
+    pub fn named_instances(&self) -> Vec<NamedInstance> {
+        self.instances
+            .iter()
+            .filter(|i| i.exports && matches!(i.export_type, ExportType::Static))
+            .map(|i| NamedInstance {
+                name: i.name.clone(),
+                postscript_name: i.postscript_name(self),
+                location: self.instance_user_location(i),
+            })
+            .collect()
+    }
+
+    /// The substitution rules the alternate ("bracket") layers of exported
+    /// glyphs make
+    ///
+    /// Each distinct set of axis ranges of a glyph's bracket layers becomes
+    /// an alternate glyph, named as glyphsLib names them
+    /// (`a.BRACKET.varAlt01`), which replaces the glyph within the ranges.
+    /// Glyphs with the same ranges share a rule.
+    pub fn feature_variations(&self) -> FeatureVariations {
+        let mut rules: Vec<SubstitutionRule> = vec![];
+        for glyph in self.glyphs.iter().filter(|g| g.export) {
+            let mut boxes: Vec<&[AxisRule]> = vec![];
+            for rules in glyph.layers.iter().filter_map(|l| l.bracket_axis_rules()) {
+                if !boxes.contains(&rules) {
+                    boxes.push(rules);
+                }
+            }
+            for (index, axis_rules) in boxes.into_iter().enumerate() {
+                let conditions: Vec<Condition> = self
+                    .axes
+                    .iter()
+                    .zip(axis_rules)
+                    .filter(|(_, rule)| rule.min.is_some() || rule.max.is_some())
+                    .map(|(axis, rule)| Condition {
+                        axis: axis.tag.clone(),
+                        minimum: rule.min,
+                        maximum: rule.max,
+                    })
+                    .collect();
+                let alternate = format!("{}.BRACKET.varAlt{:02}", glyph.name, index + 1);
+                match rules.iter_mut().find(|r| r.conditions == conditions) {
+                    Some(rule) => {
+                        rule.substitutions.insert(glyph.name.clone(), alternate);
+                    }
+                    None => rules.push(SubstitutionRule {
+                        conditions,
+                        substitutions: BTreeMap::from([(glyph.name.clone(), alternate)]),
+                    }),
+                }
+            }
+        }
+        FeatureVariations {
+            feature: parameter_str(&self.custom_parameters, "Feature for Feature Variations")
+                .unwrap_or("rvrn")
+                .to_string(),
+            rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use openstep_plist::Plist;
+    use std::path;
+
+    fn load(file: &str) -> Glyphs3 {
+        let file = path::Path::new("resources").join(file);
+        match Font::load(&file).unwrap() {
+            Font::Glyphs3(glyphs3) => glyphs3,
+            Font::Glyphs2(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_variable_font_plan() {
+        let mut font = load("WghtVar_3master_CustomOrigin.glyphs");
+        let plan = font.variable_font_plan().unwrap();
+        assert_eq!(plan.origin, "E09E0C54-128D-4FEA-B209-1B70BEFE300B");
+        assert_eq!(plan.fonts.len(), 1);
+        assert_eq!(plan.fonts[0].name, None);
+        assert_eq!(plan.fonts[0].masters.len(), 3);
+        let wght = &plan.fonts[0].axes[0];
+        assert_eq!(
+            (wght.minimum, wght.default, wght.maximum),
+            (200.0, 700.0, 700.0)
+        );
+
+        // A variable instance leaving out the lightest master
+        let thin = font
+            .masters
+            .iter()
+            .find(|m| m.name == "Thin")
+            .unwrap()
+            .clone();
+        font.instances.push(Instance {
+            name: "Heavier".to_string(),
+            export_type: ExportType::Variable,
+            exports: true,
+            custom_parameters: vec![CustomParameter {
+                name: "Disable Masters".to_string(),
+                value: Plist::Array(vec![Plist::String(thin.name.clone())]),
+                disabled: false,
+            }],
+            ..Default::default()
+        });
+        let plan = font.variable_font_plan().unwrap();
+        assert_eq!(plan.fonts.len(), 1);
+        let heavier = &plan.fonts[0];
+        assert_eq!(heavier.name.as_deref(), Some("Heavier"));
+        assert!(!heavier.masters.contains(&thin.id));
+        assert_eq!(heavier.axes[0].minimum, 400.0);
+        assert_eq!(
+            plan.named_instances.len(),
+            font.instances.iter().filter(|i| i.exports).count() - 1
+        );
+    }
+
+    #[test]
+    fn test_feature_variations() {
+        use crate::glyphs3::{Glyph, Layer, LayerAttributes};
+        let mut font = load("WghtVar_3master_CustomOrigin.glyphs");
+        let layer = font.glyphs[0].layers[0].clone();
+        let bracket = |min: Option<f64>, max: Option<f64>| Layer {
+            layer_id: "bracket".to_string(),
+            associated_master_id: Some(font.masters[0].id.clone()),
+            attr: LayerAttributes {
+                axis_rules: vec![AxisRule { min, max }],
+                ..Default::default()
+            },
+            ..layer.clone()
+        };
+        let glyphs = [
+            ("dollar", vec![bracket(Some(600.0), None)]),
+            (
+                "cent",
+                vec![bracket(Some(600.0), None), bracket(None, Some(300.0))],
+            ),
+        ];
+        let glyphs: Vec<Glyph> = glyphs
+            .into_iter()
+            .map(|(name, layers)| Glyph {
+                name: name.to_string(),
+                export: true,
+                layers,
+                ..Default::default()
+            })
+            .collect();
+        font.glyphs = glyphs;
+        let variations = font.feature_variations();
+        assert_eq!(variations.feature, "rvrn");
+        assert_eq!(
+            variations.rules,
+            [
+                SubstitutionRule {
+                    conditions: vec![Condition {
+                        axis: "wght".to_string(),
+                        minimum: Some(600.0),
+                        maximum: None,
+                    }],
+                    substitutions: BTreeMap::from([
+                        ("cent".to_string(), "cent.BRACKET.varAlt01".to_string()),
+                        ("dollar".to_string(), "dollar.BRACKET.varAlt01".to_string()),
+                    ]),
+                },
+                SubstitutionRule {
+                    conditions: vec![Condition {
+                        axis: "wght".to_string(),
+                        minimum: None,
+                        maximum: Some(300.0),
+                    }],
+                    substitutions: BTreeMap::from([(
+                        "cent".to_string(),
+                        "cent.BRACKET.varAlt02".to_string()
+                    )]),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use openstep_plist::{de::Deserializer, Plist};
+
+use crate::{glyphs3, Font, Result};
+
+/// What changed on disk, as reported by [`Watcher::poll`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// Something other than the glyphs changed, such as masters or kerning
+    FontInfoChanged,
+    /// The named glyph was added
+    GlyphAdded(String),
+    /// The named glyph was changed
+    GlyphModified(String),
+    /// The named glyph was removed
+    GlyphRemoved(String),
+}
+
+// Modification time and length; the length catches writes which land
+// within the timestamp resolution of the file system.
+type Stamp = (SystemTime, u64);
+
+fn is_package(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("glyphspackage"))
+}
+
+fn stamp(path: &Path) -> std::io::Result<Stamp> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.modified()?, metadata.len()))
+}
+
+fn stamps(path: &Path) -> std::io::Result<BTreeMap<PathBuf, Stamp>> {
+    let mut stamps = BTreeMap::new();
+    if !is_package(path) {
+        stamps.insert(path.to_path_buf(), stamp(path)?);
+        return Ok(stamps);
+    }
+    for name in ["fontinfo.plist", "order.plist", "UIState.plist"] {
+        let file = path.join(name);
+        if let Ok(file_stamp) = stamp(&file) {
+            stamps.insert(file, file_stamp);
+        }
+    }
+    if let Ok(entries) = fs::read_dir(path.join("glyphs")) {
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension() == Some(OsStr::new("glyph")) {
+                let file_stamp = stamp(&file)?;
+                stamps.insert(file, file_stamp);
+            }
+        }
+    }
+    Ok(stamps)
+}
+
+fn diff_glyphs<G: PartialEq>(
+    old: &[G],
+    new: &[G],
+    name: impl Fn(&G) -> &str,
+    events: &mut Vec<WatchEvent>,
+) {
+    for glyph in new {
+        match old.iter().find(|g| name(g) == name(glyph)) {
+            None => events.push(WatchEvent::GlyphAdded(name(glyph).to_string())),
+            Some(before) if before != glyph => {
+                events.push(WatchEvent::GlyphModified(name(glyph).to_string()))
+            }
+            Some(_) => {}
+        }
+    }
+    for glyph in old {
+        if !new.iter().any(|g| name(g) == name(glyph)) {
+            events.push(WatchEvent::GlyphRemoved(name(glyph).to_string()));
+        }
+    }
+}
+
+// Compare two loads of a font. The glyphs are taken out for the comparison
+// of everything else, rather than cloning the rest of the font.
+fn font_events(old: &mut Font, new: &mut Font) -> Vec<WatchEvent> {
+    let mut events = vec![];
+    match (old, new) {
+        (Font::Glyphs3(old), Font::Glyphs3(new)) => {
+            diff_glyphs(&old.glyphs, &new.glyphs, |g| &g.name, &mut events);
+            let (old_glyphs, new_glyphs) = (
+                std::mem::take(&mut old.glyphs),
+                std::mem::take(&mut new.glyphs),
+            );
+            if old != new {
+                events.insert(0, WatchEvent::FontInfoChanged);
+            }
+            (old.glyphs, new.glyphs) = (old_glyphs, new_glyphs);
+        }
+        (Font::Glyphs2(old), Font::Glyphs2(new)) => {
+            diff_glyphs(&old.glyphs, &new.glyphs, |g| &g.name, &mut events);
+            let (old_glyphs, new_glyphs) = (
+                std::mem::take(&mut old.glyphs),
+                std::mem::take(&mut new.glyphs),
+            );
+            if old != new {
+                events.insert(0, WatchEvent::FontInfoChanged);
+            }
+            (old.glyphs, new.glyphs) = (old_glyphs, new_glyphs);
+        }
+        // The file was saved in the other format version
+        _ => events.push(WatchEvent::FontInfoChanged),
+    }
+    events
+}
+
+/// Keeps a font loaded from disk up to date as its file changes
+///
+/// The watcher polls modification times, so it needs no platform support
+/// and notices changes made by any program. A `.glyphs` file is reloaded
+/// whole when it changes. In a `.glyphspackage`, a change to only some
+/// glyph files reloads just those glyphs; any other change reloads the
+/// package.
+///
+/// ```no_run
+/// use glyphslib::watch::Watcher;
+/// use std::{path::Path, time::Duration};
+///
+/// let mut watcher = Watcher::new(Path::new("MyFont.glyphspackage")).unwrap();
+/// loop {
+///     for event in watcher.wait(Duration::from_millis(250)).unwrap() {
+///         println!("{event:?}");
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Watcher {
+    path: PathBuf,
+    font: Font,
+    stamps: BTreeMap<PathBuf, Stamp>,
+}
+
+impl Watcher {
+    /// Load a font and start watching it
+    pub fn new(path: &Path) -> Result<Self> {
+        let stamps = stamps(path)?;
+        Ok(Watcher {
+            path: path.to_path_buf(),
+            font: Font::load(path)?,
+            stamps,
+        })
+    }
+
+    /// The font as of the most recent reload
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    /// Stop watching, keeping the font
+    pub fn into_font(self) -> Font {
+        self.font
+    }
+
+    /// Check for changes, reloading whatever changed
+    ///
+    /// Returns no events if nothing has changed since the last call. If the
+    /// font cannot be reloaded, for example because it was caught half
+    /// written, the error is returned and the change is picked up again by
+    /// the next call.
+    pub fn poll(&mut self) -> Result<Vec<WatchEvent>> {
+        let stamps = stamps(&self.path)?;
+        if stamps == self.stamps {
+            return Ok(vec![]);
+        }
+        let mut events = None;
+        if is_package(&self.path) && stamps.keys().eq(self.stamps.keys()) {
+            let changed: Vec<&PathBuf> = stamps
+                .iter()
+                .filter(|(file, stamp)| self.stamps.get(*file) != Some(stamp))
+                .map(|(file, _)| file)
+                .collect();
+            if changed
+                .iter()
+                .all(|file| file.extension() == Some(OsStr::new("glyph")))
+            {
+                events = self.reload_glyphs(&changed)?;
+            }
+        }
+        let events = match events {
+            Some(events) => events,
+            None => self.reload()?,
+        };
+        self.stamps = stamps;
+        Ok(events)
+    }
+
+    /// Block until something changes, checking every `interval`
+    pub fn wait(&mut self, interval: Duration) -> Result<Vec<WatchEvent>> {
+        loop {
+            let events = self.poll()?;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    fn reload(&mut self) -> Result<Vec<WatchEvent>> {
+        let mut font = Font::load(&self.path)?;
+        let events = font_events(&mut self.font, &mut font);
+        self.font = font;
+        Ok(events)
+    }
+
+    // Returns `None` if a glyph file no longer matches a glyph of the font,
+    // such as when a glyph was renamed, so the package must be reloaded.
+    fn reload_glyphs(&mut self, files: &[&PathBuf]) -> Result<Option<Vec<WatchEvent>>> {
+        let Font::Glyphs3(font) = &mut self.font else {
+            return Ok(None);
+        };
+        let mut reloaded = Vec::with_capacity(files.len());
+        for file in files {
+            let plist = Plist::parse(&fs::read_to_string(file)?)?;
+            let glyph: glyphs3::Glyph =
+                serde_path_to_error::deserialize(&mut Deserializer::from_plist(&plist))?;
+            match font.glyphs.iter().position(|g| g.name == glyph.name) {
+                Some(ix) => reloaded.push((ix, glyph)),
+                None => return Ok(None),
+            }
+        }
+        let mut events = vec![];
+        for (ix, glyph) in reloaded {
+            if font.glyphs[ix] != glyph {
+                events.push(WatchEvent::GlyphModified(glyph.name.clone()));
+                font.glyphs[ix] = glyph;
+            }
+        }
+        Ok(Some(events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&path);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn radio_canada() -> Font {
+        Font::load(Path::new("resources/RadioCanadaDisplay.glyphs")).unwrap()
+    }
+
+    fn width_of_a(font: &Font) -> f64 {
+        let glyph = font
+            .as_glyphs3()
+            .unwrap()
+            .glyphs
+            .iter()
+            .find(|g| g.name == "A");
+        glyph.unwrap().layers[0].width
+    }
+
+    #[test]
+    fn test_watch_file() {
+        let path = scratch("glyphslib-watch.glyphs");
+        let mut font = radio_canada();
+        font.save(&path).unwrap();
+        let mut watcher = Watcher::new(&path).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        let Font::Glyphs3(glyphs3) = &mut font else {
+            unreachable!()
+        };
+        let a = glyphs3.glyphs.iter_mut().find(|g| g.name == "A").unwrap();
+        a.layers[0].width += 12345.0;
+        glyphs3.family_name = "Watched".to_string();
+        font.save(&path).unwrap();
+        assert_eq!(
+            watcher.poll().unwrap(),
+            vec![
+                WatchEvent::FontInfoChanged,
+                WatchEvent::GlyphModified("A".to_string())
+            ]
+        );
+        assert_eq!(width_of_a(watcher.font()), width_of_a(&font));
+        assert!(watcher.poll().unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_watch_package_glyph() {
+        let path = scratch("glyphslib-watch.glyphspackage");
+        let font = radio_canada();
+        font.save(&path).unwrap();
+        let mut watcher = Watcher::new(&path).unwrap();
+
+        let mut a = font
+            .as_glyphs3()
+            .unwrap()
+            .glyphs
+            .iter()
+            .find(|g| g.name == "A")
+            .unwrap()
+            .clone();
+        a.layers[0].width += 12345.0;
+        fs::write(
+            path.join("glyphs").join("A_.glyph"),
+            openstep_plist::ser::to_string(&a).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            watcher.poll().unwrap(),
+            vec![WatchEvent::GlyphModified("A".to_string())]
+        );
+        assert_eq!(width_of_a(watcher.font()), a.layers[0].width);
+
+        // Removing a glyph file and its order entry reloads the package
+        let mut smaller = font.clone();
+        let Font::Glyphs3(glyphs3) = &mut smaller else {
+            unreachable!()
+        };
+        glyphs3.glyphs.retain(|g| g.name != "B");
+        fs::remove_dir_all(&path).unwrap();
+        smaller.save(&path).unwrap();
+        let events = watcher.poll().unwrap();
+        assert!(events.contains(&WatchEvent::GlyphRemoved("B".to_string())));
+        assert!(events.contains(&WatchEvent::GlyphModified("A".to_string())));
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
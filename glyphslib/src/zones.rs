@@ -0,0 +1,173 @@
+use crate::{
+    glyphs2::{self, AlignmentZone},
+    glyphs3::{self, Glyphs3, Metric, MetricType, MetricValue},
+};
+
+/// The alignment zones of a master as PostScript hinting wants them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlueZones {
+    /// `BlueValues`: the baseline zone and the zones above it, as bottom
+    /// and top pairs, flattened
+    pub blue_values: Vec<f64>,
+    /// `OtherBlues`: the zones below the baseline, as bottom and top pairs,
+    /// flattened
+    pub other_blues: Vec<f64>,
+}
+
+impl BlueZones {
+    /// Sort alignment zones into blue values as glyphsLib does: the zone at
+    /// the baseline and those whose overshoot goes up are `BlueValues`, the
+    /// rest `OtherBlues`
+    pub fn from_alignment_zones(zones: &[AlignmentZone]) -> Self {
+        let mut blues = BlueZones::default();
+        for zone in zones {
+            let values = if zone.position == 0.0 || zone.overshoot >= 0.0 {
+                &mut blues.blue_values
+            } else {
+                &mut blues.other_blues
+            };
+            let (a, b) = (zone.position, zone.position + zone.overshoot);
+            values.extend([a.min(b), a.max(b)]);
+        }
+        blues
+    }
+}
+
+// The overshoot of the zone at a position, if there is one
+fn overshoot_at(zones: &[AlignmentZone], position: f64) -> f64 {
+    zones
+        .iter()
+        .find(|zone| zone.position == position)
+        .map(|zone| zone.overshoot)
+        .unwrap_or(0.0)
+}
+
+impl glyphs2::Master {
+    /// The Glyphs 3 metric values of the master for the metrics of a font,
+    /// with the overshoots of the alignment zones at their positions
+    ///
+    /// Metrics the master has no value for are at 0.
+    pub fn metric_values(&self, metrics: &[Metric]) -> Vec<MetricValue> {
+        metrics
+            .iter()
+            .map(|metric| {
+                let pos = match metric.metric_type {
+                    Some(MetricType::Ascender) => self.ascender,
+                    Some(MetricType::CapHeight) => self.cap_height,
+                    Some(MetricType::Descender) => self.descender,
+                    Some(MetricType::XHeight) => self.x_height,
+                    Some(MetricType::ItalicAngle) => {
+                        return MetricValue {
+                            pos: self.italic_angle,
+                            over: 0.0,
+                        }
+                    }
+                    _ => None,
+                }
+                .unwrap_or_default();
+                MetricValue {
+                    pos,
+                    over: overshoot_at(&self.alignment_zones, pos),
+                }
+            })
+            .collect()
+    }
+
+    /// The blue values of the alignment zones of the master
+    pub fn blue_zones(&self) -> BlueZones {
+        BlueZones::from_alignment_zones(&self.alignment_zones)
+    }
+}
+
+impl glyphs3::Master {
+    /// The Glyphs 2 alignment zones of the master: its metrics with an
+    /// overshoot, other than those filtered to some glyphs
+    pub fn alignment_zones(&self, font: &Glyphs3) -> Vec<AlignmentZone> {
+        font.metrics
+            .iter()
+            .zip(&self.metric_values)
+            .filter(|(metric, value)| {
+                metric.filter.is_none()
+                    && metric.metric_type != Some(MetricType::ItalicAngle)
+                    && value.over != 0.0
+            })
+            .map(|(_, value)| AlignmentZone {
+                position: value.pos,
+                overshoot: value.over,
+            })
+            .collect()
+    }
+
+    /// The blue values of the alignment zones of the master
+    pub fn blue_zones(&self, font: &Glyphs3) -> BlueZones {
+        BlueZones::from_alignment_zones(&self.alignment_zones(font))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+    use std::path;
+
+    #[test]
+    fn test_zones_round_trip() {
+        let file = path::Path::new("resources/RadioCanadaDisplay.glyphs");
+        let Font::Glyphs3(font) = Font::load(file).unwrap() else {
+            unreachable!()
+        };
+        let master = &font.masters[0];
+        let zones = master.alignment_zones(&font);
+        assert!(!zones.is_empty());
+        let blues = master.blue_zones(&font);
+        assert_eq!(
+            blues.blue_values.len() + blues.other_blues.len(),
+            2 * zones.len()
+        );
+        assert!(blues
+            .blue_values
+            .windows(2)
+            .step_by(2)
+            .all(|p| p[0] <= p[1]));
+
+        let glyphs2 = glyphs2::Master {
+            ascender: master.ascender(&font),
+            cap_height: master.cap_height(&font),
+            descender: master.descender(&font),
+            x_height: master.x_height(&font),
+            alignment_zones: zones,
+            ..Default::default()
+        };
+        assert_eq!(glyphs2.blue_zones(), blues);
+        let values = glyphs2.metric_values(&font.metrics);
+        for (metric, (value, original)) in font
+            .metrics
+            .iter()
+            .zip(values.iter().zip(&master.metric_values))
+        {
+            if metric.filter.is_none()
+                && matches!(
+                    metric.metric_type,
+                    Some(MetricType::Ascender | MetricType::XHeight | MetricType::Descender)
+                )
+            {
+                assert_eq!(value, original);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blue_zones_from_alignment_zones() {
+        let zone = |position, overshoot| AlignmentZone {
+            position,
+            overshoot,
+        };
+        let blues = BlueZones::from_alignment_zones(&[
+            zone(800.0, 10.0),
+            zone(0.0, -10.0),
+            zone(-200.0, -10.0),
+        ]);
+        assert_eq!(blues.blue_values, [800.0, 810.0, -10.0, 0.0]);
+        assert_eq!(blues.other_blues, [-210.0, -200.0]);
+    }
+}
@@ -0,0 +1,40 @@
+//! Random property lists for fuzzing, with the `arbitrary` feature
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Dictionary, Plist};
+
+// Deeper values add little coverage and risk overflowing the stack of the
+// recursive parser and serializer.
+const MAX_DEPTH: usize = 8;
+
+fn arbitrary_plist(u: &mut Unstructured, depth: usize) -> Result<Plist> {
+    let kinds = if depth >= MAX_DEPTH { 4 } else { 6 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Plist::String(String::arbitrary(u)?),
+        1 => Plist::Integer(i64::arbitrary(u)?),
+        2 => Plist::Float(f64::arbitrary(u)?),
+        3 => Plist::Data(Vec::arbitrary(u)?),
+        4 => {
+            let len = u.arbitrary_len::<u8>()?;
+            let mut array = Vec::with_capacity(len);
+            for _ in 0..len {
+                array.push(arbitrary_plist(u, depth + 1)?);
+            }
+            Plist::Array(array)
+        }
+        _ => {
+            let mut dict = Dictionary::new();
+            for _ in 0..u.arbitrary_len::<u8>()? {
+                let key = String::arbitrary(u)?;
+                dict.insert(key.into(), arbitrary_plist(u, depth + 1)?);
+            }
+            Plist::Dictionary(dict)
+        }
+    })
+}
+
+impl<'a> Arbitrary<'a> for Plist {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_plist(u, 0)
+    }
+}
@@ -3,9 +3,11 @@ use serde::{
     forward_to_deserialize_any,
 };
 use smol_str::SmolStr;
+use std::borrow::Cow;
 
 use crate::{
     error::{Error, Result},
+    events::{Event, Lexer},
     Plist,
 };
 
@@ -302,6 +304,339 @@ impl<'de> MapAccess<'de> for DictDeserializer<'_, 'de> {
     }
 }
 
+/// Deserialize a value straight from plist text
+///
+/// Unlike parsing to a [`Plist`] and using [`Deserializer::from_plist`], no
+/// tree is built, and strings without escapes are borrowed from the input
+/// rather than copied, so `&str` and `Cow<str>` fields need no allocation.
+pub fn from_str<'de, T>(s: &'de str) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    T::deserialize(&mut StrDeserializer::new(s))
+}
+
+/// A deserializer reading plist text as it goes; see [`from_str`]
+///
+/// Errors in values are [`Error::Located`] at the value which could not be
+/// deserialized.
+pub struct StrDeserializer<'de> {
+    s: &'de str,
+    lexer: Lexer<'de>,
+    peeked: Option<Event<'de>>,
+}
+
+fn event_name(event: &Event) -> &'static str {
+    match event {
+        Event::StartDictionary => "dictionary",
+        Event::StartArray => "array",
+        Event::String(_) => "string",
+        Event::Integer(_) => "integer",
+        Event::Float(_) => "float",
+        Event::Data(_) => "data",
+        Event::Key(_) => "key",
+        Event::EndDictionary | Event::EndArray => "end of container",
+    }
+}
+
+impl<'de> StrDeserializer<'de> {
+    /// Read the given plist text
+    pub fn new(s: &'de str) -> Self {
+        StrDeserializer {
+            s,
+            lexer: Lexer::new(s),
+            peeked: None,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Event<'de>> {
+        match self.peeked.take() {
+            Some(event) => Ok(event),
+            None => self
+                .lexer
+                .next()
+                .unwrap_or_else(|| Err(Error::Parse("unexpected end of input".to_string()))),
+        }
+    }
+
+    fn peek_event(&mut self) -> Result<&Event<'de>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next_event()?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    // Read past the rest of the container whose start was just read
+    fn finish_container(&mut self) -> Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_event()? {
+                Event::StartDictionary | Event::StartArray => depth += 1,
+                Event::EndDictionary | Event::EndArray => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Deserialize the next value, locating any error at its start
+    fn located_value<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.peek_event()?;
+        let offset = self.lexer.offset();
+        seed.deserialize(&mut *self)
+            .map_err(|error| error.located(self.s, offset))
+    }
+
+    fn unexpected(expected: &'static str, event: &Event) -> Error {
+        Error::UnexpectedDataType {
+            expected,
+            found: event_name(event),
+        }
+    }
+}
+
+macro_rules! deserialize_str_number {
+    ($method:ident, $visit:ident, $type: ty, $expected:expr) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: Visitor<'de>,
+        {
+            match self.next_event()? {
+                Event::Integer(i) => visitor.$visit(i as $type),
+                Event::Float(f) => visitor.$visit(f as $type),
+                Event::String(s) => {
+                    let i: $type = s.parse().map_err(|_| {
+                        de::Error::custom(format!("expected a number, got string {s:?}"))
+                    })?;
+                    visitor.$visit(i)
+                }
+                other => Err(StrDeserializer::unexpected($expected, &other)),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut StrDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.next_event()? {
+            Event::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Event::String(Cow::Owned(s)) => visitor.visit_string(s),
+            Event::Integer(i) => visitor.visit_i64(i),
+            Event::Float(f) => visitor.visit_f64(f),
+            Event::Data(data) => visitor.visit_byte_buf(data),
+            Event::StartDictionary => {
+                let mut access = StrDictDeserializer {
+                    de: self,
+                    done: false,
+                };
+                let value = visitor.visit_map(&mut access)?;
+                if !access.done {
+                    access.de.finish_container()?;
+                }
+                Ok(value)
+            }
+            Event::StartArray => {
+                let mut access = StrArrayDeserializer {
+                    de: self,
+                    done: false,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                if !access.done {
+                    access.de.finish_container()?;
+                }
+                Ok(value)
+            }
+            other => Err(StrDeserializer::unexpected("value", &other)),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.next_event()? {
+            Event::Integer(i) => visitor.visit_bool(i != 0),
+            other => Err(StrDeserializer::unexpected("integer", &other)),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.peek_event()? {
+            Event::String(_) => self.deserialize_any(visitor),
+            other => Err(StrDeserializer::unexpected("string", other)),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.peek_event()? {
+            Event::StartArray => self.deserialize_any(visitor),
+            other => Err(StrDeserializer::unexpected("array", other)),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.peek_event()? {
+            Event::StartDictionary => self.deserialize_any(visitor),
+            other => Err(StrDeserializer::unexpected("dictionary", other)),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.peek_event()? {
+            Event::Data(_) => self.deserialize_any(visitor),
+            other => Err(StrDeserializer::unexpected("data", other)),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.next_event()? {
+            Event::String(s) => visitor.visit_enum(de::value::CowStrDeserializer::new(s)),
+            other => Err(StrDeserializer::unexpected("string", &other)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Skip without handing anything to the visitor
+        if let Event::StartDictionary | Event::StartArray = self.next_event()? {
+            self.finish_container()?;
+        }
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {char unit unit_struct bytes}
+    forward_to_deserialize_any! {tuple tuple_struct struct identifier}
+
+    deserialize_str_number!(deserialize_i8, visit_i8, i8, "integer or numeric string");
+    deserialize_str_number!(deserialize_i16, visit_i16, i16, "integer or numeric string");
+    deserialize_str_number!(deserialize_i32, visit_i32, i32, "integer or numeric string");
+    deserialize_str_number!(deserialize_i64, visit_i64, i64, "integer or numeric string");
+    deserialize_str_number!(deserialize_u8, visit_u8, u8, "integer or numeric string");
+    deserialize_str_number!(deserialize_u16, visit_u16, u16, "integer or numeric string");
+    deserialize_str_number!(deserialize_u32, visit_u32, u32, "integer or numeric string");
+    deserialize_str_number!(deserialize_u64, visit_u64, u64, "integer or numeric string");
+    deserialize_str_number!(
+        deserialize_f32,
+        visit_f32,
+        f32,
+        "float, integer, or numeric string"
+    );
+    deserialize_str_number!(deserialize_f64, visit_f64, f64, "integer or numeric string");
+}
+
+struct StrArrayDeserializer<'a, 'de: 'a> {
+    de: &'a mut StrDeserializer<'de>,
+    done: bool,
+}
+
+impl<'de> SeqAccess<'de> for &mut StrArrayDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        if let Event::EndArray = self.de.peek_event()? {
+            self.de.peeked = None;
+            self.done = true;
+            return Ok(None);
+        }
+        self.de.located_value(seed).map(Some)
+    }
+}
+
+struct StrDictDeserializer<'a, 'de: 'a> {
+    de: &'a mut StrDeserializer<'de>,
+    done: bool,
+}
+
+impl<'de> MapAccess<'de> for &mut StrDictDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        match self.de.next_event()? {
+            Event::EndDictionary => {
+                self.done = true;
+                Ok(None)
+            }
+            Event::Key(Cow::Borrowed(key)) => seed
+                .deserialize(de::value::BorrowedStrDeserializer::new(key))
+                .map(Some),
+            Event::Key(Cow::Owned(key)) => seed
+                .deserialize(de::value::StringDeserializer::new(key))
+                .map(Some),
+            other => Err(StrDeserializer::unexpected("key", &other)),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.de.located_value(seed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -476,4 +811,63 @@ mod tests {
         let value: Foo = Foo::deserialize(&mut deserializer).unwrap();
         assert_eq!(value, Foo { descender: -123 });
     }
+    #[test]
+    fn test_from_str() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Glyph<'a> {
+            glyphname: &'a str,
+            #[serde(borrow)]
+            note: Cow<'a, str>,
+            unicode: Option<u32>,
+            width: f32,
+            #[serde(default)]
+            export: bool,
+            layers: Vec<(i64, i64)>,
+        }
+        let contents = r#"
+        {
+            glyphname = A;
+            note = "needs \"work\"";
+            unicode = 0041;
+            lib = {ignored = (1, {deeply = nested;});};
+            width = "600.5";
+            layers = ((1, 2), (3, 4),);
+        }
+        "#;
+        let glyph: Glyph = from_str(contents).unwrap();
+        assert_eq!(
+            glyph,
+            Glyph {
+                glyphname: "A",
+                note: Cow::Owned("needs \"work\"".to_string()),
+                unicode: Some(41),
+                width: 600.5,
+                export: false,
+                layers: vec![(1, 2), (3, 4)],
+            }
+        );
+
+        // Agrees with deserializing from a parsed plist
+        let plist: Plist = from_str(contents).unwrap();
+        assert_eq!(plist, Plist::parse(contents).unwrap());
+        let Err(Error::Located { error, offset, .. }) = from_str::<Glyph>("{glyphname = (A);}")
+        else {
+            panic!("error was not located");
+        };
+        assert_eq!(
+            *error,
+            Error::UnexpectedDataType {
+                expected: "string",
+                found: "array"
+            }
+        );
+        assert_eq!(offset, 13);
+        let error =
+            from_str::<Glyph>("{\nglyphname = A;\nlayers = ((1, 2), (3, x));\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "parsing failed: 'expected a number, got string \"x\"' at line 3, column 23\n  \
+             3 | layers = ((1, 2), (3, x));\n    |                       ^"
+        );
+    }
 }
@@ -0,0 +1,165 @@
+//! Structural comparison of property lists
+use std::fmt;
+
+use crate::Plist;
+
+/// One difference between two property lists
+///
+/// Paths use `.` to separate dictionary keys and `[n]` for array indices,
+/// for example `fontMaster[0].metricValues[2].pos`. The root is the empty path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// A value present on the left but not on the right
+    Removed { path: String, value: Plist },
+    /// A value present on the right but not on the left
+    Added { path: String, value: Plist },
+    /// A value which differs between the two sides
+    Changed {
+        path: String,
+        left: Plist,
+        right: Plist,
+    },
+}
+
+impl Difference {
+    /// The location of the difference
+    pub fn path(&self) -> &str {
+        match self {
+            Difference::Removed { path, .. }
+            | Difference::Added { path, .. }
+            | Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path().is_empty() {
+            "<root>"
+        } else {
+            self.path()
+        };
+        match self {
+            Difference::Removed { value, .. } => {
+                write!(f, "{path}: removed {}", value.to_string().trim())
+            }
+            Difference::Added { value, .. } => {
+                write!(f, "{path}: added {}", value.to_string().trim())
+            }
+            Difference::Changed { left, right, .. } => write!(
+                f,
+                "{path}: changed {} to {}",
+                left.to_string().trim(),
+                right.to_string().trim()
+            ),
+        }
+    }
+}
+
+/// List the differences between two property lists
+///
+/// Dictionaries are compared key by key and arrays element by element.
+/// Integers and floats compare equal when they have the same numeric value,
+/// since serializers may write `1.0` as `1`.
+pub fn diff(left: &Plist, right: &Plist) -> Vec<Difference> {
+    let mut differences = vec![];
+    diff_into(left, right, String::new(), &mut differences);
+    differences
+}
+
+fn child_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn numerically_equal(left: &Plist, right: &Plist) -> bool {
+    match (left, right) {
+        (Plist::Integer(i), Plist::Float(f)) | (Plist::Float(f), Plist::Integer(i)) => {
+            Plist::Float(*i as f64) == Plist::Float(*f)
+        }
+        _ => false,
+    }
+}
+
+fn diff_into(left: &Plist, right: &Plist, path: String, differences: &mut Vec<Difference>) {
+    match (left, right) {
+        (Plist::Dictionary(l), Plist::Dictionary(r)) => {
+            for (key, value) in l {
+                let child = child_key(&path, key);
+                match r.get(key) {
+                    Some(other) => diff_into(value, other, child, differences),
+                    None => differences.push(Difference::Removed {
+                        path: child,
+                        value: value.clone(),
+                    }),
+                }
+            }
+            for (key, value) in r.iter().filter(|(k, _)| !l.contains_key(*k)) {
+                differences.push(Difference::Added {
+                    path: child_key(&path, key),
+                    value: value.clone(),
+                });
+            }
+        }
+        (Plist::Array(l), Plist::Array(r)) => {
+            for (ix, value) in l.iter().enumerate() {
+                let child = format!("{path}[{ix}]");
+                match r.get(ix) {
+                    Some(other) => diff_into(value, other, child, differences),
+                    None => differences.push(Difference::Removed {
+                        path: child,
+                        value: value.clone(),
+                    }),
+                }
+            }
+            for (ix, value) in r.iter().enumerate().skip(l.len()) {
+                differences.push(Difference::Added {
+                    path: format!("{path}[{ix}]"),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => {
+            if left != right && !numerically_equal(left, right) {
+                differences.push(Difference::Changed {
+                    path,
+                    left: left.clone(),
+                    right: right.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical() {
+        let plist = Plist::parse("{a = (1, 2, {b = c;}); d = 1.5;}").unwrap();
+        assert!(diff(&plist, &plist).is_empty());
+    }
+
+    #[test]
+    fn nested_differences() {
+        let left = Plist::parse("{a = (1, 2, {b = c;}); d = 1; e = x;}").unwrap();
+        let right = Plist::parse("{a = (1, 2, {b = z;}, 4); d = 1.0; f = y;}").unwrap();
+        let differences = diff(&left, &right);
+        let paths: Vec<&str> = differences.iter().map(|d| d.path()).collect();
+        assert_eq!(paths, vec!["a[2].b", "a[3]", "e", "f"]);
+        assert_eq!(
+            differences[0],
+            Difference::Changed {
+                path: "a[2].b".to_string(),
+                left: Plist::String("c".to_string()),
+                right: Plist::String("z".to_string()),
+            }
+        );
+        assert!(matches!(differences[2], Difference::Removed { .. }));
+        assert_eq!(differences[3].to_string(), "f: added y");
+    }
+}
@@ -87,6 +87,15 @@ pub enum Error {
     Parse(String),
     #[error("serializing failed: '{0}'")]
     Serialize(String),
+    #[error("writing failed: '{0}'")]
+    Io(String),
+    #[error("{error} at line {}, column {}\n{snippet}", .lc.line, .lc.column)]
+    Located {
+        error: Box<Error>,
+        offset: usize,
+        lc: LineColumn,
+        snippet: String,
+    },
 }
 
 impl ser::Error for Error {
@@ -112,4 +121,69 @@ impl Error {
             other => other,
         }
     }
+
+    /// Attach the byte offset in `s` where this error happened, along with
+    /// a snippet of the line there
+    ///
+    /// An error which is already located is left alone, so that the place
+    /// reported is the innermost one.
+    pub fn located(self, s: &str, offset: usize) -> Self {
+        if self.offset().is_some() {
+            return self;
+        }
+        Error::Located {
+            error: Box::new(self),
+            offset,
+            lc: LineColumn::from_pos(s, offset),
+            snippet: snippet(s, offset),
+        }
+    }
+
+    /// The byte offset where the error happened, if it is known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::Located { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+// Show at most this many characters either side of the caret
+const SNIPPET_WIDTH: usize = 60;
+
+/// The line of `s` containing the byte offset `pos`, numbered, with a caret
+/// underneath pointing at `pos`
+///
+/// ```
+/// let s = "{\n    width = abc;\n}";
+/// assert_eq!(
+///     openstep_plist::error::snippet(s, 14),
+///     "  2 |     width = abc;\n    |             ^"
+/// );
+/// ```
+pub fn snippet(s: &str, pos: usize) -> String {
+    let mut pos = pos.min(s.len());
+    while !s.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    let line_start = s[..pos].rfind('\n').map_or(0, |ix| ix + 1);
+    let line_end = s[pos..].find('\n').map_or(s.len(), |ix| pos + ix);
+    let before: Vec<char> = s[line_start..pos].chars().collect();
+    let before = &before[before.len().saturating_sub(SNIPPET_WIDTH)..];
+    let after: String = s[pos..line_end]
+        .trim_end_matches('\r')
+        .chars()
+        .take(SNIPPET_WIDTH)
+        .collect();
+    // Tabs are kept so that the caret lines up however wide they are shown
+    let padding: String = before
+        .iter()
+        .map(|ch| if *ch == '\t' { '\t' } else { ' ' })
+        .collect();
+    let line = LineColumn::from_pos(s, pos).line.to_string();
+    let gutter = " ".repeat(line.len());
+    format!(
+        "  {line} | {}{after}\n  {gutter} | {padding}^",
+        before.iter().collect::<String>()
+    )
 }
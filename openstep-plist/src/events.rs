@@ -0,0 +1,423 @@
+//! Reading a plist as a stream of events
+//!
+//! [`Plist::parse`] builds the whole tree in memory, which for a large
+//! `.glyphs` file takes far longer and far more memory than a tool that only
+//! wants the glyph names needs. A [`Lexer`] instead reads the text a piece at
+//! a time, so a caller can pick out what it wants and skip the rest.
+
+use std::borrow::Cow;
+
+use crate::{error::LineColumn, skip_ws, Error, PathSegment, Plist, Token};
+
+/// A piece of a plist, as read by a [`Lexer`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// The start of a dictionary; keys and values follow until
+    /// [`EndDictionary`](Event::EndDictionary)
+    StartDictionary,
+    /// The end of the innermost dictionary
+    EndDictionary,
+    /// A dictionary key; the next event starts its value
+    Key(Cow<'a, str>),
+    /// The start of an array; values follow until [`EndArray`](Event::EndArray)
+    StartArray,
+    /// The end of the innermost array
+    EndArray,
+    /// A string, quoted or not
+    String(Cow<'a, str>),
+    /// An integer
+    Integer(i64),
+    /// A float
+    Float(f64),
+    /// A `<hex>` data block
+    Data(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    // Expecting a key or the closing brace
+    DictKey,
+    // Read a key, expecting `=` and its value
+    DictValue,
+    // Read a value, expecting `;`
+    DictEnd,
+    // Expecting a value or the closing paren
+    ArrayValue,
+    // Read a value, expecting `,` or the closing paren
+    ArrayEnd,
+}
+
+/// Reads a plist as a sequence of [`Event`]s without building a [`Plist`]
+///
+/// The lexer is an iterator of events, ending after the top-level value or at
+/// the first error. Errors are the same as those of [`Plist::parse`].
+///
+/// ```
+/// use openstep_plist::events::{Event, Lexer};
+///
+/// let mut lexer = Lexer::new("{glyphs = ({glyphname = A; layers = ();});}");
+/// let mut names = vec![];
+/// while let Some(event) = lexer.next() {
+///     match event.unwrap() {
+///         Event::Key(key) if key == "glyphname" => {
+///             if let Some(Ok(Event::String(name))) = lexer.next() {
+///                 names.push(name.to_string());
+///             }
+///         }
+///         Event::Key(key) if key == "layers" => lexer.skip_value().unwrap(),
+///         _ => {}
+///     }
+/// }
+/// assert_eq!(names, ["A"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    s: &'a str,
+    ix: usize,
+    start: usize,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Read the given plist text
+    pub fn new(s: &'a str) -> Self {
+        Lexer {
+            s,
+            ix: 0,
+            start: 0,
+            stack: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    /// How many dictionaries and arrays the lexer is inside
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// The byte offset where the last event read began
+    pub fn offset(&self) -> usize {
+        self.start
+    }
+
+    /// Read past the next value, including everything inside it
+    ///
+    /// Call this after a [`Key`](Event::Key) to skip its value.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        let mut depth = 0;
+        for event in self.by_ref() {
+            match event? {
+                Event::StartDictionary | Event::StartArray => depth += 1,
+                Event::EndDictionary | Event::EndArray => depth -= 1,
+                Event::Key(_) => continue,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, delim: u8) -> bool {
+        self.start = skip_ws(self.s, self.ix);
+        match Token::expect(self.s, self.ix, delim) {
+            Some(ix) => {
+                self.ix = ix;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn replace_frame(&mut self, frame: Frame) {
+        if let Some(last) = self.stack.last_mut() {
+            *last = frame;
+        }
+    }
+
+    fn error_at(&self, pos: usize) -> LineColumn {
+        LineColumn::from_pos(self.s, pos)
+    }
+
+    fn key(&mut self) -> Result<Event<'a>, Error> {
+        self.start = skip_ws(self.s, self.ix);
+        let (token, next) = Token::lex(self.s, self.ix)?;
+        let key = match token {
+            Token::Atom(atom) => Cow::Borrowed(atom),
+            Token::String(string) => string,
+            other => {
+                return Err(Error::NotAString {
+                    token_name: other.name(),
+                    lc: self.error_at(self.ix),
+                })
+            }
+        };
+        self.ix = next;
+        Ok(Event::Key(key))
+    }
+
+    fn value(&mut self) -> Result<Event<'a>, Error> {
+        self.start = skip_ws(self.s, self.ix);
+        let (token, next) = Token::lex(self.s, self.ix)?;
+        let event = match token {
+            Token::Atom(atom) => match Plist::parse_atom(atom) {
+                Plist::Integer(integer) => Event::Integer(integer),
+                Plist::Float(float) => Event::Float(float),
+                _ => Event::String(Cow::Borrowed(atom)),
+            },
+            Token::String(string) => Event::String(string),
+            Token::Data(data) => Event::Data(data),
+            Token::OpenBrace => {
+                self.stack.push(Frame::DictKey);
+                Event::StartDictionary
+            }
+            Token::OpenParen => {
+                self.stack.push(Frame::ArrayValue);
+                Event::StartArray
+            }
+            Token::Eof => {
+                return Err(Error::UnexpectedToken {
+                    name: token.name(),
+                    lc: self.error_at(next),
+                })
+            }
+        };
+        self.ix = next;
+        Ok(event)
+    }
+
+    fn step(&mut self) -> Option<Result<Event<'a>, Error>> {
+        loop {
+            let Some(&frame) = self.stack.last() else {
+                if self.started {
+                    return None;
+                }
+                self.started = true;
+                return Some(self.value());
+            };
+            match frame {
+                Frame::DictKey => {
+                    if self.expect(b'}') {
+                        self.stack.pop();
+                        return Some(Ok(Event::EndDictionary));
+                    }
+                    self.replace_frame(Frame::DictValue);
+                    return Some(self.key());
+                }
+                Frame::DictValue => {
+                    self.replace_frame(Frame::DictEnd);
+                    if !self.expect(b'=') {
+                        return Some(Err(Error::ExpectedEquals {
+                            lc: self.error_at(self.ix),
+                        }));
+                    }
+                    return Some(self.value());
+                }
+                Frame::DictEnd => {
+                    self.replace_frame(Frame::DictKey);
+                    if !self.expect(b';') {
+                        return Some(Err(Error::ExpectedSemicolon {
+                            lc: self.error_at(self.ix),
+                        }));
+                    }
+                }
+                Frame::ArrayValue => {
+                    if self.expect(b')') {
+                        self.stack.pop();
+                        return Some(Ok(Event::EndArray));
+                    }
+                    self.replace_frame(Frame::ArrayEnd);
+                    return Some(self.value());
+                }
+                Frame::ArrayEnd => {
+                    if self.expect(b')') {
+                        self.stack.pop();
+                        return Some(Ok(Event::EndArray));
+                    }
+                    self.replace_frame(Frame::ArrayValue);
+                    if !self.expect(b',') {
+                        return Some(Err(Error::ExpectedComma {
+                            lc: self.error_at(self.ix),
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let event = self.step();
+        if !matches!(event, Some(Ok(_))) {
+            self.done = true;
+        }
+        event
+    }
+}
+
+/// Find where the value at a path begins in plist text, as a byte offset
+///
+/// Only as much of the text is read as is needed to get there. Returns
+/// `None` if there is no such value, or the text cannot be read up to it.
+///
+/// ```
+/// use openstep_plist::events::offset_of;
+///
+/// let s = "{glyphs = ({glyphname = A;}, {glyphname = B;});}";
+/// assert_eq!(offset_of(s, &["glyphs".into(), 1.into(), "glyphname".into()]), Some(42));
+/// ```
+pub fn offset_of(s: &str, path: &[PathSegment]) -> Option<usize> {
+    let mut lexer = Lexer::new(s);
+    let mut event = lexer.next()?.ok()?;
+    for segment in path {
+        event = match (event, segment) {
+            (Event::StartDictionary, PathSegment::Key(key)) => loop {
+                match lexer.next()?.ok()? {
+                    Event::Key(k) if k == *key => break lexer.next()?.ok()?,
+                    Event::Key(_) => lexer.skip_value().ok()?,
+                    _ => return None,
+                }
+            },
+            (Event::StartArray, PathSegment::Index(index)) => {
+                let mut value = lexer.next()?.ok()?;
+                for _ in 0..*index {
+                    if matches!(value, Event::StartDictionary | Event::StartArray) {
+                        let depth = lexer.depth();
+                        while lexer.depth() >= depth {
+                            lexer.next()?.ok()?;
+                        }
+                    }
+                    value = lexer.next()?.ok()?;
+                }
+                if value == Event::EndArray {
+                    return None;
+                }
+                value
+            }
+            _ => return None,
+        };
+    }
+    Some(lexer.offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dictionary;
+
+    // Build a value from events, to check them against `Plist::parse`
+    fn build(lexer: &mut Lexer) -> Plist {
+        match lexer.next().unwrap().unwrap() {
+            Event::StartDictionary => {
+                let mut dict = Dictionary::new();
+                loop {
+                    match lexer.next().unwrap().unwrap() {
+                        Event::EndDictionary => return Plist::Dictionary(dict),
+                        Event::Key(key) => {
+                            dict.insert(key.into(), build(lexer));
+                        }
+                        other => panic!("unexpected {other:?}"),
+                    }
+                }
+            }
+            Event::StartArray => {
+                let mut array = vec![];
+                while lexer.clone().next().unwrap().unwrap() != Event::EndArray {
+                    array.push(build(lexer));
+                }
+                lexer.next();
+                Plist::Array(array)
+            }
+            Event::String(string) => Plist::String(string.into()),
+            Event::Integer(integer) => Plist::Integer(integer),
+            Event::Float(float) => Plist::Float(float),
+            Event::Data(data) => Plist::Data(data),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let contents = r#"
+        {
+            name = "Hello \"world\"";
+            count = 42;
+            scale = 0.5;
+            data = <89504e47>;
+            values = (1, (), two, {a = b;},);
+            nested = {};
+        }
+        "#;
+        let events: Vec<_> = Lexer::new(contents).map(Result::unwrap).collect();
+        assert_eq!(
+            events[..5],
+            [
+                Event::StartDictionary,
+                Event::Key("name".into()),
+                Event::String("Hello \"world\"".into()),
+                Event::Key("count".into()),
+                Event::Integer(42),
+            ]
+        );
+        assert_eq!(events.len(), 24);
+        assert_eq!(events.last(), Some(&Event::EndDictionary));
+        assert_eq!(
+            build(&mut Lexer::new(contents)),
+            Plist::parse(contents).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_skip_value() {
+        let contents = "{a = {b = (1, 2);}; c = (3); d = 4;}";
+        let mut lexer = Lexer::new(contents);
+        let mut keys = vec![];
+        while let Some(event) = lexer.next() {
+            if let Event::Key(key) = event.unwrap() {
+                keys.push(key);
+                assert_eq!(lexer.depth(), 1);
+                lexer.skip_value().unwrap();
+            }
+        }
+        assert_eq!(keys, ["a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_event_errors() {
+        let errors: Vec<_> = ["{a = 1}", "{a 1;}", "(1 2)", "{a = (1;}"]
+            .into_iter()
+            .map(|contents| Lexer::new(contents).find_map(Result::err))
+            .collect();
+        assert!(matches!(errors[0], Some(Error::ExpectedSemicolon { .. })));
+        assert!(matches!(errors[1], Some(Error::ExpectedEquals { .. })));
+        assert!(matches!(errors[2], Some(Error::ExpectedComma { .. })));
+        assert!(matches!(errors[3], Some(Error::ExpectedComma { .. })));
+        // Nothing more is read after an error
+        let mut lexer = Lexer::new("(1 2)");
+        assert!(lexer.by_ref().any(|event| event.is_err()));
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn test_offset_of() {
+        let contents = "{a = (1, {b = (2, 3);}, x);\nc = {d = 4;};\n}";
+        let at = |path: &[PathSegment]| offset_of(contents, path).map(|ix| &contents[ix..ix + 1]);
+        assert_eq!(at(&[]), Some("{"));
+        assert_eq!(at(&["a".into(), 2.into()]), Some("x"));
+        assert_eq!(at(&["a".into(), 1.into(), "b".into(), 1.into()]), Some("3"));
+        assert_eq!(at(&["c".into(), "d".into()]), Some("4"));
+        assert_eq!(at(&["a".into(), 3.into()]), None);
+        assert_eq!(at(&["e".into()]), None);
+        assert_eq!(at(&[0.into()]), None);
+    }
+}
@@ -3,13 +3,18 @@ use std::{borrow::Cow, collections::BTreeMap, fmt::Debug};
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
 pub mod de;
+pub mod diff;
 pub mod error;
+pub mod events;
 pub mod ser;
 
 pub use crate::error::Error;
 use crate::error::LineColumn;
-pub use ser::to_string;
+pub use de::from_str;
+pub use ser::{to_string, to_writer};
 
 /// A plist dictionary
 pub type Dictionary = BTreeMap<SmolStr, Plist>;
@@ -44,6 +49,119 @@ impl PartialEq for Plist {
     }
 }
 
+/// One step of a path into a plist: a dictionary key or an array index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl<'a> From<&'a str> for PathSegment<'a> {
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl<'a> From<&'a String> for PathSegment<'a> {
+    fn from(key: &'a String) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment<'_> {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// Look up a value in a plist by keys and indices
+///
+/// Strings are dictionary keys and integers are array indices, so keys may
+/// contain dots or be made of digits. Start with `mut` for a mutable
+/// reference.
+///
+/// ```
+/// use openstep_plist::{pointer, Plist};
+///
+/// let mut plist = Plist::parse("{glyphs = ({userData = {com.example.x = 1;};});}").unwrap();
+/// let x = pointer!(plist, "glyphs", 0, "userData", "com.example.x");
+/// assert_eq!(x, Some(&Plist::Integer(1)));
+/// *pointer!(mut plist, "glyphs", 0, "userData").unwrap() = Plist::Integer(2);
+/// assert_eq!(plist.get_path("glyphs.0.userData"), Some(&Plist::Integer(2)));
+/// ```
+#[macro_export]
+macro_rules! pointer {
+    (mut $plist:expr, $($segment:expr),+ $(,)?) => {
+        $plist.pointer_mut(&[$($crate::PathSegment::from($segment)),+])
+    };
+    ($plist:expr, $($segment:expr),+ $(,)?) => {
+        $plist.pointer(&[$($crate::PathSegment::from($segment)),+])
+    };
+}
+
+/// Build a [`Plist`] from text much like a plist file
+///
+/// Dictionaries are written `{ key = value; }` and arrays `(a, b)`. Keys are
+/// identifiers, string literals or integers; values are literals, nested
+/// dictionaries and arrays, or the names of variables holding anything that
+/// converts into a `Plist`. Call [`expect_dict`](Plist::expect_dict) on the
+/// result for a [`Dictionary`].
+///
+/// ```
+/// use openstep_plist::{plist, Plist};
+///
+/// let name = "Weight".to_string();
+/// let axis = plist!({ name = name; tag = "wght"; hidden = 1; range = (-100, 0.5, 900); });
+/// assert_eq!(
+///     axis,
+///     Plist::parse("{name = Weight; tag = wght; hidden = 1; range = (-100, 0.5, 900);}").unwrap()
+/// );
+/// ```
+#[macro_export]
+macro_rules! plist {
+    (@dict $dict:ident ()) => {};
+    (@dict $dict:ident ($key:ident = $($rest:tt)*)) => {
+        $crate::plist!(@value $dict (stringify!($key).to_string()) ($($rest)*));
+    };
+    (@dict $dict:ident ($key:literal = $($rest:tt)*)) => {
+        $crate::plist!(@value $dict ($key.to_string()) ($($rest)*));
+    };
+    (@value $dict:ident ($key:expr) (- $value:tt ; $($rest:tt)*)) => {
+        $dict.insert($key.into(), $crate::plist!(- $value));
+        $crate::plist!(@dict $dict ($($rest)*));
+    };
+    (@value $dict:ident ($key:expr) ($value:tt ; $($rest:tt)*)) => {
+        $dict.insert($key.into(), $crate::plist!($value));
+        $crate::plist!(@dict $dict ($($rest)*));
+    };
+    (@array [$($done:expr,)*] ()) => {
+        $crate::Plist::Array(::std::vec![$($done),*])
+    };
+    (@array [$($done:expr,)*] (- $value:tt $(, $($rest:tt)*)?)) => {
+        $crate::plist!(@array [$($done,)* $crate::plist!(- $value),] ($($($rest)*)?))
+    };
+    (@array [$($done:expr,)*] ($value:tt $(, $($rest:tt)*)?)) => {
+        $crate::plist!(@array [$($done,)* $crate::plist!($value),] ($($($rest)*)?))
+    };
+    ({}) => {
+        $crate::Plist::Dictionary($crate::Dictionary::new())
+    };
+    ({ $($entries:tt)* }) => {{
+        let mut dict = $crate::Dictionary::new();
+        $crate::plist!(@dict dict ($($entries)*));
+        $crate::Plist::Dictionary(dict)
+    }};
+    (( $($elements:tt)* )) => {
+        $crate::plist!(@array [] ($($elements)*))
+    };
+    (- $value:tt) => {
+        $crate::Plist::from(-$value)
+    };
+    ($value:expr) => {
+        $crate::Plist::from($value)
+    };
+}
+
 #[derive(Debug)]
 pub(crate) enum Token<'a> {
     Eof,
@@ -121,7 +239,7 @@ fn skip_ws(s: &str, mut ix: usize) -> usize {
 
 impl Plist {
     pub fn parse(s: &str) -> Result<Plist, Error> {
-        let (plist, _ix) = Plist::parse_rec(s, 0)?;
+        let (plist, _ix) = Plist::parse_rec(s, 0, None)?;
         // TODO: check that we're actually at eof
         Ok(plist)
     }
@@ -144,6 +262,49 @@ impl Plist {
         }
     }
 
+    /// Look up a value by a path of keys and indices separated by dots
+    ///
+    /// Each part of the path is a key into a dictionary or an index into an
+    /// array, so `glyphs.3.layers.0.width` is the width of the first layer of
+    /// the fourth glyph. Keys which themselves contain dots can be reached
+    /// with [`pointer!`] instead.
+    pub fn get_path(&self, path: &str) -> Option<&Plist> {
+        path.split('.').try_fold(self, |plist, part| match plist {
+            Plist::Dictionary(d) => d.get(part),
+            Plist::Array(a) => a.get(part.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Look up a value to change by a path; see [`get_path`](Self::get_path)
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Plist> {
+        path.split('.').try_fold(self, |plist, part| match plist {
+            Plist::Dictionary(d) => d.get_mut(part),
+            Plist::Array(a) => a.get_mut(part.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Look up a value by a list of keys and indices; see [`pointer!`]
+    pub fn pointer(&self, path: &[PathSegment]) -> Option<&Plist> {
+        path.iter()
+            .try_fold(self, |plist, segment| match (plist, segment) {
+                (Plist::Dictionary(d), PathSegment::Key(key)) => d.get(*key),
+                (Plist::Array(a), PathSegment::Index(index)) => a.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Look up a value to change by a list of keys and indices
+    pub fn pointer_mut(&mut self, path: &[PathSegment]) -> Option<&mut Plist> {
+        path.iter()
+            .try_fold(self, |plist, segment| match (plist, segment) {
+                (Plist::Dictionary(d), PathSegment::Key(key)) => d.get_mut(*key),
+                (Plist::Array(a), PathSegment::Index(index)) => a.get_mut(*index),
+                _ => None,
+            })
+    }
+
     pub fn as_dict(&self) -> Option<&BTreeMap<SmolStr, Plist>> {
         match self {
             Plist::Dictionary(d) => Some(d),
@@ -253,7 +414,32 @@ impl Plist {
         }
     }
 
-    fn parse_rec(s: &str, ix: usize) -> Result<(Plist, usize), Error> {
+    /// Parse, carrying on past errors
+    ///
+    /// A dictionary entry or array element which cannot be parsed is left
+    /// out, and parsing resumes after the next `;` or `,`, or at the `}` or
+    /// `)` closing its container. Returns what could be parsed along with
+    /// every error found, in order, so all the mistakes in a hand-edited
+    /// file can be fixed at once.
+    pub fn parse_recovering(s: &str) -> (Plist, Vec<Error>) {
+        let mut errors = vec![];
+        let plist = match Plist::parse_rec(s, 0, Some(&mut errors)) {
+            Ok((plist, _ix)) => plist,
+            Err(error) => {
+                errors.push(error);
+                Plist::default()
+            }
+        };
+        (plist, errors)
+    }
+
+    // With `errors`, problems inside containers are collected there rather
+    // than ending the parse
+    fn parse_rec(
+        s: &str,
+        ix: usize,
+        mut errors: Option<&mut Vec<Error>>,
+    ) -> Result<(Plist, usize), Error> {
         let (tok, mut ix) = Token::lex(s, ix)?;
         match tok {
             Token::Atom(s) => Ok((Plist::parse_atom(s), ix)),
@@ -265,23 +451,33 @@ impl Plist {
                     if let Some(ix) = Token::expect(s, ix, b'}') {
                         return Ok((Plist::Dictionary(dict), ix));
                     }
-                    let (key, next) = Token::lex(s, ix)?;
-                    let key_str = Token::try_into_smolstr(key).map_err(|e| e.at(s, ix))?;
-                    let next = Token::expect(s, next, b'=');
-                    if next.is_none() {
-                        return Err(Error::ExpectedEquals {
-                            lc: LineColumn::from_pos(s, ix),
-                        });
-                    }
-                    let (val, next) = Self::parse_rec(s, next.unwrap())?;
-                    dict.insert(key_str, val);
-                    if let Some(next_semicolon) = Token::expect(s, next, b';') {
-                        ix = next_semicolon;
-                    } else {
-                        return Err(Error::ExpectedSemicolon {
-                            lc: LineColumn::from_pos(s, next),
-                        });
+                    if errors.is_some() && skip_ws(s, ix) == s.len() {
+                        errors
+                            .as_deref_mut()
+                            .unwrap()
+                            .push(Error::ExpectedCloseBrace {
+                                lc: LineColumn::from_pos(s, s.len()),
+                            });
+                        return Ok((Plist::Dictionary(dict), s.len()));
                     }
+                    let (error, resume_from) = match Self::parse_entry(s, ix, errors.as_deref_mut())
+                    {
+                        Ok((key, val, next)) => {
+                            dict.insert(key, val);
+                            if let Some(next_semicolon) = Token::expect(s, next, b';') {
+                                ix = next_semicolon;
+                                continue;
+                            }
+                            let lc = LineColumn::from_pos(s, next);
+                            (Error::ExpectedSemicolon { lc }, next)
+                        }
+                        Err(error) => (error, ix),
+                    };
+                    let Some(errors) = errors.as_deref_mut() else {
+                        return Err(error);
+                    };
+                    errors.push(error);
+                    (ix, _) = skip_past_error(s, resume_from, false);
                 }
             }
             Token::OpenParen => {
@@ -290,20 +486,41 @@ impl Plist {
                     if let Some(ix) = Token::expect(s, ix, b')') {
                         return Ok((Plist::Array(list), ix));
                     }
-                    let (val, next) = Self::parse_rec(s, ix)?;
-                    list.push(val);
-                    if let Some(ix) = Token::expect(s, next, b')') {
-                        return Ok((Plist::Array(list), ix));
+                    if errors.is_some() && skip_ws(s, ix) == s.len() {
+                        errors
+                            .as_deref_mut()
+                            .unwrap()
+                            .push(Error::ExpectedCloseParen {
+                                lc: LineColumn::from_pos(s, s.len()),
+                            });
+                        return Ok((Plist::Array(list), s.len()));
                     }
-                    if let Some(next_comma) = Token::expect(s, next, b',') {
-                        ix = next_comma;
-                        if let Some(next_paren) = Token::expect(s, next_comma, b')') {
-                            return Ok((Plist::Array(list), next_paren));
+                    let (error, resume_from) = match Self::parse_rec(s, ix, errors.as_deref_mut()) {
+                        Ok((val, next)) => {
+                            list.push(val);
+                            if let Some(ix) = Token::expect(s, next, b')') {
+                                return Ok((Plist::Array(list), ix));
+                            }
+                            if let Some(next_comma) = Token::expect(s, next, b',') {
+                                ix = next_comma;
+                                if let Some(next_paren) = Token::expect(s, next_comma, b')') {
+                                    return Ok((Plist::Array(list), next_paren));
+                                }
+                                continue;
+                            }
+                            let lc = LineColumn::from_pos(s, next);
+                            (Error::ExpectedComma { lc }, next)
                         }
-                    } else {
-                        return Err(Error::ExpectedComma {
-                            lc: LineColumn::from_pos(s, next),
-                        });
+                        Err(error) => (error, ix),
+                    };
+                    let Some(errors) = errors.as_deref_mut() else {
+                        return Err(error);
+                    };
+                    errors.push(error);
+                    let close;
+                    (ix, close) = skip_past_error(s, resume_from, true);
+                    if close {
+                        return Ok((Plist::Array(list), ix));
                     }
                 }
             }
@@ -314,6 +531,21 @@ impl Plist {
         }
     }
 
+    // A dictionary key, `=` and value, returning where the value ends
+    fn parse_entry(
+        s: &str,
+        ix: usize,
+        errors: Option<&mut Vec<Error>>,
+    ) -> Result<(SmolStr, Plist, usize), Error> {
+        let (key, next) = Token::lex(s, ix)?;
+        let key_str = Token::try_into_smolstr(key).map_err(|e| e.at(s, ix))?;
+        let next = Token::expect(s, next, b'=').ok_or_else(|| Error::ExpectedEquals {
+            lc: LineColumn::from_pos(s, ix),
+        })?;
+        let (val, next) = Self::parse_rec(s, next, errors)?;
+        Ok((key_str, val, next))
+    }
+
     fn parse_atom(s: &str) -> Plist {
         if numeric_ok(s) {
             if let Ok(num) = s.parse() {
@@ -350,6 +582,53 @@ impl Default for Plist {
     }
 }
 
+// Where to carry on parsing after an error at `ix`: past the next `;` or `,`,
+// or at the `}` or `)` which closes the container the error was in, skipping
+// strings and whole containers along the way. Also says whether an array the
+// error was in should be closed there, as when it was never closed.
+fn skip_past_error(s: &str, ix: usize, in_array: bool) -> (usize, bool) {
+    let bytes = s.as_bytes();
+    let start = skip_ws(s, ix);
+    if !in_array && bytes.get(start) == Some(&b')') {
+        // A stray paren in a dictionary
+        return (start + 1, false);
+    }
+    let mut open = vec![];
+    let mut ix = start;
+    let (resume, close) = loop {
+        if ix >= bytes.len() {
+            break (bytes.len(), false);
+        }
+        match bytes[ix] {
+            b'"' => {
+                ix += 1;
+                while ix < bytes.len() && bytes[ix] != b'"' {
+                    ix += if bytes[ix] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'{' | b'(' => open.push(bytes[ix]),
+            b'}' if open.is_empty() => break (ix, in_array),
+            b')' if open.is_empty() && in_array => break (ix, false),
+            b')' if open.is_empty() => {}
+            b'}' | b')' => {
+                open.pop();
+            }
+            // A `;` outside a dictionary means an array was never closed
+            b';' if open.last() != Some(&b'{') && in_array => break (ix, true),
+            b';' if open.last() != Some(&b'{') => break (ix + 1, false),
+            b',' if open.is_empty() => break (ix + 1, false),
+            _ => {}
+        }
+        ix += 1;
+    };
+    if resume == start && !close && start < bytes.len() && bytes[start] != b'}' {
+        // Always move on, past whatever could not be parsed
+        let len = s[start..].chars().next().map_or(1, char::len_utf8);
+        return (start + len, false);
+    }
+    (resume, close)
+}
+
 fn byte_from_hex(hex: [u8; 2]) -> Result<u8, Error> {
     fn hex_digit_to_byte(digit: u8) -> Result<u8, Error> {
         match digit {
@@ -383,7 +662,13 @@ impl<'a> Token<'a> {
                         .ok_or(Error::UnclosedData {
                             lc: LineColumn::from_pos(s, start),
                         })?;
-                let chunks = s.as_bytes()[data_start..data_end].chunks_exact(2);
+                // Apple's tools group the digits with spaces
+                let digits: Vec<u8> = s.as_bytes()[data_start..data_end]
+                    .iter()
+                    .copied()
+                    .filter(|b| !is_ascii_whitespace(*b))
+                    .collect();
+                let chunks = digits.chunks_exact(2);
                 if !chunks.remainder().is_empty() {
                     return Err(Error::BadData {
                         lc: LineColumn::from_pos(s, data_start),
@@ -569,6 +854,12 @@ impl From<String> for Plist {
     }
 }
 
+impl From<&str> for Plist {
+    fn from(x: &str) -> Plist {
+        Plist::String(x.to_string())
+    }
+}
+
 impl From<SmolStr> for Plist {
     fn from(x: SmolStr) -> Plist {
         Plist::String(x.into())
@@ -666,6 +957,116 @@ mod tests {
         Plist::deserialize(&mut deserializer).unwrap();
     }
 
+    #[test]
+    fn test_data_roundtrip() {
+        let contents = "{\nde.kutilek.scrawl.data = <89504e47 0d0a1a0a>;\nempty = <>;\n}";
+        let plist = Plist::parse(contents).unwrap();
+        let png = vec![0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+        assert_eq!(
+            plist.as_dict().unwrap().get("de.kutilek.scrawl.data"),
+            Some(&Plist::Data(png.clone()))
+        );
+        let serialized = crate::ser::to_string(&plist).unwrap();
+        assert!(serialized.contains("<89504e470d0a1a0a>"));
+        assert_eq!(Plist::parse(&serialized).unwrap(), plist);
+
+        #[derive(Deserialize)]
+        struct Scrawl {
+            #[serde(rename = "de.kutilek.scrawl.data", with = "serde_bytes")]
+            data: Vec<u8>,
+        }
+        let mut deserializer = crate::de::Deserializer::from_plist(&plist);
+        let scrawl = Scrawl::deserialize(&mut deserializer).unwrap();
+        assert_eq!(scrawl.data, png);
+        assert!(matches!(
+            Plist::parse("<89504>"),
+            Err(Error::BadData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_path() {
+        let mut plist = Plist::parse(
+            "{glyphs = ({glyphname = A;}, {glyphname = B; layers = ({width = 600;});});\n\
+             kerning = {1 = {a.sc = -10;};};}",
+        )
+        .unwrap();
+        assert_eq!(
+            plist.get_path("glyphs.1.layers.0.width"),
+            Some(&Plist::Integer(600))
+        );
+        assert_eq!(plist.get_path("glyphs.2"), None);
+        assert_eq!(plist.get_path("glyphs.first"), None);
+        assert_eq!(plist.get_path("glyphs.0.glyphname.x"), None);
+        assert_eq!(plist.get_path("kerning.1.a.sc"), None);
+        assert_eq!(
+            pointer!(plist, "kerning", "1", "a.sc"),
+            Some(&Plist::Integer(-10))
+        );
+        assert_eq!(pointer!(plist, "glyphs", "0"), None);
+
+        *plist.get_path_mut("glyphs.0.glyphname").unwrap() = "C".to_string().into();
+        assert_eq!(
+            pointer!(plist, "glyphs", 0, "glyphname").and_then(Plist::as_str),
+            Some("C")
+        );
+    }
+
+    #[test]
+    fn test_plist_macro() {
+        let width = 600;
+        let plist = plist!({
+            glyphname = "A";
+            "com.example.x" = -1.5;
+            400 = ();
+            layers = (
+                {
+                    width = width;
+                },
+                {
+                    width = -10;
+                },
+            );
+            unicode = ("0041", 65);
+            empty = {};
+        });
+        let expected = r#"{glyphname = A; com.example.x = -1.5; "400" = ();
+            layers = ({width = 600;}, {width = -10;}); unicode = ("0041", 65); empty = {};}"#;
+        assert_eq!(plist, Plist::parse(expected).unwrap());
+        assert_eq!(plist!(()), Plist::Array(vec![]));
+        assert_eq!(plist!("text"), Plist::String("text".to_string()));
+    }
+
+    #[test]
+    fn test_parse_recovering() {
+        let contents =
+            "{\na = 1\nb = 2;\nc = (1 2, 3);\nd = {x = \"y;\" z = 1;};\ne = (1, 2;\nf = 5;\n}";
+        let (plist, errors) = Plist::parse_recovering(contents);
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                Error::ExpectedSemicolon { lc } | Error::ExpectedComma { lc } => lc.line,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(lines, [2, 4, 5, 6]);
+        assert_eq!(
+            plist,
+            Plist::parse("{a = 1; c = (1, 3); d = {x = \"y;\";}; e = (1, 2); f = 5;}").unwrap()
+        );
+        assert_eq!(Plist::parse(contents).unwrap_err(), errors[0]);
+
+        // A stray closer is stepped over, a missing one reported
+        let (plist, errors) = Plist::parse_recovering("{a = 1;) b = 2;");
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[1], Error::ExpectedCloseBrace { .. }));
+        assert_eq!(plist, Plist::parse("{a = 1; b = 2;}").unwrap());
+        assert_eq!(
+            Plist::parse_recovering("{a = (1, 2);}"),
+            (Plist::parse("{a = (1, 2);}").unwrap(), vec![])
+        );
+    }
+
     #[test]
     fn ascii_to_hex() {
         assert_eq!(byte_from_hex([b'0', b'1']), Ok(0x01));
@@ -1,25 +1,102 @@
+use std::{fmt::Write, io};
+
 use serde::{ser, Serialize};
-use smol_str::{SmolStr, SmolStrBuilder};
 
 use crate::{
     error::{Error, Result},
     is_alnum_strict, is_numeric,
 };
 
-pub struct Serializer {
-    output: Vec<SmolStr>,
-    // Stack to buffer sequence elements and decide inline vs block formatting
+/// Writes values as plist text
+///
+/// Output is built in a single buffer, handed to the writer whenever no
+/// array still being written might need laying out again.
+pub struct Serializer<W> {
+    output: String,
+    writer: W,
+    // How large the buffer can grow before it is written out
+    flush_at: usize,
+    // Arrays being written, to decide inline vs block formatting
     seq_stack: Vec<SeqState>,
-    // Track nesting of maps to append trailing semicolon for top-level map
-    map_depth: usize,
+    // How many arrays on the stack are still inline, and so may be rewritten
+    pending_inline: usize,
+    options: SerializerOptions,
+    // Whether a dictionary key is being serialized
+    in_key: bool,
+    // Whether the next array is a `BlockArray`
+    next_block: bool,
+}
+
+/// Options controlling how values are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializerOptions {
+    /// The number of decimal places floats are rounded to
+    pub float_precision: u8,
+    /// Leave off zeros at the end of floats, writing `0.5` rather than `0.5000`
+    pub trim_trailing_zeros: bool,
+    /// Write floats with no fractional part with a decimal point, such as
+    /// `600.0`, so they are read back as floats rather than integers
+    pub preserve_integer_types: bool,
+    /// Format exactly as Glyphs 3 does; see [`to_string_canonical`]
+    pub canonical: bool,
+}
+
+impl Default for SerializerOptions {
+    fn default() -> Self {
+        SerializerOptions {
+            float_precision: 4,
+            trim_trailing_zeros: true,
+            preserve_integer_types: false,
+            canonical: false,
+        }
+    }
 }
 
+impl SerializerOptions {
+    /// The options of [`to_string_canonical`], formatting as Glyphs 3 does
+    pub fn canonical() -> Self {
+        SerializerOptions {
+            // Glyphs writes five decimal places
+            float_precision: 5,
+            canonical: true,
+            ..Default::default()
+        }
+    }
+}
+
+// An array is written inline, `(a, b)`, until it turns out to hold a
+// dictionary or another array, when what was written so far is laid out
+// again one element per line. Inline arrays are short, so little is ever
+// rewritten.
 struct SeqState {
-    elements: Vec<Vec<SmolStr>>, // serialized tokens per element
-    all_simple: bool,
+    block: bool,
+    len: usize,
+    // Where each element starts, while inline
+    offsets: Vec<usize>,
     all_numeric: bool, // true if all elements are numeric (affects comma spacing)
 }
 
+// Anything this big is written out as soon as it can be
+const FLUSH_SIZE: usize = 1 << 16;
+
+// The newtype name by which a `BlockArray` makes itself known
+const BLOCK_ARRAY: &str = "$openstep_plist::BlockArray";
+
+/// An array which is always written one element per line
+///
+/// Arrays holding only strings or numbers are otherwise written inline,
+/// `(a, b)`. Glyphs 2 writes the nodes of a path one per line, and wrapping
+/// them in this keeps saved files the same. Other serializers see just the
+/// array.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockArray<'a, T>(pub &'a [T]);
+
+impl<T: Serialize> Serialize for BlockArray<'_, T> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(BLOCK_ARRAY, self.0)
+    }
+}
+
 macro_rules! forward_to {
     ($method_from: ident, $t: ty, $method_to:ident, $conversion:expr) => {
         fn $method_from(self, v: $t) -> Result<()> {
@@ -28,22 +105,116 @@ macro_rules! forward_to {
     };
 }
 
-const FLOAT_PRECISION: i32 = 4;
+impl<W: io::Write> Serializer<W> {
+    fn new(writer: W, flush_at: usize, options: SerializerOptions) -> Self {
+        Serializer {
+            output: String::new(),
+            writer,
+            flush_at,
+            seq_stack: Vec::new(),
+            pending_inline: 0,
+            options,
+            in_key: false,
+            next_block: false,
+        }
+    }
+
+    fn maybe_flush(&mut self) -> Result<()> {
+        if self.output.len() >= self.flush_at && self.pending_inline == 0 {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer
+            .write_all(self.output.as_bytes())
+            .map_err(|e| Error::Io(e.to_string()))?;
+        // Only inline arrays keep offsets into the output, and there are none
+        self.output.clear();
+        Ok(())
+    }
+
+    // Write the elements of an inline array again, with the given text
+    // before them and between them
+    fn relayout(&mut self, offsets: &[usize], prefix: &str, separator: &str) {
+        let Some(&base) = offsets.first() else {
+            return;
+        };
+        let elements = self.output.split_off(base);
+        self.output.push_str(prefix);
+        for (i, &offset) in offsets.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(separator);
+            }
+            let end = offsets
+                .get(i + 1)
+                .map_or(elements.len(), |next| next - base);
+            self.output.push_str(&elements[offset - base..end]);
+        }
+    }
+}
 
 pub fn to_string<T>(value: &T) -> Result<String>
 where
     T: Serialize,
 {
-    let mut serializer = Serializer {
-        output: Vec::new(),
-        seq_stack: Vec::new(),
-        map_depth: 0,
-    };
+    to_string_with_options(value, &SerializerOptions::default())
+}
+
+/// Serialize a value with the given options
+pub fn to_string_with_options<T>(value: &T, options: &SerializerOptions) -> Result<String>
+where
+    T: Serialize,
+{
+    let mut serializer = Serializer::new(io::sink(), usize::MAX, *options);
+    value.serialize(&mut serializer)?;
+    if options.canonical {
+        serializer.output.push('\n');
+    }
+    Ok(serializer.output)
+}
+
+/// Serialize a value formatted exactly as Glyphs 3 writes it
+///
+/// Unlike [`to_string`], every array except a tuple is written one element
+/// per line, floats keep five decimal places, negative zero is written as
+/// zero, dictionary keys made only of digits are not quoted, and the output
+/// ends with a newline.
+pub fn to_string_canonical<T>(value: &T) -> Result<String>
+where
+    T: Serialize,
+{
+    to_string_with_options(value, &SerializerOptions::canonical())
+}
+
+/// Serialize a value to a writer, as [`to_string`] does
+///
+/// The text is handed to the writer as it is produced rather than built up
+/// in memory first.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize,
+{
+    to_writer_with_options(writer, value, &SerializerOptions::default())
+}
+
+/// Serialize a value to a writer with the given options
+pub fn to_writer_with_options<W, T>(writer: W, value: &T, options: &SerializerOptions) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize,
+{
+    let mut serializer = Serializer::new(writer, FLUSH_SIZE, *options);
     value.serialize(&mut serializer)?;
-    Ok(serializer.output.join(""))
+    if options.canonical {
+        serializer.output.push('\n');
+    }
+    serializer.flush()
 }
 
-impl ser::Serializer for &mut Serializer {
+impl<W: io::Write> ser::Serializer for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
     type SerializeSeq = Self;
@@ -55,8 +226,7 @@ impl ser::Serializer for &mut Serializer {
     type SerializeStructVariant = Self;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.output
-            .push(SmolStr::new_static(if v { "1" } else { "0" }));
+        self.output.push(if v { '1' } else { '0' });
         Ok(())
     }
     forward_to!(serialize_i8, i8, serialize_i64, i64::from);
@@ -67,45 +237,75 @@ impl ser::Serializer for &mut Serializer {
     forward_to!(serialize_u32, u32, serialize_u64, u64::from);
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.output.push(SmolStr::new(format!("{v}")));
+        let _ = write!(self.output, "{v}");
         Ok(())
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.output.push(SmolStr::new(format!("{v}")));
+        let _ = write!(self.output, "{v}");
         Ok(())
     }
 
-    forward_to!(serialize_f32, f32, serialize_f64, f64::from);
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        if self.options.canonical {
+            // Widen by way of the shortest decimal, so 0.1f32 is 0.1 rather
+            // than 0.10000000149011612
+            let widened = v.to_string().parse().unwrap_or_else(|_| f64::from(v));
+            self.serialize_f64(widened)
+        } else {
+            self.serialize_f64(f64::from(v))
+        }
+    }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.output.push(SmolStr::new(format!(
-            "{}",
-            (v * 10_f64.powi(FLOAT_PRECISION)).round() / 10_f64.powi(FLOAT_PRECISION)
-        )));
+        let precision = self.options.float_precision;
+        let scale = 10_f64.powi(i32::from(precision));
+        let mut rounded = (v * scale).round() / scale;
+        if self.options.canonical && rounded == 0.0 {
+            rounded = 0.0;
+        }
+        let start = self.output.len();
+        if self.options.trim_trailing_zeros {
+            let _ = write!(self.output, "{rounded}");
+        } else {
+            let _ = write!(self.output, "{rounded:.*}", usize::from(precision));
+        }
+        if self.options.preserve_integer_types
+            && rounded.is_finite()
+            && !self.output[start..].contains('.')
+        {
+            self.output.push_str(".0");
+        }
         Ok(())
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.output.push(SmolStr::new_inline(&format!("{v}")));
+        self.output.push(v);
         Ok(())
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        escape_string(&mut self.output, v);
+        if self.options.canonical
+            && self.in_key
+            && !v.is_empty()
+            && v.bytes().all(|b| b.is_ascii_digit())
+        {
+            self.output.push_str(v);
+        } else {
+            escape_string(&mut self.output, v);
+        }
         Ok(())
     }
 
     fn serialize_bytes(self, data: &[u8]) -> Result<()> {
-        let mut builder = SmolStrBuilder::new();
-        builder.push('<');
+        self.output.reserve(data.len() * 2 + 2);
+        self.output.push('<');
         for byte in data {
             let [one, two] = hex_digits_for_byte(*byte);
-            builder.push(one);
-            builder.push(two);
+            self.output.push(one);
+            self.output.push(two);
         }
-        builder.push('>');
-        self.output.push(builder.finish());
+        self.output.push('>');
         Ok(())
     }
 
@@ -122,7 +322,7 @@ impl ser::Serializer for &mut Serializer {
 
     fn serialize_unit(self) -> Result<()> {
         // ????
-        self.output.push(SmolStr::new_static("null"));
+        self.output.push_str("null");
         Ok(())
     }
 
@@ -139,10 +339,11 @@ impl ser::Serializer for &mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
+        self.next_block = name == BLOCK_ARRAY;
         value.serialize(self)
     }
 
@@ -156,25 +357,31 @@ impl ser::Serializer for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output.push(SmolStr::new_static("{newtype"));
+        self.output.push_str("{newtype");
         variant.serialize(&mut *self)?;
-        self.output.push(SmolStr::new_static(" = "));
+        self.output.push_str(" = ");
         value.serialize(&mut *self)?;
-        self.output.push(SmolStr::new_static(";}"));
+        self.output.push_str(";}");
         Ok(())
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let block = self.options.canonical || std::mem::take(&mut self.next_block);
+        if !block {
+            self.pending_inline += 1;
+        }
         self.seq_stack.push(SeqState {
-            elements: Vec::new(),
-            all_simple: true,
+            block,
+            len: 0,
+            offsets: Vec::new(),
             all_numeric: true,
         });
+        self.output.push_str(if block { "(\n" } else { "(" });
         Ok(self)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        self.output.push(SmolStr::new_static("("));
+        self.output.push('(');
         Ok(self)
     }
 
@@ -194,15 +401,14 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.output.push(SmolStr::new_static("{tuplevariant"));
+        self.output.push_str("{tuplevariant");
         variant.serialize(&mut *self)?;
-        self.output.push(SmolStr::new_static(" = ("));
+        self.output.push_str(" = (");
         Ok(self)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.map_depth += 1;
-        self.output.push(SmolStr::new_static("{"));
+        self.output.push('{');
         Ok(self)
     }
 
@@ -217,14 +423,14 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.output.push(SmolStr::new_static("{structvariant"));
+        self.output.push_str("{structvariant");
         variant.serialize(&mut *self)?;
-        self.output.push(SmolStr::new_static(" = }"));
+        self.output.push_str(" = }");
         Ok(self)
     }
 }
 
-impl ser::SerializeSeq for &mut Serializer {
+impl<W: io::Write> ser::SerializeSeq for &mut Serializer<W> {
     // Must match the `Ok` type of the serializer.
     type Ok = ();
     // Must match the `Error` type of the serializer.
@@ -235,85 +441,58 @@ impl ser::SerializeSeq for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        // Serialize element into a temporary serializer to inspect complexity
-        let mut tmp = Serializer {
-            output: Vec::new(),
-            seq_stack: Vec::new(),
-            map_depth: 0,
+        let Some(mut state) = self.seq_stack.pop() else {
+            return Ok(());
         };
-        value.serialize(&mut tmp)?;
-        // Determine if element is complex (starts with map or sequence)
-        let complex = match tmp.output.first() {
-            Some(first) => {
-                let s = first.as_str();
-                s.starts_with("(\n") || s == "(" || s == "{"
-            }
-            None => false,
-        };
-        // Check if all output tokens are numeric (for comma spacing decision)
-        let all_numeric = tmp.output.iter().all(|tok| {
-            let s = tok.as_str();
-            s.parse::<f64>().is_ok() || s == "-" || s == "."
-        });
-        if let Some(state) = self.seq_stack.last_mut() {
-            if complex {
-                state.all_simple = false;
-            }
-            if !all_numeric {
+        if state.block && state.len > 0 {
+            self.output.push_str(",\n");
+        }
+        let start = self.output.len();
+        if !state.block {
+            state.offsets.push(start);
+        }
+        value.serialize(&mut **self)?;
+        state.len += 1;
+        if !state.block {
+            let element = &self.output[start..];
+            // Dictionaries and arrays make the array a block
+            if element.starts_with('(') || element.starts_with("{\n") {
+                self.relayout(&state.offsets, "\n", ",\n");
+                state.offsets = Vec::new();
+                state.block = true;
+                self.pending_inline -= 1;
+            } else if !(element.parse::<f64>().is_ok() || element == "-" || element == ".") {
                 state.all_numeric = false;
             }
-            state.elements.push(tmp.output);
         }
-        Ok(())
+        self.seq_stack.push(state);
+        self.maybe_flush()
     }
 
     // Close the sequence.
     fn end(self) -> Result<()> {
-        if let Some(state) = self.seq_stack.pop() {
-            if state.all_simple {
-                // Inline formatting: (a,b,c) or (a, b, c) depending on type
-                self.output.push(SmolStr::new_static("("));
-                for (i, elem) in state.elements.iter().enumerate() {
-                    if i > 0 {
-                        // Numbers: no space. Strings/other: space after comma
-                        if state.all_numeric {
-                            self.output.push(SmolStr::new_static(","));
-                        } else {
-                            self.output.push(SmolStr::new_static(", "));
-                        }
-                    }
-                    // Append element tokens verbatim
-                    for tok in elem {
-                        self.output.push(tok.clone());
-                    }
-                }
-                self.output.push(SmolStr::new_static(")"));
-            } else {
-                // Block formatting with newlines
-                self.output.push(SmolStr::new_static("(\n"));
-                for (i, elem) in state.elements.iter().enumerate() {
-                    if i > 0 {
-                        self.output.push(SmolStr::new_static(",\n"));
-                    }
-                    for tok in elem {
-                        self.output.push(tok.clone());
-                    }
-                }
-                self.output.push(SmolStr::new_static("\n)"));
-            }
-            // Semicolons are never added after arrays - they're only added by
-            // serialize_value for dictionary values
-            Ok(())
+        let Some(state) = self.seq_stack.pop() else {
+            return Ok(());
+        };
+        if state.block {
+            self.output
+                .push_str(if state.len == 0 { ")" } else { "\n)" });
         } else {
-            // Should not happen
-            Ok(())
+            // Numbers: no space. Strings/other: space after comma
+            let separator = if state.all_numeric { "," } else { ", " };
+            self.relayout(&state.offsets, "", separator);
+            self.output.push(')');
+            self.pending_inline -= 1;
         }
+        // Semicolons are never added after arrays - they're only added by
+        // serialize_value for dictionary values
+        Ok(())
     }
 }
 
 // Same thing but for tuples.
 // Tuple (pos, node, etc.) have no space between elements and no newlines.
-impl ser::SerializeTuple for &mut Serializer {
+impl<W: io::Write> ser::SerializeTuple for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -321,20 +500,20 @@ impl ser::SerializeTuple for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with(&[SmolStr::new_static("(")]) {
-            self.output.push(SmolStr::new_static(","));
+        if !self.output.ends_with('(') {
+            self.output.push(',');
         }
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(SmolStr::new_static(")"));
+        self.output.push(')');
         Ok(())
     }
 }
 
 // Same thing but for tuple structs.
-impl ser::SerializeTupleStruct for &mut Serializer {
+impl<W: io::Write> ser::SerializeTupleStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -342,19 +521,15 @@ impl ser::SerializeTupleStruct for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with(&[SmolStr::new_static("(")]) {
-            self.output.push(SmolStr::new_static(", "));
-        }
-        value.serialize(&mut **self)
+        ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(SmolStr::new_static(")"));
-        Ok(())
+        ser::SerializeSeq::end(self)
     }
 }
 
-impl ser::SerializeTupleVariant for &mut Serializer {
+impl<W: io::Write> ser::SerializeTupleVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -362,19 +537,19 @@ impl ser::SerializeTupleVariant for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with(&[SmolStr::new_static("(")]) {
-            self.output.push(SmolStr::new_static(", "));
+        if !self.output.ends_with('(') {
+            self.output.push_str(", ");
         }
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(SmolStr::new_static(");}"));
+        self.output.push_str(");}");
         Ok(())
     }
 }
 
-impl ser::SerializeMap for &mut Serializer {
+impl<W: io::Write> ser::SerializeMap for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -382,33 +557,32 @@ impl ser::SerializeMap for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        // if !self.output.ends_with('{') {
-        //     self.output.push(SmolStr::new_static(";"));
-        // }
-        self.output.push(SmolStr::new_static("\n"));
-        key.serialize(&mut **self)
+        self.output.push('\n');
+        self.in_key = true;
+        let result = key.serialize(&mut **self);
+        self.in_key = false;
+        result
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.output.push(SmolStr::new_static(" = "));
+        self.output.push_str(" = ");
         value.serialize(&mut **self)?;
-        self.output.push(SmolStr::new_static(";"));
-        Ok(())
+        self.output.push(';');
+        self.maybe_flush()
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(SmolStr::new_static("\n}"));
         // Never add semicolon after closing brace - semicolons are only added
         // by serialize_value for dictionary values
-        self.map_depth = self.map_depth.saturating_sub(1);
+        self.output.push_str("\n}");
         Ok(())
     }
 }
 
-impl ser::SerializeStruct for &mut Serializer {
+impl<W: io::Write> ser::SerializeStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -416,25 +590,21 @@ impl ser::SerializeStruct for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output.push(SmolStr::new_static("\n"));
+        self.output.push('\n');
         key.serialize(&mut **self)?;
-        self.output.push(SmolStr::new_static(" = "));
+        self.output.push_str(" = ");
         value.serialize(&mut **self)?;
-        self.output.push(SmolStr::new_static(";"));
-        Ok(())
+        self.output.push(';');
+        self.maybe_flush()
     }
 
     fn end(self) -> Result<()> {
-        if self.output.last() == Some(&SmolStr::new_static(";")) {
-            self.output.pop();
-            self.output.push(SmolStr::new_static(";"));
-        }
-        self.output.push(SmolStr::new_static("\n}"));
+        self.output.push_str("\n}");
         Ok(())
     }
 }
 
-impl ser::SerializeStructVariant for &mut Serializer {
+impl<W: io::Write> ser::SerializeStructVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -442,44 +612,44 @@ impl ser::SerializeStructVariant for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with(&[SmolStr::new_static("{")]) {
-            self.output.push(SmolStr::new_static("; "));
+        if !self.output.ends_with('{') {
+            self.output.push_str("; ");
         }
         key.serialize(&mut **self)?;
-        self.output.push(SmolStr::new_static(" = "));
+        self.output.push_str(" = ");
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(SmolStr::new_static("};}"));
+        self.output.push_str("};}");
         Ok(())
     }
 }
 
-fn escape_string(buf: &mut Vec<SmolStr>, s: &str) {
+fn escape_string(buf: &mut String, s: &str) {
     if !s.is_empty()
         && (s.as_bytes().iter().all(|&b| is_alnum_strict(b))
             && !s.as_bytes().iter().all(|&b| is_numeric(b)))
     {
-        buf.push(SmolStr::new(s));
+        buf.push_str(s);
     } else {
-        buf.push(SmolStr::new_static("\""));
+        buf.push('"');
         let mut start = 0;
         let mut ix = start;
         while ix < s.len() {
             let b = s.as_bytes()[ix];
             match b {
                 b'"' | b'\\' => {
-                    buf.push(SmolStr::new(&s[start..ix]));
-                    buf.push(SmolStr::new_static("\\"));
+                    buf.push_str(&s[start..ix]);
+                    buf.push('\\');
                     start = ix;
                 }
                 _ => (),
             }
             ix += 1;
         }
-        buf.push(SmolStr::new(&s[start..]));
-        buf.push(SmolStr::new_static("\""));
+        buf.push_str(&s[start..]);
+        buf.push('"');
     }
 }
 
@@ -522,6 +692,17 @@ mod tests {
         assert_eq!(s, r#"(hello, world)"#);
     }
 
+    #[test]
+    fn test_serialize_block_array() {
+        let nodes = ["1 2 LINE", "3 4 LINE"];
+        assert_eq!(
+            to_string(&BlockArray(&nodes)).unwrap(),
+            "(\n\"1 2 LINE\",\n\"3 4 LINE\"\n)"
+        );
+        let inline = to_string(&nodes.to_vec()).unwrap();
+        assert_eq!(inline, r#"("1 2 LINE", "3 4 LINE")"#);
+    }
+
     #[test]
     fn test_serialize_map() {
         let plist_str = "{\nfoo = bar;\nhello = world;\ntuple = (1,2);\n}";
@@ -574,12 +755,78 @@ tag = wght;
         assert_eq!(s, expected);
     }
 
+    #[test]
+    fn test_canonical() {
+        let plist_str = "{\n400 = (\n-0.0,\n0.333333\n);\nempty = ();\nname = \"400\";\n}";
+        let plist: Plist = Plist::parse(plist_str).unwrap();
+        assert_eq!(
+            to_string(&plist).unwrap(),
+            "{\n\"400\" = (-0,0.3333);\nempty = ();\nname = \"400\";\n}"
+        );
+        assert_eq!(
+            to_string_canonical(&plist).unwrap(),
+            "{\n400 = (\n0,\n0.33333\n);\nempty = (\n);\nname = \"400\";\n}\n"
+        );
+        assert_eq!(
+            to_string_canonical(&(536.725_f32, 1)).unwrap(),
+            "(536.725,1)\n"
+        );
+    }
+
+    #[test]
+    fn test_float_options() {
+        let values = (0.5_f64, 600.0_f64, 1.23456_f64);
+        let with = |options: SerializerOptions| to_string_with_options(&values, &options).unwrap();
+        assert_eq!(to_string(&values).unwrap(), "(0.5,600,1.2346)");
+        assert_eq!(
+            with(SerializerOptions {
+                float_precision: 2,
+                ..Default::default()
+            }),
+            "(0.5,600,1.23)"
+        );
+        assert_eq!(
+            with(SerializerOptions {
+                trim_trailing_zeros: false,
+                ..Default::default()
+            }),
+            "(0.5000,600.0000,1.2346)"
+        );
+        let preserving = SerializerOptions {
+            preserve_integer_types: true,
+            ..Default::default()
+        };
+        assert_eq!(with(preserving), "(0.5,600.0,1.2346)");
+        let plist = Plist::parse("{a = 600.0; b = 600;}").unwrap();
+        let reparsed = Plist::parse(&to_string_with_options(&plist, &preserving).unwrap());
+        assert_eq!(reparsed.unwrap(), plist);
+    }
+
+    #[test]
+    fn test_to_writer() {
+        // Big enough to be written out in pieces
+        let glyph = Plist::parse("{name = A; nodes = ((1, 2, l), (3, 4, o)); tags = (a, b);}");
+        let plist: Plist = vec![glyph.unwrap(); 2000].into();
+        for options in [SerializerOptions::default(), SerializerOptions::canonical()] {
+            let mut written = Vec::new();
+            to_writer_with_options(&mut written, &plist, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(written).unwrap(),
+                to_string_with_options(&plist, &options).unwrap()
+            );
+        }
+        // Arrays become blocks once they turn out to hold containers
+        assert_eq!(
+            to_string(&Plist::parse("(1, a, (2))").unwrap()).unwrap(),
+            "(\n1,\na,\n(2)\n)"
+        );
+    }
+
     #[test]
     fn test_string_escaping() {
         let str = "files/LinkedFontv3.glyphs";
-        let mut buf = Vec::new();
-        escape_string(&mut buf, str);
-        let output = buf.join("");
+        let mut output = String::new();
+        escape_string(&mut output, str);
         assert_eq!(output, "\"files/LinkedFontv3.glyphs\"");
     }
 }